@@ -0,0 +1,37 @@
+//! With the `ffi` feature, generates the C header of the bindings and
+//! compiles the C program that tests them.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "ffi")]
+    ffi();
+}
+
+#[cfg(feature = "ffi")]
+fn ffi() {
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=tests/ffi/extract_test.c");
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+
+    let header = out_dir.join("mddocmrg.h");
+    let config = cbindgen::Config::from_file("cbindgen.toml").expect("cbindgen.toml is valid");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src("src/ffi.rs")
+        .generate()
+        .expect("src/ffi.rs has C bindings")
+        .write_to_file(&header);
+    // tests/ffi.rs checks that include/mddocmrg.h is this header.
+    println!("cargo:rustc-env=MDM_GENERATED_HEADER={}", header.display());
+
+    // Linked by tests/ffi.rs alone, not into the library.
+    cc::Build::new()
+        .file("tests/ffi/extract_test.c")
+        .include(&out_dir)
+        .cargo_metadata(false)
+        .compile("mdm_ffi_test");
+    println!("cargo:rustc-link-search=native={}", out_dir.display());
+}
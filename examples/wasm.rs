@@ -0,0 +1,52 @@
+//! Text extraction for a document previewer running in the browser.
+//!
+//! Build it without the file and merge functions, which WebAssembly has no
+//! use for, and generate the JavaScript bindings with `wasm-bindgen`:
+//!
+//! ```text
+//! cargo build --example wasm --release --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/examples/wasm.wasm
+//! ```
+//!
+//! The page then passes the bytes of a file the user picked:
+//!
+//! ```text
+//! import init, { extract_text } from "./pkg/wasm.js";
+//!
+//! await init();
+//! const bytes = new Uint8Array(await file.arrayBuffer());
+//! preview.textContent = extract_text(bytes);
+//! ```
+
+use mddocmrg::{extract_text_from_bytes, ExtractOptions};
+use wasm_bindgen::prelude::*;
+
+/// The text of the DOCX document in `bytes`. A document that cannot be
+/// read throws an `Error` saying why.
+#[wasm_bindgen]
+pub fn extract_text(bytes: &[u8]) -> Result<String, JsError> {
+    extract_text_from_bytes(bytes, &ExtractOptions::default())
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+
+    #[test]
+    fn test_extract_text() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("word/document.xml", FileOptions::default()).unwrap();
+        zip.write_all(
+            br#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Picked in the browser</w:t></w:r></w:p></w:body></w:document>"#,
+        )
+        .unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        assert_eq!(extract_text(&bytes).unwrap(), "Picked in the browser");
+    }
+}
@@ -0,0 +1,118 @@
+/* The C bindings of mddocmrg. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef MDDOCMRG_H
+#define MDDOCMRG_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * How a call went.
+ */
+typedef enum {
+  /**
+   * The text was extracted.
+   */
+  MDM_STATUS_OK = 0,
+  /**
+   * The document could not be read; the error says why.
+   */
+  MDM_STATUS_FAILED = 1,
+  /**
+   * An argument was null where it may not be, or an option is not one
+   * this library knows.
+   */
+  MDM_STATUS_INVALID_ARGUMENT = 2,
+  /**
+   * The library panicked, which is a bug in it. The error has the panic
+   * message.
+   */
+  MDM_STATUS_PANICKED = 3,
+} MdmStatus;
+
+/**
+ * Options for [`mdm_extract_text`], a subset of [`ExtractOptions`]. Start
+ * from [`mdm_default_options`], so that fields added later get their
+ * defaults. Null strings leave an option at its default; the others take
+ * the values of the command-line options of the same names.
+ */
+typedef struct {
+  /**
+   * `"text"` or `"markdown"`, as `--format`.
+   */
+  const char *format;
+  /**
+   * `"strip"`, `"url"` or `"markdown"`, as `--links`.
+   */
+  const char *links;
+  /**
+   * `"accept"`, `"reject"` or `"all"`, as `--revisions`.
+   */
+  const char *revisions;
+  /**
+   * `"skip"`, `"inline"` or `"end"`, as `--comments`.
+   */
+  const char *comments;
+  /**
+   * Text written after every paragraph.
+   */
+  const char *paragraph_separator;
+  /**
+   * Write the headers of the document before its text and the footers
+   * after it.
+   */
+  bool include_headers_footers;
+  /**
+   * Extract text formatted as hidden.
+   */
+  bool include_hidden;
+} MdmOptions;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The options [`mdm_extract_text`] uses when given none.
+ */
+MdmOptions mdm_default_options(void);
+
+/**
+ * Extracts the text of the DOCX document in the `len` bytes at `data`.
+ *
+ * On success, sets `*out_text` to the text and returns
+ * [`MdmStatus::Ok`]. Otherwise sets `*out_text` to null and, if
+ * `out_error` is not null, `*out_error` to why. Free both with
+ * [`mdm_free_string`]. NUL characters in the text, which C strings cannot
+ * hold, are left out.
+ *
+ * # Safety
+ *
+ * `data` must point to `len` readable bytes, or may be null if `len` is
+ * 0. `opts` must be null or point to valid options, whose strings are null
+ * or NUL-terminated. `out_text` must point to a writable `char *`, and
+ * `out_error` be null or do so.
+ */
+MdmStatus mdm_extract_text(const uint8_t *data,
+                           size_t len,
+                           const MdmOptions *opts,
+                           char **out_text,
+                           char **out_error);
+
+/**
+ * Frees a string returned by this library. Does nothing with null.
+ *
+ * # Safety
+ *
+ * `s` must be null or a string this library returned, not yet freed.
+ */
+void mdm_free_string(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MDDOCMRG_H */
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+use crate::ExtractOptions;
+
+/// A store of the text extracted from files, so that a merge run again
+/// over mostly the same files extracts only those that changed; see
+/// [`MergeOptions::cache`](crate::MergeOptions::cache). [`DirCache`]
+/// keeps the text in a directory; embedders may keep it anywhere else.
+///
+/// A cache is best effort: an entry it cannot find or store is extracted
+/// again next time, never an error.
+pub trait TextCache: fmt::Debug + Send + Sync {
+    /// The text stored under `key`, if any.
+    fn get(&self, key: &CacheKey) -> Option<String>;
+
+    /// Stores the text extracted for `key`.
+    fn put(&self, key: &CacheKey, text: &str);
+}
+
+/// What the text of a file depends on: the file's size, modification time
+/// and content, and the options it is extracted with. A file whose content
+/// or options change gets a new key, whatever its time says.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    /// The key for the file at `path` extracted with `options`. Reads the
+    /// whole file, to hash its content.
+    pub fn for_file(path: impl AsRef<Path>, options: &ExtractOptions) -> io::Result<Self> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos());
+        let mut hasher = Xxh3::new();
+        let mut file = File::open(path)?;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            match file.read(&mut buf)? {
+                0 => break,
+                read => hasher.update(&buf[..read]),
+            }
+        }
+        Ok(CacheKey(format!(
+            "{:016x}-{:x}-{:x}-{:016x}",
+            hasher.digest(),
+            metadata.len(),
+            modified,
+            options_fingerprint(options)
+        )))
+    }
+
+    /// The key as text: hexadecimal digits and dashes, fit for a file name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A hash of the options that change the text extracted, and of the
+/// version of this crate, whose extraction may differ from the last.
+fn options_fingerprint(options: &ExtractOptions) -> u64 {
+    // Logging, cancellation and the limits on what is read leave the text
+    // as it is. Every other option counts, including any added later.
+    let options = ExtractOptions {
+        verbose: false,
+        cancellation: None,
+        max_part_size: 0,
+        max_total_uncompressed: 0,
+        ..options.clone()
+    };
+    let options = format!("{} {:?}", env!("CARGO_PKG_VERSION"), options);
+    xxh3_64(options.as_bytes())
+}
+
+/// A [`TextCache`] keeping each text in a file of a directory, named after
+/// its key. The directory is created when the first text is stored.
+#[derive(Debug, Clone)]
+pub struct DirCache {
+    dir: PathBuf,
+}
+
+impl DirCache {
+    /// A cache in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DirCache { dir: dir.into() }
+    }
+
+    /// Removes the stored texts. Other files in the directory stay.
+    pub fn clear(&self) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if is_entry(&path) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}.txt", key.as_str()))
+    }
+}
+
+/// Whether a file is one a [`DirCache`] stored.
+fn is_entry(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.strip_suffix(".txt").is_some_and(|key| {
+        key.split('-').count() == 4 && key.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+    })
+}
+
+impl TextCache for DirCache {
+    fn get(&self, key: &CacheKey) -> Option<String> {
+        fs::read_to_string(self.entry_path(key)).ok()
+    }
+
+    fn put(&self, key: &CacheKey, text: &str) {
+        // Written aside and renamed into place, so that an interrupted run
+        // never leaves a partial text to be read as whole.
+        let path = self.entry_path(key);
+        let partial = path.with_extension("partial");
+        let stored = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&partial, text))
+            .and_then(|_| fs::rename(&partial, &path));
+        if stored.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CancellationToken, OutputFormat};
+
+    #[test]
+    fn test_fingerprint_leaves_out_options_that_keep_the_text() {
+        let options = ExtractOptions::default();
+        let fingerprint = options_fingerprint(&options);
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let unchanged = [
+            ExtractOptions::new().verbose(true),
+            ExtractOptions {
+                cancellation: Some(cancellation),
+                max_part_size: 1,
+                ..ExtractOptions::default()
+            },
+        ];
+        for options in unchanged {
+            assert_eq!(options_fingerprint(&options), fingerprint, "{:?}", options);
+        }
+        let markdown = ExtractOptions::new().format(OutputFormat::Markdown);
+        assert_ne!(options_fingerprint(&markdown), fingerprint);
+    }
+}
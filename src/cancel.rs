@@ -0,0 +1,30 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Lets another thread stop an extraction or merge, such as when the user
+/// of a GUI presses Cancel. Clones share the flag: keep one, give one to
+/// the options, and [`cancel`](Self::cancel) the one kept.
+///
+/// Work that is cancelled stops with
+/// [`MergeError::Cancelled`](crate::MergeError::Cancelled) soon after, at
+/// the next element of the document being read or before the next file of
+/// a merge.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token not yet cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the work using this token, or any clone of it, to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
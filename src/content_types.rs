@@ -0,0 +1,119 @@
+use quick_xml::events::Event;
+use std::collections::HashMap;
+
+use crate::attribute;
+use crate::error::XmlError;
+use crate::namespaces::WordReader;
+
+/// Content types of the main part of the Wordprocessing packages: documents,
+/// macro-enabled documents, templates and macro-enabled templates.
+const WORDPROCESSING_MAIN_TYPES: [&str; 4] = [
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
+    "application/vnd.ms-word.document.macroEnabled.main+xml",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.template.main+xml",
+    "application/vnd.ms-word.template.macroEnabledTemplate.main+xml",
+];
+
+/// The content types of a package's parts, from `[Content_Types].xml`.
+#[derive(Debug, Clone, Default)]
+pub struct ContentTypes {
+    /// Types by lowercase file extension (`Default` elements).
+    defaults: HashMap<String, String>,
+    /// Types by lowercase part name without the leading slash (`Override`
+    /// elements).
+    overrides: HashMap<String, String>,
+}
+
+impl ContentTypes {
+    /// Parses the XML of the content types part.
+    pub fn parse(xml: &str) -> Result<Self, XmlError> {
+        let mut reader = WordReader::from_str(xml);
+        let mut content_types = ContentTypes::default();
+        loop {
+            match reader.read_event()? {
+                Event::Start(ref e) | Event::Empty(ref e) => {
+                    let Some(content_type) = attribute(e, b"ContentType") else {
+                        continue;
+                    };
+                    match e.local_name().as_ref() {
+                        b"Default" => {
+                            if let Some(extension) = attribute(e, b"Extension") {
+                                content_types
+                                    .defaults
+                                    .insert(extension.to_ascii_lowercase(), content_type);
+                            }
+                        }
+                        b"Override" => {
+                            if let Some(part_name) = attribute(e, b"PartName") {
+                                let part_name =
+                                    part_name.trim_start_matches('/').to_ascii_lowercase();
+                                content_types.overrides.insert(part_name, content_type);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(content_types)
+    }
+
+    /// The content type of a part: its override, or else the default for
+    /// its extension. Part names are compared without regard to case.
+    pub fn get(&self, part_name: &str) -> Option<&str> {
+        let part_name = part_name.trim_start_matches('/').to_ascii_lowercase();
+        if let Some(content_type) = self.overrides.get(&part_name) {
+            return Some(content_type);
+        }
+        let (_, extension) = part_name.rsplit_once('.')?;
+        self.defaults.get(extension).map(String::as_str)
+    }
+}
+
+/// Whether a content type is that of a Wordprocessing main document part.
+pub fn is_wordprocessing_main(content_type: &str) -> bool {
+    WORDPROCESSING_MAIN_TYPES.contains(&content_type)
+}
+
+/// Describes the kind of document a non-Wordprocessing main part type
+/// belongs to, for error messages.
+pub fn describe_document_kind(content_type: &str) -> String {
+    if content_type.contains("spreadsheetml") || content_type.contains("ms-excel") {
+        "spreadsheet (.xlsx)".to_string()
+    } else if content_type.contains("presentationml") || content_type.contains("ms-powerpoint") {
+        "presentation (.pptx)".to_string()
+    } else {
+        format!("package of type {}", content_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_lookup() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/word/document.xml" ContentType="application/vnd.ms-word.document.macroEnabled.main+xml"/>
+</Types>"#;
+        let content_types = ContentTypes::parse(xml).unwrap();
+        let main = content_types.get("Word/Document.xml").unwrap();
+        assert!(is_wordprocessing_main(main));
+        assert_eq!(
+            content_types.get("word/styles.xml"),
+            Some("application/xml")
+        );
+        assert_eq!(content_types.get("word/media/image1.png"), None);
+        assert_eq!(
+            describe_document_kind(
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"
+            ),
+            "spreadsheet (.xlsx)"
+        );
+    }
+}
@@ -0,0 +1,397 @@
+#[cfg(feature = "cli")]
+use quick_xml::events::Event;
+
+#[cfg(feature = "cli")]
+use crate::error::XmlError;
+#[cfg(feature = "cli")]
+use crate::namespaces::WordReader;
+use crate::properties::PropertyValue;
+#[cfg(feature = "cli")]
+use crate::visitor::{CellInfo, DocxVisitor, LinkInfo, ParagraphInfo, RunInfo};
+
+/// The content of a document as a tree of blocks, for tools that want its
+/// structure rather than its text; see [`parse_docx`](crate::parse_docx).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Document {
+    /// The document properties Word shows under File > Info.
+    pub metadata: Metadata,
+    /// The blocks of the main document, in order.
+    pub blocks: Vec<Block>,
+}
+
+/// The properties of a document: the core properties
+/// (`docProps/core.xml`) and the extended properties the application that
+/// saved it records (`docProps/app.xml`). Each is `None` if the document
+/// does not have it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    /// The title (`dc:title`).
+    pub title: Option<String>,
+    /// The subject (`dc:subject`).
+    pub subject: Option<String>,
+    /// The author (`dc:creator`).
+    pub author: Option<String>,
+    /// Who saved the document last (`cp:lastModifiedBy`).
+    pub last_modified_by: Option<String>,
+    /// When the document was created, as written, e.g. `2024-03-01T09:30:00Z`.
+    pub created: Option<String>,
+    /// When the document was last saved, as written.
+    pub modified: Option<String>,
+    /// How many times the document has been saved (`cp:revision`).
+    pub revision: Option<u32>,
+    /// The application that saved the document, e.g. `Microsoft Office Word`.
+    pub application: Option<String>,
+    /// The number of pages when the document was last saved. Like the word
+    /// and character counts, it is what the application recorded, and
+    /// may be stale.
+    pub pages: Option<u64>,
+    /// The number of words when the document was last saved.
+    pub words: Option<u64>,
+    /// The number of characters, without spaces, when the document was
+    /// last saved.
+    pub characters: Option<u64>,
+    /// The custom properties (`docProps/custom.xml`), by name.
+    pub custom_properties: Vec<(String, PropertyValue)>,
+}
+
+/// A block of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Block {
+    /// A paragraph outside lists.
+    Paragraph {
+        /// The id of the paragraph's style (`w:pStyle`), if it names one.
+        style: Option<String>,
+        /// The outline level of a heading, 1 for top-level headings.
+        heading_level: Option<usize>,
+        /// The paragraph's text, by formatting.
+        runs: Vec<Run>,
+    },
+    /// Consecutive list paragraphs at the same level.
+    List {
+        /// The level of the items, 0 for the outermost.
+        level: usize,
+        /// The items are numbered rather than bulleted.
+        numbered: bool,
+        /// The runs of each item.
+        items: Vec<Vec<Run>>,
+    },
+    /// A table.
+    Table {
+        /// The cells of each row, each holding blocks of its own.
+        rows: Vec<Vec<Vec<Block>>>,
+    },
+}
+
+/// Text with the same formatting, as far as extraction tells it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Run {
+    /// The text.
+    pub text: String,
+    /// The target of the hyperlink the text is in, if it is in one.
+    pub link: Option<String>,
+    /// The text is hidden, and only here because the options include it.
+    pub hidden: bool,
+    /// The text is struck through.
+    pub struck: bool,
+}
+
+impl Document {
+    /// The text of the document, a line per paragraph and list item, and
+    /// table cells separated by tabs. This is a plain rendering of the
+    /// blocks; [`extract_text_from_docx_with`](crate::extract_text_from_docx_with)
+    /// writes the text with its list labels, links and layout.
+    pub fn to_plain_text(&self) -> String {
+        blocks_text(&self.blocks, "\n")
+    }
+}
+
+fn runs_text(runs: &[Run]) -> String {
+    runs.iter().map(|run| run.text.as_str()).collect()
+}
+
+fn blocks_text(blocks: &[Block], separator: &str) -> String {
+    let lines: Vec<String> = blocks
+        .iter()
+        .map(|block| match block {
+            Block::Paragraph { runs, .. } => runs_text(runs),
+            Block::List { level, items, .. } => items
+                .iter()
+                .map(|item| format!("{}{}", "  ".repeat(*level), runs_text(item)))
+                .collect::<Vec<_>>()
+                .join(separator),
+            Block::Table { rows } => rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| blocks_text(cell, " "))
+                        .collect::<Vec<_>>()
+                        .join("\t")
+                })
+                .collect::<Vec<_>>()
+                .join(separator),
+        })
+        .collect();
+    lines.join(separator)
+}
+
+impl Metadata {
+    /// The properties the document has, by name, as the command line lists
+    /// them. Custom properties are left out.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let text = [
+            ("title", &self.title),
+            ("subject", &self.subject),
+            ("author", &self.author),
+            ("last modified by", &self.last_modified_by),
+            ("created", &self.created),
+            ("modified", &self.modified),
+        ];
+        let numbers = [
+            ("pages", self.pages),
+            ("words", self.words),
+            ("characters", self.characters),
+        ];
+        let mut fields: Vec<(&'static str, String)> = text
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value.clone()?)))
+            .collect();
+        if let Some(revision) = self.revision {
+            fields.push(("revision", revision.to_string()));
+        }
+        if let Some(application) = &self.application {
+            fields.push(("application", application.clone()));
+        }
+        fields.extend(
+            numbers
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value?.to_string()))),
+        );
+        fields
+    }
+
+    /// Reads the XML of a core properties part.
+    #[cfg(feature = "cli")]
+    pub(crate) fn read_core_properties(&mut self, xml: &str) -> Result<(), XmlError> {
+        for (name, value) in read_properties(xml)? {
+            match name.as_str() {
+                "dc:title" => self.title = Some(value),
+                "dc:subject" => self.subject = Some(value),
+                "dc:creator" => self.author = Some(value),
+                "cp:lastModifiedBy" => self.last_modified_by = Some(value),
+                "dcterms:created" => self.created = Some(value),
+                "dcterms:modified" => self.modified = Some(value),
+                "cp:revision" => self.revision = value.parse().ok(),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the XML of an extended properties part.
+    #[cfg(feature = "cli")]
+    pub(crate) fn read_app_properties(&mut self, xml: &str) -> Result<(), XmlError> {
+        for (name, value) in read_properties(xml)? {
+            match name.as_str() {
+                "Application" => self.application = Some(value),
+                "Pages" => self.pages = value.parse().ok(),
+                "Words" => self.words = value.parse().ok(),
+                "Characters" => self.characters = value.parse().ok(),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The elements of a properties part that hold text, by name, with the
+/// text trimmed. Empty elements are left out.
+#[cfg(feature = "cli")]
+fn read_properties(xml: &str) -> Result<Vec<(String, String)>, XmlError> {
+    let mut reader = WordReader::from_str(xml);
+    let mut properties = Vec::new();
+    let mut name = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) => {
+                name = Some(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+            }
+            Event::Text(e) => {
+                if let Some(name) = name.take() {
+                    let text = match e.unescape() {
+                        Ok(text) => text.trim().to_string(),
+                        Err(error) => return Err(reader.error(error)),
+                    };
+                    if !text.is_empty() {
+                        properties.push((name, text));
+                    }
+                }
+            }
+            Event::End(_) => name = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(properties)
+}
+
+/// Builds the blocks of a document from what extraction visits.
+#[cfg(feature = "cli")]
+#[derive(Debug)]
+pub(crate) struct DocumentBuilder {
+    /// The blocks of the document, then those of each open table cell.
+    blocks: Vec<Vec<Block>>,
+    /// The rows of the open tables, innermost last.
+    tables: Vec<Vec<Vec<Vec<Block>>>>,
+    /// The open paragraphs, innermost last; those of a text box nest in the
+    /// paragraph it is anchored in, and come before it.
+    paragraphs: Vec<(ParagraphInfo, Vec<Run>)>,
+    link: Option<String>,
+}
+
+#[cfg(feature = "cli")]
+impl DocumentBuilder {
+    pub fn new() -> Self {
+        DocumentBuilder {
+            blocks: vec![Vec::new()],
+            tables: Vec::new(),
+            paragraphs: Vec::new(),
+            link: None,
+        }
+    }
+
+    pub fn finish(mut self, metadata: Metadata) -> Document {
+        // Tables left open by a damaged document keep what they have.
+        while !self.tables.is_empty() {
+            self.table_end();
+        }
+        Document {
+            metadata,
+            blocks: self.blocks.swap_remove(0),
+        }
+    }
+
+    fn push_block(&mut self, block: Block) {
+        if let Some(blocks) = self.blocks.last_mut() {
+            blocks.push(block);
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl DocxVisitor for DocumentBuilder {
+    fn paragraph_start(&mut self, paragraph: &ParagraphInfo) {
+        self.paragraphs.push((paragraph.clone(), Vec::new()));
+    }
+
+    fn paragraph_end(&mut self) {
+        let Some((info, runs)) = self.paragraphs.pop() else {
+            return;
+        };
+        let Some(level) = info.list_level else {
+            self.push_block(Block::Paragraph {
+                style: info.style,
+                heading_level: info.heading_level,
+                runs,
+            });
+            return;
+        };
+        match self.blocks.last_mut().and_then(|blocks| blocks.last_mut()) {
+            Some(Block::List {
+                level: list_level,
+                numbered,
+                items,
+            }) if *list_level == level && *numbered == info.numbered => items.push(runs),
+            _ => self.push_block(Block::List {
+                level,
+                numbered: info.numbered,
+                items: vec![runs],
+            }),
+        }
+    }
+
+    fn text(&mut self, text: &str, run: &RunInfo) {
+        let Some((_, runs)) = self.paragraphs.last_mut() else {
+            return;
+        };
+        match runs.last_mut() {
+            Some(last)
+                if last.link == self.link
+                    && last.hidden == run.hidden
+                    && last.struck == run.struck =>
+            {
+                last.text.push_str(text)
+            }
+            _ => runs.push(Run {
+                text: text.to_string(),
+                link: self.link.clone(),
+                hidden: run.hidden,
+                struck: run.struck,
+            }),
+        }
+    }
+
+    fn hyperlink_start(&mut self, link: &LinkInfo) {
+        self.link = link.target.clone();
+    }
+
+    fn hyperlink_end(&mut self) {
+        self.link = None;
+    }
+
+    fn table_start(&mut self) {
+        self.tables.push(Vec::new());
+    }
+
+    fn table_end(&mut self) {
+        if let Some(rows) = self.tables.pop() {
+            self.push_block(Block::Table { rows });
+        }
+    }
+
+    fn row_start(&mut self) {
+        if let Some(rows) = self.tables.last_mut() {
+            rows.push(Vec::new());
+        }
+    }
+
+    fn cell_start(&mut self, _cell: &CellInfo) {
+        self.blocks.push(Vec::new());
+    }
+
+    fn cell_end(&mut self) {
+        if self.blocks.len() < 2 {
+            return;
+        }
+        let cell = self.blocks.pop().unwrap_or_default();
+        if let Some(row) = self.tables.last_mut().and_then(|rows| rows.last_mut()) {
+            row.push(cell);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_core_properties() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>Annual report</dc:title><dc:creator>A. Writer</dc:creator><cp:lastModifiedBy>An Editor</cp:lastModifiedBy><dcterms:created xsi:type="dcterms:W3CDTF">2024-03-01T09:30:00Z</dcterms:created><dc:subject></dc:subject></cp:coreProperties>"#;
+        let mut metadata = Metadata::default();
+        metadata.read_core_properties(xml).unwrap();
+        assert_eq!(
+            metadata,
+            Metadata {
+                title: Some("Annual report".to_string()),
+                author: Some("A. Writer".to_string()),
+                last_modified_by: Some("An Editor".to_string()),
+                created: Some("2024-03-01T09:30:00Z".to_string()),
+                ..Metadata::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::string::FromUtf8Error;
+
+/// Decodes the bytes of an XML part. Office writes UTF-8, but other
+/// writers may use UTF-16, which they mark with a byte order mark, or an
+/// encoding named in the XML declaration. UTF-8 without a byte order mark,
+/// which is nearly every part, is taken as it is, without copying.
+///
+/// Other encodings are transcoded, replacing malformed sequences; only
+/// invalid UTF-8 is an error, as it always was.
+pub fn decode_xml(bytes: Vec<u8>) -> Result<String, FromUtf8Error> {
+    let encoding = match Encoding::for_bom(&bytes) {
+        Some((encoding, _)) => encoding,
+        None => match bytes.get(..4) {
+            // UTF-16 without a byte order mark still starts with `<?`.
+            Some([b'<', 0, b'?', 0]) => UTF_16LE,
+            Some([0, b'<', 0, b'?']) => UTF_16BE,
+            _ => declared_encoding(&bytes).unwrap_or(UTF_8),
+        },
+    };
+    if encoding == UTF_8 {
+        let mut bytes = bytes;
+        if bytes.starts_with(b"\xEF\xBB\xBF") {
+            bytes.drain(..3);
+        }
+        return String::from_utf8(bytes);
+    }
+    // `decode` sniffs and removes the byte order mark itself.
+    let (text, _, _) = encoding.decode(&bytes);
+    Ok(text.into_owned())
+}
+
+/// Whether a part starting with these bytes can be parsed as it is, being
+/// UTF-8 by what its start says: no byte order mark or declaration names
+/// another encoding. `start` should reach past the XML declaration, if the
+/// part has one.
+pub fn is_utf8(start: &[u8]) -> bool {
+    match Encoding::for_bom(start) {
+        Some((encoding, _)) => encoding == UTF_8,
+        None => {
+            !matches!(start.get(..4), Some([b'<', 0, b'?', 0] | [0, b'<', 0, b'?']))
+                && declared_encoding(start).is_none_or(|encoding| encoding == UTF_8)
+        }
+    }
+}
+
+/// The encoding named by the XML declaration, if the part starts with one.
+/// Declarations are ASCII in every encoding this is asked about.
+fn declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    if !bytes.starts_with(b"<?xml") {
+        return None;
+    }
+    let end = bytes.iter().position(|&byte| byte == b'>')?;
+    let declaration = std::str::from_utf8(&bytes[..end]).ok()?;
+    let (_, rest) = declaration.split_once("encoding")?;
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let label = rest[1..].split(quote).next()?;
+    Encoding::for_label(label.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+        let mut bytes = if bom { vec![0xFF, 0xFE] } else { Vec::new() };
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_decode_xml_encodings() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-16"?><w:t>Grüße</w:t>"#;
+        assert_eq!(decode_xml(utf16le(xml, true)).unwrap(), xml);
+        assert_eq!(decode_xml(utf16le(xml, false)).unwrap(), xml);
+
+        let utf8 = r#"<?xml version="1.0" encoding="UTF-8"?><w:t>Grüße</w:t>"#;
+        assert_eq!(decode_xml(utf8.as_bytes().to_vec()).unwrap(), utf8);
+        let with_bom = [b"\xEF\xBB\xBF".as_slice(), utf8.as_bytes()].concat();
+        assert_eq!(decode_xml(with_bom).unwrap(), utf8);
+
+        let latin1 = b"<?xml version='1.0' encoding='ISO-8859-1'?><w:t>Gr\xFC\xDFe</w:t>";
+        assert_eq!(
+            decode_xml(latin1.to_vec()).unwrap(),
+            "<?xml version='1.0' encoding='ISO-8859-1'?><w:t>Grüße</w:t>"
+        );
+        assert!(decode_xml(b"<w:t>Gr\xFC\xDFe</w:t>".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::result::ZipError;
+
+/// Why extracting or merging a document failed. Every variant names the
+/// file that failed, so a batch can report which one broke.
+#[derive(Debug, Error)]
+pub enum MergeError {
+    /// The file could not be opened or read.
+    #[error("{path}: {source}", path = .path.display())]
+    Io {
+        /// The file that failed.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+    /// The file is not a readable zip package: damaged, truncated, or not
+    /// a Word document at all.
+    #[error("{path} is damaged or not a Word document: {source}", path = .path.display())]
+    Zip {
+        /// The file that failed.
+        path: PathBuf,
+        /// What the zip reader found wrong.
+        source: ZipError,
+    },
+    /// A part of the document, or the document itself for Flat OPC, is not
+    /// well-formed XML, or ends before its elements do, as a truncated part
+    /// does. Written as `report.docx (word/footnotes.xml @ byte 18273):
+    /// Unexpected EOF during reading w:p`.
+    #[error(
+        "{path} ({}@ byte {position}): {source}",
+        .part.as_ref().map(|part| format!("{} ", part)).unwrap_or_default(),
+        path = .path.display()
+    )]
+    Xml {
+        /// The file that failed.
+        path: PathBuf,
+        /// The part within the package; `None` if the file itself is XML.
+        part: Option<String>,
+        /// Byte offset into the part where reading stopped.
+        position: usize,
+        /// What the XML reader found wrong.
+        source: quick_xml::Error,
+    },
+    /// A part every document of its kind has, such as `word/document.xml`,
+    /// is missing.
+    #[error("{path} has no {part} part", path = .path.display())]
+    MissingPart {
+        /// The file that failed.
+        path: PathBuf,
+        /// The name of the missing part.
+        part: String,
+    },
+    /// The file is an encrypted, password-protected document. Word stores
+    /// these in a compound file rather than a zip package.
+    #[error(
+        "{path} is encrypted or password-protected; remove the password in Word \
+         (File > Info > Protect Document) and try again",
+        path = .path.display()
+    )]
+    Encrypted {
+        /// The file that failed.
+        path: PathBuf,
+    },
+    /// The file is a Word 97-2003 binary document (`.doc`).
+    #[error(
+        "{path} is a legacy Word 97-2003 (.doc) file; save it as .docx in Word \
+         or convert it with `soffice --convert-to docx` first",
+        path = .path.display()
+    )]
+    LegacyDoc {
+        /// The file that failed.
+        path: PathBuf,
+    },
+    /// The file is an Office package of another kind, such as a spreadsheet.
+    #[error("{path} is a {kind}, not a Word document", path = .path.display())]
+    NotWordDocument {
+        /// The file that failed.
+        path: PathBuf,
+        /// What kind of document it is instead, e.g. `spreadsheet (.xlsx)`.
+        kind: String,
+    },
+    /// The document has no bookmark of the requested name.
+    #[error("{path} has no bookmark named {name}", path = .path.display())]
+    BookmarkNotFound {
+        /// The file that failed.
+        path: PathBuf,
+        /// The bookmark asked for.
+        name: String,
+    },
+    /// A plain text file is not valid UTF-8.
+    #[error("{path} is not UTF-8 text; convert it to UTF-8 first", path = .path.display())]
+    NotUtf8 {
+        /// The file that failed.
+        path: PathBuf,
+    },
+    /// A part inflates to more than the per-part limit.
+    #[error("{path}: part {part} inflates to more than the limit of {limit} bytes", path = .path.display())]
+    PartTooLarge {
+        /// The file that failed.
+        path: PathBuf,
+        /// The name of the part within the package.
+        part: String,
+        /// The per-part limit, in bytes.
+        limit: u64,
+    },
+    /// The parts read so far inflate to more than the per-document limit.
+    #[error("{path}: reading part {part} exceeds the limit of {limit} bytes for the whole document", path = .path.display())]
+    PackageTooLarge {
+        /// The file that failed.
+        path: PathBuf,
+        /// The part being read when the limit was reached.
+        part: String,
+        /// The per-document limit, in bytes.
+        limit: u64,
+    },
+    /// Writing the extracted text to its destination failed.
+    #[error("writing the text failed: {source}")]
+    Write {
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+    /// The work was cancelled through its
+    /// [`CancellationToken`](crate::CancellationToken). Text already
+    /// written to a writer stays written, and is truncated.
+    #[error("cancelled")]
+    Cancelled,
+    /// A file pattern is not a valid glob pattern.
+    #[error("invalid file pattern {pattern}: {source}")]
+    Pattern {
+        /// The pattern as given.
+        pattern: String,
+        /// What is wrong with it.
+        source: glob::PatternError,
+    },
+    /// A file named in a list of files does not exist; see
+    /// [`read_file_list`](crate::read_file_list).
+    #[error("{list}, line {line}: {path} does not exist", list = .list.display(), path = .path.display())]
+    ListedFileMissing {
+        /// The list.
+        list: PathBuf,
+        /// The line naming the file, counted from 1.
+        line: usize,
+        /// The file, as the line names it.
+        path: PathBuf,
+    },
+}
+
+/// An XML part that failed to parse, before it is known which file and
+/// part it came from.
+#[derive(Debug)]
+pub(crate) struct XmlError {
+    /// Byte offset into the XML where reading stopped.
+    pub position: usize,
+    pub source: quick_xml::Error,
+}
+
+impl XmlError {
+    /// Names the file and part the XML came from.
+    pub fn in_part(self, path: &Path, part: Option<&str>) -> MergeError {
+        MergeError::Xml {
+            path: path.to_path_buf(),
+            part: part.map(str::to_string),
+            position: self.position,
+            source: self.source,
+        }
+    }
+}
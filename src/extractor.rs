@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+#[cfg(feature = "cli")]
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::error::XmlError;
+use crate::numbering::Numbering;
+use crate::styles::Styles;
+#[cfg(feature = "cli")]
+use crate::{write_text_from_file, ExtractOptions, MergeError};
+
+/// How many different styles parts, and numbering parts, a cache keeps.
+/// Documents made from the same few templates share them; past this, parts
+/// are parsed for each document as they would be without a cache.
+const MAX_CACHED_PARTS: usize = 64;
+
+/// Styles and numbering parts already parsed, by their XML, for documents
+/// that share a template and so the same parts.
+#[derive(Debug, Default)]
+pub(crate) struct PartCache {
+    styles: Mutex<HashMap<String, Arc<Styles>>>,
+    numbering: Mutex<HashMap<String, Arc<Numbering>>>,
+}
+
+impl PartCache {
+    /// The parsed styles part with this XML.
+    pub fn styles(&self, xml: String) -> Result<Arc<Styles>, XmlError> {
+        cached(&self.styles, xml, Styles::parse)
+    }
+
+    /// The parsed numbering part with this XML.
+    pub fn numbering(&self, xml: String) -> Result<Arc<Numbering>, XmlError> {
+        cached(&self.numbering, xml, Numbering::parse)
+    }
+}
+
+/// Looks a part up by its XML, parsing and keeping it if it is new.
+fn cached<T>(
+    parts: &Mutex<HashMap<String, Arc<T>>>,
+    xml: String,
+    parse: impl FnOnce(&str) -> Result<T, XmlError>,
+) -> Result<Arc<T>, XmlError> {
+    // A panic elsewhere while the lock was held leaves the map as it was.
+    if let Some(part) = parts.lock().unwrap_or_else(PoisonError::into_inner).get(&xml) {
+        return Ok(part.clone());
+    }
+    // Parsing happens outside the lock; two threads meeting a new part at
+    // once both parse it, and the second keeps its own.
+    let part = Arc::new(parse(&xml)?);
+    let mut parts = parts.lock().unwrap_or_else(PoisonError::into_inner);
+    if parts.len() < MAX_CACHED_PARTS {
+        parts.insert(xml, part.clone());
+    }
+    Ok(part)
+}
+
+/// Extracts the text of many files with the same options, parsing the
+/// styles and numbering parts each template has once rather than once per
+/// document. The documents a template makes share those parts, and in
+/// large ones they take longer to parse than short bodies do.
+///
+/// An `Extractor` is `Send` and `Sync`, so one can serve the threads of a
+/// pool:
+///
+/// ```no_run
+/// use mddocmrg::{ExtractOptions, Extractor};
+///
+/// let extractor = Extractor::new(ExtractOptions::default());
+/// for path in ["letter-1.docx", "letter-2.docx"] {
+///     println!("{}", extractor.extract(path)?);
+/// }
+/// # Ok::<(), mddocmrg::MergeError>(())
+/// ```
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone)]
+pub struct Extractor {
+    options: ExtractOptions,
+    /// Shared by clones, so each thread may take its own.
+    cache: Arc<PartCache>,
+}
+
+#[cfg(feature = "cli")]
+impl Extractor {
+    /// An extractor using `options`, with nothing cached yet.
+    pub fn new(options: ExtractOptions) -> Self {
+        Extractor {
+            options,
+            cache: Arc::default(),
+        }
+    }
+
+    /// The options the text is extracted with.
+    pub fn options(&self) -> &ExtractOptions {
+        &self.options
+    }
+
+    /// Extracts the text of a Word, OpenDocument or plain text file, as
+    /// [`extract_text_from_file`](crate::extract_text_from_file) does.
+    pub fn extract(&self, path: impl AsRef<Path>) -> Result<String, MergeError> {
+        let mut text = String::new();
+        write_text_from_file(
+            path.as_ref(),
+            &self.options,
+            &mut text,
+            None,
+            None,
+            Some(&self.cache),
+        )?;
+        Ok(text)
+    }
+
+    /// How many styles parts the cache holds, for tests.
+    #[cfg(test)]
+    pub(crate) fn cached_styles(&self) -> usize {
+        self.cache.styles.lock().map_or(0, |styles| styles.len())
+    }
+}
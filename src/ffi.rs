@@ -0,0 +1,274 @@
+//! C bindings, for applications in C, C++ or any language with a C foreign
+//! function interface that extract text in-process rather than running the
+//! `mddocmrg` program.
+//!
+//! The header, `include/mddocmrg.h`, is generated from this module by
+//! cbindgen when the crate is built with the `ffi` feature. Build a library
+//! to link against with:
+//!
+//! ```text
+//! cargo rustc --release --lib --no-default-features --features ffi --crate-type staticlib
+//! ```
+//!
+//! Text and error messages are returned as NUL-terminated UTF-8 strings
+//! that the caller frees with [`mdm_free_string`]. No panic unwinds into
+//! the caller: one is caught and returned as [`MdmStatus::Panicked`].
+
+use std::any::Any;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use crate::{
+    extract_text_from_bytes, CommentMode, ExtractOptions, LinkStyle, OutputFormat, RevisionMode,
+};
+
+/// How a call went.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MdmStatus {
+    /// The text was extracted.
+    Ok = 0,
+    /// The document could not be read; the error says why.
+    Failed = 1,
+    /// An argument was null where it may not be, or an option is not one
+    /// this library knows.
+    InvalidArgument = 2,
+    /// The library panicked, which is a bug in it. The error has the panic
+    /// message.
+    Panicked = 3,
+}
+
+/// Why a call failed, and the message for `out_error`.
+type Failure = (MdmStatus, String);
+
+/// Options for [`mdm_extract_text`], a subset of [`ExtractOptions`]. Start
+/// from [`mdm_default_options`], so that fields added later get their
+/// defaults. Null strings leave an option at its default; the others take
+/// the values of the command-line options of the same names.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MdmOptions {
+    /// `"text"` or `"markdown"`, as `--format`.
+    pub format: *const c_char,
+    /// `"strip"`, `"url"` or `"markdown"`, as `--links`.
+    pub links: *const c_char,
+    /// `"accept"`, `"reject"` or `"all"`, as `--revisions`.
+    pub revisions: *const c_char,
+    /// `"skip"`, `"inline"` or `"end"`, as `--comments`.
+    pub comments: *const c_char,
+    /// Text written after every paragraph.
+    pub paragraph_separator: *const c_char,
+    /// Write the headers of the document before its text and the footers
+    /// after it.
+    pub include_headers_footers: bool,
+    /// Extract text formatted as hidden.
+    pub include_hidden: bool,
+}
+
+/// The options [`mdm_extract_text`] uses when given none.
+#[no_mangle]
+pub extern "C" fn mdm_default_options() -> MdmOptions {
+    let defaults = ExtractOptions::default();
+    MdmOptions {
+        format: ptr::null(),
+        links: ptr::null(),
+        revisions: ptr::null(),
+        comments: ptr::null(),
+        paragraph_separator: ptr::null(),
+        include_headers_footers: defaults.include_headers_footers,
+        include_hidden: defaults.include_hidden,
+    }
+}
+
+/// Extracts the text of the DOCX document in the `len` bytes at `data`.
+///
+/// On success, sets `*out_text` to the text and returns
+/// [`MdmStatus::Ok`]. Otherwise sets `*out_text` to null and, if
+/// `out_error` is not null, `*out_error` to why. Free both with
+/// [`mdm_free_string`]. NUL characters in the text, which C strings cannot
+/// hold, are left out.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, or may be null if `len` is
+/// 0. `opts` must be null or point to valid options, whose strings are null
+/// or NUL-terminated. `out_text` must point to a writable `char *`, and
+/// `out_error` be null or do so.
+#[no_mangle]
+pub unsafe extern "C" fn mdm_extract_text(
+    data: *const u8,
+    len: usize,
+    opts: *const MdmOptions,
+    out_text: *mut *mut c_char,
+    out_error: *mut *mut c_char,
+) -> MdmStatus {
+    if !out_error.is_null() {
+        *out_error = ptr::null_mut();
+    }
+    if out_text.is_null() {
+        set_error(out_error, "out_text is null");
+        return MdmStatus::InvalidArgument;
+    }
+    *out_text = ptr::null_mut();
+    let result = guarded(|| {
+        if data.is_null() && len > 0 {
+            return Err((MdmStatus::InvalidArgument, "data is null".to_string()));
+        }
+        let options = match opts.as_ref() {
+            Some(opts) => extract_options(opts)?,
+            None => ExtractOptions::default(),
+        };
+        let data = if len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(data, len)
+        };
+        extract_text_from_bytes(data, &options)
+            .map_err(|error| (MdmStatus::Failed, error.to_string()))
+    });
+    match result {
+        Ok(text) => {
+            *out_text = c_string(text).into_raw();
+            MdmStatus::Ok
+        }
+        Err((status, message)) => {
+            set_error(out_error, &message);
+            status
+        }
+    }
+}
+
+/// Frees a string returned by this library. Does nothing with null.
+///
+/// # Safety
+///
+/// `s` must be null or a string this library returned, not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn mdm_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Runs `f`, turning a panic into an error rather than letting it unwind
+/// into C.
+fn guarded<T>(f: impl FnOnce() -> Result<T, Failure>) -> Result<T, Failure> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err((MdmStatus::Panicked, panic_message(payload))))
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    };
+    format!("panicked: {}", message)
+}
+
+/// Reads the options given through C.
+///
+/// # Safety
+///
+/// The strings of `opts` must be null or NUL-terminated.
+unsafe fn extract_options(opts: &MdmOptions) -> Result<ExtractOptions, Failure> {
+    let mut options = ExtractOptions::default();
+    if let Some(name) = option_str(opts.format, "format")? {
+        options.format = parsed(OutputFormat::from_name(name), "format", name)?;
+    }
+    if let Some(name) = option_str(opts.links, "links")? {
+        options.link_style = parsed(LinkStyle::from_name(name), "links", name)?;
+    }
+    if let Some(name) = option_str(opts.revisions, "revisions")? {
+        options.revisions = parsed(RevisionMode::from_name(name), "revisions", name)?;
+    }
+    if let Some(name) = option_str(opts.comments, "comments")? {
+        options.comments = parsed(CommentMode::from_name(name), "comments", name)?;
+    }
+    if let Some(separator) = option_str(opts.paragraph_separator, "paragraph_separator")? {
+        options.paragraph_separator = separator.to_string();
+    }
+    options.include_headers_footers = opts.include_headers_footers;
+    options.include_hidden = opts.include_hidden;
+    Ok(options)
+}
+
+/// The text of a string option, `None` if it is null.
+///
+/// # Safety
+///
+/// `s` must be null or NUL-terminated.
+unsafe fn option_str<'a>(s: *const c_char, option: &str) -> Result<Option<&'a str>, Failure> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    match CStr::from_ptr(s).to_str() {
+        Ok(s) => Ok(Some(s)),
+        Err(_) => Err((
+            MdmStatus::InvalidArgument,
+            format!("{} is not UTF-8", option),
+        )),
+    }
+}
+
+fn parsed<T>(value: Option<T>, option: &str, name: &str) -> Result<T, Failure> {
+    value.ok_or_else(|| {
+        (
+            MdmStatus::InvalidArgument,
+            format!("unknown {}: {}", option, name),
+        )
+    })
+}
+
+/// A C string of `text`, without the NUL characters it cannot hold.
+fn c_string(text: String) -> CString {
+    CString::new(text).unwrap_or_else(|error| {
+        let mut bytes = error.into_vec();
+        bytes.retain(|&byte| byte != 0);
+        CString::new(bytes).expect("NUL characters were removed")
+    })
+}
+
+/// Sets `*out_error` to `message`, if the caller asked for errors.
+///
+/// # Safety
+///
+/// `out_error` must be null or point to a writable `char *`.
+unsafe fn set_error(out_error: *mut *mut c_char, message: &str) {
+    if !out_error.is_null() {
+        *out_error = c_string(message.to_string()).into_raw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panics_become_errors() {
+        let result: Result<(), _> = guarded(|| panic!("part {} missing", 3));
+        assert_eq!(
+            result,
+            Err((MdmStatus::Panicked, "panicked: part 3 missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unknown_option_is_invalid_argument() {
+        let mut options = mdm_default_options();
+        options.format = c"html".as_ptr();
+        let mut text = ptr::null_mut();
+        let mut error = ptr::null_mut();
+        unsafe {
+            let status = mdm_extract_text(ptr::null(), 0, &options, &mut text, &mut error);
+            assert_eq!(status, MdmStatus::InvalidArgument);
+            assert!(text.is_null());
+            assert_eq!(CStr::from_ptr(error).to_str(), Ok("unknown format: html"));
+            mdm_free_string(error);
+        }
+    }
+}
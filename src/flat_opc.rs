@@ -0,0 +1,146 @@
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::attribute;
+use crate::error::{MergeError, XmlError};
+
+/// A part embedded in a Flat OPC document.
+struct FlatPart {
+    /// Part name without the leading slash, e.g. `word/document.xml`.
+    name: String,
+    content_type: Option<String>,
+    xml: String,
+}
+
+/// Whether a file starting with `bytes` is XML rather than a zip package.
+/// Only XML can start with `<`, after an optional byte order mark.
+pub fn looks_like_xml(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    bytes
+        .iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        .is_some_and(|&byte| byte == b'<')
+}
+
+/// Rebuilds the zip package that a Flat OPC document stands for. Word
+/// saves these as "Word XML Document": the whole package in one XML file,
+/// each part in a `pkg:part`. XML parts (`pkg:xmlData`) are copied as they
+/// are; binary parts (`pkg:binaryData`), such as images and fonts, hold
+/// nothing extraction reads and are left out. The content types of the
+/// parts go into a `[Content_Types].xml` as in any package.
+///
+/// Returns `None` if the XML is not a Flat OPC package. Errors name `path`,
+/// the file the XML was read from.
+pub fn to_zip(xml: &str, path: &Path) -> Result<Option<Vec<u8>>, MergeError> {
+    let mut reader = Reader::from_str(xml);
+    let error = |reader: &Reader<&[u8]>, source| {
+        XmlError {
+            position: reader.buffer_position(),
+            source,
+        }
+        .in_part(path, None)
+    };
+    let mut parts = Vec::new();
+    let mut part: Option<(String, Option<String>)> = None;
+    let mut seen_root = false;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|source| error(&reader, source))?;
+        match event {
+            Event::Start(ref e) if !seen_root => {
+                if e.name().as_ref() != b"pkg:package" {
+                    return Ok(None);
+                }
+                seen_root = true;
+            }
+            Event::Empty(_) if !seen_root => return Ok(None),
+            Event::Start(ref e) => match e.name().as_ref() {
+                b"pkg:part" => {
+                    part = attribute(e, b"pkg:name").map(|name| {
+                        let name = name.trim_start_matches('/').to_string();
+                        (name, attribute(e, b"pkg:contentType"))
+                    });
+                }
+                b"pkg:xmlData" => {
+                    let content = reader
+                        .read_text(e.name())
+                        .map_err(|source| error(&reader, source))?;
+                    if let Some((name, content_type)) = part.take() {
+                        parts.push(FlatPart {
+                            name,
+                            content_type,
+                            xml: content.trim().to_string(),
+                        });
+                    }
+                }
+                b"pkg:binaryData" => {
+                    reader
+                        .read_to_end(e.name())
+                        .map_err(|source| error(&reader, source))?;
+                }
+                _ => {}
+            },
+            Event::End(ref e) if e.name().as_ref() == b"pkg:part" => part = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !seen_root {
+        return Ok(None);
+    }
+
+    let zip_error = |source| MergeError::Zip {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut content_types = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    );
+    for part in &parts {
+        if let Some(content_type) = &part.content_type {
+            content_types.push_str(&format!(
+                r#"<Override PartName="/{}" ContentType="{}"/>"#,
+                escape(&part.name),
+                escape(content_type)
+            ));
+        }
+        zip.start_file(part.name.as_str(), options)
+            .map_err(zip_error)?;
+        zip.write_all(part.xml.as_bytes())
+            .map_err(|e| zip_error(e.into()))?;
+    }
+    content_types.push_str("</Types>");
+    zip.start_file("[Content_Types].xml", options)
+        .map_err(zip_error)?;
+    zip.write_all(content_types.as_bytes())
+        .map_err(|e| zip_error(e.into()))?;
+    Ok(Some(zip.finish().map_err(zip_error)?.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_like_xml() {
+        assert!(looks_like_xml(b"\xEF\xBB\xBF<?xml"));
+        assert!(looks_like_xml(b"\r\n  <pkg:pa"));
+        assert!(!looks_like_xml(b"PK\x03\x04"));
+        assert!(!looks_like_xml(b""));
+    }
+
+    #[test]
+    fn test_other_xml_is_not_flat_opc() {
+        let xml = r#"<?xml version="1.0"?><w:wordDocument><w:body/></w:wordDocument>"#;
+        assert!(to_zip(xml, Path::new("other.xml")).unwrap().is_none());
+    }
+}
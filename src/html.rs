@@ -0,0 +1,293 @@
+use std::io::Write;
+use std::path::Path;
+
+use crate::toc::Slugs;
+use crate::{
+    parse_file, Block, Document, ErrorPolicy, ExtractOptions, FileStats, MergeError, MergeOptions,
+    MergeReport, ProgressCallback, ProgressEvent, Run, SkippedFile, TextStats,
+};
+
+/// Writes the files as one standalone HTML document to `writer`, each in a
+/// `<section>` whose id comes from its name, such as
+/// `<section id="annual-report">` for `Annual Report.docx`, so that links
+/// can point at it. Headings become `<h1>` to `<h6>`, lists `<ul>` and
+/// `<ol>`, and tables and links `<table>` and `<a>`; other paragraphs are
+/// `<p>`. Files other than Word documents have a paragraph per line.
+///
+/// `css`, if given, is the stylesheet put in the document's `<style>`.
+/// Files are read one at a time, and skipped as
+/// [`on_error`](MergeOptions::on_error) says; the separator, headers and
+/// footers are not used. Nothing is written until every file is read.
+/// Returns what the merge reports, as
+/// [`merge_docx_files_to_writer`](crate::merge_docx_files_to_writer) does.
+pub fn merge_files_to_html_writer<W: Write>(
+    paths: &[impl AsRef<Path>],
+    writer: &mut W,
+    options: &MergeOptions,
+    css: Option<&str>,
+) -> Result<MergeReport, MergeError> {
+    let mut report = MergeReport::default();
+    let mut slugs = Slugs::default();
+    let mut titles = Vec::new();
+    let mut body = String::new();
+    for (index, path) in paths.iter().enumerate() {
+        let path = path.as_ref();
+        options.extract.check_cancelled()?;
+        progress(options, ProgressEvent::FileStarted {
+            index,
+            total: paths.len(),
+            path,
+        });
+        let stem = path
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+        let id = match slugs.next(&stem) {
+            slug if slug.is_empty() => format!("file-{}", index + 1),
+            slug => slug,
+        };
+        let start = body.len();
+        body.push_str(&format!("<section id=\"{}\">\n", escape(&id)));
+        match parse_file(path, &options.extract) {
+            Ok(document) => {
+                write_blocks(&mut body, &document.blocks, &options.extract);
+                body.push_str("</section>\n");
+                progress(options, ProgressEvent::FileFinished {
+                    index,
+                    bytes_extracted: (body.len() - start) as u64,
+                });
+                if options.stats {
+                    let mut stats = TextStats::default();
+                    stats.add_lines(&document.to_plain_text());
+                    report.stats.push(FileStats {
+                        path: path.to_path_buf(),
+                        stats,
+                    });
+                }
+                titles.push(title(&document, stem));
+            }
+            Err(error) => {
+                progress(options, ProgressEvent::FileFailed {
+                    index,
+                    error: &error,
+                });
+                if options.on_error == ErrorPolicy::FailFast
+                    || matches!(error, MergeError::Cancelled)
+                {
+                    return Err(error);
+                }
+                if options.error_markers {
+                    body.push_str(&format!(
+                        "<p>{}</p>\n</section>\n",
+                        escape(&format!("[failed to extract: {}]", error))
+                    ));
+                } else {
+                    body.truncate(start);
+                }
+                report.skipped.push(SkippedFile {
+                    path: path.to_path_buf(),
+                    error,
+                });
+            }
+        }
+    }
+    let title = match titles.as_slice() {
+        [title] => title.as_str(),
+        _ => "Merged documents",
+    };
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\"/>\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(title)));
+    if let Some(css) = css {
+        // `</` would end the element early; `<\/` means the same in CSS.
+        html.push_str(&format!("<style>\n{}\n</style>\n", css.trim().replace("</", "<\\/")));
+    }
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&body);
+    html.push_str("</body>\n</html>\n");
+    writer
+        .write_all(html.as_bytes())
+        .and_then(|()| writer.flush())
+        .map_err(|source| MergeError::Write { source })?;
+    Ok(report)
+}
+
+/// Tells the callback of the options, if any, of progress.
+fn progress(options: &MergeOptions, event: ProgressEvent) {
+    if let Some(ProgressCallback(on_progress)) = &options.on_progress {
+        on_progress(event);
+    }
+}
+
+/// The title in a document's properties, or else `stem`.
+fn title(document: &Document, stem: String) -> String {
+    document
+        .metadata
+        .title
+        .clone()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or(stem)
+}
+
+/// Writes blocks as HTML. Lists are nested by level, each list inside the
+/// item before it.
+fn write_blocks(html: &mut String, blocks: &[Block], options: &ExtractOptions) {
+    // Whether each open list is numbered, outermost first. Each has an
+    // item open.
+    let mut lists = Vec::new();
+    for block in blocks {
+        match block {
+            Block::List {
+                level,
+                numbered,
+                items,
+            } => {
+                for item in items {
+                    start_item(html, &mut lists, *level, *numbered);
+                    write_runs(html, item);
+                }
+            }
+            Block::Paragraph {
+                heading_level,
+                runs,
+                ..
+            } => {
+                end_lists(html, &mut lists, 0);
+                if runs.iter().all(|run| run.text.trim().is_empty()) {
+                    continue;
+                }
+                let tag = match heading_level {
+                    Some(level) => format!("h{}", (level + options.heading_offset).clamp(1, 6)),
+                    None => "p".to_string(),
+                };
+                html.push_str(&format!("<{}>", tag));
+                write_runs(html, runs);
+                html.push_str(&format!("</{}>\n", tag));
+            }
+            Block::Table { rows } => {
+                end_lists(html, &mut lists, 0);
+                html.push_str("<table>\n");
+                for row in rows {
+                    html.push_str("<tr>\n");
+                    for cell in row {
+                        html.push_str("<td>\n");
+                        write_blocks(html, cell, options);
+                        html.push_str("</td>\n");
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+        }
+    }
+    end_lists(html, &mut lists, 0);
+}
+
+/// Starts a list item at `level`, closing the items and lists it does not
+/// belong to and opening the lists it needs.
+fn start_item(html: &mut String, lists: &mut Vec<bool>, level: usize, numbered: bool) {
+    end_lists(html, lists, level + 1);
+    if lists.len() == level + 1 {
+        if lists[level] == numbered {
+            html.push_str("</li>\n");
+        } else {
+            end_lists(html, lists, level);
+        }
+    }
+    while lists.len() <= level {
+        html.push_str(if numbered { "<ol>\n" } else { "<ul>\n" });
+        lists.push(numbered);
+        // A level skipped has an item of its own to hold the next.
+        if lists.len() <= level {
+            html.push_str("<li>\n");
+        }
+    }
+    html.push_str("<li>");
+}
+
+/// Closes the open items and lists deeper than `depth`.
+fn end_lists(html: &mut String, lists: &mut Vec<bool>, depth: usize) {
+    while lists.len() > depth {
+        let numbered = lists.pop().unwrap_or_default();
+        html.push_str(if numbered { "</li>\n</ol>\n" } else { "</li>\n</ul>\n" });
+    }
+}
+
+/// Writes the text of runs, those in the same link inside one `<a>`.
+fn write_runs(html: &mut String, runs: &[Run]) {
+    let mut link: Option<&str> = None;
+    for run in runs {
+        if run.link.as_deref() != link {
+            if link.is_some() {
+                html.push_str("</a>");
+            }
+            link = run.link.as_deref();
+            if let Some(target) = link {
+                html.push_str(&format!("<a href=\"{}\">", escape(target)));
+            }
+        }
+        html.push_str(&escape(&run.text).replace('\n', "<br/>\n"));
+    }
+    if link.is_some() {
+        html.push_str("</a>");
+    }
+}
+
+/// Escapes text for HTML, in content and attribute values alike. Control
+/// characters, such as the form feed of a page break, have no place in
+/// HTML and are left out.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\t' | '\n' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>\x0c"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_lists_nest_inside_items() {
+        let item = |text: &str| {
+            vec![Run {
+                text: text.to_string(),
+                ..Run::default()
+            }]
+        };
+        let list = |level: usize, numbered: bool, items: Vec<Vec<Run>>| Block::List {
+            level,
+            numbered,
+            items,
+        };
+        // The list at level 2 skips level 1, which gets an item to hold it.
+        let blocks = [
+            list(0, true, vec![item("One"), item("Two")]),
+            list(2, false, vec![item("Deep")]),
+            list(0, false, vec![item("Bullet")]),
+        ];
+        let mut html = String::new();
+        write_blocks(&mut html, &blocks, &ExtractOptions::default());
+        assert_eq!(
+            html,
+            "<ol>\n<li>One</li>\n<li>Two<ul>\n<li>\n<ul>\n<li>Deep</li>\n</ul>\n</li>\n</ul>\n</li>\n</ol>\n\
+             <ul>\n<li>Bullet</li>\n</ul>\n"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::borrow::Cow;
+
+/// The format characters (general category Cf): zero-width spaces and
+/// joiners, bidirectional controls, byte order marks, soft hyphens and the
+/// like.
+const FORMAT_CHARACTERS: &[(char, char)] = &[
+    ('\u{00AD}', '\u{00AD}'),
+    ('\u{0600}', '\u{0605}'),
+    ('\u{061C}', '\u{061C}'),
+    ('\u{06DD}', '\u{06DD}'),
+    ('\u{070F}', '\u{070F}'),
+    ('\u{0890}', '\u{0891}'),
+    ('\u{08E2}', '\u{08E2}'),
+    ('\u{180E}', '\u{180E}'),
+    ('\u{200B}', '\u{200F}'),
+    ('\u{202A}', '\u{202E}'),
+    ('\u{2060}', '\u{2064}'),
+    ('\u{2066}', '\u{206F}'),
+    ('\u{FEFF}', '\u{FEFF}'),
+    ('\u{FFF9}', '\u{FFFB}'),
+    ('\u{110BD}', '\u{110BD}'),
+    ('\u{110CD}', '\u{110CD}'),
+    ('\u{13430}', '\u{1343F}'),
+    ('\u{1BCA0}', '\u{1BCA3}'),
+    ('\u{1D173}', '\u{1D17A}'),
+    ('\u{E0001}', '\u{E0001}'),
+    ('\u{E0020}', '\u{E007F}'),
+];
+
+/// Whether a character is invisible: a control (Cc) or format (Cf)
+/// character other than the newline and the tab.
+pub fn is_invisible(c: char) -> bool {
+    if c == '\n' || c == '\t' {
+        return false;
+    }
+    c.is_control()
+        || FORMAT_CHARACTERS
+            .iter()
+            .any(|&(first, last)| (first..=last).contains(&c))
+}
+
+/// Removes invisible characters from text, returning the text and how many
+/// characters were removed.
+pub fn strip_invisible(text: &str) -> (Cow<'_, str>, usize) {
+    let removed = text.chars().filter(|&c| is_invisible(c)).count();
+    if removed == 0 {
+        return (Cow::Borrowed(text), 0);
+    }
+    let stripped = text.chars().filter(|&c| !is_invisible(c)).collect();
+    (Cow::Owned(stripped), removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_invisible() {
+        let (text, removed) =
+            strip_invisible("zero\u{200B}width\u{FEFF} and\u{202E} \u{7}bell\n\ttab");
+        assert_eq!(text, "zerowidth and bell\n\ttab");
+        assert_eq!(removed, 4);
+        let (text, removed) = strip_invisible("visible é");
+        assert!(matches!(text, Cow::Borrowed(_)));
+        assert_eq!(removed, 0);
+    }
+}
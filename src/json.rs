@@ -0,0 +1,221 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::template::format_time;
+use crate::{
+    chaptered, extract_each, extract_metadata, ExtractOptions, MergeError, MergeOptions, MergeReport,
+    Merger, Metadata, TextStats, Warning,
+};
+
+/// The text of several files, each with what is known of it, as
+/// `mddocmrg merge --format json` writes it; see [`merge_files_to_json`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonOutput {
+    /// The program that wrote it: `mddocmrg`.
+    pub tool: String,
+    /// The version of the program.
+    pub version: String,
+    /// When it was written, in UTC, e.g. `2024-03-01T09:30:00Z`.
+    pub generated: String,
+    /// The files, in the order given.
+    pub files: Vec<JsonFile>,
+    /// The files left out under
+    /// [`ErrorPolicy::SkipAndReport`](crate::ErrorPolicy::SkipAndReport).
+    pub skipped: Vec<JsonSkippedFile>,
+}
+
+/// A file of a [`JsonOutput`], or a line of
+/// [`merge_files_to_json_lines`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonFile {
+    /// The file, as given.
+    pub path: PathBuf,
+    /// The title in its properties, or else its name without extension.
+    pub title: String,
+    /// Its properties, or `None` for a file that has none, such as a text
+    /// file.
+    pub metadata: Option<Metadata>,
+    /// Its text, as a merge of the file alone writes it, header and footer
+    /// included.
+    pub text: String,
+    /// The counts of its text.
+    pub stats: TextStats,
+    /// What extracting it warned of.
+    pub warnings: Vec<Warning>,
+}
+
+impl JsonFile {
+    /// A file as a merge of it alone wrote it, or `None` if the merge
+    /// skipped it.
+    fn from_merge(
+        path: &Path,
+        text: Option<String>,
+        report: &MergeReport,
+        options: &ExtractOptions,
+    ) -> Option<Self> {
+        let text = text.filter(|_| report.skipped.is_empty())?;
+        let metadata = extract_metadata(path, options).ok();
+        let title = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.title.clone())
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| {
+                path.file_stem()
+                    .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())
+            });
+        Some(JsonFile {
+            path: path.to_path_buf(),
+            title,
+            metadata,
+            text,
+            stats: report.stats.first().map_or_else(TextStats::default, |file| file.stats),
+            warnings: report.warnings.clone(),
+        })
+    }
+}
+
+/// A file a [`JsonOutput`] left out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonSkippedFile {
+    /// The file, as given.
+    pub path: PathBuf,
+    /// Why it could not be extracted.
+    pub error: String,
+}
+
+impl JsonOutput {
+    /// An output with no files yet, written now.
+    pub(crate) fn new() -> Self {
+        JsonOutput {
+            tool: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            generated: format_time(SystemTime::now()),
+            files: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Adds a file as a merge of it alone wrote it, `text` being `None`
+    /// if the merge skipped it without a marker. A file skipped is listed
+    /// as skipped, whether or not it has a marker.
+    pub(crate) fn add(
+        &mut self,
+        path: &Path,
+        text: Option<String>,
+        report: &MergeReport,
+        options: &ExtractOptions,
+    ) {
+        self.skipped
+            .extend(report.skipped.iter().map(|file| JsonSkippedFile {
+                path: file.path.clone(),
+                error: file.error.to_string(),
+            }));
+        self.files
+            .extend(JsonFile::from_merge(path, text, report, options));
+    }
+
+    /// Writes the output as JSON, indented if `pretty`.
+    pub fn write_to(&self, writer: impl Write, pretty: bool) -> io::Result<()> {
+        if pretty {
+            serde_json::to_writer_pretty(writer, self)?;
+        } else {
+            serde_json::to_writer(writer, self)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the text of each file with `options`, as
+/// [`merge_files_with_options`](crate::merge_files_with_options) does, but keeps the files apart, each with its title, properties,
+/// counts and warnings, for programs that read JSON. The separator is not
+/// used.
+pub fn merge_files_to_json(
+    paths: &[impl AsRef<Path> + Sync],
+    options: &MergeOptions,
+) -> Result<JsonOutput, MergeError> {
+    collect(paths, options).map(|(output, _)| output)
+}
+
+/// Writes the JSON of [`merge_files_to_json`] to `writer`, indented if
+/// `pretty`, and returns what the merge reports, as
+/// [`merge_docx_files_to_writer`](crate::merge_docx_files_to_writer) does.
+/// Nothing is written until every file is extracted.
+pub fn merge_files_to_json_writer<W: Write>(
+    paths: &[impl AsRef<Path> + Sync],
+    writer: &mut W,
+    options: &MergeOptions,
+    pretty: bool,
+) -> Result<MergeReport, MergeError> {
+    let (output, report) = collect(paths, options)?;
+    output
+        .write_to(&mut *writer, pretty)
+        .and_then(|()| writer.flush())
+        .map_err(|source| MergeError::Write { source })?;
+    Ok(report)
+}
+
+/// Writes a [`JsonFile`] for each file to `writer` as soon as it is
+/// extracted, on a line of its own, for programs that read JSON Lines as
+/// it comes: a merge stopped partway leaves whole lines for the files
+/// before. Files extracted at once are written in the order given, and
+/// files skipped are left out. Returns what the merge reports, as
+/// [`merge_docx_files_to_writer`](crate::merge_docx_files_to_writer) does.
+pub fn merge_files_to_json_lines<W: Write>(
+    paths: &[impl AsRef<Path> + Sync],
+    writer: &mut W,
+    options: &MergeOptions,
+) -> Result<MergeReport, MergeError> {
+    let counted = counted(options);
+    let mut report = MergeReport::default();
+    extract_each(paths, &counted, |index, path, text| {
+        let (text, merged) = Merger::alone(index, path, text, &counted, paths.len())?;
+        if let Some(file) = JsonFile::from_merge(path, text, &merged, &counted.extract) {
+            serde_json::to_writer(&mut *writer, &file)
+                .map_err(io::Error::from)
+                .and_then(|()| writer.write_all(b"\n"))
+                .and_then(|()| writer.flush())
+                .map_err(|source| MergeError::Write { source })?;
+        }
+        absorb(&mut report, merged, options.stats);
+        Ok(())
+    })?;
+    Ok(report)
+}
+
+/// The JSON of the files, and what merging them reports.
+fn collect(
+    paths: &[impl AsRef<Path> + Sync],
+    options: &MergeOptions,
+) -> Result<(JsonOutput, MergeReport), MergeError> {
+    let counted = counted(options);
+    let mut output = JsonOutput::new();
+    let mut report = MergeReport::default();
+    extract_each(paths, &counted, |index, path, text| {
+        let (text, merged) = Merger::alone(index, path, text, &counted, paths.len())?;
+        output.add(path, text, &merged, &counted.extract);
+        absorb(&mut report, merged, options.stats);
+        Ok(())
+    })?;
+    Ok((output, report))
+}
+
+/// The options, counting every file for its JSON.
+fn counted(options: &MergeOptions) -> MergeOptions {
+    let mut counted = chaptered(options).into_owned();
+    counted.stats = true;
+    counted
+}
+
+/// Adds what merging a file alone reports to what the whole merge does,
+/// the counts only if the merge asks for them.
+fn absorb(report: &mut MergeReport, mut merged: MergeReport, stats: bool) {
+    report.skipped.append(&mut merged.skipped);
+    report.warnings.append(&mut merged.warnings);
+    if stats {
+        report.stats.append(&mut merged.stats);
+    }
+}
+
@@ -0,0 +1,8689 @@
+//! Extracts plain text from Word (DOCX) and OpenDocument text documents
+//! and merges the text of several documents into one.
+//!
+//! [`extract_text_from_docx_with`] and [`merge_docx_files_with`] take an
+//! [`ExtractOptions`] that says what to include and how to write it:
+//!
+//! ```no_run
+//! # #[cfg(feature = "cli")] {
+//! use mddocmrg::{extract_text_from_docx_with, ExtractOptions};
+//!
+//! let options = ExtractOptions::new()
+//!     .strip_hyperlinks(true)
+//!     .paragraph_separator("\n\n");
+//! let text = extract_text_from_docx_with("report.docx", &options)?;
+//! # }
+//! # Ok::<(), mddocmrg::MergeError>(())
+//! ```
+//!
+//! Every function fails with a [`MergeError`] that names the file that broke.
+//! Nothing is printed: details, such as how long each file of a merge took,
+//! are logged through the [`log`](https://docs.rs/log) crate at debug level,
+//! for whatever logger the application sets up.
+//!
+//! # Features
+//!
+//! - `cli` (default): everything that reads files or expands file patterns,
+//!   merging, JSON output, and the `mddocmrg` program. It turns on `serde`.
+//! - `serde`: `Serialize` and `Deserialize` for [`Document`] and the other
+//!   types extraction returns.
+//! - `ffi`: C bindings, in [`ffi`], and their header.
+//! - `wasm`: the `wasm-bindgen` bindings of the `wasm` example.
+//!
+//! Without `cli`, text is extracted from bytes and readers with
+//! [`extract_text_from_bytes`] and [`extract_text_from_reader`], and the
+//! library builds for WebAssembly, to extract text in the browser:
+//!
+//! ```text
+//! cargo check --target wasm32-unknown-unknown --no-default-features
+//! ```
+
+#![warn(missing_docs)]
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "cli")]
+use std::fmt;
+#[cfg(feature = "cli")]
+use std::fs::File;
+use std::cell::Cell;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+#[cfg(feature = "cli")]
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "cli")]
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+#[cfg(feature = "cli")]
+use std::time::{Duration, Instant};
+use zip::read::ZipArchive;
+use zip::result::ZipError;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use glob::{MatchOptions, Pattern};
+#[cfg(feature = "cli")]
+use glob::glob;
+#[cfg(feature = "cli")]
+use rayon::prelude::*;
+#[cfg(feature = "cli")]
+use rayon::ThreadPoolBuilder;
+#[cfg(feature = "cli")]
+use walkdir::{DirEntry, WalkDir};
+
+#[cfg(feature = "cli")]
+mod cache;
+mod cancel;
+mod content_types;
+mod document;
+mod encoding;
+mod error;
+mod extractor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod flat_opc;
+mod invisible;
+mod links;
+mod math;
+mod namespaces;
+mod numbering;
+#[cfg(feature = "cli")]
+mod odt;
+mod properties;
+mod punctuation;
+mod rels;
+mod render;
+mod sink;
+#[cfg(feature = "cli")]
+mod sort;
+mod stats;
+mod styles;
+mod symbols;
+#[cfg(feature = "cli")]
+mod html;
+#[cfg(feature = "cli")]
+mod json;
+#[cfg(feature = "cli")]
+mod split;
+#[cfg(feature = "cli")]
+mod template;
+#[cfg(feature = "cli")]
+mod toc;
+mod visitor;
+mod warning;
+
+#[cfg(feature = "cli")]
+pub use cache::{CacheKey, DirCache, TextCache};
+pub use cancel::CancellationToken;
+pub use document::{Block, Document, Metadata, Run};
+pub use error::MergeError;
+#[cfg(feature = "cli")]
+pub use extractor::Extractor;
+#[cfg(feature = "cli")]
+pub use html::merge_files_to_html_writer;
+#[cfg(feature = "cli")]
+pub use json::{
+    merge_files_to_json, merge_files_to_json_lines, merge_files_to_json_writer, JsonFile, JsonOutput,
+    JsonSkippedFile,
+};
+pub use links::{Hyperlink, LinkKind};
+pub use properties::PropertyValue;
+#[cfg(feature = "cli")]
+pub use sort::SortOrder;
+#[cfg(feature = "cli")]
+pub use split::{split_files, SplitFile, SplitOptions, SplitReport};
+pub use stats::TextStats;
+#[cfg(feature = "cli")]
+pub use toc::with_table_of_contents;
+pub use visitor::{CellInfo, ControlInfo, DocxVisitor, LinkInfo, ParagraphInfo, RunInfo, SectionInfo};
+pub use warning::Warning;
+use error::XmlError;
+#[cfg(feature = "cli")]
+use template::{chapter_heading, render_file_template};
+use extractor::PartCache;
+use content_types::ContentTypes;
+#[cfg(feature = "cli")]
+use document::DocumentBuilder;
+#[cfg(feature = "cli")]
+use links::LinkCollector;
+use math::Equation;
+use namespaces::WordReader;
+use numbering::{ListCounters, Numbering};
+use rels::{resolve_part_name, Relationships};
+use render::TextRenderer;
+#[cfg(feature = "cli")]
+use sink::{CountingSink, IoSink};
+use sink::{TextSink, TextWriter};
+use styles::Styles;
+use visitor::Visit;
+
+/// How hyperlinks (`w:hyperlink` elements and HYPERLINK fields) are written.
+/// The target is a URL, or `#name` for links to a bookmark in the document.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LinkStyle {
+    /// Keep only the visible link text.
+    Strip,
+    /// Follow the link text with its target: `text <https://...>`.
+    #[default]
+    Url,
+    /// Render a Markdown link: `[text](https://...)`.
+    Markdown,
+    /// A custom template in which `{text}` and `{target}` are replaced.
+    Template(String),
+}
+
+impl LinkStyle {
+    /// Parses a `--links` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "strip" => Some(LinkStyle::Strip),
+            "url" => Some(LinkStyle::Url),
+            "markdown" => Some(LinkStyle::Markdown),
+            _ => None,
+        }
+    }
+
+    /// `Strip` if `strip` is true, otherwise the default style.
+    fn stripped_if(strip: bool) -> Self {
+        if strip {
+            LinkStyle::Strip
+        } else {
+            LinkStyle::default()
+        }
+    }
+
+    /// Renders a link, or returns `None` to leave the text as it is.
+    fn render(&self, text: &str, target: &str) -> Option<String> {
+        match self {
+            LinkStyle::Strip => None,
+            LinkStyle::Url => Some(format!("{} <{}>", text, target)),
+            LinkStyle::Markdown => Some(format!("[{}]({})", text, target)),
+            LinkStyle::Template(template) => {
+                Some(template.replace("{target}", target).replace("{text}", text))
+            }
+        }
+    }
+}
+
+/// How tracked changes (revisions) in a document are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RevisionMode {
+    /// Extract the text as if every change had been accepted: insertions are
+    /// kept and deletions dropped.
+    #[default]
+    Accept,
+    /// Extract the text as it was before the changes: insertions are dropped
+    /// and deletions kept.
+    Reject,
+    /// Extract inserted and deleted text alike, as stored in the file.
+    All,
+}
+
+impl RevisionMode {
+    /// Parses a `--revisions` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "accept" => Some(RevisionMode::Accept),
+            "reject" => Some(RevisionMode::Reject),
+            "all" => Some(RevisionMode::All),
+            _ => None,
+        }
+    }
+
+    /// Whether the content of a revision element is left out. Moved text
+    /// has a source (`w:moveFrom`) and destination (`w:moveTo`) copy, and
+    /// exactly one of them is kept unless showing everything.
+    fn removes(self, element: &[u8]) -> bool {
+        match self {
+            RevisionMode::Accept => matches!(element, b"w:del" | b"w:moveFrom"),
+            RevisionMode::Reject => matches!(element, b"w:ins" | b"w:moveTo"),
+            RevisionMode::All => false,
+        }
+    }
+}
+
+/// Whether and where reviewer comments from `word/comments.xml` are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommentMode {
+    /// Leave comments out.
+    #[default]
+    Skip,
+    /// Write each comment where it is anchored: `[comment by Alice: ...]`.
+    Inline,
+    /// List all comments, with their dates, after the document text.
+    End,
+}
+
+impl CommentMode {
+    /// Parses a `--comments` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(CommentMode::Skip),
+            "inline" => Some(CommentMode::Inline),
+            "end" => Some(CommentMode::End),
+            _ => None,
+        }
+    }
+}
+
+/// The markup of the extracted text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Plain text.
+    #[default]
+    Text,
+    /// Markdown: headings are marked with `#`, lists with `-` and `1.`,
+    /// tables become pipe tables, bold and italic runs are marked with `**`
+    /// and `*`, and characters Markdown would read as markup are escaped.
+    /// Links in the [`Url`](LinkStyle::Url) style become Markdown links,
+    /// and the default page break a rule, `---`.
+    Markdown,
+}
+
+impl OutputFormat {
+    /// Parses a format name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" | "txt" => Some(OutputFormat::Text),
+            "markdown" | "md" => Some(OutputFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// The text written after every paragraph in this format: a line
+    /// break in plain text, and a blank line in Markdown, where lines that
+    /// follow each other make one paragraph.
+    pub fn paragraph_separator(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "\n",
+            OutputFormat::Markdown => "\n\n",
+        }
+    }
+
+    /// The usual extension of files in this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Markdown => "md",
+        }
+    }
+}
+
+/// What merged table cells contribute to the cells they cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergedCells {
+    /// Covered cells are empty.
+    #[default]
+    Empty,
+    /// Covered cells repeat the merged cell's text.
+    Repeat,
+}
+
+impl MergedCells {
+    /// Parses a name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "empty" => Some(MergedCells::Empty),
+            "repeat" => Some(MergedCells::Repeat),
+            _ => None,
+        }
+    }
+}
+
+/// What becomes of blank lines in a document's text, such as those from
+/// empty paragraphs used for spacing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlankLines {
+    /// Keep every blank line.
+    #[default]
+    Keep,
+    /// Reduce each run of blank lines to one.
+    Collapse,
+    /// Remove blank lines.
+    Strip,
+}
+
+impl BlankLines {
+    /// Parses a policy name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "keep" => Some(BlankLines::Keep),
+            "collapse" => Some(BlankLines::Collapse),
+            "strip" => Some(BlankLines::Strip),
+            _ => None,
+        }
+    }
+
+    /// Applies the policy to a text. Lines of only whitespace count as
+    /// blank.
+    #[cfg(feature = "cli")]
+    fn apply(self, text: String) -> String {
+        if self == BlankLines::Keep {
+            return text;
+        }
+        let mut lines: Vec<&str> = Vec::new();
+        for line in text.split('\n') {
+            let blank = line.trim().is_empty();
+            let after_blank = lines.last().is_some_and(|last| last.trim().is_empty());
+            if blank && (self == BlankLines::Strip || after_blank) {
+                continue;
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+/// What is written for symbols (`w:sym`) without a Unicode equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnmappedSymbols {
+    /// A `[symbol]` placeholder.
+    #[default]
+    Placeholder,
+    /// The character code as it is, usually in the private use area.
+    Raw,
+}
+
+impl UnmappedSymbols {
+    /// Parses a name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "placeholder" => Some(UnmappedSymbols::Placeholder),
+            "raw" => Some(UnmappedSymbols::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// How phonetic guides (ruby, `w:ruby`) over East Asian text are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RubyMode {
+    /// Only the base text.
+    #[default]
+    Base,
+    /// The base text followed by the guide in parentheses: `漢字(かんじ)`.
+    Annotate,
+}
+
+impl RubyMode {
+    /// Parses a mode name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "base" => Some(RubyMode::Base),
+            "annotate" => Some(RubyMode::Annotate),
+            _ => None,
+        }
+    }
+}
+
+/// How equations (`m:oMath`) are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EquationMode {
+    /// Replace each equation with an `[equation]` placeholder.
+    Skip,
+    /// A best-effort linear form: `x^2`, `(a+b)/2`, `√x`.
+    #[default]
+    Linear,
+}
+
+impl EquationMode {
+    /// Parses a mode name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(EquationMode::Skip),
+            "linear" => Some(EquationMode::Linear),
+            _ => None,
+        }
+    }
+}
+
+/// What becomes of tables of contents: the paragraphs of a TOC field's
+/// result, and paragraphs in the TOC 1 to TOC 9 styles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TocMode {
+    /// Leave out the table of contents.
+    Skip,
+    /// Write the entries like any other paragraphs.
+    #[default]
+    Keep,
+    /// Replace the table of contents with a `[table of contents]` line.
+    Marker,
+}
+
+impl TocMode {
+    /// Parses a mode name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(TocMode::Skip),
+            "keep" => Some(TocMode::Keep),
+            "marker" => Some(TocMode::Marker),
+            _ => None,
+        }
+    }
+}
+
+/// Whether and where footnotes or endnotes are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteMode {
+    /// Leave the notes out.
+    #[default]
+    Skip,
+    /// Write each note's text where it is referenced: `[footnote 1: ...]`.
+    Inline,
+    /// Mark each reference (`[^1]` for footnotes, `[^i]` for endnotes) and
+    /// list the note texts after the document text, in reference order.
+    End,
+}
+
+impl NoteMode {
+    /// Parses a `--footnotes` or `--endnotes` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(NoteMode::Skip),
+            "inline" => Some(NoteMode::Inline),
+            "end" => Some(NoteMode::End),
+            _ => None,
+        }
+    }
+}
+
+/// Footnotes and endnotes share their handling but are numbered separately:
+/// footnotes with arabic numerals, endnotes with lowercase roman ones, as in
+/// Word's default layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteKind {
+    /// A note at the foot of the page (`word/footnotes.xml`).
+    Footnote,
+    /// A note at the end of the document (`word/endnotes.xml`).
+    Endnote,
+}
+
+impl NoteKind {
+    fn name(self) -> &'static str {
+        match self {
+            NoteKind::Footnote => "footnote",
+            NoteKind::Endnote => "endnote",
+        }
+    }
+
+    fn part_name(self) -> &'static str {
+        match self {
+            NoteKind::Footnote => "word/footnotes.xml",
+            NoteKind::Endnote => "word/endnotes.xml",
+        }
+    }
+
+    /// The label of the `number`th note of this kind.
+    fn label(self, number: usize) -> String {
+        match self {
+            NoteKind::Footnote => number.to_string(),
+            NoteKind::Endnote => roman_numeral(number).to_lowercase(),
+        }
+    }
+}
+
+/// A footnote or endnote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// Whether this is a footnote or an endnote.
+    pub kind: NoteKind,
+    /// The note's `w:id`, as used by its references.
+    pub id: String,
+    /// The note text, paragraphs separated as in the document text.
+    pub text: String,
+}
+
+/// A heading: a paragraph whose style, or a style it is based on, has an
+/// outline level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 for top-level headings, up to 9.
+    pub level: usize,
+    /// The heading text, on a single line.
+    pub text: String,
+}
+
+/// A paragraph of a document, as [`paragraphs`] yields it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paragraph {
+    /// The paragraph text as written in the document text, with its list
+    /// label or heading marker.
+    pub text: String,
+    /// The id of the paragraph's style (`w:pStyle`), if it names one.
+    pub style: Option<String>,
+    /// The level of a list paragraph, 0 for the outermost; `None` outside
+    /// lists.
+    pub list_level: Option<usize>,
+}
+
+/// A reviewer comment from `word/comments.xml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The comment's `w:id`, as used by its anchors in the document.
+    pub id: String,
+    /// The reviewer's name (`w:author`); empty if not recorded.
+    pub author: String,
+    /// When the comment was written, as an ISO 8601 timestamp.
+    pub date: Option<String>,
+    /// The comment text, paragraphs separated as in the document text.
+    pub text: String,
+    /// For replies, the id of the comment replied to (from
+    /// `word/commentsExtended.xml`).
+    pub parent: Option<String>,
+}
+
+impl Comment {
+    /// Renders the comment on a single line, e.g. `[comment by Alice: text]`.
+    fn render(&self) -> String {
+        let kind = if self.parent.is_some() { "reply" } else { "comment" };
+        format!("[{} by {}: {}]", kind, self.author, single_line(&self.text))
+    }
+}
+
+/// Options controlling how text is extracted from a DOCX file.
+#[derive(Debug, Clone)]
+pub struct ExtractOptions {
+    /// The markup of the output.
+    pub format: OutputFormat,
+    /// How hyperlinks with a known target are written.
+    pub link_style: LinkStyle,
+    /// How tracked insertions and deletions are treated.
+    pub revisions: RevisionMode,
+    /// Extract text formatted as hidden (`w:vanish`), which is skipped by default.
+    pub include_hidden: bool,
+    /// Leave out struck-through text (`w:strike` or `w:dstrike`), which
+    /// some review workflows use to mark text for removal.
+    pub skip_strikethrough: bool,
+    /// Whether and where reviewer comments are included.
+    pub comments: CommentMode,
+    /// Whether and where footnotes from `word/footnotes.xml` are included.
+    pub footnotes: NoteMode,
+    /// Whether and where endnotes from `word/endnotes.xml` are included.
+    pub endnotes: NoteMode,
+    /// Write the headers used by the document's sections before its text and
+    /// the footers after it, each once.
+    pub include_headers_footers: bool,
+    /// Write the building blocks of the glossary document (AutoText and
+    /// other reusable content, `word/glossary/document.xml`) after the text,
+    /// each under its name.
+    pub include_glossary: bool,
+    /// Include the text of text boxes and other shapes (`w:txbxContent`).
+    pub include_text_boxes: bool,
+    /// Write `[image: <description>]` for pictures, using their alt text.
+    pub image_alt_text: bool,
+    /// Write `[embedded object: <file>]` where an object embedded in the
+    /// document, such as a spreadsheet, appears (`o:OLEObject`), and
+    /// `[linked object: <file>]` for one linked to. On by default.
+    pub object_placeholders: bool,
+    /// The directory [`extract_media`] writes the pictures of documents to.
+    /// Markdown output then writes pictures as `![<alt text>](<file>)`,
+    /// with the path of the extracted file.
+    pub media_dir: Option<PathBuf>,
+    /// How equations are written.
+    pub equations: EquationMode,
+    /// What becomes of tables of contents.
+    pub toc: TocMode,
+    /// How phonetic guides are written.
+    pub ruby: RubyMode,
+    /// Write every field's instruction as `{INSTRUCTION}` ahead of its result.
+    /// Meant for debugging documents with unexpected field output.
+    pub include_field_instructions: bool,
+    /// Text written after every paragraph. Use "\n\n" to separate paragraphs
+    /// with a blank line. Defaults to that of the default format;
+    /// [`format`](Self::format()) changes it to that of the format chosen,
+    /// see [`OutputFormat::paragraph_separator`].
+    pub paragraph_separator: String,
+    /// Text written for a tab character (`w:tab`) inside a run.
+    pub tab_string: String,
+    /// Text written for a manual page break (`<w:br w:type="page"/>`).
+    /// Defaults to a form feed.
+    pub page_break: String,
+    /// Template of a marker written where Word last broke a page when it
+    /// laid out the document (`w:lastRenderedPageBreak`), with `{n}`
+    /// standing for the number of the page that starts there, e.g.
+    /// `[page {n}]`. These breaks are cached by whichever application last
+    /// saved the document and only approximate its pages. `None`, the
+    /// default, writes no markers.
+    pub page_marker: Option<String>,
+    /// Text written between sections (`w:sectPr`), on its own after the
+    /// paragraph that ends a section. `{type}` stands for how the next
+    /// section starts: `nextPage`, `continuous`, `evenPage`, `oddPage` or
+    /// `nextColumn`. Empty by default, writing nothing.
+    pub section_separator: String,
+    /// What becomes of blank lines in each document's text.
+    pub blank_lines: BlankLines,
+    /// Text written between the cells of a table row. Defaults to a tab.
+    pub table_delimiter: String,
+    /// What merged table cells write in the columns and rows they span, which
+    /// keep every row of a table at the same number of cells.
+    pub merged_cells: MergedCells,
+    /// What symbols from symbol fonts without a Unicode equivalent become.
+    pub unmapped_symbols: UnmappedSymbols,
+    /// Write non-breaking hyphens (`w:noBreakHyphen`) as ASCII `-` instead
+    /// of U+2011.
+    pub ascii_hyphens: bool,
+    /// Write curly quotes, dashes, ellipses and non-breaking spaces in the
+    /// document's text as their ASCII equivalents. Link targets are left as
+    /// they are.
+    pub normalize_punctuation: bool,
+    /// Compose the text into Unicode Normalization Form C, paragraph by
+    /// paragraph, so that `e` followed by a combining acute accent becomes
+    /// `é` as most text is written.
+    pub nfc: bool,
+    /// Remove invisible characters from the document's text: controls and
+    /// format characters such as zero-width spaces, byte order marks and
+    /// bidirectional controls. Newlines and tabs stay.
+    pub strip_invisible: bool,
+    /// Log details of each document, such as how many invisible characters
+    /// were removed from its body, at info level rather than debug. Details
+    /// are logged through the [`log`](https://docs.rs/log) crate, to
+    /// whatever logger the application sets up.
+    pub verbose: bool,
+    /// Keep optional hyphens (`w:softHyphen`) as U+00AD. They are dropped
+    /// by default, since they only mark where Word may break a word.
+    pub keep_soft_hyphens: bool,
+    /// Text written once per level before the label of a nested list item.
+    /// Markdown indents a nested item by the width of the labels of the
+    /// items it is nested in instead, as it must to nest it.
+    pub list_indent: String,
+    /// In plain text, prefix headings with their level, as in `[H2] `.
+    /// Markdown output always marks headings, with `#` to `#########`.
+    pub mark_headings: bool,
+    /// Mark headings this many levels deeper than the document has them,
+    /// as when it is a chapter under a heading of its own. 0 by default.
+    pub heading_offset: usize,
+    /// Keep only paragraphs whose style id or display name matches one of
+    /// these glob patterns (`Heading*`); all paragraphs if empty.
+    pub include_styles: Vec<String>,
+    /// Drop paragraphs whose style id or display name matches one of these
+    /// glob patterns (`TOC*`, `Caption`).
+    pub exclude_styles: Vec<String>,
+    /// Write the content of content controls (`w:sdt`) as `[<title>: <text>]`,
+    /// using the control's title or else its tag, for harvesting forms.
+    pub annotate_content_controls: bool,
+    /// Extract only the content of the bookmark with this name, from its
+    /// `w:bookmarkStart` to its `w:bookmarkEnd`.
+    pub bookmark: Option<String>,
+    /// Largest size, in bytes, that any one part may inflate to. Guards
+    /// against zip bombs; defaults to 256 MiB.
+    pub max_part_size: u64,
+    /// Largest size, in bytes, that all parts read from one document may
+    /// inflate to together. Defaults to 1 GiB.
+    pub max_total_uncompressed: u64,
+    /// Stops extraction with [`MergeError::Cancelled`] once cancelled.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl ExtractOptions {
+    /// The default options, to be changed through the methods below:
+    /// `ExtractOptions::new().strip_hyperlinks(true).paragraph_separator("\n\n")`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps only the visible text of hyperlinks, as
+    /// [`LinkStyle::Strip`], or writes them in the default style.
+    pub fn strip_hyperlinks(mut self, strip: bool) -> Self {
+        self.link_style = LinkStyle::stripped_if(strip);
+        self
+    }
+
+    /// Sets [`format`](Self::format), and the paragraph separator to the
+    /// format's own unless another was chosen.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        if self.paragraph_separator == self.format.paragraph_separator() {
+            self.paragraph_separator = format.paragraph_separator().to_string();
+        }
+        self.format = format;
+        self
+    }
+
+    /// Sets [`link_style`](Self::link_style).
+    pub fn link_style(mut self, link_style: LinkStyle) -> Self {
+        self.link_style = link_style;
+        self
+    }
+
+    /// Sets [`revisions`](Self::revisions).
+    pub fn revisions(mut self, revisions: RevisionMode) -> Self {
+        self.revisions = revisions;
+        self
+    }
+
+    /// Sets [`include_hidden`](Self::include_hidden).
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Sets [`skip_strikethrough`](Self::skip_strikethrough).
+    pub fn skip_strikethrough(mut self, skip_strikethrough: bool) -> Self {
+        self.skip_strikethrough = skip_strikethrough;
+        self
+    }
+
+    /// Sets [`comments`](Self::comments).
+    pub fn comments(mut self, comments: CommentMode) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Sets [`footnotes`](Self::footnotes).
+    pub fn footnotes(mut self, footnotes: NoteMode) -> Self {
+        self.footnotes = footnotes;
+        self
+    }
+
+    /// Sets [`endnotes`](Self::endnotes).
+    pub fn endnotes(mut self, endnotes: NoteMode) -> Self {
+        self.endnotes = endnotes;
+        self
+    }
+
+    /// Sets [`include_headers_footers`](Self::include_headers_footers).
+    pub fn include_headers_footers(mut self, include_headers_footers: bool) -> Self {
+        self.include_headers_footers = include_headers_footers;
+        self
+    }
+
+    /// Sets [`include_glossary`](Self::include_glossary).
+    pub fn include_glossary(mut self, include_glossary: bool) -> Self {
+        self.include_glossary = include_glossary;
+        self
+    }
+
+    /// Sets [`include_text_boxes`](Self::include_text_boxes).
+    pub fn include_text_boxes(mut self, include_text_boxes: bool) -> Self {
+        self.include_text_boxes = include_text_boxes;
+        self
+    }
+
+    /// Sets [`image_alt_text`](Self::image_alt_text).
+    pub fn image_alt_text(mut self, image_alt_text: bool) -> Self {
+        self.image_alt_text = image_alt_text;
+        self
+    }
+
+    /// Sets [`object_placeholders`](Self::object_placeholders).
+    pub fn object_placeholders(mut self, object_placeholders: bool) -> Self {
+        self.object_placeholders = object_placeholders;
+        self
+    }
+
+    /// Sets [`media_dir`](Self::media_dir).
+    pub fn media_dir(mut self, media_dir: impl Into<PathBuf>) -> Self {
+        self.media_dir = Some(media_dir.into());
+        self
+    }
+
+    /// Sets [`equations`](Self::equations).
+    pub fn equations(mut self, equations: EquationMode) -> Self {
+        self.equations = equations;
+        self
+    }
+
+    /// Sets [`toc`](Self::toc).
+    pub fn toc(mut self, toc: TocMode) -> Self {
+        self.toc = toc;
+        self
+    }
+
+    /// Sets [`ruby`](Self::ruby).
+    pub fn ruby(mut self, ruby: RubyMode) -> Self {
+        self.ruby = ruby;
+        self
+    }
+
+    /// Sets [`include_field_instructions`](Self::include_field_instructions).
+    pub fn include_field_instructions(mut self, include_field_instructions: bool) -> Self {
+        self.include_field_instructions = include_field_instructions;
+        self
+    }
+
+    /// Sets [`paragraph_separator`](Self::paragraph_separator).
+    pub fn paragraph_separator(mut self, paragraph_separator: impl Into<String>) -> Self {
+        self.paragraph_separator = paragraph_separator.into();
+        self
+    }
+
+    /// Sets [`tab_string`](Self::tab_string).
+    pub fn tab_string(mut self, tab_string: impl Into<String>) -> Self {
+        self.tab_string = tab_string.into();
+        self
+    }
+
+    /// Sets [`page_break`](Self::page_break).
+    pub fn page_break(mut self, page_break: impl Into<String>) -> Self {
+        self.page_break = page_break.into();
+        self
+    }
+
+    /// Sets [`page_marker`](Self::page_marker).
+    pub fn page_marker(mut self, page_marker: impl Into<String>) -> Self {
+        self.page_marker = Some(page_marker.into());
+        self
+    }
+
+    /// Sets [`section_separator`](Self::section_separator).
+    pub fn section_separator(mut self, section_separator: impl Into<String>) -> Self {
+        self.section_separator = section_separator.into();
+        self
+    }
+
+    /// Sets [`blank_lines`](Self::blank_lines).
+    pub fn blank_lines(mut self, blank_lines: BlankLines) -> Self {
+        self.blank_lines = blank_lines;
+        self
+    }
+
+    /// Sets [`table_delimiter`](Self::table_delimiter).
+    pub fn table_delimiter(mut self, table_delimiter: impl Into<String>) -> Self {
+        self.table_delimiter = table_delimiter.into();
+        self
+    }
+
+    /// Sets [`merged_cells`](Self::merged_cells).
+    pub fn merged_cells(mut self, merged_cells: MergedCells) -> Self {
+        self.merged_cells = merged_cells;
+        self
+    }
+
+    /// Sets [`unmapped_symbols`](Self::unmapped_symbols).
+    pub fn unmapped_symbols(mut self, unmapped_symbols: UnmappedSymbols) -> Self {
+        self.unmapped_symbols = unmapped_symbols;
+        self
+    }
+
+    /// Sets [`ascii_hyphens`](Self::ascii_hyphens).
+    pub fn ascii_hyphens(mut self, ascii_hyphens: bool) -> Self {
+        self.ascii_hyphens = ascii_hyphens;
+        self
+    }
+
+    /// Sets [`normalize_punctuation`](Self::normalize_punctuation).
+    pub fn normalize_punctuation(mut self, normalize_punctuation: bool) -> Self {
+        self.normalize_punctuation = normalize_punctuation;
+        self
+    }
+
+    /// Sets [`nfc`](Self::nfc).
+    pub fn nfc(mut self, nfc: bool) -> Self {
+        self.nfc = nfc;
+        self
+    }
+
+    /// Sets [`strip_invisible`](Self::strip_invisible).
+    pub fn strip_invisible(mut self, strip_invisible: bool) -> Self {
+        self.strip_invisible = strip_invisible;
+        self
+    }
+
+    /// Sets [`verbose`](Self::verbose).
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Sets [`keep_soft_hyphens`](Self::keep_soft_hyphens).
+    pub fn keep_soft_hyphens(mut self, keep_soft_hyphens: bool) -> Self {
+        self.keep_soft_hyphens = keep_soft_hyphens;
+        self
+    }
+
+    /// Sets [`list_indent`](Self::list_indent).
+    pub fn list_indent(mut self, list_indent: impl Into<String>) -> Self {
+        self.list_indent = list_indent.into();
+        self
+    }
+
+    /// Sets [`mark_headings`](Self::mark_headings).
+    pub fn mark_headings(mut self, mark_headings: bool) -> Self {
+        self.mark_headings = mark_headings;
+        self
+    }
+
+    /// Sets [`heading_offset`](Self::heading_offset).
+    pub fn heading_offset(mut self, heading_offset: usize) -> Self {
+        self.heading_offset = heading_offset;
+        self
+    }
+
+    /// Adds a pattern to [`include_styles`](Self::include_styles).
+    pub fn include_style(mut self, pattern: impl Into<String>) -> Self {
+        self.include_styles.push(pattern.into());
+        self
+    }
+
+    /// Adds a pattern to [`exclude_styles`](Self::exclude_styles).
+    pub fn exclude_style(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude_styles.push(pattern.into());
+        self
+    }
+
+    /// Sets [`annotate_content_controls`](Self::annotate_content_controls).
+    pub fn annotate_content_controls(mut self, annotate_content_controls: bool) -> Self {
+        self.annotate_content_controls = annotate_content_controls;
+        self
+    }
+
+    /// Sets [`bookmark`](Self::bookmark).
+    pub fn bookmark(mut self, bookmark: impl Into<String>) -> Self {
+        self.bookmark = Some(bookmark.into());
+        self
+    }
+
+    /// Sets [`max_part_size`](Self::max_part_size).
+    pub fn max_part_size(mut self, max_part_size: u64) -> Self {
+        self.max_part_size = max_part_size;
+        self
+    }
+
+    /// Sets [`max_total_uncompressed`](Self::max_total_uncompressed).
+    pub fn max_total_uncompressed(mut self, max_total_uncompressed: u64) -> Self {
+        self.max_total_uncompressed = max_total_uncompressed;
+        self
+    }
+
+    /// Sets [`cancellation`](Self::cancellation).
+    pub fn cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Whether the work using these options has been cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Fails with [`MergeError::Cancelled`] if the work using these options
+    /// has been cancelled.
+    fn check_cancelled(&self) -> Result<(), MergeError> {
+        match self.is_cancelled() {
+            true => Err(MergeError::Cancelled),
+            false => Ok(()),
+        }
+    }
+
+    fn note_mode(&self, kind: NoteKind) -> NoteMode {
+        match kind {
+            NoteKind::Footnote => self.footnotes,
+            NoteKind::Endnote => self.endnotes,
+        }
+    }
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            format: OutputFormat::default(),
+            link_style: LinkStyle::default(),
+            revisions: RevisionMode::default(),
+            include_hidden: false,
+            skip_strikethrough: false,
+            comments: CommentMode::default(),
+            footnotes: NoteMode::default(),
+            endnotes: NoteMode::default(),
+            include_headers_footers: false,
+            include_glossary: false,
+            include_text_boxes: true,
+            image_alt_text: false,
+            object_placeholders: true,
+            media_dir: None,
+            equations: EquationMode::default(),
+            toc: TocMode::default(),
+            ruby: RubyMode::default(),
+            include_field_instructions: false,
+            paragraph_separator: OutputFormat::default().paragraph_separator().to_string(),
+            tab_string: "\t".to_string(),
+            page_break: "\x0c".to_string(),
+            page_marker: None,
+            section_separator: String::new(),
+            blank_lines: BlankLines::default(),
+            table_delimiter: "\t".to_string(),
+            merged_cells: MergedCells::default(),
+            unmapped_symbols: UnmappedSymbols::default(),
+            ascii_hyphens: false,
+            normalize_punctuation: false,
+            nfc: false,
+            strip_invisible: false,
+            verbose: false,
+            keep_soft_hyphens: false,
+            list_indent: "  ".to_string(),
+            mark_headings: false,
+            heading_offset: 0,
+            include_styles: Vec::new(),
+            exclude_styles: Vec::new(),
+            annotate_content_controls: false,
+            bookmark: None,
+            max_part_size: 256 * 1024 * 1024,
+            max_total_uncompressed: 1024 * 1024 * 1024,
+            cancellation: None,
+        }
+    }
+}
+
+/// What a merge does when a file cannot be extracted.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Stop at the first file that fails and return its error.
+    #[default]
+    FailFast,
+    /// Leave out the files that fail, and report them with the merged
+    /// text. Failing to write still stops the merge.
+    SkipAndReport,
+}
+
+/// Options for merging files: how the text of each is extracted, and how
+/// the texts are put together.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// How the text of each file is extracted.
+    pub extract: ExtractOptions,
+    /// Text written between the texts of consecutive files. Defaults to a
+    /// blank line.
+    pub separator: String,
+    /// Template of a line written before the text of each file, e.g.
+    /// `===== {index}/{total}: {name} =====`. In it `{path}` stands for the
+    /// file's path, `{name}` for its name and `{stem}` for its name without
+    /// extension; `{index}` for its place in the merge, from 1, and
+    /// `{total}` for the number of files; `{title}` for the title in its
+    /// properties, or its stem if it has none; and `{mtime}` for when it
+    /// was last modified, in UTC. `None`, the default, writes no headers.
+    pub file_header: Option<String>,
+    /// Template of a line written after the text of each file, with the
+    /// placeholders of [`file_header`](Self::file_header). `None`, the
+    /// default, writes no footers.
+    pub file_footer: Option<String>,
+    /// Write a heading of this level before the text of each file, with
+    /// the title in its properties or else its name, and mark the file's
+    /// own headings that many levels deeper, so that they come under it.
+    /// `None`, the default, writes no chapter headings.
+    pub chapter_headings: Option<usize>,
+    /// What happens when a file cannot be extracted.
+    pub on_error: ErrorPolicy,
+    /// Write `[failed to extract: <error>]` in place of the text of each
+    /// file skipped under [`ErrorPolicy::SkipAndReport`]; the error names
+    /// the file.
+    pub error_markers: bool,
+    /// How many files are extracted at once. 1, the default, extracts them
+    /// one after another, writing each file's text as it is read; 0 uses a
+    /// thread per CPU. The merged text is the same either way.
+    pub threads: usize,
+    /// Count the words, characters and paragraphs of each file as it is
+    /// merged, for [`MergeReport::stats`].
+    pub stats: bool,
+    /// Called as each file of the merge starts, and as it is written or
+    /// fails; see [`ProgressEvent`].
+    pub on_progress: Option<ProgressCallback>,
+    /// Where the text of each file is kept between merges, by the file's
+    /// content and the extract options, so that an unchanged file is not
+    /// extracted again. Counts are not cached: with
+    /// [`stats`](Self::stats) on, every file is extracted. Nor are
+    /// warnings, so a file taken from the cache warns of nothing.
+    pub cache: Option<Arc<dyn TextCache>>,
+}
+
+#[cfg(feature = "cli")]
+impl MergeOptions {
+    /// The default options, to be changed through the methods below:
+    /// `MergeOptions::new().separator("\n---\n").file_header("# {path}")`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets [`extract`](Self::extract).
+    pub fn extract(mut self, extract: ExtractOptions) -> Self {
+        self.extract = extract;
+        self
+    }
+
+    /// Sets [`separator`](Self::separator).
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets [`file_header`](Self::file_header).
+    pub fn file_header(mut self, file_header: impl Into<String>) -> Self {
+        self.file_header = Some(file_header.into());
+        self
+    }
+
+    /// Sets [`file_footer`](Self::file_footer).
+    pub fn file_footer(mut self, file_footer: impl Into<String>) -> Self {
+        self.file_footer = Some(file_footer.into());
+        self
+    }
+
+    /// Sets [`chapter_headings`](Self::chapter_headings).
+    pub fn chapter_headings(mut self, level: usize) -> Self {
+        self.chapter_headings = Some(level);
+        self
+    }
+
+    /// Sets [`on_error`](Self::on_error).
+    pub fn on_error(mut self, on_error: ErrorPolicy) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Sets [`error_markers`](Self::error_markers).
+    pub fn error_markers(mut self, error_markers: bool) -> Self {
+        self.error_markers = error_markers;
+        self
+    }
+
+    /// Sets [`threads`](Self::threads).
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Sets [`stats`](Self::stats).
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Sets [`on_progress`](Self::on_progress):
+    /// `MergeOptions::new().on_progress(|event| eprintln!("{:?}", event))`.
+    pub fn on_progress(
+        mut self,
+        on_progress: impl Fn(ProgressEvent<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(ProgressCallback(Arc::new(on_progress)));
+        self
+    }
+
+    /// Sets [`cache`](Self::cache), e.g. to a [`DirCache`].
+    pub fn cache(mut self, cache: impl TextCache + 'static) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Sets the [`cancellation`](ExtractOptions::cancellation) token of
+    /// [`extract`](Self::extract), which the merge checks before each
+    /// file as well.
+    pub fn cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.extract.cancellation = Some(cancellation);
+        self
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Default for MergeOptions {
+    fn default() -> Self {
+        MergeOptions {
+            extract: ExtractOptions::default(),
+            separator: "\n\n".to_string(),
+            file_header: None,
+            file_footer: None,
+            chapter_headings: None,
+            on_error: ErrorPolicy::default(),
+            error_markers: false,
+            threads: 1,
+            stats: false,
+            on_progress: None,
+            cache: None,
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<ExtractOptions> for MergeOptions {
+    fn from(extract: ExtractOptions) -> Self {
+        MergeOptions {
+            extract,
+            ..MergeOptions::default()
+        }
+    }
+}
+
+/// The kind of a complex field, taken from the first word of its instruction.
+/// Only links and tables of contents are handled apart; the result of any
+/// other field, such as a page number or cross-reference, is plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Hyperlink,
+    Toc,
+    Other,
+}
+
+impl FieldKind {
+    fn classify(instruction: &str) -> Self {
+        let keyword = instruction.split_whitespace().next().unwrap_or("");
+        match keyword.to_ascii_uppercase().as_str() {
+            "HYPERLINK" => FieldKind::Hyperlink,
+            "TOC" => FieldKind::Toc,
+            _ => FieldKind::Other,
+        }
+    }
+}
+
+/// A complex field (`w:fldChar` begin .. end) that is currently open.
+#[derive(Debug, Default)]
+struct Field {
+    /// Instruction text collected so far; it may be split over many
+    /// `w:instrText` elements and include the results of nested fields.
+    instruction: String,
+    /// The `separate` marker was seen, so what follows is the field result.
+    separated: bool,
+    /// A HYPERLINK field whose result is being collected as link text.
+    hyperlink: bool,
+    /// A TOC field, whose result is the table of contents. It spans many
+    /// paragraphs, one per entry.
+    toc: bool,
+}
+
+/// Formatting of the run being read, as far as extraction cares about it.
+#[derive(Debug, Clone, Default)]
+struct RunProperties {
+    /// Hidden text (`w:vanish` or `w:specVanish`).
+    hidden: bool,
+    /// Single or double strikethrough (`w:strike` or `w:dstrike`).
+    struck: bool,
+    /// Bold (`w:b`), set on the run itself rather than by its style.
+    bold: bool,
+    /// Italic (`w:i`), likewise.
+    italic: bool,
+}
+
+impl RunProperties {
+    /// Whether `options` leave out text with this formatting.
+    fn skipped(&self, options: &ExtractOptions) -> bool {
+        (self.hidden && !options.include_hidden) || (self.struck && options.skip_strikethrough)
+    }
+
+    /// What a visitor is told about the run.
+    fn info(&self) -> RunInfo {
+        RunInfo {
+            hidden: self.hidden,
+            struck: self.struck,
+            bold: self.bold,
+            italic: self.italic,
+        }
+    }
+}
+
+/// A story kept in a part of its own (a comment or note), read by
+/// collecting the text between its start and end tags.
+#[derive(Debug, Clone, Default)]
+struct Story {
+    id: String,
+    /// `w:type` of a note; separators and continuation notices have one.
+    note_type: Option<String>,
+    author: Option<String>,
+    date: Option<String>,
+    /// `w14:paraId` of the story's last paragraph, which comment replies use
+    /// to refer to their parent.
+    para_id: Option<String>,
+    text: String,
+}
+
+/// Prefixes of the namespaces whose markup an `mc:Choice` branch may
+/// require and still be read: the namespaces this reader handles, and the
+/// extensions to drawings and text that only wrap content in elements it
+/// handles. `Requires` names namespaces by the document's prefixes, which
+/// [`WordReader`] rewrites to these.
+const UNDERSTOOD_PREFIXES: [&str; 15] = [
+    "w", "r", "m", "wp", "a", "pic", "v", "o", "w14", "w15", "wp14", "wps", "wpg", "wpc", "a14",
+];
+
+/// A `w:drawing` that is currently open.
+#[derive(Debug, Default)]
+struct Drawing {
+    /// Description, or failing that title, from `wp:docPr`.
+    alt_text: Option<String>,
+    /// The drawing holds a picture rather than, say, a text box.
+    picture: bool,
+    /// The relationship of the picture's image (`a:blip r:embed`).
+    image_id: Option<String>,
+}
+
+impl Drawing {
+    fn render(&self) -> String {
+        match &self.alt_text {
+            Some(alt_text) => format!("[image: {}]", single_line(alt_text)),
+            None => "[image]".to_string(),
+        }
+    }
+
+    /// Renders the picture as a Markdown image of the file it was
+    /// extracted to.
+    fn render_markdown(&self, file: &Path) -> String {
+        let alt_text = self.alt_text.as_deref().map(single_line).unwrap_or_default();
+        let file = file.to_string_lossy().replace('\\', "/").replace(' ', "%20");
+        format!("![{}]({})", alt_text.replace(['[', ']'], ""), file)
+    }
+}
+
+/// A paragraph that is currently open. What is visited in it is held until
+/// it ends and is known to be kept.
+#[derive(Debug, Default)]
+struct OpenParagraph {
+    info: ParagraphInfo,
+    visits: Vec<Visit>,
+}
+
+/// A content control (`w:sdt`) that is currently open.
+#[derive(Debug, Default)]
+struct ContentControl {
+    info: ControlInfo,
+    /// The content is placeholder text prompting for input (`w:showingPlcHdr`).
+    showing_placeholder: bool,
+    /// Its content (`w:sdtContent`) has started.
+    started: bool,
+}
+
+/// A `w:headerReference` or `w:footerReference` in section properties.
+#[derive(Debug, Clone)]
+struct HeaderFooterReference {
+    footer: bool,
+    /// `default`, `first` or `even`.
+    kind: String,
+    /// Relationship id of the header or footer part.
+    id: String,
+}
+
+impl HeaderFooterReference {
+    /// The prefix that labels the header or footer in the output.
+    fn label(&self) -> String {
+        let name = if self.footer { "footer" } else { "header" };
+        match self.kind.as_str() {
+            "first" => format!("[first page {}]", name),
+            "even" => format!("[even page {}]", name),
+            _ => format!("[{}]", name),
+        }
+    }
+}
+
+/// Content of other parts that the main document refers to.
+#[derive(Debug, Default)]
+struct DocumentParts {
+    comments: Vec<Comment>,
+    /// Note texts by kind and id.
+    notes: HashMap<(NoteKind, String), String>,
+    numbering: Arc<Numbering>,
+    styles: Arc<Styles>,
+}
+
+/// Reads the text of `word/document.xml` while the reader walks it, and
+/// tells the renderer, and the visitor of [`extract_with_visitor`] if
+/// any, what it keeps.
+struct TextCollector<'a> {
+    options: &'a ExtractOptions,
+    /// Relationships of the part being read, used to resolve link targets.
+    relationships: &'a Relationships,
+    parts: &'a DocumentParts,
+    /// Writes the text or Markdown of what is visited.
+    renderer: TextRenderer<'a>,
+    /// The visitor of [`extract_with_visitor`], if any.
+    visitor: Option<&'a mut dyn DocxVisitor>,
+    /// The paragraphs currently open. Paragraphs can nest (a text box
+    /// anchored inside a paragraph has paragraphs of its own), so this is a stack.
+    paragraphs: Vec<OpenParagraph>,
+    /// Inside a `w:t` element, the only place visible run text lives.
+    in_run_text: bool,
+    in_instr_text: bool,
+    /// The open `w:t`/`w:instrText` declared `xml:space="preserve"`.
+    preserve_space: bool,
+    /// Inside paragraph tab stop definitions, whose `w:tab` children are not text.
+    in_tab_stops: bool,
+    /// Complex fields currently open, innermost last.
+    fields: Vec<Field>,
+    /// Depth of open revision elements (`w:ins`, `w:del`, ...) whose
+    /// content the revision mode leaves out.
+    removed_depth: usize,
+    /// Properties of the runs currently open, innermost last.
+    runs: Vec<RunProperties>,
+    /// Depth of `w:pPr` elements; run properties inside them describe the
+    /// paragraph mark rather than any text.
+    paragraph_properties_depth: usize,
+    /// Depth of `w:rPr` elements. Only the outermost one applies to the run;
+    /// nested ones record earlier formatting in `w:rPrChange`.
+    run_properties_depth: usize,
+    /// The story being read and the length of the rendered text when it
+    /// started.
+    story: Option<(Story, usize)>,
+    /// Stories read so far, in document order.
+    stories: Vec<Story>,
+    /// Comments already written inline, so replies appear only once.
+    written_comments: HashSet<String>,
+    /// Ids of the footnotes and endnotes referenced so far; a note's number
+    /// is its position among the notes of its kind plus one.
+    footnote_references: Vec<String>,
+    endnote_references: Vec<String>,
+    /// Headers and footers referenced by section properties, in order.
+    header_footer_references: Vec<HeaderFooterReference>,
+    /// Depth of the element being skipped entirely, such as the branches of
+    /// `mc:AlternateContent` not chosen; zero when reading normally.
+    skipped_depth: usize,
+    /// For each open `mc:AlternateContent`, whether a branch was chosen yet.
+    alternate_content: Vec<bool>,
+    drawing: Option<Drawing>,
+    /// The equation being read, which receives every event until it ends.
+    equation: Option<Equation>,
+    /// Equations read so far in the open `m:oMathPara`, if any.
+    equations_in_paragraph: Option<usize>,
+    /// The cell started last, until its properties (`w:tcPr`) are read.
+    cell: Option<CellInfo>,
+    /// List (`w:numId`) and level (`w:ilvl`) of the paragraph being read.
+    list_item: (Option<String>, usize),
+    lists: ListCounters,
+    /// The file the document was read from, which names the pictures
+    /// extracted from it.
+    document_path: Option<PathBuf>,
+    /// `options.include_styles` and `options.exclude_styles`, compiled.
+    include_styles: Vec<Pattern>,
+    exclude_styles: Vec<Pattern>,
+    /// Content controls currently open, innermost last.
+    content_controls: Vec<ContentControl>,
+    /// Depth inside `w:sdtPr`, whose children describe the control and are
+    /// never text.
+    sdt_properties_depth: usize,
+    /// Depth inside `w:docPartPr`, the properties of a glossary entry.
+    doc_part_properties_depth: usize,
+    /// Name of the glossary entry being read.
+    doc_part_name: Option<String>,
+    /// The open paragraph is part of a TOC field's result.
+    in_toc_paragraph: bool,
+    /// The `[table of contents]` marker stands for the paragraphs dropped
+    /// since the last paragraph written.
+    toc_marker_written: bool,
+    /// Number of the page being read, as last rendered.
+    rendered_page: usize,
+    /// Names of the bookmarks started so far, in document order.
+    bookmark_names: Vec<String>,
+    /// The id of `options.bookmark` while its range is open.
+    bookmark_range: Option<String>,
+    bookmark_found: bool,
+    /// The open paragraph has content inside the bookmark range.
+    paragraph_in_bookmark: bool,
+    /// Depth inside `w:sectPr`, which nests in `w:sectPrChange`.
+    section_properties_depth: usize,
+    /// The `w:type` of the section properties being read.
+    section_type: Option<String>,
+    /// The section the open paragraph ends, if it ends one.
+    section_end: Option<SectionInfo>,
+    /// Where the phonetic guide (`w:rt`) being read starts among what is
+    /// visited in the open paragraph, and the guide once read, until its
+    /// base text is written.
+    ruby_start: Option<usize>,
+    ruby_text: Option<String>,
+    /// Invisible characters removed from the text so far.
+    invisible_removed: usize,
+    /// Paragraphs started so far, which locate warnings.
+    paragraphs_started: usize,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> TextCollector<'a> {
+    fn new(
+        options: &'a ExtractOptions,
+        relationships: &'a Relationships,
+        parts: &'a DocumentParts,
+    ) -> Self {
+        TextCollector {
+            options,
+            relationships,
+            parts,
+            renderer: TextRenderer::new(options),
+            visitor: None,
+            paragraphs: Vec::new(),
+            in_run_text: false,
+            in_instr_text: false,
+            preserve_space: false,
+            in_tab_stops: false,
+            fields: Vec::new(),
+            removed_depth: 0,
+            runs: Vec::new(),
+            paragraph_properties_depth: 0,
+            run_properties_depth: 0,
+            story: None,
+            stories: Vec::new(),
+            written_comments: HashSet::new(),
+            footnote_references: Vec::new(),
+            endnote_references: Vec::new(),
+            header_footer_references: Vec::new(),
+            skipped_depth: 0,
+            alternate_content: Vec::new(),
+            drawing: None,
+            equation: None,
+            equations_in_paragraph: None,
+            cell: None,
+            list_item: (None, 0),
+            lists: ListCounters::default(),
+            document_path: None,
+            include_styles: style_patterns(&options.include_styles),
+            exclude_styles: style_patterns(&options.exclude_styles),
+            content_controls: Vec::new(),
+            sdt_properties_depth: 0,
+            doc_part_properties_depth: 0,
+            doc_part_name: None,
+            in_toc_paragraph: false,
+            toc_marker_written: false,
+            rendered_page: 1,
+            bookmark_names: Vec::new(),
+            bookmark_range: None,
+            bookmark_found: false,
+            paragraph_in_bookmark: false,
+            section_properties_depth: 0,
+            section_type: None,
+            section_end: None,
+            ruby_start: None,
+            ruby_text: None,
+            invisible_removed: 0,
+            paragraphs_started: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn start(&mut self, e: &BytesStart) {
+        if self.skipped_depth > 0 {
+            self.skipped_depth += 1;
+            return;
+        }
+        if let Some(equation) = self.equation.as_mut() {
+            equation.start(e);
+            return;
+        }
+        if self.sdt_properties_depth > 0 {
+            self.sdt_properties_depth += 1;
+            self.sdt_property(e);
+            return;
+        }
+        if self.doc_part_properties_depth > 0 {
+            self.doc_part_properties_depth += 1;
+            return;
+        }
+        match e.name().as_ref() {
+            b"w:p" => {
+                if let (Some((story, _)), Some(para_id)) =
+                    (self.story.as_mut(), attribute(e, b"w14:paraId"))
+                {
+                    story.para_id = Some(para_id);
+                }
+                self.start_paragraph();
+            }
+            b"w:comment" | b"w:footnote" | b"w:endnote" => self.start_story(e),
+            b"w:r" => self.runs.push(RunProperties::default()),
+            b"w:pPr" => self.paragraph_properties_depth += 1,
+            b"w:rPr" => self.run_properties_depth += 1,
+            b"w:vanish" | b"w:specVanish" | b"w:strike" | b"w:dstrike" | b"w:b" | b"w:i" => {
+                self.run_property(e);
+            }
+            // Deleted runs keep their text in `w:delText` instead of `w:t`.
+            b"w:t" | b"w:delText" => {
+                self.in_run_text = true;
+                self.preserve_space = preserves_space(e);
+            }
+            b"w:instrText" => {
+                self.in_instr_text = true;
+                self.preserve_space = preserves_space(e);
+            }
+            b"w:tabs" => self.in_tab_stops = true,
+            b"w:tab" => self.tab(),
+            b"w:fldChar" => self.field_char(e),
+            b"w:hyperlink" => self.start_hyperlink(e),
+            b"w:txbxContent" if !self.options.include_text_boxes => self.skipped_depth = 1,
+            b"mc:AlternateContent" => self.alternate_content.push(false),
+            b"mc:Choice" | b"mc:Fallback" => self.alternate_branch(e),
+            b"w:drawing" => self.drawing = Some(Drawing::default()),
+            b"w:altChunk" => self.imported_content(e),
+            b"m:oMathPara" => self.equations_in_paragraph = Some(0),
+            b"m:oMath" => self.start_equation(),
+            b"w:sdt" => self.content_controls.push(ContentControl::default()),
+            b"w:sdtPr" => self.sdt_properties_depth = 1,
+            b"w:sdtEndPr" => self.skipped_depth = 1,
+            b"w:sdtContent" => self.start_content_control(),
+            b"w:docPartPr" => self.doc_part_properties_depth = 1,
+            b"w:docPartBody" => self.start_doc_part(),
+            b"w:tbl" => self.visit(Visit::TableStart),
+            // The guide comes before the base text in `w:ruby`.
+            b"w:rt" => match self.options.ruby {
+                RubyMode::Base => self.skipped_depth = 1,
+                RubyMode::Annotate => {
+                    let start = self.paragraphs.last().map_or(0, |paragraph| paragraph.visits.len());
+                    self.ruby_start = Some(start);
+                }
+            },
+            b"w:sectPr" => {
+                if self.section_properties_depth == 0 {
+                    self.section_type = None;
+                }
+                self.section_properties_depth += 1;
+            }
+            b"w:tr" => self.visit(Visit::RowStart),
+            b"w:tc" => self.cell = Some(CellInfo::default()),
+            b"wp:docPr" => self.drawing_properties(e),
+            b"a:blip" => self.picture_image(e),
+            b"o:OLEObject" => self.ole_object(e),
+            b"pic:pic" => {
+                if let Some(drawing) = self.drawing.as_mut() {
+                    drawing.picture = true;
+                }
+            }
+            name if self.options.revisions.removes(name) => self.removed_depth += 1,
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, e: &BytesEnd) {
+        if self.skipped_depth > 0 {
+            self.skipped_depth -= 1;
+            return;
+        }
+        if e.name().as_ref() == b"m:oMath" {
+            self.end_equation();
+            return;
+        }
+        if let Some(equation) = self.equation.as_mut() {
+            equation.end();
+            return;
+        }
+        if self.sdt_properties_depth > 0 {
+            self.sdt_properties_depth -= 1;
+            return;
+        }
+        if self.doc_part_properties_depth > 0 {
+            self.doc_part_properties_depth -= 1;
+            return;
+        }
+        match e.name().as_ref() {
+            b"w:p" => {
+                self.end_paragraph();
+                if let Some(section) = self.section_end.take() {
+                    self.visit(Visit::SectionEnd(section));
+                }
+            }
+            b"w:rt" => {
+                let start = self.ruby_start.take();
+                if let (Some(start), Some(paragraph)) = (start, self.paragraphs.last_mut()) {
+                    if start <= paragraph.visits.len() {
+                        let guide = paragraph.visits.split_off(start);
+                        self.ruby_text = Some(
+                            guide
+                                .iter()
+                                .filter_map(|visit| match visit {
+                                    Visit::Text(text, _) | Visit::Marker(text) => Some(text.as_str()),
+                                    _ => None,
+                                })
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            b"w:ruby" => {
+                if let Some(guide) = self.ruby_text.take().filter(|guide| !guide.is_empty()) {
+                    self.push(&format!("({})", guide));
+                }
+            }
+            b"w:sectPr" => {
+                self.section_properties_depth -= 1;
+                if self.section_properties_depth == 0 {
+                    self.end_section_properties();
+                }
+            }
+            b"w:comment" | b"w:footnote" | b"w:endnote" => self.end_story(),
+            b"w:r" => {
+                self.runs.pop();
+            }
+            b"w:pPr" => {
+                self.paragraph_properties_depth -= 1;
+                if self.paragraph_properties_depth == 0 {
+                    self.number_list_item();
+                }
+            }
+            b"w:rPr" => self.run_properties_depth -= 1,
+            b"w:t" | b"w:delText" => self.in_run_text = false,
+            name if self.options.revisions.removes(name) => {
+                self.removed_depth = self.removed_depth.saturating_sub(1);
+            }
+            b"w:instrText" => self.in_instr_text = false,
+            b"w:tabs" => self.in_tab_stops = false,
+            b"w:hyperlink" => self.visit(Visit::HyperlinkEnd),
+            b"mc:AlternateContent" => {
+                self.alternate_content.pop();
+            }
+            b"w:drawing" => self.end_drawing(),
+            b"m:oMathPara" => self.equations_in_paragraph = None,
+            b"w:sdt" => self.end_content_control(),
+            b"w:tr" => self.visit(Visit::RowEnd),
+            b"w:tcPr" => self.start_cell(),
+            b"w:tc" => self.visit(Visit::CellEnd),
+            b"w:tbl" => self.visit(Visit::TableEnd),
+            _ => {}
+        }
+    }
+
+    fn empty(&mut self, e: &BytesStart) {
+        if self.skipped_depth > 0 {
+            return;
+        }
+        if let Some(equation) = self.equation.as_mut() {
+            equation.empty(e);
+            return;
+        }
+        if self.sdt_properties_depth > 0 {
+            self.sdt_property(e);
+            return;
+        }
+        if self.doc_part_properties_depth > 0 {
+            // `w:category` has a `w:name` too, one level deeper.
+            if self.doc_part_properties_depth == 1 && e.name().as_ref() == b"w:name" {
+                self.doc_part_name = attribute(e, b"w:val");
+            }
+            return;
+        }
+        match e.name().as_ref() {
+            // `<w:p/>` is an empty paragraph and still occupies a line.
+            b"w:p" => {
+                self.start_paragraph();
+                self.end_paragraph();
+            }
+            b"w:tab" => self.tab(),
+            b"w:vanish" | b"w:specVanish" | b"w:strike" | b"w:dstrike" | b"w:b" | b"w:i" => {
+                self.run_property(e);
+            }
+            b"w:br" => self.line_break(e),
+            b"w:cr" => self.push_content("\n"),
+            b"w:sym" => self.symbol(e),
+            b"w:altChunk" => self.imported_content(e),
+            b"w:lastRenderedPageBreak" => self.rendered_page_break(),
+            b"w:type" if self.section_properties_depth == 1 => {
+                self.section_type = attribute(e, b"w:val");
+            }
+            b"w:sectPr" if self.section_properties_depth == 0 => {
+                self.section_type = None;
+                self.end_section_properties();
+            }
+            b"w:bookmarkStart" => self.bookmark_start(e),
+            b"w:bookmarkEnd"
+                if self.bookmark_range.is_some() && self.bookmark_range == attribute(e, b"w:id") =>
+            {
+                self.bookmark_range = None;
+            }
+            b"w:noBreakHyphen" => {
+                let ascii = self.options.ascii_hyphens || self.options.normalize_punctuation;
+                self.push_content(if ascii { "-" } else { "\u{2011}" });
+            }
+            b"w:softHyphen" if self.options.keep_soft_hyphens => self.push_content("\u{AD}"),
+            b"w:fldChar" => self.field_char(e),
+            b"w:commentReference" => self.comment_reference(e),
+            b"w:footnoteReference" => self.note_reference(NoteKind::Footnote, e),
+            b"w:endnoteReference" => self.note_reference(NoteKind::Endnote, e),
+            b"w:headerReference" | b"w:footerReference" => self.header_footer_reference(e),
+            b"w:vMerge" => {
+                if let Some(cell) = self.cell.as_mut() {
+                    cell.vertically_merged = true;
+                    // Without a value, the cell continues the merge.
+                    cell.continues_merge = attribute(e, b"w:val").is_none_or(|value| value == "continue");
+                }
+            }
+            // Numbering of the paragraph, but not of an earlier version of
+            // its properties kept in `w:pPrChange`.
+            b"w:pStyle" if self.paragraph_properties_depth == 1 => {
+                if let Some(paragraph) = self.paragraphs.last_mut() {
+                    paragraph.info.style = attribute(e, b"w:val");
+                }
+            }
+            b"w:numId" if self.paragraph_properties_depth == 1 => {
+                self.list_item.0 = attribute(e, b"w:val");
+            }
+            b"w:ilvl" if self.paragraph_properties_depth == 1 => {
+                self.list_item.1 = attribute(e, b"w:val")
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(0);
+            }
+            b"w:gridSpan" => {
+                let span = attribute(e, b"w:val").and_then(|value| value.parse().ok());
+                if let (Some(cell), Some(span)) = (self.cell.as_mut(), span) {
+                    cell.grid_span = std::cmp::max(span, 1);
+                }
+            }
+            b"wp:docPr" => self.drawing_properties(e),
+            b"a:blip" => self.picture_image(e),
+            b"o:OLEObject" => self.ole_object(e),
+            _ => {}
+        }
+    }
+
+    /// Reads exactly one branch of `mc:AlternateContent`: the first
+    /// `mc:Choice` whose required namespaces are all understood, otherwise
+    /// the `mc:Fallback`. Word writes drawings both ways, so reading every
+    /// branch would repeat their text.
+    fn alternate_branch(&mut self, e: &BytesStart) {
+        let Some(chosen) = self.alternate_content.last_mut() else {
+            return;
+        };
+        let satisfiable = e.name().as_ref() == b"mc:Fallback"
+            || attribute(e, b"Requires").is_some_and(|requires| {
+                requires
+                    .split_whitespace()
+                    .all(|prefix| UNDERSTOOD_PREFIXES.contains(&prefix))
+            });
+        if *chosen || !satisfiable {
+            self.skipped_depth = 1;
+        } else {
+            *chosen = true;
+        }
+    }
+
+    /// Takes the alt text of the open drawing from its `wp:docPr`, which
+    /// inline and anchored drawings both have.
+    fn drawing_properties(&mut self, e: &BytesStart) {
+        if let Some(drawing) = self.drawing.as_mut() {
+            drawing.alt_text = [b"descr".as_slice(), b"title"]
+                .into_iter()
+                .filter_map(|key| attribute(e, key))
+                .find(|value| !value.trim().is_empty());
+        }
+    }
+
+    /// Writes a placeholder for an object embedded in or linked to the
+    /// document, named after the file of its relationship.
+    fn ole_object(&mut self, e: &BytesStart) {
+        if !self.options.object_placeholders {
+            return;
+        }
+        let relationship = attribute(e, b"r:id").and_then(|id| self.relationships.get(&id));
+        let name = relationship
+            .and_then(|relationship| relationship.target.rsplit(['/', '\\']).next())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        let kind = match attribute(e, b"Type").as_deref() {
+            Some("Link") => "linked object",
+            _ => "embedded object",
+        };
+        match name {
+            Some(name) => self.push(&format!("[{}: {}]", kind, name)),
+            None => self.push(&format!("[{}]", kind)),
+        }
+    }
+
+    /// Takes the relationship of the image of the open drawing's picture.
+    fn picture_image(&mut self, e: &BytesStart) {
+        if let Some(drawing) = self.drawing.as_mut() {
+            drawing.image_id = attribute(e, b"r:embed");
+        }
+    }
+
+    fn end_drawing(&mut self) {
+        let Some(drawing) = self.drawing.take() else {
+            return;
+        };
+        if !drawing.picture {
+            return;
+        }
+        if let Some(file) = self.extracted_image(&drawing) {
+            self.push(&drawing.render_markdown(&file));
+        } else if self.options.image_alt_text {
+            self.push(&drawing.render());
+        }
+    }
+
+    /// The file a picture's image is extracted to, when Markdown is written
+    /// with the media of documents extracted.
+    fn extracted_image(&self, drawing: &Drawing) -> Option<PathBuf> {
+        if self.options.format != OutputFormat::Markdown {
+            return None;
+        }
+        let media_dir = self.options.media_dir.as_ref()?;
+        let relationship = self.relationships.get(drawing.image_id.as_ref()?)?;
+        if relationship.external {
+            return None;
+        }
+        let name = relationship.target.rsplit('/').next()?;
+        Some(media_dir.join(media_file_name(self.document_path.as_deref()?, name)))
+    }
+
+    /// Starts an equation. Its runs hold the characters of the equation in
+    /// document order, which only make sense together with its structure,
+    /// so it is written as a whole when it ends.
+    fn start_equation(&mut self) {
+        // Equations of a display paragraph go on lines of their own.
+        if let Some(count) = self.equations_in_paragraph.as_mut() {
+            *count += 1;
+            if *count > 1 {
+                self.push("\n");
+            }
+        }
+        match self.options.equations {
+            EquationMode::Skip => {
+                self.push("[equation]");
+                self.skipped_depth = 1;
+                self.warn(|path, paragraph| Warning::EquationSkipped { path, paragraph });
+            }
+            EquationMode::Linear => self.equation = Some(Equation::new()),
+        }
+    }
+
+    fn end_equation(&mut self) {
+        if let Some(equation) = self.equation.take() {
+            self.push(&equation.finish());
+        }
+    }
+
+    /// Reads a property of the open content control. Drop-down entries,
+    /// placeholder references and date formats in `w:sdtPr` are not text.
+    fn sdt_property(&mut self, e: &BytesStart) {
+        let Some(control) = self.content_controls.last_mut() else {
+            return;
+        };
+        match e.name().as_ref() {
+            b"w:alias" => control.info.alias = attribute(e, b"w:val"),
+            b"w:tag" => control.info.tag = attribute(e, b"w:val"),
+            b"w:showingPlcHdr" => control.showing_placeholder = toggle_value(e),
+            _ => {}
+        }
+    }
+
+    /// Starts the content of a glossary entry with a line naming it.
+    fn start_doc_part(&mut self) {
+        let name = self.doc_part_name.take().unwrap_or_default();
+        self.visit(Visit::Marker(format!("[{}]", name)));
+    }
+
+    /// Starts the content of a content control, which is what Word displays:
+    /// the entered text, the chosen drop-down entry, the formatted date, or
+    /// the value bound from custom XML. Placeholder text prompting for input
+    /// is left out.
+    fn start_content_control(&mut self) {
+        let Some(control) = self.content_controls.last_mut() else {
+            return;
+        };
+        control.started = true;
+        let info = control.info.clone();
+        if control.showing_placeholder {
+            self.skipped_depth = 1;
+        }
+        self.visit(Visit::ContentControlStart(info));
+    }
+
+    fn end_content_control(&mut self) {
+        if self.content_controls.pop().is_some_and(|control| control.started) {
+            self.visit(Visit::ContentControlEnd);
+        }
+    }
+
+    /// Numbers a list paragraph, for its label.
+    fn number_list_item(&mut self) {
+        let (num_id, level) = std::mem::take(&mut self.list_item);
+        // numId 0 removes numbering a style would otherwise give.
+        let Some(num_id) = num_id.filter(|id| id != "0") else {
+            return;
+        };
+        let parts = self.parts;
+        let numbered = parts.numbering.numbered(&num_id, level);
+        let label = self.lists.next_label(&parts.numbering, &num_id, level);
+        let number = if numbered {
+            self.lists.counter(&num_id, level)
+        } else {
+            None
+        };
+        if let Some(paragraph) = self.paragraphs.last_mut() {
+            paragraph.info.list_level = Some(level);
+            paragraph.info.numbered = numbered;
+            paragraph.info.list_label = label;
+            paragraph.info.list_number = number;
+        }
+    }
+
+    fn start_story(&mut self, e: &BytesStart) {
+        let story = Story {
+            id: attribute(e, b"w:id").unwrap_or_default(),
+            note_type: attribute(e, b"w:type"),
+            author: attribute(e, b"w:author"),
+            date: attribute(e, b"w:date"),
+            ..Story::default()
+        };
+        self.story = Some((story, self.renderer.text.len()));
+    }
+
+    fn end_story(&mut self) {
+        if let Some((mut story, start)) = self.story.take() {
+            story.text = self.renderer.text.split_off(start).trim_end().to_string();
+            self.stories.push(story);
+        }
+    }
+
+    /// Writes a comment, followed by its replies, where it is anchored.
+    fn comment_reference(&mut self, e: &BytesStart) {
+        if self.options.comments != CommentMode::Inline {
+            return;
+        }
+        let Some(id) = attribute(e, b"w:id") else {
+            return;
+        };
+        let parts = self.parts;
+        let thread = parts.comments.iter().filter(|comment| {
+            comment.id == id || comment.parent.as_deref() == Some(id.as_str())
+        });
+        for comment in thread {
+            if self.written_comments.insert(comment.id.clone()) {
+                self.push(&comment.render());
+            }
+        }
+    }
+
+    /// Resolves the target of a `w:hyperlink`: external links through the
+    /// part's relationships, internal ones from their bookmark anchor.
+    fn start_hyperlink(&mut self, e: &BytesStart) {
+        let target = match attribute(e, b"r:id") {
+            Some(id) => self
+                .relationships
+                .get(&id)
+                .map(|relationship| relationship.target.clone()),
+            None => attribute(e, b"w:anchor").map(|anchor| format!("#{}", anchor)),
+        };
+        self.visit(Visit::HyperlinkStart(LinkInfo { target }));
+    }
+
+    /// Handles the begin/separate/end markers of complex fields. Only the
+    /// field result, between `separate` and `end`, is visible text; fields
+    /// without a separator have no result.
+    fn field_char(&mut self, e: &BytesStart) {
+        match attribute(e, b"w:fldCharType").as_deref() {
+            Some("begin") => self.fields.push(Field::default()),
+            Some("separate") => {
+                let Some(field) = self.fields.last_mut() else {
+                    return;
+                };
+                field.separated = true;
+                let instruction = std::mem::take(&mut field.instruction);
+                // The result of a HYPERLINK field is the link text.
+                let kind = FieldKind::classify(&instruction);
+                field.hyperlink = kind == FieldKind::Hyperlink;
+                field.toc = kind == FieldKind::Toc;
+                let hyperlink = field.hyperlink;
+                if field.toc {
+                    self.in_toc_paragraph = true;
+                }
+                self.write_instruction(&instruction);
+                if hyperlink {
+                    let target = hyperlink_field_target(&instruction);
+                    self.visit(Visit::HyperlinkStart(LinkInfo { target }));
+                }
+            }
+            Some("end") => {
+                if let Some(field) = self.fields.pop() {
+                    if field.hyperlink {
+                        self.visit(Visit::HyperlinkEnd);
+                    }
+                    if !field.separated {
+                        self.write_instruction(&field.instruction);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes a completed instruction out if field instructions were
+    /// explicitly requested; page numbers, cross-references, TOC switches and
+    /// link targets are never useful text otherwise.
+    fn write_instruction(&mut self, instruction: &str) {
+        if self.options.include_field_instructions {
+            self.push(&format!("{{{}}}", instruction.trim()));
+        }
+    }
+
+    /// Writes the note text, or a labelled marker for the end section.
+    fn note_reference(&mut self, kind: NoteKind, e: &BytesStart) {
+        let mode = self.options.note_mode(kind);
+        if mode == NoteMode::Skip {
+            return;
+        }
+        let Some(id) = attribute(e, b"w:id") else {
+            return;
+        };
+        let references = match kind {
+            NoteKind::Footnote => &mut self.footnote_references,
+            NoteKind::Endnote => &mut self.endnote_references,
+        };
+        references.push(id.clone());
+        let label = kind.label(references.len());
+        match mode {
+            NoteMode::Inline => {
+                let parts = self.parts;
+                let text = parts.notes.get(&(kind, id)).map(String::as_str).unwrap_or("");
+                self.push(&format!("[{} {}: {}]", kind.name(), label, single_line(text)));
+            }
+            _ => self.push(&format!("[^{}]", label)),
+        }
+    }
+
+    fn header_footer_reference(&mut self, e: &BytesStart) {
+        if !self.options.include_headers_footers {
+            return;
+        }
+        if let Some(id) = attribute(e, b"r:id") {
+            self.header_footer_references.push(HeaderFooterReference {
+                footer: e.name().as_ref() == b"w:footerReference",
+                kind: attribute(e, b"w:type").unwrap_or_else(|| "default".to_string()),
+                id,
+            });
+        }
+    }
+
+    /// Records a formatting property of the current run.
+    fn run_property(&mut self, e: &BytesStart) {
+        if self.run_properties_depth != 1 || self.paragraph_properties_depth > 0 {
+            return;
+        }
+        let Some(run) = self.runs.last_mut() else {
+            return;
+        };
+        match e.name().as_ref() {
+            b"w:vanish" | b"w:specVanish" => run.hidden = toggle_value(e),
+            b"w:strike" | b"w:dstrike" => run.struck = toggle_value(e),
+            b"w:b" => run.bold = toggle_value(e),
+            b"w:i" => run.italic = toggle_value(e),
+            _ => {}
+        }
+    }
+
+    /// A manual break. Page breaks are left to the renderer; line and column
+    /// breaks simply end the line.
+    fn line_break(&mut self, e: &BytesStart) {
+        if attribute(e, b"w:type").as_deref() == Some("page") {
+            if self.writes_output() {
+                self.visit(Visit::PageBreak);
+            }
+        } else {
+            self.push_content("\n");
+        }
+    }
+
+    /// Ends the properties of a section: those in a paragraph end the
+    /// section at that paragraph, those at the end of the body belong to the
+    /// last section.
+    fn end_section_properties(&mut self) {
+        let section = SectionInfo {
+            start: self.section_type.clone(),
+        };
+        if self.paragraph_properties_depth > 0 {
+            self.section_end = Some(section);
+        } else {
+            self.visit(Visit::SectionEnd(section));
+        }
+    }
+
+    /// Records a bookmark and opens the requested one's range. Bookmarks
+    /// may span paragraphs and nest; only the requested one matters.
+    fn bookmark_start(&mut self, e: &BytesStart) {
+        let Some(name) = attribute(e, b"w:name") else {
+            return;
+        };
+        if self.options.bookmark.as_ref() == Some(&name) && !self.bookmark_found {
+            self.bookmark_found = true;
+            self.bookmark_range = attribute(e, b"w:id");
+            self.paragraph_in_bookmark = true;
+        }
+        self.bookmark_names.push(name);
+    }
+
+    /// Whether content is left out for being outside the requested
+    /// bookmark.
+    fn outside_bookmark(&self) -> bool {
+        self.options.bookmark.is_some() && self.bookmark_range.is_none()
+    }
+
+    /// Counts the page starting here.
+    fn rendered_page_break(&mut self) {
+        self.rendered_page += 1;
+        if self.writes_output() {
+            self.visit(Visit::RenderedPageBreak(self.rendered_page));
+        }
+    }
+
+    /// A symbol inserted from a font (Insert > Symbol), such as a Wingdings
+    /// check mark, written as its Unicode equivalent.
+    fn symbol(&mut self, e: &BytesStart) {
+        let font = attribute(e, b"w:font").unwrap_or_default();
+        let code = attribute(e, b"w:char").and_then(|code| u32::from_str_radix(&code, 16).ok());
+        let Some(code) = code else {
+            return;
+        };
+        if let Some(c) = symbols::symbol_char(&font, code) {
+            self.push_content(&c.to_string());
+            return;
+        }
+        match self.options.unmapped_symbols {
+            UnmappedSymbols::Raw => {
+                if let Some(c) = char::from_u32(code) {
+                    self.push_content(&c.to_string());
+                }
+            }
+            UnmappedSymbols::Placeholder => self.push("[symbol]"),
+        }
+        self.warn(|path, paragraph| Warning::UnmappedSymbol {
+            path,
+            paragraph,
+            font,
+            code,
+        });
+    }
+
+    /// Content imported from another file (`w:altChunk`), which is left
+    /// out.
+    fn imported_content(&mut self, e: &BytesStart) {
+        let id = attribute(e, b"r:id").unwrap_or_default();
+        let part = match self.relationships.get(&id) {
+            Some(relationship) => relationship.target.clone(),
+            None => id,
+        };
+        self.warn(|path, paragraph| Warning::ImportedContent {
+            path,
+            paragraph,
+            part,
+        });
+    }
+
+    /// Records a warning about the paragraph being read, or, between
+    /// paragraphs, the next one. Content that is not written, such as a
+    /// deleted revision, warrants none.
+    fn warn(&mut self, warning: impl FnOnce(PathBuf, usize) -> Warning) {
+        if !self.writes_output() {
+            return;
+        }
+        let path = self.document_path.clone().unwrap_or_default();
+        let open = usize::from(!self.paragraphs.is_empty());
+        self.warnings.push(warning(path, self.paragraphs_started - open));
+    }
+
+    /// A run-level tab. `w:tab` is also the element name of tab stops in
+    /// paragraph properties, which produce no output.
+    fn tab(&mut self) {
+        if !self.in_tab_stops {
+            let options = self.options;
+            self.push_content(&options.tab_string);
+        }
+    }
+
+    /// Appends character data verbatim to the open paragraph. Runs are not
+    /// separated from each other: Word splits words across runs freely, so
+    /// separators only ever come from paragraph boundaries.
+    fn text(&mut self, content: &str) {
+        if self.skipped_depth > 0 {
+            return;
+        }
+        if let Some(equation) = self.equation.as_mut() {
+            equation.text(content);
+            return;
+        }
+        if self.sdt_properties_depth > 0 {
+            return;
+        }
+        if self.in_instr_text {
+            // Instructions are buffered per field and classified as a whole
+            // once complete, since they are often split across runs.
+            if let Some(field) = self.fields.last_mut() {
+                field.instruction.push_str(content);
+            }
+            return;
+        }
+        if !self.in_run_text {
+            return;
+        }
+        // Text before a field's separator is the result of a field nested in
+        // its instruction, and belongs to that instruction.
+        if let Some(field) = self.fields.iter_mut().rev().find(|field| !field.separated) {
+            field.instruction.push_str(content);
+            return;
+        }
+        // Without xml:space="preserve", leading and trailing whitespace in a
+        // text element is insignificant and Word ignores it too.
+        let content = if self.preserve_space {
+            content
+        } else {
+            content.trim()
+        };
+        let mut content = Cow::Borrowed(content);
+        if self.options.normalize_punctuation {
+            content = Cow::Owned(punctuation::to_ascii(&content).into_owned());
+        }
+        if self.options.strip_invisible {
+            let (stripped, removed) = invisible::strip_invisible(&content);
+            let stripped = stripped.into_owned();
+            self.invisible_removed += removed;
+            content = Cow::Owned(stripped);
+        }
+        self.push_content(&content);
+    }
+
+    /// Writes a marker the collector adds itself, such as a placeholder, in
+    /// the open paragraph. Nothing inside a field's instruction part is
+    /// output, nor are revisions the revision mode leaves out, text outside
+    /// the requested bookmark, or runs whose formatting the options leave
+    /// out (hidden, struck through).
+    fn push(&mut self, content: &str) {
+        if self.writes_output() {
+            self.visit(Visit::Marker(content.to_string()));
+        }
+    }
+
+    /// Whether output at this point is written; see [`Self::push`].
+    fn writes_output(&self) -> bool {
+        !(self.fields.iter().any(|field| !field.separated)
+            || self.removed_depth > 0
+            || self.outside_bookmark()
+            || self.runs.last().is_some_and(|run| run.skipped(self.options)))
+    }
+
+    /// Writes the content of a run, as formatted by the run.
+    fn push_content(&mut self, content: &str) {
+        if self.writes_output() {
+            let run = self.runs.last().map_or(RunInfo::default(), RunProperties::info);
+            self.visit(Visit::Text(content.to_string(), run));
+        }
+    }
+
+    /// Tells the renderer and the visitor, if there is one, of content: at
+    /// once outside paragraphs, otherwise once the paragraph is known to be
+    /// kept.
+    fn visit(&mut self, visit: Visit) {
+        self.start_cell();
+        if let Some(paragraph) = self.paragraphs.last_mut() {
+            paragraph.visits.push(visit);
+            return;
+        }
+        visit.send(&mut self.renderer);
+        if let Some(visitor) = self.visitor.as_deref_mut() {
+            visit.send(visitor);
+        }
+    }
+
+    /// Starts the cell started last, once its properties are read or its
+    /// content starts.
+    fn start_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            self.visit(Visit::CellStart(cell));
+        }
+    }
+
+    fn start_paragraph(&mut self) {
+        self.start_cell();
+        self.paragraphs.push(OpenParagraph::default());
+        self.paragraphs_started += 1;
+        self.in_toc_paragraph = self.fields.iter().any(|field| field.toc && field.separated);
+        self.paragraph_in_bookmark = !self.outside_bookmark();
+    }
+
+    fn end_paragraph(&mut self) {
+        let Some(OpenParagraph { mut info, visits }) = self.paragraphs.pop() else {
+            return;
+        };
+        let style = info.style.as_deref();
+        let toc = self.options.toc != TocMode::Keep && (self.in_toc_paragraph || self.is_toc_style(style));
+        let outside_bookmark = self.options.bookmark.is_some() && !self.paragraph_in_bookmark;
+        if toc || outside_bookmark || !self.keeps_style(style) {
+            // The paragraphs and tables of a text box anchored in the
+            // paragraph are blocks of their own, which stay.
+            let mut depth = 0usize;
+            for visit in visits {
+                if visit.starts_block() {
+                    depth += 1;
+                }
+                let in_block = depth > 0;
+                if visit.ends_block() {
+                    depth = depth.saturating_sub(1);
+                }
+                if in_block {
+                    self.visit(visit);
+                }
+            }
+            if toc && self.options.toc == TocMode::Marker && !self.toc_marker_written {
+                self.toc_marker_written = true;
+                self.visit(Visit::Marker("[table of contents]".to_string()));
+            }
+            return;
+        }
+        self.toc_marker_written = false;
+        let styles = &self.parts.styles;
+        info.heading_level = style.and_then(|style| styles.heading_level(style));
+        self.visit(Visit::ParagraphStart(info));
+        for visit in visits {
+            self.visit(visit);
+        }
+        self.visit(Visit::ParagraphEnd);
+    }
+
+    /// Whether a style is one of the built-in styles of TOC entries, `TOC1`
+    /// to `TOC9` (named `toc 1` to `toc 9`).
+    fn is_toc_style(&self, style_id: Option<&str>) -> bool {
+        let Some(style_id) = style_id else {
+            return false;
+        };
+        let name = self.parts.styles.name(style_id).unwrap_or(style_id);
+        [style_id, name].iter().any(|name| {
+            let name = name.to_ascii_lowercase();
+            name.strip_prefix("toc")
+                .map(str::trim_start)
+                .is_some_and(|level| matches!(level.as_bytes(), [b'1'..=b'9']))
+        })
+    }
+
+    /// Whether paragraphs in the given style, or the default paragraph style
+    /// if none, pass the style filters. Patterns match style ids and display
+    /// names alike, since Word localizes the names users see.
+    fn keeps_style(&self, style_id: Option<&str>) -> bool {
+        if self.include_styles.is_empty() && self.exclude_styles.is_empty() {
+            return true;
+        }
+        let styles = &self.parts.styles;
+        let style_id = style_id.or_else(|| styles.default_paragraph_style());
+        let names: Vec<&str> = style_id
+            .into_iter()
+            .chain(style_id.and_then(|id| styles.name(id)))
+            .collect();
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+        let matches = |patterns: &[Pattern]| {
+            patterns.iter().any(|pattern| {
+                names
+                    .iter()
+                    .any(|name| pattern.matches_with(name, options))
+            })
+        };
+        (self.include_styles.is_empty() || matches(&self.include_styles))
+            && !matches(&self.exclude_styles)
+    }
+
+    /// Writes the text rendered so far to `sink`, if nothing read later can
+    /// change it: no paragraph, content control or story is open, nor
+    /// anything the renderer holds back.
+    fn flush(&mut self, sink: &mut dyn TextSink) {
+        if !self.paragraphs.is_empty() || !self.content_controls.is_empty() || self.story.is_some() {
+            return;
+        }
+        self.renderer.flush(sink);
+    }
+
+    fn finish(self) -> String {
+        self.renderer.finish()
+    }
+}
+
+/// Compiles style filter patterns; a pattern that is not a valid glob
+/// matches its literal text.
+fn style_patterns(patterns: &[String]) -> Vec<Pattern> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).unwrap_or_else(|_| {
+                Pattern::new(&Pattern::escape(pattern)).expect("escaped pattern is valid")
+            })
+        })
+        .collect()
+}
+
+/// Formats a positive number as an uppercase roman numeral.
+fn roman_numeral(mut number: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut numeral = String::new();
+    for (value, symbol) in NUMERALS {
+        while number >= value {
+            numeral.push_str(symbol);
+            number -= value;
+        }
+    }
+    numeral
+}
+
+/// Joins the non-empty lines of a multi-paragraph text with spaces.
+fn single_line(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.join(" ")
+}
+
+/// Returns the unescaped value of the attribute named `key`, if present.
+fn attribute(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key)
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+/// Returns the target of a HYPERLINK field instruction such as
+/// `HYPERLINK "https://example.com" \o "tooltip"` or `HYPERLINK \l "_Toc1"`.
+fn hyperlink_field_target(instruction: &str) -> Option<String> {
+    // Split into words, keeping quoted arguments together.
+    let mut words = Vec::new();
+    let mut chars = instruction.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            words.push(chars.by_ref().take_while(|&c| c != '"').collect::<String>());
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            words.push(word);
+        }
+    }
+
+    let mut url = None;
+    let mut anchor = None;
+    let mut words = words.into_iter().skip(1);
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "\\l" => anchor = words.next(),
+            // Switches taking an argument: tooltip, target frame.
+            "\\o" | "\\t" => {
+                words.next();
+            }
+            _ if word.starts_with('\\') => {}
+            _ if url.is_none() => url = Some(word),
+            _ => {}
+        }
+    }
+    match (url, anchor) {
+        (Some(url), Some(anchor)) => Some(format!("{}#{}", url, anchor)),
+        (Some(url), None) => Some(url),
+        (None, Some(anchor)) => Some(format!("#{}", anchor)),
+        (None, None) => None,
+    }
+}
+
+/// Reads an on/off property such as `<w:vanish/>`, which is on unless its
+/// `w:val` says otherwise.
+fn toggle_value(e: &BytesStart) -> bool {
+    !matches!(attribute(e, b"w:val").as_deref(), Some("false" | "0" | "off"))
+}
+
+/// Returns true if the element carries `xml:space="preserve"`.
+fn preserves_space(e: &BytesStart) -> bool {
+    attribute(e, b"xml:space").as_deref() == Some("preserve")
+}
+
+/// The name under which the archive stores a part. Part names are
+/// case-insensitive in OPC, and some writers store them with backslashes
+/// (`word\document.xml`), so after the exact name this tries the name with
+/// separators normalized, then without regard to case. An exact match always
+/// wins, so archives with entries differing only in case still work.
+fn find_part_name<R: Read + Seek>(archive: &ZipArchive<R>, name: &str) -> Option<String> {
+    let name = name.trim_start_matches('/');
+    let normalized = |entry: &str| entry.replace('\\', "/").trim_start_matches('/').to_string();
+    let entries: Vec<&str> = archive.file_names().collect();
+    entries
+        .iter()
+        .find(|entry| **entry == name)
+        .or_else(|| entries.iter().find(|entry| normalized(entry) == name))
+        .or_else(|| {
+            entries
+                .iter()
+                .find(|entry| normalized(entry).eq_ignore_ascii_case(name))
+        })
+        .map(|entry| entry.to_string())
+}
+
+/// Reads a part of the package as a string, or `None` if the package has no
+/// such part. Parts in UTF-16 or a declared legacy encoding are transcoded
+/// to UTF-8.
+fn read_optional_part<R: Read + Seek>(
+    package: &mut Package<R>,
+    name: &str,
+) -> Result<Option<String>, MergeError> {
+    let Some(name) = find_part_name(&package.archive, name) else {
+        return Ok(None);
+    };
+    let Some(content) = read_entry(package, &name)? else {
+        return Ok(None);
+    };
+    match encoding::decode_xml(content) {
+        Ok(xml) => Ok(Some(xml)),
+        Err(e) => Err(not_utf8(e).in_part(&package.path, Some(&name))),
+    }
+}
+
+/// Reads an entry of the archive by its exact name, or `None` if there is
+/// no such entry. Entries are inflated through a limit, so a small archive
+/// cannot expand into more memory than the package's limits allow.
+fn read_entry<R: Read + Seek>(
+    package: &mut Package<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, MergeError> {
+    let remaining = package.max_total_uncompressed.saturating_sub(package.total_uncompressed);
+    let limit = package.max_part_size.min(remaining);
+    let part = match package.archive.by_name(name) {
+        Ok(part) => part,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(source) => {
+            return Err(MergeError::Zip {
+                path: package.path.clone(),
+                source,
+            })
+        }
+    };
+    let mut content = Vec::new();
+    if let Err(e) = part.take(limit.saturating_add(1)).read_to_end(&mut content) {
+        return Err(MergeError::Zip {
+            path: package.path.clone(),
+            source: ZipError::Io(e),
+        });
+    }
+    let size = content.len() as u64;
+    if size > limit {
+        return Err(package.too_large(name, size));
+    }
+    package.total_uncompressed += size;
+    Ok(Some(content))
+}
+
+/// Reads an XML part as it is inflated, feeding its events to `collector`
+/// without holding the whole part, which for a large document's body can
+/// be many megabytes. Parts in encodings other than UTF-8 are rare, and
+/// read whole to be transcoded. `after_end` is as for [`stream_xml`].
+fn stream_part<R: Read + Seek>(
+    package: &mut Package<R>,
+    part_name: &str,
+    collector: &mut TextCollector,
+    after_end: impl FnMut(&mut TextCollector) -> ControlFlow<()>,
+) -> Result<(), MergeError> {
+    let missing = || MergeError::MissingPart {
+        path: package.path.clone(),
+        part: part_name.to_string(),
+    };
+    let Some(name) = find_part_name(&package.archive, part_name) else {
+        return Err(missing());
+    };
+    let remaining = package.max_total_uncompressed.saturating_sub(package.total_uncompressed);
+    let limit = package.max_part_size.min(remaining);
+    let inflated = Cell::new(0);
+    let result = match package.archive.by_name(&name) {
+        Ok(part) => {
+            let mut reader = BufReader::new(LimitedReader {
+                inner: part,
+                limit,
+                inflated: &inflated,
+            });
+            let utf8 = reader.fill_buf().map(encoding::is_utf8).unwrap_or(true);
+            if utf8 {
+                stream_xml(WordReader::from_reader(reader), collector, after_end)
+            } else {
+                let mut content = Vec::new();
+                match reader.read_to_end(&mut content) {
+                    Ok(_) => match encoding::decode_xml(content) {
+                        Ok(xml) => stream_xml(WordReader::from_str(&xml), collector, after_end),
+                        Err(e) => Err(not_utf8(e)),
+                    },
+                    // Told apart below, by the bytes inflated.
+                    Err(e) => Err(XmlError {
+                        position: 0,
+                        source: quick_xml::Error::Io(Arc::new(e)),
+                    }),
+                }
+            }
+        }
+        Err(ZipError::FileNotFound) => return Err(missing()),
+        Err(source) => {
+            return Err(MergeError::Zip {
+                path: package.path.clone(),
+                source,
+            })
+        }
+    };
+    if inflated.get() > limit {
+        return Err(package.too_large(&name, inflated.get()));
+    }
+    package.total_uncompressed += inflated.get();
+    result.map_err(|e| match e.source {
+        quick_xml::Error::Io(source) => MergeError::Zip {
+            path: package.path.clone(),
+            source: ZipError::Io(Arc::try_unwrap(source).unwrap_or_else(|source| {
+                std::io::Error::new(source.kind(), source.to_string())
+            })),
+        },
+        _ => e.in_part(&package.path, Some(&name)),
+    })
+}
+
+/// Reads an entry of the archive, failing once more than `limit` bytes
+/// have been inflated from it, and counting the bytes in `inflated`.
+struct LimitedReader<'c, R> {
+    inner: R,
+    limit: u64,
+    inflated: &'c Cell<u64>,
+}
+
+impl<R: Read> Read for LimitedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.inflated.set(self.inflated.get() + read as u64);
+        if self.inflated.get() > self.limit {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "part exceeds the size limit",
+            ));
+        }
+        Ok(read)
+    }
+}
+
+/// The error for XML that is not valid UTF-8, at the first invalid byte.
+fn not_utf8(error: std::string::FromUtf8Error) -> XmlError {
+    let error = error.utf8_error();
+    XmlError {
+        position: error.valid_up_to(),
+        source: quick_xml::Error::NonDecodable(Some(error)),
+    }
+}
+
+/// An open document package, with the limits on how much of it may be
+/// inflated.
+struct Package<R> {
+    archive: ZipArchive<R>,
+    /// The file the package was read from, or the name given to a reader,
+    /// for error messages.
+    path: PathBuf,
+    max_part_size: u64,
+    max_total_uncompressed: u64,
+    /// Bytes inflated so far, over all parts read.
+    total_uncompressed: u64,
+    /// Parts parsed for other documents, which this one may share.
+    cache: Option<Arc<PartCache>>,
+}
+
+impl<R> Package<R> {
+    /// The error for a part that inflated to `size` bytes, more than the
+    /// limits allow.
+    fn too_large(&self, part: &str, size: u64) -> MergeError {
+        let path = self.path.clone();
+        if size > self.max_part_size {
+            MergeError::PartTooLarge {
+                path,
+                part: part.to_string(),
+                limit: self.max_part_size,
+            }
+        } else {
+            MergeError::PackageTooLarge {
+                path,
+                part: part.to_string(),
+                limit: self.max_total_uncompressed,
+            }
+        }
+    }
+}
+
+/// Where the bytes of a package come from: the document itself, or, for a
+/// Flat OPC document, the package rebuilt from it in memory.
+enum PackageSource<R> {
+    Reader(R),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl<R: Read> Read for PackageSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            PackageSource::Reader(reader) => reader.read(buf),
+            PackageSource::Memory(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl<R: Seek> Seek for PackageSource<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            PackageSource::Reader(reader) => reader.seek(pos),
+            PackageSource::Memory(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
+/// Reads the first bytes of a document into `signature`, returning how many
+/// there were. Unlike a single `read`, this does not stop short on readers
+/// that return data in small pieces.
+fn read_signature(reader: &mut impl Read, signature: &mut [u8]) -> std::io::Result<usize> {
+    let mut read = 0;
+    while read < signature.len() {
+        match reader.read(&mut signature[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    Ok(read)
+}
+
+/// Signature of a compound file (CFB), the container of both encrypted
+/// documents and legacy `.doc` files.
+const COMPOUND_FILE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Opens a document package, recognizing files that are not zip packages
+/// so they fail with an error that says why. Flat OPC documents, which are
+/// XML, are turned back into the package they stand for.
+#[cfg(feature = "cli")]
+fn open_package(
+    path: &Path,
+    options: &ExtractOptions,
+) -> Result<Package<PackageSource<File>>, MergeError> {
+    let file = File::open(path).map_err(|source| MergeError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    open_package_reader(file, path, options)
+}
+
+/// Opens a document package from any seekable reader, as [`open_package`]
+/// does a file. `path` names the document in errors.
+fn open_package_reader<R: Read + Seek>(
+    mut reader: R,
+    path: &Path,
+    options: &ExtractOptions,
+) -> Result<Package<PackageSource<R>>, MergeError> {
+    let io_error = |source| MergeError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut signature = [0; 8];
+    let read = read_signature(&mut reader, &mut signature).map_err(io_error)?;
+    reader.seek(SeekFrom::Start(0)).map_err(io_error)?;
+    if read == signature.len() && signature == COMPOUND_FILE_SIGNATURE {
+        return Err(classify_compound_file(path, &mut reader).map_err(io_error)?);
+    }
+    let source = if flat_opc::looks_like_xml(&signature[..read]) {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(io_error)?;
+        let xml = encoding::decode_xml(bytes).map_err(|e| not_utf8(e).in_part(path, None))?;
+        match flat_opc::to_zip(&xml, path)? {
+            Some(zip) => PackageSource::Memory(Cursor::new(zip)),
+            None => {
+                return Err(MergeError::Zip {
+                    path: path.to_path_buf(),
+                    source: ZipError::InvalidArchive("XML file that is not a Flat OPC package"),
+                })
+            }
+        }
+    } else {
+        PackageSource::Reader(reader)
+    };
+    let archive = ZipArchive::new(source).map_err(|source| MergeError::Zip {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(Package {
+        archive,
+        path: path.to_path_buf(),
+        max_part_size: options.max_part_size,
+        max_total_uncompressed: options.max_total_uncompressed,
+        total_uncompressed: 0,
+        cache: None,
+    })
+}
+
+/// Tells an encrypted document from a legacy `.doc` file by the streams
+/// of the compound file. Rather than parse the container, this looks for
+/// the stream names, which directory entries store in UTF-16.
+fn classify_compound_file(path: &Path, reader: &mut impl Read) -> std::io::Result<MergeError> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+    let contains_stream = |name: &str| {
+        let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        content.windows(name.len()).any(|window| window == name.as_slice())
+    };
+    let path = path.to_path_buf();
+    if contains_stream("EncryptedPackage") {
+        return Ok(MergeError::Encrypted { path });
+    }
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    if contains_stream("WordDocument") || matches!(extension.as_deref(), Some("doc" | "dot")) {
+        return Ok(MergeError::LegacyDoc { path });
+    }
+    Ok(MergeError::Zip {
+        path,
+        source: ZipError::InvalidArchive("compound file without a Word document"),
+    })
+}
+
+/// Name of the main document part used when the package does not say.
+const DEFAULT_MAIN_PART: &str = "word/document.xml";
+
+/// Finds the main document part through the package relationships
+/// (`_rels/.rels`). Some producers name it other than `word/document.xml`.
+/// Spreadsheets and presentations are packages too; if `[Content_Types].xml`
+/// says the main part is not a Word document, this fails saying what it is.
+fn main_document_part<R: Read + Seek>(
+    package: &mut Package<R>,
+) -> Result<String, MergeError> {
+    let relationships = read_relationships(package, "")?;
+    let main_part = match relationships.find_by_type("officeDocument") {
+        Some(relationship) => resolve_part_name("", &relationship.target),
+        None => DEFAULT_MAIN_PART.to_string(),
+    };
+    if let Some(xml) = read_optional_part(package, "[Content_Types].xml")? {
+        let content_types = ContentTypes::parse(&xml)
+            .map_err(|e| e.in_part(&package.path, Some("[Content_Types].xml")))?;
+        if let Some(content_type) = content_types.get(&main_part) {
+            if !content_types::is_wordprocessing_main(content_type) {
+                return Err(MergeError::NotWordDocument {
+                    path: package.path.clone(),
+                    kind: content_types::describe_document_kind(content_type),
+                });
+            }
+        }
+    }
+    Ok(main_part)
+}
+
+/// Reads the main document part, which every document has.
+#[cfg(feature = "cli")]
+fn read_main_part<R: Read + Seek>(
+    package: &mut Package<R>,
+    part_name: &str,
+) -> Result<String, MergeError> {
+    match read_optional_part(package, part_name)? {
+        Some(xml) => Ok(xml),
+        None => Err(MergeError::MissingPart {
+            path: package.path.clone(),
+            part: part_name.to_string(),
+        }),
+    }
+}
+
+/// Extracts the text content from the provided DOCX file.
+/// Hyperlinks are written as their text followed by the target, or, if
+/// `strip_hyperlinks` is true, as their visible text only.
+#[cfg(feature = "cli")]
+#[deprecated(note = "use `extract_text_from_docx_with` and `ExtractOptions::strip_hyperlinks`")]
+pub fn extract_text_from_docx(
+    path: impl AsRef<Path>,
+    strip_hyperlinks: bool,
+) -> Result<String, MergeError> {
+    extract_text_from_docx_with(path, &ExtractOptions::new().strip_hyperlinks(strip_hyperlinks))
+}
+
+/// Extracts the text content from the provided DOCX file using `options`.
+/// Each paragraph (including those in table cells and text boxes) is followed
+/// by `options.paragraph_separator`; runs within a paragraph are joined as-is.
+/// Hyperlink targets are resolved through the relationships of the main
+/// document part, usually `word/_rels/document.xml.rels`.
+#[cfg(feature = "cli")]
+pub fn extract_text_from_docx_with(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let mut text = String::new();
+    write_text_from_docx(path.as_ref(), options, &mut text)?;
+    Ok(text)
+}
+
+/// Extracts the text content from the provided DOCX file using `options`,
+/// as [`extract_text_from_docx_with`] does, writing it to `writer` as it
+/// is read rather than collecting it first. The body's XML is parsed as it
+/// is inflated, so neither it nor the text of a large document is held in
+/// memory whole. With headers and footers included, the body is written
+/// only once the headers are known, at its end.
+///
+/// If extraction fails part way, the text written so far stays written.
+/// Failing to write is a [`MergeError::Write`].
+#[cfg(feature = "cli")]
+pub fn extract_text_to_writer<W: Write>(
+    path: impl AsRef<Path>,
+    writer: &mut W,
+    options: &ExtractOptions,
+) -> Result<(), MergeError> {
+    let mut sink = IoSink::new(writer);
+    write_text_from_docx(path.as_ref(), options, &mut sink)?;
+    sink.finish().map_err(|source| MergeError::Write { source })
+}
+
+/// Writes the text of a DOCX file to `sink`.
+#[cfg(feature = "cli")]
+fn write_text_from_docx(
+    path: &Path,
+    options: &ExtractOptions,
+    sink: &mut dyn TextSink,
+) -> Result<(), MergeError> {
+    let file = File::open(path).map_err(|source| MergeError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut package = open_package_reader(file, path, options)?;
+    write_docx_package(&mut package, options, sink, None, None)
+}
+
+/// Reads the paragraphs of a DOCX file one at a time, as they are
+/// extracted with `options`, so that a caller needing only the first few
+/// never has the rest read. Paragraphs in table cells and text boxes are
+/// paragraphs of their own; paragraphs left out by `options` are not
+/// yielded.
+///
+/// The document is read on a thread of its own, at most a paragraph ahead
+/// of the iterator; dropping the iterator stops it. A failure to open or
+/// read the document is the last item.
+///
+/// ```no_run
+/// use mddocmrg::{paragraphs, ExtractOptions};
+///
+/// for paragraph in paragraphs("report.docx", &ExtractOptions::default()).take(50) {
+///     println!("{}", paragraph?.text);
+/// }
+/// # Ok::<(), mddocmrg::MergeError>(())
+/// ```
+#[cfg(feature = "cli")]
+pub fn paragraphs(path: impl AsRef<Path>, options: &ExtractOptions) -> DocxParagraphs {
+    // A rendezvous channel: the reader waits until each paragraph is taken.
+    let (sender, receiver) = mpsc::sync_channel(0);
+    let path = path.as_ref().to_path_buf();
+    let options = options.clone();
+    std::thread::spawn(move || {
+        if let Err(error) = send_paragraphs(&path, &options, &sender) {
+            // The iterator may be gone already, with no one to tell.
+            let _ = sender.send(Err(error));
+        }
+    });
+    DocxParagraphs { receiver }
+}
+
+/// The paragraphs of a DOCX document, read as they are asked for; see
+/// [`paragraphs`].
+#[cfg(feature = "cli")]
+pub struct DocxParagraphs {
+    receiver: Receiver<Result<Paragraph, MergeError>>,
+}
+
+#[cfg(feature = "cli")]
+impl Iterator for DocxParagraphs {
+    type Item = Result<Paragraph, MergeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Sends the paragraphs of a DOCX file as they are read, until the
+/// receiver hangs up.
+#[cfg(feature = "cli")]
+fn send_paragraphs(
+    path: &Path,
+    options: &ExtractOptions,
+    sender: &SyncSender<Result<Paragraph, MergeError>>,
+) -> Result<(), MergeError> {
+    let mut package = open_package(path, options)?;
+    let main_part = main_document_part(&mut package)?;
+    let relationships = read_relationships(&mut package, &main_part)?;
+    let parts = read_document_parts(&mut package, options)?;
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    collector.renderer.written_paragraphs = Some(Vec::new());
+    let mut hung_up = false;
+    let send = |collector: &mut TextCollector| {
+        let written = collector.renderer.written_paragraphs.as_mut().map(std::mem::take);
+        for paragraph in written.unwrap_or_default() {
+            if sender.send(Ok(paragraph)).is_err() {
+                hung_up = true;
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    };
+    stream_part(&mut package, &main_part, &mut collector, send)?;
+    options.check_cancelled()?;
+    if let (Some(name), false, false) = (&options.bookmark, collector.bookmark_found, hung_up) {
+        return Err(MergeError::BookmarkNotFound {
+            path: package.path.clone(),
+            name: name.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Walks the main document of a DOCX file, telling `visitor` of its
+/// paragraphs, runs, hyperlinks and tables, for rendering it in a format
+/// of one's own. `options` decide what is extracted, as for
+/// [`extract_text_from_docx_with`]; those about how text is written, such
+/// as the link style, do not apply.
+#[cfg(feature = "cli")]
+pub fn extract_with_visitor<V: DocxVisitor>(
+    path: impl AsRef<Path>,
+    visitor: &mut V,
+    options: &ExtractOptions,
+) -> Result<(), MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    visit_docx_package(&mut package, visitor, options)
+}
+
+/// Walks the main document of an opened Word package with `visitor`.
+#[cfg(feature = "cli")]
+fn visit_docx_package<R: Read + Seek>(
+    package: &mut Package<R>,
+    visitor: &mut dyn DocxVisitor,
+    options: &ExtractOptions,
+) -> Result<(), MergeError> {
+    let main_part = main_document_part(package)?;
+    let relationships = read_relationships(package, &main_part)?;
+    let parts = read_document_parts(package, options)?;
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    collector.visitor = Some(visitor);
+    stream_part(package, &main_part, &mut collector, |_| ControlFlow::Continue(()))?;
+    options.check_cancelled()?;
+    if let (Some(name), false) = (&options.bookmark, collector.bookmark_found) {
+        return Err(MergeError::BookmarkNotFound {
+            path: package.path.clone(),
+            name: name.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Reads a DOCX file into a [`Document`]: its core properties and the
+/// blocks of its main document, extracted as `options` say. With the
+/// `serde` feature, documents serialize, for tools that want them as, say,
+/// JSON.
+#[cfg(feature = "cli")]
+pub fn parse_docx(path: impl AsRef<Path>, options: &ExtractOptions) -> Result<Document, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    parse_docx_package(&mut package, options)
+}
+
+/// Reads an opened Word package into a [`Document`].
+#[cfg(feature = "cli")]
+fn parse_docx_package<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+) -> Result<Document, MergeError> {
+    let mut builder = DocumentBuilder::new();
+    visit_docx_package(package, &mut builder, options)?;
+    let metadata = read_metadata(package)?;
+    Ok(builder.finish(metadata))
+}
+
+/// Reads any file a merge takes into a [`Document`]: a Word document as
+/// [`parse_docx`] does, and other files as a paragraph for each line of
+/// their text that is not blank, without metadata.
+#[cfg(feature = "cli")]
+fn parse_file(path: &Path, options: &ExtractOptions) -> Result<Document, MergeError> {
+    if !is_plain_text(path) {
+        let mut package = open_package(path, options)?;
+        if detect_format(&mut package)? == DocumentFormat::Docx {
+            return parse_docx_package(&mut package, options);
+        }
+    }
+    let text = extract_text_from_file(path, options)?;
+    let blocks = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Block::Paragraph {
+            style: None,
+            heading_level: None,
+            runs: vec![Run {
+                text: line.to_string(),
+                ..Run::default()
+            }],
+        })
+        .collect();
+    Ok(Document {
+        metadata: Metadata::default(),
+        blocks,
+    })
+}
+
+/// Reads the core and extended properties of a package, found through the
+/// package relationships. A package without them has no metadata.
+#[cfg(feature = "cli")]
+fn read_metadata<R: Read + Seek>(package: &mut Package<R>) -> Result<Metadata, MergeError> {
+    let relationships = read_relationships(package, "")?;
+    let mut metadata = Metadata::default();
+    let core = read_properties_part(package, &relationships, "core-properties", "docProps/core.xml")?;
+    if let Some((part_name, xml)) = core {
+        metadata
+            .read_core_properties(&xml)
+            .map_err(|e| e.in_part(&package.path, Some(&part_name)))?;
+    }
+    let app = read_properties_part(package, &relationships, "extended-properties", "docProps/app.xml")?;
+    if let Some((part_name, xml)) = app {
+        metadata
+            .read_app_properties(&xml)
+            .map_err(|e| e.in_part(&package.path, Some(&part_name)))?;
+    }
+    metadata.custom_properties = read_custom_properties_part(package, &relationships)?;
+    Ok(metadata)
+}
+
+/// Reads the custom properties of a package, if it has any.
+#[cfg(feature = "cli")]
+fn read_custom_properties_part<R: Read + Seek>(
+    package: &mut Package<R>,
+    relationships: &Relationships,
+) -> Result<Vec<(String, PropertyValue)>, MergeError> {
+    let custom = read_properties_part(package, relationships, "custom-properties", "docProps/custom.xml")?;
+    match custom {
+        Some((part_name, xml)) => properties::read_custom_properties(&xml)
+            .map_err(|e| e.in_part(&package.path, Some(&part_name))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Reads a properties part, found through the package relationship of its
+/// type or else by its usual name, with the name it was found by.
+#[cfg(feature = "cli")]
+fn read_properties_part<R: Read + Seek>(
+    package: &mut Package<R>,
+    relationships: &Relationships,
+    rel_type: &str,
+    default_name: &str,
+) -> Result<Option<(String, String)>, MergeError> {
+    let part_name = match relationships.find_by_type(rel_type) {
+        Some(relationship) => resolve_part_name("", &relationship.target),
+        None => default_name.to_string(),
+    };
+    Ok(read_optional_part(package, &part_name)?.map(|xml| (part_name, xml)))
+}
+
+/// Reads the properties of a DOCX file: its title, author, dates and the
+/// like, and the page and word counts Word recorded when it saved it.
+#[cfg(feature = "cli")]
+pub fn extract_metadata(path: impl AsRef<Path>, options: &ExtractOptions) -> Result<Metadata, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    read_metadata(&mut package)
+}
+
+/// Reads the custom properties of a DOCX file (`docProps/custom.xml`), the
+/// values such as a client name that templates and document management
+/// systems stamp into documents, in the order they are written. Values of
+/// types without a [`PropertyValue`] of their own are kept as written.
+#[cfg(feature = "cli")]
+pub fn extract_custom_properties(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<(String, PropertyValue)>, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    let relationships = read_relationships(&mut package, "")?;
+    read_custom_properties_part(&mut package, &relationships)
+}
+
+/// Extracts the text content of a DOCX document from any seekable reader,
+/// such as a `Cursor<Vec<u8>>` over bytes received from the network, as
+/// [`extract_text_from_docx_with`] does from a file. Errors name the
+/// document `<reader>`; see [`extract_text_from_reader_named`].
+pub fn extract_text_from_reader<R: Read + Seek>(
+    reader: R,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    extract_text_from_reader_named(reader, Path::new("<reader>"), options)
+}
+
+/// Extracts the text content of a DOCX document held in memory, such as a
+/// blob read from a database, as [`extract_text_from_reader`] does.
+///
+/// ```no_run
+/// use mddocmrg::{extract_text_from_bytes, ExtractOptions};
+///
+/// let data: Vec<u8> = std::fs::read("report.docx")?;
+/// let text = extract_text_from_bytes(&data, &ExtractOptions::default())?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn extract_text_from_bytes(data: &[u8], options: &ExtractOptions) -> Result<String, MergeError> {
+    extract_text_from_reader(Cursor::new(data), options)
+}
+
+/// Extracts the text content of a DOCX document from a seekable reader, like
+/// [`extract_text_from_reader`], naming the document `name` in errors.
+pub fn extract_text_from_reader_named<R: Read + Seek>(
+    reader: R,
+    name: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let mut package = open_package_reader(reader, name.as_ref(), options)?;
+    extract_docx_package(&mut package, options)
+}
+
+/// Extracts the text of an opened Word package.
+fn extract_docx_package<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let mut text = String::new();
+    write_docx_package(package, options, &mut text, None, None)?;
+    Ok(text)
+}
+
+/// Writes the text of an opened Word package to `sink`, the body a
+/// paragraph at a time as it is read. The paragraphs of the body are
+/// counted into `stats`, if given, and what it warns of is added to
+/// `warnings`, if given.
+fn write_docx_package<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+    sink: &mut dyn TextSink,
+    stats: Option<&mut TextStats>,
+    mut warnings: Option<&mut Vec<Warning>>,
+) -> Result<(), MergeError> {
+    let main_part = main_document_part(package)?;
+    let relationships = read_relationships(package, &main_part)?;
+    let parts = read_document_parts(package, options)?;
+    let mut out = TextWriter::new(sink, options.blank_lines);
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    collector.document_path = Some(package.path.clone());
+    collector.renderer.stats = stats.as_ref().map(|_| TextStats::default());
+    let flush = |collector: &mut TextCollector| {
+        collector.flush(&mut out);
+        ControlFlow::Continue(())
+    };
+    stream_part(package, &main_part, &mut collector, flush)?;
+    options.check_cancelled()?;
+    if let (Some(name), false) = (&options.bookmark, collector.bookmark_found) {
+        return Err(MergeError::BookmarkNotFound {
+            path: package.path.clone(),
+            name: name.clone(),
+        });
+    }
+    let references = [
+        (NoteKind::Footnote, std::mem::take(&mut collector.footnote_references)),
+        (NoteKind::Endnote, std::mem::take(&mut collector.endnote_references)),
+    ];
+    let header_footer_references = std::mem::take(&mut collector.header_footer_references);
+    if options.strip_invisible {
+        let level = if options.verbose { log::Level::Info } else { log::Level::Debug };
+        log::log!(
+            level,
+            "{}: removed {} invisible characters",
+            package.path.display(),
+            collector.invisible_removed
+        );
+    }
+    if let (Some(stats), Some(mut counted)) = (stats, collector.renderer.stats.take()) {
+        counted.pages = collector.rendered_page as u64;
+        *stats += counted;
+    }
+    if let Some(warnings) = warnings.as_mut() {
+        warnings.append(&mut collector.warnings);
+    }
+    let body = collector.finish();
+
+    let mut headers = Vec::new();
+    let mut footers = Vec::new();
+    for reference in &header_footer_references {
+        let Some(relationship) = relationships.get(&reference.id) else {
+            continue;
+        };
+        let part_name = resolve_part_name(&main_part, &relationship.target);
+        let Some(part_text) = read_part_text(package, &part_name, options)? else {
+            if let Some(warnings) = warnings.as_mut() {
+                warnings.push(Warning::MissingPart {
+                    path: package.path.clone(),
+                    part: part_name,
+                });
+            }
+            continue;
+        };
+        if part_text.is_empty() {
+            continue;
+        }
+        // Sections usually repeat the same header; write each one once.
+        let line = format!("{} {}", reference.label(), single_line(&part_text));
+        let lines = if reference.footer { &mut footers } else { &mut headers };
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+    if headers.is_empty() {
+        // The body may have been written already, up to its last paragraph.
+        out.write_text(&body);
+        out.trim_end();
+    } else {
+        out.write_text(&headers.join("\n"));
+        out.write_text("\n\n");
+        out.write_text(&body);
+    }
+
+    for (kind, ids) in references {
+        if options.note_mode(kind) != NoteMode::End || ids.is_empty() {
+            continue;
+        }
+        out.write_text("\n");
+        for (index, id) in ids.into_iter().enumerate() {
+            let note = parts.notes.get(&(kind, id)).map(String::as_str).unwrap_or("");
+            out.write_text(&format!("\n[^{}]: {}", kind.label(index + 1), single_line(note)));
+        }
+    }
+
+    if options.comments == CommentMode::End && !parts.comments.is_empty() {
+        out.write_text("\n\nComments:");
+        for comment in &parts.comments {
+            out.write_text("\n");
+            out.write_text(&comment.render());
+            if let Some(date) = &comment.date {
+                out.write_text(&format!(" ({})", date));
+            }
+        }
+    }
+
+    if options.include_glossary {
+        if let Some(glossary) = read_glossary(package, &main_part, &relationships, options)? {
+            out.write_text("\n\nGlossary:\n");
+            out.write_text(&glossary);
+        }
+    }
+
+    if !footers.is_empty() {
+        out.write_text("\n\n");
+        out.write_text(&footers.join("\n"));
+    }
+    out.finish().map_err(|source| MergeError::Write { source })
+}
+
+/// Reads the glossary document of a package, found through the
+/// relationships of the main document part. Its hyperlinks resolve through
+/// its own relationships. Returns `None` if there is no glossary or it has
+/// no text.
+fn read_glossary<R: Read + Seek>(
+    package: &mut Package<R>,
+    main_part: &str,
+    relationships: &Relationships,
+    options: &ExtractOptions,
+) -> Result<Option<String>, MergeError> {
+    let part_name = match relationships.find_by_type("glossaryDocument") {
+        Some(relationship) => resolve_part_name(main_part, &relationship.target),
+        None => "word/glossary/document.xml".to_string(),
+    };
+    let glossary = read_part_text(package, &part_name, options)?;
+    Ok(glossary.filter(|text| !text.is_empty()))
+}
+
+/// Extracts the text content from the provided OpenDocument text file
+/// (`.odt`) using `options`. The text is read from `content.xml`.
+#[cfg(feature = "cli")]
+pub fn extract_text_from_odt(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    extract_odt_package(&mut package, options)
+}
+
+#[cfg(feature = "cli")]
+fn extract_odt_package<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let xml_content = read_main_part(package, "content.xml")?;
+    let text = odt::extract_odt_text(&xml_content, options)
+        .map_err(|e| e.in_part(&package.path, Some("content.xml")))?;
+    options.check_cancelled()?;
+    Ok(options.blank_lines.apply(text))
+}
+
+/// The kinds of documents text can be extracted from.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentFormat {
+    Docx,
+    Odt,
+}
+
+/// Tells the format of a package from its content rather than its file
+/// name. OpenDocument packages start with a `mimetype` entry naming their
+/// media type; anything else is taken for a Word package.
+#[cfg(feature = "cli")]
+fn detect_format<R: Read + Seek>(
+    package: &mut Package<R>,
+) -> Result<DocumentFormat, MergeError> {
+    let Some(media_type) = read_optional_part(package, "mimetype")? else {
+        return Ok(DocumentFormat::Docx);
+    };
+    let media_type = media_type.trim();
+    if odt::ODT_MEDIA_TYPES.contains(&media_type) {
+        Ok(DocumentFormat::Odt)
+    } else if media_type.starts_with("application/vnd.oasis.opendocument.") {
+        Err(MergeError::NotWordDocument {
+            path: package.path.clone(),
+            kind: content_types::describe_document_kind(media_type),
+        })
+    } else {
+        Ok(DocumentFormat::Docx)
+    }
+}
+
+/// Extensions of plain text files, which are merged as they are.
+#[cfg(feature = "cli")]
+const PLAIN_TEXT_EXTENSIONS: [&str; 3] = ["txt", "md", "markdown"];
+
+#[cfg(feature = "cli")]
+fn is_plain_text(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            PLAIN_TEXT_EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Reads a plain text file, which must be UTF-8, with or without a byte
+/// order mark.
+#[cfg(feature = "cli")]
+fn read_plain_text(path: &Path) -> Result<String, MergeError> {
+    let content = std::fs::read(path).map_err(|source| MergeError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&content);
+    match String::from_utf8(content.to_vec()) {
+        Ok(text) => Ok(text),
+        Err(_) => Err(MergeError::NotUtf8 {
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+/// Extracts the text content from a Word or OpenDocument text file using
+/// `options`, whichever format the file turns out to be. Plain text and
+/// Markdown files (`.txt`, `.md`) are read verbatim.
+#[cfg(feature = "cli")]
+pub fn extract_text_from_file(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let mut text = String::new();
+    write_text_from_file(path.as_ref(), options, &mut text, None, None, None)?;
+    Ok(text)
+}
+
+/// Extracts the text of a file as [`extract_text_from_file`] does, along
+/// with what extraction got past but may have written differently from
+/// what the document shows: equations left out, symbols with no Unicode
+/// equivalent, imported content and missing parts.
+#[cfg(feature = "cli")]
+pub fn extract_text_with_warnings(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<(String, Vec<Warning>), MergeError> {
+    let mut text = String::new();
+    let mut warnings = Vec::new();
+    write_text_from_file(path.as_ref(), options, &mut text, None, Some(&mut warnings), None)?;
+    Ok((text, warnings))
+}
+
+/// Counts the words, characters, paragraphs, headings and pages of the
+/// text extracted from a file using `options`. Only the body of a Word
+/// document is counted, not the headers, footers or notes written after
+/// it. The paragraphs of OpenDocument and plain text files are their lines.
+#[cfg(feature = "cli")]
+pub fn extract_stats(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<TextStats, MergeError> {
+    let mut stats = TextStats::default();
+    let mut sink = IoSink::new(std::io::sink());
+    write_text_from_file(path.as_ref(), options, &mut sink, Some(&mut stats), None, None)?;
+    Ok(stats)
+}
+
+/// Writes the text of a Word, OpenDocument or plain text file to `sink`,
+/// counting it into `stats` and adding its warnings to `warnings` if
+/// given, and sharing the parsed parts of `cache` if given.
+#[cfg(feature = "cli")]
+fn write_text_from_file(
+    path: &Path,
+    options: &ExtractOptions,
+    sink: &mut dyn TextSink,
+    stats: Option<&mut TextStats>,
+    warnings: Option<&mut Vec<Warning>>,
+    cache: Option<&Arc<PartCache>>,
+) -> Result<(), MergeError> {
+    let text = if is_plain_text(path) {
+        read_plain_text(path)?
+    } else {
+        let mut package = open_package(path, options)?;
+        package.cache = cache.cloned();
+        match detect_format(&mut package)? {
+            DocumentFormat::Docx => {
+                return write_docx_package(&mut package, options, sink, stats, warnings)
+            }
+            DocumentFormat::Odt => extract_odt_package(&mut package, options)?,
+        }
+    };
+    if let Some(stats) = stats {
+        stats.add_lines(&text);
+    }
+    sink.write_text(&text);
+    Ok(())
+}
+
+/// Reads the parts the main document refers to, skipping those `options`
+/// leave out.
+fn read_document_parts<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+) -> Result<DocumentParts, MergeError> {
+    Ok(DocumentParts {
+        comments: match options.comments {
+            CommentMode::Skip => Vec::new(),
+            _ => read_comments(package, options)?,
+        },
+        notes: if options.footnotes == NoteMode::Skip && options.endnotes == NoteMode::Skip {
+            HashMap::new()
+        } else {
+            read_notes(package, options)?
+                .into_iter()
+                .map(|note| ((note.kind, note.id), note.text))
+                .collect()
+        },
+        numbering: match read_optional_part(package, "word/numbering.xml")? {
+            Some(xml) => match &package.cache {
+                Some(cache) => cache.numbering(xml),
+                None => Numbering::parse(&xml).map(Arc::new),
+            }
+            .map_err(|e| e.in_part(&package.path, Some("word/numbering.xml")))?,
+            None => Arc::default(),
+        },
+        styles: match read_optional_part(package, "word/styles.xml")? {
+            Some(xml) => match &package.cache {
+                Some(cache) => cache.styles(xml),
+                None => Styles::parse(&xml).map(Arc::new),
+            }
+            .map_err(|e| e.in_part(&package.path, Some("word/styles.xml")))?,
+            None => Arc::default(),
+        },
+    })
+}
+
+/// Extracts the headings of a DOCX file, the document outline, in order.
+/// Heading texts are extracted with `options` like the document text, but
+/// without heading markers.
+#[cfg(feature = "cli")]
+pub fn extract_headings_from_docx(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<Heading>, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    let main_part = main_document_part(&mut package)?;
+    let relationships = read_relationships(&mut package, &main_part)?;
+    let parts = read_document_parts(&mut package, options)?;
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    stream_part(&mut package, &main_part, &mut collector, |_| ControlFlow::Continue(()))?;
+    options.check_cancelled()?;
+    Ok(collector.renderer.headings)
+}
+
+/// Lists the names of the bookmarks in the main document of a DOCX file,
+/// in order. Hidden bookmarks, whose names start with `_` (such as the
+/// `_Toc` anchors of a table of contents), are left out.
+#[cfg(feature = "cli")]
+pub fn extract_bookmarks_from_docx(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<String>, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    let main_part = main_document_part(&mut package)?;
+    let relationships = read_relationships(&mut package, &main_part)?;
+    let parts = DocumentParts::default();
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    stream_part(&mut package, &main_part, &mut collector, |_| ControlFlow::Continue(()))?;
+    options.check_cancelled()?;
+    let mut names = collector.bookmark_names;
+    names.retain(|name| !name.starts_with('_'));
+    Ok(names)
+}
+
+/// Lists the hyperlinks in the main document of a DOCX file, both
+/// `w:hyperlink` elements and HYPERLINK fields, with their visible text, in
+/// the order they appear. A link with the same target and text as one
+/// before it is listed once; links without a target are left out.
+#[cfg(feature = "cli")]
+pub fn extract_hyperlinks(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<Hyperlink>, MergeError> {
+    let mut collector = LinkCollector::default();
+    extract_with_visitor(path, &mut collector, options)?;
+    Ok(collector.links)
+}
+
+/// The name a file extracted from a document's media is written under: the
+/// document's file stem, then the name of the file in the document, so that
+/// the `image1.png` of different documents do not collide.
+fn media_file_name(document: &Path, name: &str) -> String {
+    match document.file_stem() {
+        Some(stem) => format!("{}-{}", stem.to_string_lossy(), name),
+        None => name.to_string(),
+    }
+}
+
+/// Copies the pictures and other media of a DOCX file (`word/media/`) to
+/// `out_dir`, creating it if need be, and returns the files written. Each
+/// is named after the document and the file's name in it, as in
+/// `report-image1.png`. Entries whose names would lead out of the media
+/// folder are skipped. Plain text files, which merges take as well, have no
+/// media.
+#[cfg(feature = "cli")]
+pub fn extract_media(
+    path: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<PathBuf>, MergeError> {
+    copy_folder(path.as_ref(), "word/media/", out_dir.as_ref(), options)
+}
+
+/// A file embedded in a document (`word/embeddings/`), such as a
+/// spreadsheet or PDF shown as an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedObject {
+    /// The name of the file in the document, e.g. `budget.xlsx` or
+    /// `oleObject1.bin`.
+    pub name: String,
+    /// The content type `[Content_Types].xml` declares for it, if any.
+    pub content_type: Option<String>,
+    /// Its size in bytes, uncompressed.
+    pub size: u64,
+}
+
+/// Lists the files embedded in a DOCX file (`word/embeddings/`), which its
+/// text cannot show. Plain text files have none.
+#[cfg(feature = "cli")]
+pub fn list_embedded_objects(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<EmbeddedObject>, MergeError> {
+    let path = path.as_ref();
+    if is_plain_text(path) {
+        return Ok(Vec::new());
+    }
+    let mut package = open_package(path, options)?;
+    let content_types = match read_optional_part(&mut package, "[Content_Types].xml")? {
+        Some(xml) => ContentTypes::parse(&xml)
+            .map_err(|e| e.in_part(&package.path, Some("[Content_Types].xml")))?,
+        None => ContentTypes::default(),
+    };
+    let mut objects = Vec::new();
+    for (entry, name) in folder_entries(&package.archive, "word/embeddings/") {
+        let size = match package.archive.by_name(&entry) {
+            Ok(file) => file.size(),
+            Err(source) => {
+                return Err(MergeError::Zip {
+                    path: package.path.clone(),
+                    source,
+                })
+            }
+        };
+        objects.push(EmbeddedObject {
+            content_type: content_types.get(&entry).map(str::to_string),
+            name,
+            size,
+        });
+    }
+    Ok(objects)
+}
+
+/// A part of a document package, which is a file in its zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackagePart {
+    /// The name of the part in the package, e.g. `word/document.xml`.
+    pub name: String,
+    /// The content type `[Content_Types].xml` declares for it, if any.
+    /// OpenDocument packages declare none there.
+    pub content_type: Option<String>,
+    /// Its size in bytes, uncompressed.
+    pub size: u64,
+}
+
+/// Lists the parts of a Word or OpenDocument package, in the order of its
+/// archive, without reading them. Plain text files have none.
+#[cfg(feature = "cli")]
+pub fn list_parts(path: impl AsRef<Path>, options: &ExtractOptions) -> Result<Vec<PackagePart>, MergeError> {
+    let path = path.as_ref();
+    if is_plain_text(path) {
+        return Ok(Vec::new());
+    }
+    let mut package = open_package(path, options)?;
+    let content_types = match read_optional_part(&mut package, "[Content_Types].xml")? {
+        Some(xml) => ContentTypes::parse(&xml)
+            .map_err(|e| e.in_part(&package.path, Some("[Content_Types].xml")))?,
+        None => ContentTypes::default(),
+    };
+    let mut parts = Vec::new();
+    for index in 0..package.archive.len() {
+        let file = package.archive.by_index_raw(index).map_err(|source| MergeError::Zip {
+            path: package.path.clone(),
+            source,
+        })?;
+        if file.is_dir() {
+            continue;
+        }
+        parts.push(PackagePart {
+            name: file.name().to_string(),
+            content_type: content_types.get(file.name()).map(str::to_string),
+            size: file.size(),
+        });
+    }
+    Ok(parts)
+}
+
+/// Copies the files embedded in a DOCX file (`word/embeddings/`) to
+/// `out_dir`, named as [`extract_media`] names pictures, and returns the
+/// files written.
+#[cfg(feature = "cli")]
+pub fn extract_embedded_objects(
+    path: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<PathBuf>, MergeError> {
+    copy_folder(path.as_ref(), "word/embeddings/", out_dir.as_ref(), options)
+}
+
+/// The entries of an archive directly in `folder`, such as `word/media/`,
+/// with their names in it. Entries whose names would lead out of the
+/// folder are left out.
+#[cfg(feature = "cli")]
+fn folder_entries<R: Read + Seek>(archive: &ZipArchive<R>, folder: &str) -> Vec<(String, String)> {
+    archive
+        .file_names()
+        .filter_map(|entry| {
+            let normalized = entry.replace('\\', "/");
+            let normalized = normalized.trim_start_matches('/');
+            let name = normalized
+                .get(..folder.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(folder))
+                .map(|_| &normalized[folder.len()..])?;
+            let plain_name = !name.is_empty() && !name.contains('/') && name != "." && name != "..";
+            plain_name.then(|| (entry.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Copies the entries directly in `folder` of a document's archive to
+/// `out_dir`, each named after the document and its name in the folder.
+#[cfg(feature = "cli")]
+fn copy_folder(
+    path: &Path,
+    folder: &str,
+    out_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<Vec<PathBuf>, MergeError> {
+    if is_plain_text(path) {
+        return Ok(Vec::new());
+    }
+    let mut package = open_package(path, options)?;
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| MergeError::Io { path, source }
+    };
+    let mut written = Vec::new();
+    for (entry, name) in folder_entries(&package.archive, folder) {
+        let Some(content) = read_entry(&mut package, &entry)? else {
+            continue;
+        };
+        if written.is_empty() {
+            std::fs::create_dir_all(out_dir).map_err(io_error(out_dir))?;
+        }
+        let file = out_dir.join(media_file_name(path, &name));
+        std::fs::write(&file, content).map_err(io_error(&file))?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+/// Extracts the text of a part other than the main document, such as a
+/// header, or returns `None` if the package has no such part.
+fn read_part_text<R: Read + Seek>(
+    package: &mut Package<R>,
+    part_name: &str,
+    options: &ExtractOptions,
+) -> Result<Option<String>, MergeError> {
+    let Some(xml) = read_optional_part(package, part_name)? else {
+        return Ok(None);
+    };
+    let relationships = read_relationships(package, part_name)?;
+    let parts = DocumentParts::default();
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    collect_xml(&xml, &mut collector).map_err(|e| e.in_part(&package.path, Some(part_name)))?;
+    Ok(Some(collector.finish()))
+}
+
+/// Feeds the events of an XML part to `collector`.
+fn collect_xml(xml: &str, collector: &mut TextCollector) -> Result<(), XmlError> {
+    stream_xml(WordReader::from_str(xml), collector, |_| ControlFlow::Continue(()))
+}
+
+/// Feeds the events of an XML part to `collector`, calling `after_end`
+/// after each end tag, where the collector may have text to hand on.
+/// Reading stops early if it breaks, or if the options are cancelled; the
+/// caller then checks for the latter.
+fn stream_xml<R: BufRead>(
+    mut reader: WordReader<R>,
+    collector: &mut TextCollector,
+    mut after_end: impl FnMut(&mut TextCollector) -> ControlFlow<()>,
+) -> Result<(), XmlError> {
+    // Whitespace is significant inside `xml:space="preserve"` runs, so the
+    // collector decides per text element what to trim.
+    loop {
+        if collector.options.is_cancelled() {
+            break;
+        }
+        match reader.read_event()? {
+            Event::Start(ref e) => collector.start(e),
+            Event::End(ref e) => {
+                collector.end(e);
+                if after_end(collector).is_break() {
+                    break;
+                }
+            }
+            Event::Empty(ref e) => collector.empty(e),
+            Event::Text(e) => match e.unescape() {
+                Ok(text) => collector.text(&text),
+                Err(error) => return Err(reader.error(error)),
+            },
+            Event::Eof => break,
+            _ => {} // Ignore other events.
+        }
+    }
+    Ok(())
+}
+
+/// Reads the stories (comments or notes) of a part through the text
+/// collector, using the part's own relationships for hyperlinks. A missing
+/// part has no stories.
+fn read_stories<R: Read + Seek>(
+    package: &mut Package<R>,
+    part_name: &str,
+    options: &ExtractOptions,
+) -> Result<Vec<Story>, MergeError> {
+    let Some(xml) = read_optional_part(package, part_name)? else {
+        return Ok(Vec::new());
+    };
+    let relationships = read_relationships(package, part_name)?;
+    let parts = DocumentParts::default();
+    let mut collector = TextCollector::new(options, &relationships, &parts);
+    collect_xml(&xml, &mut collector).map_err(|e| e.in_part(&package.path, Some(part_name)))?;
+    Ok(collector.stories)
+}
+
+/// Reads the relationships of a part (`word/_rels/<name>.rels` for
+/// `word/<name>`), or none if it has no relationships part.
+fn read_relationships<R: Read + Seek>(
+    package: &mut Package<R>,
+    part_name: &str,
+) -> Result<Relationships, MergeError> {
+    let (directory, file_name) = part_name.rsplit_once('/').unwrap_or(("", part_name));
+    let rels_name = if directory.is_empty() {
+        format!("_rels/{}.rels", file_name)
+    } else {
+        format!("{}/_rels/{}.rels", directory, file_name)
+    };
+    match read_optional_part(package, &rels_name)? {
+        Some(xml) => {
+            Relationships::parse(&xml).map_err(|e| e.in_part(&package.path, Some(&rels_name)))
+        }
+        None => Ok(Relationships::default()),
+    }
+}
+
+/// Reads the footnotes and then the endnotes of the package, in part order,
+/// leaving out the separator and continuation pseudo-notes.
+fn read_notes<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+) -> Result<Vec<Note>, MergeError> {
+    let mut notes = Vec::new();
+    for kind in [NoteKind::Footnote, NoteKind::Endnote] {
+        let stories = read_stories(package, kind.part_name(), options)?;
+        notes.extend(
+            stories
+                .into_iter()
+                .filter(|story| story.note_type.as_deref().is_none_or(|kind| kind == "normal"))
+                .map(|story| Note {
+                    kind,
+                    id: story.id,
+                    text: story.text,
+                }),
+        );
+    }
+    Ok(notes)
+}
+
+/// Extracts the footnotes and endnotes of a DOCX file as a list, footnotes
+/// first, each in the order of its part. Note texts are extracted with
+/// `options` like the document text.
+#[cfg(feature = "cli")]
+pub fn extract_notes_from_docx(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Vec<Note>, MergeError> {
+    let mut package = open_package(path.as_ref(), options)?;
+    read_notes(&mut package, options)
+}
+
+/// Reads the comments of the package, in the order of `word/comments.xml`
+/// but with replies (from `word/commentsExtended.xml`) following the
+/// comment they reply to. A package without comments has none.
+fn read_comments<R: Read + Seek>(
+    package: &mut Package<R>,
+    options: &ExtractOptions,
+) -> Result<Vec<Comment>, MergeError> {
+    let stories = read_stories(package, "word/comments.xml", options)?;
+
+    // commentsExtended maps the paraId of a reply's last paragraph to that of
+    // its parent comment.
+    let mut parent_para_ids = HashMap::new();
+    if let Some(extended) = read_optional_part(package, "word/commentsExtended.xml")? {
+        let mut reader = WordReader::from_str(&extended);
+        loop {
+            let event = reader.read_event().map_err(|e| {
+                e.in_part(&package.path, Some("word/commentsExtended.xml"))
+            })?;
+            match event {
+                Event::Start(ref e) | Event::Empty(ref e)
+                    if e.local_name().as_ref() == b"commentEx" =>
+                {
+                    if let (Some(para_id), Some(parent)) =
+                        (attribute(e, b"w15:paraId"), attribute(e, b"w15:paraIdParent"))
+                    {
+                        parent_para_ids.insert(para_id, parent);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+    }
+    let id_for_para_id = |para_id: &str| {
+        stories
+            .iter()
+            .find(|story| story.para_id.as_deref() == Some(para_id))
+            .map(|story| story.id.clone())
+    };
+    let comments: Vec<Comment> = stories
+        .iter()
+        .map(|story| Comment {
+            id: story.id.clone(),
+            author: story.author.clone().unwrap_or_default(),
+            date: story.date.clone(),
+            text: story.text.clone(),
+            parent: story
+                .para_id
+                .as_ref()
+                .and_then(|para_id| parent_para_ids.get(para_id))
+                .and_then(|parent| id_for_para_id(parent)),
+        })
+        .collect();
+
+    // Flatten threads: each top-level comment is followed by its replies.
+    let mut ordered = Vec::with_capacity(comments.len());
+    for comment in comments.iter().filter(|comment| comment.parent.is_none()) {
+        ordered.push(comment.clone());
+        ordered.extend(
+            comments
+                .iter()
+                .filter(|reply| reply.parent.as_deref() == Some(comment.id.as_str()))
+                .cloned(),
+        );
+    }
+    Ok(ordered)
+}
+
+/// Merges the text extracted from multiple DOCX files into one string.
+/// Each file's text is separated by two newline characters.
+#[cfg(feature = "cli")]
+#[deprecated(note = "use `merge_docx_files_with` and `ExtractOptions::strip_hyperlinks`")]
+pub fn merge_docx_files(
+    paths: &[impl AsRef<Path> + Sync],
+    strip_hyperlinks: bool,
+) -> Result<String, MergeError> {
+    merge_docx_files_with(paths, &ExtractOptions::new().strip_hyperlinks(strip_hyperlinks))
+}
+
+/// Merges the text extracted from multiple DOCX files using `options`.
+/// OpenDocument text files and plain text files may be mixed in; see
+/// [`extract_text_from_file`].
+#[cfg(feature = "cli")]
+pub fn merge_docx_files_with(
+    paths: &[impl AsRef<Path> + Sync],
+    options: &ExtractOptions,
+) -> Result<String, MergeError> {
+    let mut merged_text = String::new();
+    merge_files(paths, &MergeOptions::from(options.clone()), &mut merged_text)?;
+    Ok(merged_text)
+}
+
+/// What a merge reports to [`MergeOptions::on_progress`]. Files are
+/// numbered from 0 in the order given. Each file starts before it finishes
+/// or fails, and files finish or fail in order; when several files are
+/// extracted at once, several may start before the first finishes.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy)]
+pub enum ProgressEvent<'a> {
+    /// Extracting a file has started.
+    FileStarted {
+        /// The number of the file.
+        index: usize,
+        /// How many files the merge has.
+        total: usize,
+        /// The file, as given.
+        path: &'a Path,
+    },
+    /// The text of a file has been written.
+    FileFinished {
+        /// The number of the file.
+        index: usize,
+        /// The length of the file's text, in bytes of UTF-8.
+        bytes_extracted: u64,
+    },
+    /// A file could not be extracted. Whether the merge goes on depends
+    /// on [`MergeOptions::on_error`].
+    FileFailed {
+        /// The number of the file.
+        index: usize,
+        /// Why it failed.
+        error: &'a MergeError,
+    },
+}
+
+/// The callback of [`MergeOptions::on_progress`].
+#[cfg(feature = "cli")]
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(ProgressEvent<'_>) + Send + Sync>);
+
+#[cfg(feature = "cli")]
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// A file a merge left out because it could not be extracted.
+#[cfg(feature = "cli")]
+#[derive(Debug)]
+pub struct SkippedFile {
+    /// The file, as given.
+    pub path: PathBuf,
+    /// Why it could not be extracted.
+    pub error: MergeError,
+}
+
+/// The counts of the text of a merged file.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone)]
+pub struct FileStats {
+    /// The file, as given.
+    pub path: PathBuf,
+    /// The counts of its text.
+    pub stats: TextStats,
+}
+
+/// Where the text of a merged file starts in the merged text.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStart {
+    /// The file, as given.
+    pub path: PathBuf,
+    /// The line its header, or else its text, starts on, counted from 1.
+    pub line: usize,
+}
+
+/// What a merge did besides writing the text.
+#[cfg(feature = "cli")]
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// The files that failed, in the order given, when the options skip
+    /// them; empty otherwise.
+    pub skipped: Vec<SkippedFile>,
+    /// The counts of each file merged, in the order given, when the
+    /// options ask for them; empty otherwise.
+    pub stats: Vec<FileStats>,
+    /// What extracting the files merged warned of, in the order given.
+    pub warnings: Vec<Warning>,
+    /// Where each file written starts, markers of skipped files included,
+    /// in the order given; see [`with_table_of_contents`].
+    pub file_starts: Vec<FileStart>,
+}
+
+/// The text of a merge, and the files it left out.
+#[cfg(feature = "cli")]
+#[derive(Debug)]
+pub struct MergedText {
+    /// The merged text.
+    pub text: String,
+    /// The files that failed, in the order given, when the options skip
+    /// them; empty otherwise.
+    pub skipped: Vec<SkippedFile>,
+    /// The counts of each file merged, when the options ask for them.
+    pub stats: Vec<FileStats>,
+    /// What extracting the files merged warned of.
+    pub warnings: Vec<Warning>,
+    /// Where each file written starts.
+    pub file_starts: Vec<FileStart>,
+}
+
+/// Merges the text extracted from multiple files using `options`, which
+/// decide among other things whether a file that fails ends the merge.
+#[cfg(feature = "cli")]
+pub fn merge_files_with_options(
+    paths: &[impl AsRef<Path> + Sync],
+    options: &MergeOptions,
+) -> Result<MergedText, MergeError> {
+    let mut text = String::new();
+    let MergeReport {
+        skipped,
+        stats,
+        warnings,
+        file_starts,
+    } = merge_files(paths, options, &mut text)?;
+    Ok(MergedText {
+        text,
+        skipped,
+        stats,
+        warnings,
+        file_starts,
+    })
+}
+
+/// Merges the text extracted from multiple files using `options`, writing
+/// each file's text to `writer` as it is read rather than collecting the
+/// merged text first. Returns the files left out, if the options skip
+/// files that fail, the counts of each file, if they ask for them, and
+/// the warnings.
+///
+/// If a file fails and ends the merge, the text of the files before it
+/// stays written. Failing to write is a [`MergeError::Write`]. A merge
+/// cancelled through [`MergeOptions::cancellation`] leaves what it wrote
+/// so far, truncated, often in the middle of a file.
+#[cfg(feature = "cli")]
+pub fn merge_docx_files_to_writer<W: Write>(
+    paths: &[impl AsRef<Path> + Sync],
+    writer: &mut W,
+    options: &MergeOptions,
+) -> Result<MergeReport, MergeError> {
+    let mut sink = IoSink::new(writer);
+    let report = merge_files(paths, options, &mut sink)?;
+    sink.finish().map_err(|source| MergeError::Write { source })?;
+    Ok(report)
+}
+
+/// Writes the text of each file to `sink`, under its header and separated
+/// from the one before, and returns the files skipped and counted and the
+/// warnings.
+#[cfg(feature = "cli")]
+fn merge_files(
+    paths: &[impl AsRef<Path> + Sync],
+    options: &MergeOptions,
+    sink: &mut dyn TextSink,
+) -> Result<MergeReport, MergeError> {
+    let options = &*chaptered(options);
+    let mut merger = Merger::new(sink, options, paths.len());
+    if options.threads == 1 && options.on_error == ErrorPolicy::FailFast && options.cache.is_none() {
+        // Files merged together often come from the same template.
+        let cache = Arc::new(PartCache::default());
+        for (index, path) in paths.iter().enumerate() {
+            options.extract.check_cancelled()?;
+            let path = path.as_ref();
+            merger.started(index, path);
+            merger.start_file(index, path);
+            let mut stats = TextStats::default();
+            let counted = options.stats.then_some(&mut stats);
+            let started = Instant::now();
+            let mut out = CountingSink::new(&mut merger.out);
+            let written = write_text_from_file(
+                path,
+                &options.extract,
+                &mut out,
+                counted,
+                Some(&mut merger.report.warnings),
+                Some(&cache),
+            );
+            let bytes_extracted = out.bytes;
+            if let Err(error) = written {
+                merger.progress(ProgressEvent::FileFailed {
+                    index,
+                    error: &error,
+                });
+                return Err(error);
+            }
+            merger.end_file(index, path);
+            merger.check_written()?;
+            merger.finished(index, path, bytes_extracted, started.elapsed());
+            merger.count(path, stats);
+        }
+    } else {
+        // Files extracted at once are written in the order given. A file
+        // that fails may do so after some of its text, so each is
+        // extracted whole before any of it is written. Cached text is
+        // whole too.
+        extract_each(paths, options, |index, path, text| merger.add(index, path, text))?;
+    }
+    merger.out.finish().map_err(|source| MergeError::Write { source })?;
+    Ok(merger.report)
+}
+
+/// The options with each file's headings under its chapter heading, if
+/// they ask for chapter headings.
+#[cfg(feature = "cli")]
+fn chaptered(options: &MergeOptions) -> Cow<'_, MergeOptions> {
+    match options.chapter_headings {
+        Some(level) => {
+            let mut with_offset = options.clone();
+            with_offset.extract.heading_offset += level;
+            Cow::Owned(with_offset)
+        }
+        None => Cow::Borrowed(options),
+    }
+}
+
+/// A pool of `threads` threads, or one per CPU for 0, or `None` to extract
+/// one file at a time.
+#[cfg(feature = "cli")]
+fn thread_pool(threads: usize) -> Option<rayon::ThreadPool> {
+    match threads {
+        1 => None,
+        threads => ThreadPoolBuilder::new().num_threads(threads).build().ok(),
+    }
+}
+
+/// Extracts the text of each file whole, as many at a time as the options
+/// say, and hands each to `each` in the order given.
+#[cfg(feature = "cli")]
+fn extract_each(
+    paths: &[impl AsRef<Path> + Sync],
+    options: &MergeOptions,
+    mut each: impl FnMut(usize, &Path, Result<Extracted, MergeError>) -> Result<(), MergeError>,
+) -> Result<(), MergeError> {
+    // Files extracted together often come from the same template.
+    let cache = Arc::new(PartCache::default());
+    let pool = thread_pool(options.threads);
+    // A batch holds a few files per thread, so that one long file keeps the
+    // other threads waiting less.
+    let batch_size = pool
+        .as_ref()
+        .map_or(1, |pool| pool.current_num_threads() * 4);
+    for (batch_index, batch) in paths.chunks(batch_size).enumerate() {
+        options.extract.check_cancelled()?;
+        let first = batch_index * batch_size;
+        if let Some(ProgressCallback(on_progress)) = &options.on_progress {
+            for (index, path) in (first..).zip(batch) {
+                on_progress(ProgressEvent::FileStarted {
+                    index,
+                    total: paths.len(),
+                    path: path.as_ref(),
+                });
+            }
+        }
+        let extract = |path: &_| extract_for_merge(AsRef::<Path>::as_ref(path), options, &cache);
+        let texts: Vec<Result<Extracted, MergeError>> = match &pool {
+            Some(pool) => pool.install(|| batch.par_iter().map(extract).collect()),
+            None => batch.iter().map(extract).collect(),
+        };
+        for ((index, path), text) in (first..).zip(batch).zip(texts) {
+            each(index, path.as_ref(), text)?;
+        }
+    }
+    Ok(())
+}
+
+/// The text of a file extracted whole for a merge.
+#[cfg(feature = "cli")]
+struct Extracted {
+    text: String,
+    /// Empty unless the options ask for counts.
+    stats: TextStats,
+    /// Empty for text from the cache.
+    warnings: Vec<Warning>,
+    /// How long extracting it, or taking it from the cache, took.
+    elapsed: Duration,
+}
+
+/// Extracts the text of a file whole for a merge, counting it if the
+/// options ask for counts, or takes it from the text cache.
+#[cfg(feature = "cli")]
+fn extract_for_merge(
+    path: &Path,
+    options: &MergeOptions,
+    parts: &Arc<PartCache>,
+) -> Result<Extracted, MergeError> {
+    let started = Instant::now();
+    // A file that cannot be read for its key fails below, as it would
+    // without a cache.
+    let cached = options
+        .cache
+        .as_ref()
+        .and_then(|cache| Some((cache, CacheKey::for_file(path, &options.extract).ok()?)));
+    if let (Some((cache, key)), false) = (&cached, options.stats) {
+        if let Some(text) = cache.get(key) {
+            log::debug!("{}: taken from the cache", path.display());
+            return Ok(Extracted {
+                text,
+                stats: TextStats::default(),
+                warnings: Vec::new(),
+                elapsed: started.elapsed(),
+            });
+        }
+    }
+    let mut text = String::new();
+    let mut stats = TextStats::default();
+    let mut warnings = Vec::new();
+    let counted = options.stats.then_some(&mut stats);
+    write_text_from_file(
+        path,
+        &options.extract,
+        &mut text,
+        counted,
+        Some(&mut warnings),
+        Some(parts),
+    )?;
+    if let Some((cache, key)) = &cached {
+        cache.put(key, &text);
+    }
+    Ok(Extracted {
+        text,
+        stats,
+        warnings,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Puts the texts of merged files together.
+#[cfg(feature = "cli")]
+struct Merger<'s, 'o> {
+    out: TextWriter<'s>,
+    options: &'o MergeOptions,
+    /// How many files the merge has.
+    total: usize,
+    /// How many files have been written, markers included.
+    files_written: usize,
+    report: MergeReport,
+}
+
+#[cfg(feature = "cli")]
+impl<'s, 'o> Merger<'s, 'o> {
+    fn new(sink: &'s mut dyn TextSink, options: &'o MergeOptions, total: usize) -> Self {
+        Merger {
+            out: TextWriter::new(sink, BlankLines::Keep).trim_start(),
+            options,
+            total,
+            files_written: 0,
+            report: MergeReport::default(),
+        }
+    }
+
+    /// Reports progress, if the options ask for it.
+    fn progress(&self, event: ProgressEvent) {
+        if let Some(ProgressCallback(on_progress)) = &self.options.on_progress {
+            on_progress(event);
+        }
+    }
+
+    /// Reports that extracting a file has started.
+    fn started(&self, index: usize, path: &Path) {
+        self.progress(ProgressEvent::FileStarted {
+            index,
+            total: self.total,
+            path,
+        });
+    }
+
+    /// Reports that a file has been written, and logs how long extracting
+    /// it took.
+    fn finished(&self, index: usize, path: &Path, bytes_extracted: u64, elapsed: Duration) {
+        log::debug!(
+            "{}: {} bytes extracted in {:.3}s",
+            path.display(),
+            bytes_extracted,
+            elapsed.as_secs_f64()
+        );
+        self.progress(ProgressEvent::FileFinished {
+            index,
+            bytes_extracted,
+        });
+    }
+
+    /// Writes what comes before the text of a file: the separator from the
+    /// file before, and the file's header.
+    fn start_file(&mut self, index: usize, path: &Path) {
+        if self.files_written > 0 {
+            self.out.write_text(&self.options.separator);
+        }
+        self.report.file_starts.push(FileStart {
+            path: path.to_path_buf(),
+            line: self.out.next_line(),
+        });
+        if let Some(header) = &self.options.file_header {
+            let header = render_file_template(header, path, index, self.total, &self.options.extract);
+            self.out.write_text(&header);
+            self.out.write_text("\n");
+        }
+        if let Some(level) = self.options.chapter_headings {
+            self.out.write_text(&chapter_heading(path, level, &self.options.extract));
+            self.out.write_text("\n\n");
+        }
+        self.files_written += 1;
+    }
+
+    /// Writes the footer of a file after its text.
+    fn end_file(&mut self, index: usize, path: &Path) {
+        if let Some(footer) = &self.options.file_footer {
+            let footer = render_file_template(footer, path, index, self.total, &self.options.extract);
+            self.out.write_text("\n");
+            self.out.write_text(&footer);
+        }
+    }
+
+    /// Records the counts of a file written, if the options ask for them.
+    fn count(&mut self, path: &Path, stats: TextStats) {
+        if self.options.stats {
+            self.report.stats.push(FileStats {
+                path: path.to_path_buf(),
+                stats,
+            });
+        }
+    }
+
+    /// Writes the text extracted from a file, or deals with its failure as
+    /// the error policy says.
+    fn add(
+        &mut self,
+        index: usize,
+        path: &Path,
+        text: Result<Extracted, MergeError>,
+    ) -> Result<(), MergeError> {
+        match text {
+            Ok(Extracted {
+                text,
+                stats,
+                mut warnings,
+                elapsed,
+            }) => {
+                self.report.warnings.append(&mut warnings);
+                self.start_file(index, path);
+                self.out.write_text(&text);
+                self.end_file(index, path);
+                self.check_written()?;
+                self.finished(index, path, text.len() as u64, elapsed);
+                self.count(path, stats);
+            }
+            Err(error) => {
+                self.progress(ProgressEvent::FileFailed {
+                    index,
+                    error: &error,
+                });
+                if self.options.on_error == ErrorPolicy::FailFast
+                    || matches!(error, MergeError::Cancelled)
+                {
+                    return Err(error);
+                }
+                if self.options.error_markers {
+                    self.start_file(index, path);
+                    self.out.write_text(&format!("[failed to extract: {}]", error));
+                    self.end_file(index, path);
+                }
+                self.report.skipped.push(SkippedFile {
+                    path: path.to_path_buf(),
+                    error,
+                });
+            }
+        }
+        self.check_written()
+    }
+
+    /// Writes the text extracted from a file as a merge of that file alone
+    /// would, and returns it with what the merge reports, or `None` for the
+    /// text if the file was skipped without a marker.
+    fn alone(
+        index: usize,
+        path: &Path,
+        text: Result<Extracted, MergeError>,
+        options: &MergeOptions,
+        total: usize,
+    ) -> Result<(Option<String>, MergeReport), MergeError> {
+        let mut written = String::new();
+        let mut merger = Merger::new(&mut written, options, total);
+        merger.add(index, path, text)?;
+        let files_written = merger.files_written;
+        let report = merger.report;
+        merger
+            .out
+            .finish()
+            .map_err(|source| MergeError::Write { source })?;
+        Ok(((files_written > 0).then_some(written), report))
+    }
+
+    fn check_written(&mut self) -> Result<(), MergeError> {
+        match self.out.take_error() {
+            Some(source) => Err(MergeError::Write { source }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Extensions of the documents a directory expands to: Word documents and
+/// templates, each with or without macros, and OpenDocument text documents
+/// and templates. Macros (`vbaProject.bin`) are never read.
+#[cfg(feature = "cli")]
+const DOCUMENT_EXTENSIONS: [&str; 6] = ["docx", "docm", "dotx", "dotm", "odt", "ott"];
+
+/// Whether a path has the extension of a supported document.
+#[cfg(feature = "cli")]
+fn is_supported_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            DOCUMENT_EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Expands wildcards using the glob crate. A pattern naming a directory
+/// stands for the supported documents directly inside it, or with
+/// [`ExpandOptions::recursive`] for those in the directories under it too.
+///
+/// The matches of each pattern are sorted in [`SortOrder::Natural`] order,
+/// so that a merge comes out the same on every machine, and the patterns
+/// keep the order given. A file that several patterns match, by whatever
+/// path, is kept where it first matches; see [`expand_patterns_with`] to
+/// sort otherwise or keep every match.
+#[cfg(feature = "cli")]
+pub fn expand_patterns(patterns: &[impl AsRef<str>]) -> Result<Vec<PathBuf>, MergeError> {
+    expand_patterns_with(patterns, &ExpandOptions::default())
+}
+
+/// Which files [`expand_patterns_with`] takes of those patterns match, and
+/// in what order.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Default)]
+pub struct ExpandOptions {
+    /// The order of the files.
+    pub sort: SortOrder,
+    /// Give the files in the opposite order. Files whose time or size
+    /// cannot be read still come last.
+    pub reverse: bool,
+    /// Keep a file each time a pattern matches it, rather than once.
+    pub keep_duplicates: bool,
+    /// Take the documents in the directories under a directory named, depth
+    /// first, besides those directly inside it. Hidden directories, whose
+    /// names start with a dot, are left out.
+    pub recursive: bool,
+    /// Leave out the files that match one of these glob patterns
+    /// (`**/drafts/**`), by their path from the current directory or their
+    /// absolute path.
+    pub exclude: Vec<String>,
+    /// Keep the owner files Word creates beside the documents it has open
+    /// (`~$report.docx`), which are left out otherwise.
+    pub include_lockfiles: bool,
+    /// Files taken before those the patterns match, in this order, such as
+    /// those [`read_file_list`] reads. They are neither sorted nor
+    /// excluded, but a pattern matching one again is left out as another
+    /// duplicate.
+    pub listed: Vec<PathBuf>,
+}
+
+#[cfg(feature = "cli")]
+impl ExpandOptions {
+    /// Sets [`sort`](Self::sort).
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Sets [`reverse`](Self::reverse).
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Sets [`keep_duplicates`](Self::keep_duplicates).
+    pub fn keep_duplicates(mut self, keep_duplicates: bool) -> Self {
+        self.keep_duplicates = keep_duplicates;
+        self
+    }
+
+    /// Sets [`recursive`](Self::recursive).
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Adds a pattern to [`exclude`](Self::exclude).
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Sets [`include_lockfiles`](Self::include_lockfiles).
+    pub fn include_lockfiles(mut self, include_lockfiles: bool) -> Self {
+        self.include_lockfiles = include_lockfiles;
+        self
+    }
+
+    /// Adds a file to [`listed`](Self::listed).
+    pub fn listed(mut self, path: impl Into<PathBuf>) -> Self {
+        self.listed.push(path.into());
+        self
+    }
+}
+
+/// Expands wildcards as [`expand_patterns`] does, in the order `options`
+/// give. Files are sorted once duplicates are left out, so a merge, even
+/// one extracting files in parallel, writes them in this order.
+#[cfg(feature = "cli")]
+pub fn expand_patterns_with(
+    patterns: &[impl AsRef<str>],
+    options: &ExpandOptions,
+) -> Result<Vec<PathBuf>, MergeError> {
+    let exclude = options
+        .exclude
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|source| MergeError::Pattern {
+                pattern: pattern.clone(),
+                source,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let current_dir = std::env::current_dir().and_then(std::fs::canonicalize).ok();
+    let mut file_paths = Vec::new();
+    let mut seen = HashSet::new();
+    // A path that cannot be resolved, such as a broken link, is known by
+    // itself.
+    let mut is_new = |path: &Path| {
+        seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+            || options.keep_duplicates
+    };
+    for path in &options.listed {
+        if is_new(path) {
+            file_paths.push(path.clone());
+        }
+    }
+    let listed = file_paths.len();
+    for pattern in patterns {
+        let pattern = pattern.as_ref();
+        let mut matches = Vec::new();
+        if options.recursive && Path::new(pattern).is_dir() {
+            walk_directory(Path::new(pattern), &mut matches);
+        } else if Path::new(pattern).is_dir() {
+            let directory = pattern.trim_end_matches(['/', '\\']);
+            let pattern = format!("{}/*", Pattern::escape(directory));
+            let paths = glob(&pattern).map_err(|source| MergeError::Pattern {
+                pattern: pattern.clone(),
+                source,
+            })?;
+            for path in paths.flatten() {
+                if path.is_file() && is_supported_document(&path) {
+                    matches.push(path);
+                }
+            }
+        } else {
+            let entries = glob(pattern).map_err(|source| MergeError::Pattern {
+                pattern: pattern.to_string(),
+                source,
+            })?;
+            for entry in entries {
+                match entry {
+                    Ok(path) => matches.push(path),
+                    Err(e) => log::warn!("skipping a match of {}: {}", pattern, e),
+                }
+            }
+        }
+        matches.retain(|path| {
+            if is_lockfile(path) && !options.include_lockfiles {
+                log::info!(
+                    "Skipping {}, the lock file Word keeps while a document is open.",
+                    path.display()
+                );
+                return false;
+            }
+            let excluded = is_excluded(path, &exclude, current_dir.as_deref());
+            if excluded {
+                log::debug!("{}: excluded", path.display());
+            }
+            !excluded
+        });
+        options.sort.sort_matches(&mut matches, options.reverse);
+        for path in matches {
+            if is_new(&path) {
+                file_paths.push(path);
+            }
+        }
+    }
+    let mut matched = file_paths.split_off(listed);
+    options.sort.sort_files(&mut matched, options.reverse);
+    file_paths.append(&mut matched);
+    Ok(file_paths)
+}
+
+/// Reads a list of files, one path on each line, such as the chapters of
+/// a book in order. Blank lines, and lines starting with `#`, are skipped.
+/// Relative paths are taken from the list's directory.
+///
+/// A listed file that does not exist fails with
+/// [`MergeError::ListedFileMissing`], or, if `skip_missing`, is left out
+/// with a warning.
+#[cfg(feature = "cli")]
+pub fn read_file_list(list: impl AsRef<Path>, skip_missing: bool) -> Result<Vec<PathBuf>, MergeError> {
+    let list = list.as_ref();
+    let file = File::open(list).map_err(|source| MergeError::Io {
+        path: list.to_path_buf(),
+        source,
+    })?;
+    let base = list.parent().unwrap_or(Path::new(""));
+    read_file_list_from(BufReader::new(file), list, base, skip_missing)
+}
+
+/// Reads a list of files as [`read_file_list`] does, from a reader such as
+/// standard input. `list` names it in errors, and relative paths are taken
+/// from `base`.
+#[cfg(feature = "cli")]
+pub fn read_file_list_from(
+    reader: impl BufRead,
+    list: &Path,
+    base: &Path,
+    skip_missing: bool,
+) -> Result<Vec<PathBuf>, MergeError> {
+    let mut file_paths = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|source| MergeError::Io {
+            path: list.to_path_buf(),
+            source,
+        })?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let path = base.join(line);
+        if !path.exists() {
+            let error = MergeError::ListedFileMissing {
+                list: list.to_path_buf(),
+                line: index + 1,
+                path,
+            };
+            if !skip_missing {
+                return Err(error);
+            }
+            log::warn!("{}; skipping it", error);
+            continue;
+        }
+        file_paths.push(path);
+    }
+    Ok(file_paths)
+}
+
+/// Whether a file is one Word creates to mark a document open, whose name
+/// is the document's with its first letters replaced by `~$`. It holds the
+/// name of the user, in 162 bytes or so, and is no package to extract.
+#[cfg(feature = "cli")]
+fn is_lockfile(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with("~$"))
+}
+
+/// Whether one of the `exclude` patterns matches a file, by the path it was
+/// found by, by its path from `current_dir`, or by its absolute path.
+#[cfg(feature = "cli")]
+fn is_excluded(path: &Path, exclude: &[Pattern], current_dir: Option<&Path>) -> bool {
+    if exclude.is_empty() {
+        return false;
+    }
+    let absolute = std::fs::canonicalize(path).ok();
+    let relative = absolute
+        .as_deref()
+        .zip(current_dir)
+        .and_then(|(absolute, current_dir)| absolute.strip_prefix(current_dir).ok());
+    let candidates = [Some(path), relative, absolute.as_deref()];
+    exclude.iter().any(|pattern| {
+        candidates
+            .iter()
+            .flatten()
+            .any(|candidate| pattern.matches_path(candidate))
+    })
+}
+
+/// Adds the supported documents in `directory`, and depth first in the
+/// directories under it other than hidden ones, to `matches`.
+#[cfg(feature = "cli")]
+fn walk_directory(directory: &Path, matches: &mut Vec<PathBuf>) {
+    // Links are followed; walkdir reports one back to a directory above it
+    // as an error rather than going round.
+    let entries = WalkDir::new(directory)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !is_hidden_directory(entry));
+    for entry in entries {
+        match entry {
+            Ok(entry) => {
+                if entry.file_type().is_file() && is_supported_document(entry.path()) {
+                    matches.push(entry.into_path());
+                }
+            }
+            Err(e) => log::warn!("skipping part of {}: {}", directory.display(), e),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+fn is_hidden_directory(entry: &DirEntry) -> bool {
+    entry.file_type().is_dir() && entry.file_name().to_string_lossy().starts_with('.')
+}
+
+// Tests of reading bytes and readers, which run without the `cli` feature.
+#[cfg(test)]
+mod reader_tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::CompressionMethod;
+
+    /// Wraps body content in a minimal document.xml.
+    pub(super) fn document_xml(body: &str) -> String {
+        format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+            xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+            xmlns:v="urn:schemas-microsoft-com:vml">
+  <w:body>
+{}
+  </w:body>
+</w:document>"#, body)
+    }
+
+    #[test]
+    fn test_hyperlink_field_targets() {
+        assert_eq!(
+            hyperlink_field_target(r#"HYPERLINK "https://example.com" \t "_blank""#).as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            hyperlink_field_target(r#"HYPERLINK \l "_Toc1""#).as_deref(),
+            Some("#_Toc1")
+        );
+        assert_eq!(
+            hyperlink_field_target(r#"HYPERLINK "https://example.com/page" \l "part""#).as_deref(),
+            Some("https://example.com/page#part")
+        );
+        assert_eq!(hyperlink_field_target("HYPERLINK"), None);
+    }
+
+    #[test]
+    fn test_roman_numerals() {
+        assert_eq!(roman_numeral(1), "I");
+        assert_eq!(roman_numeral(4), "IV");
+        assert_eq!(roman_numeral(14), "XIV");
+        assert_eq!(roman_numeral(1994), "MCMXCIV");
+    }
+
+    #[test]
+    fn test_find_part_name_tolerates_case_and_separators() {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        for name in ["word\\document.xml", "Word/Styles.xml", "word/styles.XML", "/customXml/item1.xml"] {
+            zip.start_file(name, options).unwrap();
+        }
+        let archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+        assert_eq!(find_part_name(&archive, "word/document.xml").as_deref(), Some("word\\document.xml"));
+        assert_eq!(find_part_name(&archive, "/word/document.xml").as_deref(), Some("word\\document.xml"));
+        assert_eq!(find_part_name(&archive, "customXml/item1.xml").as_deref(), Some("/customXml/item1.xml"));
+        // An exact match is preferred over entries differing in case.
+        assert_eq!(find_part_name(&archive, "word/styles.XML").as_deref(), Some("word/styles.XML"));
+        assert_eq!(find_part_name(&archive, "Word/Styles.xml").as_deref(), Some("Word/Styles.xml"));
+        assert!(find_part_name(&archive, "word/numbering.xml").is_none());
+    }
+
+    #[test]
+    fn test_extract_text_from_bytes() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("word/document.xml", FileOptions::default()).unwrap();
+        zip.write_all(document_xml("<w:p><w:r><w:t>In memory</w:t></w:r></w:p>").as_bytes())
+            .unwrap();
+        let data = zip.finish().unwrap().into_inner();
+
+        let extracted = extract_text_from_bytes(&data, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "In memory");
+        let error = extract_text_from_bytes(&data[..data.len() / 2], &ExtractOptions::default())
+            .unwrap_err();
+        assert!(matches!(error, MergeError::Zip { .. }));
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use super::reader_tests::document_xml;
+    use tempfile::tempdir;
+    use std::fs::File;
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::CompressionMethod;
+
+    /// Creates a temporary DOCX file with minimal content (a single paragraph).
+    fn create_test_docx(text: &str) -> Result<(tempfile::TempDir, String), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let file_path = temp_dir.path().join("test.docx");
+        let file_path_str = file_path.to_str().unwrap().to_string();
+
+        let file = File::create(&file_path)?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+        // Create a minimal document.xml with a single paragraph.
+        let xml_content = format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p>
+      <w:r>
+        <w:t>{}</w:t>
+      </w:r>
+    </w:p>
+  </w:body>
+</w:document>"#, text);
+
+        zip.start_file("word/document.xml", options)?;
+        zip.write_all(xml_content.as_bytes())?;
+        zip.finish()?;
+
+        Ok((temp_dir, file_path_str))
+    }
+
+    /// Creates a temporary DOCX file with custom XML content.
+    fn create_test_docx_with_xml(xml_content: &str) -> Result<(tempfile::TempDir, String), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let file_path = temp_dir.path().join("test.docx");
+        let file_path_str = file_path.to_str().unwrap().to_string();
+
+        let file = File::create(&file_path)?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+        zip.start_file("word/document.xml", options)?;
+        zip.write_all(xml_content.as_bytes())?;
+        zip.finish()?;
+
+        Ok((temp_dir, file_path_str))
+    }
+
+    /// Creates a temporary DOCX file containing the given parts.
+    fn create_test_docx_with_parts(parts: &[(&str, &str)]) -> Result<(tempfile::TempDir, String), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let file_path = temp_dir.path().join("test.docx");
+        let file_path_str = file_path.to_str().unwrap().to_string();
+
+        let file = File::create(&file_path)?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, content) in parts {
+            zip.start_file(*name, options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+
+        Ok((temp_dir, file_path_str))
+    }
+
+    /// Wraps relationship entries in a minimal relationships part.
+    fn rels_xml(relationships: &str) -> String {
+        format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+{}
+</Relationships>"#, relationships)
+    }
+
+
+    #[test]
+    fn test_extract_text_from_docx_without_strip() {
+        let test_text = "Hello, world!";
+        let (_temp_dir, docx_path) = create_test_docx(test_text).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert!(extracted.contains(test_text));
+    }
+
+    #[test]
+    fn test_merge_docx_files_without_strip() {
+        let test_text1 = "First document text.";
+        let test_text2 = "Second document text.";
+        let (_temp_dir1, docx_path1) = create_test_docx(test_text1).unwrap();
+        let (_temp_dir2, docx_path2) = create_test_docx(test_text2).unwrap();
+
+        let merged = merge_docx_files_with(&[&docx_path1, &docx_path2], &ExtractOptions::default()).unwrap();
+        assert!(merged.contains(test_text1));
+        assert!(merged.contains(test_text2));
+        assert!(merged.contains("\n\n"));
+    }
+
+    #[test]
+    fn test_invalid_docx_file() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("invalid.docx");
+        let file_path_str = file_path.to_str().unwrap().to_string();
+        std::fs::write(&file_path, "Not a valid docx file").unwrap();
+        let result = extract_text_from_docx_with(&file_path_str, &ExtractOptions::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_strip_hyperlink_instr_text() {
+        // Create a DOCX file that contains a hyperlink field.
+        // Typically, a hyperlink field is stored as an instruction text (w:instrText)
+        // followed by the visible text.
+        let xml_content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+            xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <w:body>
+    <w:p>
+      <w:r>
+        <w:fldChar w:fldCharType="begin"/>
+      </w:r>
+      <w:r>
+        <w:instrText>HYPERLINK "https://example.com" \t "_blank"</w:instrText>
+      </w:r>
+      <w:r>
+        <w:fldChar w:fldCharType="separate"/>
+      </w:r>
+      <w:r>
+        <w:t>Visible Link Text</w:t>
+      </w:r>
+      <w:r>
+        <w:fldChar w:fldCharType="end"/>
+      </w:r>
+    </w:p>
+  </w:body>
+</w:document>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(xml_content).unwrap();
+
+        // When strip_hyperlinks is true, the hyperlink field instruction should be omitted.
+        let extracted_with_strip = extract_text_from_docx_with(&docx_path, &ExtractOptions::new().strip_hyperlinks(true)).unwrap();
+        // When strip_hyperlinks is false, the visible text is followed by the link target.
+        let extracted_without_strip = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+
+        assert!(!extracted_with_strip.contains("HYPERLINK"), "Instruction text should be stripped");
+        assert!(extracted_with_strip.contains("Visible Link Text"), "Visible text should be kept");
+        assert!(!extracted_with_strip.contains("https://example.com"), "Target is stripped");
+        assert!(!extracted_without_strip.contains("HYPERLINK"), "Instruction text is never output");
+        assert!(extracted_without_strip.contains("Visible Link Text <https://example.com>"), "Target follows the visible text");
+    }
+
+    #[test]
+    fn test_paragraphs_are_separated_by_newlines() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>First paragraph.</w:t></w:r></w:p>
+    <w:p><w:pPr><w:jc w:val="center"/></w:pPr></w:p>
+    <w:p/>
+    <w:p><w:r><w:t>Fourth paragraph.</w:t></w:r></w:p>
+    <w:tbl>
+      <w:tr>
+        <w:tc><w:p><w:r><w:t>Cell one</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>Cell two</w:t></w:r></w:p></w:tc>
+      </w:tr>
+    </w:tbl>
+    <w:p>
+      <w:r><w:t>Before box</w:t></w:r>
+      <w:r>
+        <w:pict><v:shape><v:textbox><w:txbxContent>
+          <w:p><w:r><w:t>Inside box</w:t></w:r></w:p>
+        </w:txbxContent></v:textbox></v:shape></w:pict>
+      </w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted,
+            "First paragraph.\n\n\nFourth paragraph.\nCell one\tCell two\nBefore box\nInside box"
+        );
+
+        let options = ExtractOptions {
+            paragraph_separator: "\n\n".to_string(),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "First paragraph.\n\n\n\n\n\nFourth paragraph.\n\nCell one\tCell two\n\nBefore box\n\nInside box"
+        );
+    }
+
+    #[test]
+    fn test_word_split_across_runs_is_joined() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t>The system will </w:t></w:r>
+      <w:r><w:t>recon</w:t></w:r>
+      <w:proofErr w:type="spellStart"/>
+      <w:r><w:rPr><w:b/></w:rPr><w:t>fig</w:t></w:r>
+      <w:proofErr w:type="spellEnd"/>
+      <w:r><w:rPr><w:i/></w:rPr><w:t>ure</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert!(extracted.contains("reconfigure"), "got {:?}", extracted);
+        assert!(!extracted.contains("recon fig"));
+    }
+
+    #[test]
+    fn test_preserved_spaces_survive() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">End of sentence. </w:t></w:r>
+      <w:r><w:t>Start of next</w:t></w:r>
+      <w:r><w:t xml:space="preserve"> trailing </w:t></w:r>
+      <w:r><w:t>  trimmed  </w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "End of sentence. Start of next trailing trimmed");
+    }
+
+    #[test]
+    fn test_run_tabs_become_tab_characters() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:pPr><w:tabs><w:tab w:val="left" w:pos="2880"/></w:tabs></w:pPr>
+      <w:r><w:t>Name</w:t></w:r>
+      <w:r><w:tab/><w:t>Value</w:t></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:t>Colour</w:t><w:tab/><w:t>Blue</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Name\tValue\nColour\tBlue");
+
+        let options = ExtractOptions {
+            tab_string: "    ".to_string(),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Name    Value\nColour    Blue");
+    }
+
+    #[test]
+    fn test_line_column_and_page_breaks() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t>221B Baker Street</w:t><w:br/><w:t>London</w:t></w:r>
+      <w:r><w:cr/><w:t>England</w:t></w:r>
+      <w:r><w:br w:type="column"/><w:t>Second column</w:t></w:r>
+      <w:r><w:br w:type="page"/><w:t>Next page</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted,
+            "221B Baker Street\nLondon\nEngland\nSecond column\x0cNext page"
+        );
+
+        let options = ExtractOptions {
+            page_break: "\n\n---\n\n".to_string(),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert!(extracted.ends_with("Second column\n\n---\n\nNext page"));
+    }
+
+    #[test]
+    fn test_hyperlink_instruction_split_across_runs() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> HYPER</w:instrText></w:r>
+      <w:r><w:instrText xml:space="preserve">LINK "https://example.com" </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>Example</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::new().strip_hyperlinks(true)).unwrap();
+        assert_eq!(extracted, "Example");
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Example <https://example.com>");
+    }
+
+    #[test]
+    fn test_page_field_instruction_is_dropped() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">Page </w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> PAGE   \* MERGEFORMAT </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>3</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        for strip in [false, true] {
+            let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::new().strip_hyperlinks(strip)).unwrap();
+            assert_eq!(extracted, "Page 3");
+        }
+    }
+
+    #[test]
+    fn test_toc_field_instructions_are_dropped() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> TOC \o "1-3" \h \z \u </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>Introduction</w:t></w:r>
+      <w:r><w:tab/></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> PAGEREF _Toc12345 \h </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>1</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Introduction\t1");
+    }
+
+    #[test]
+    fn test_nested_and_separator_less_fields() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> TOC \o "1-3" \h </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> HYPERLINK \l "_Toc1" </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>Overview</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Before </w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> XE "Index entry" </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+      <w:r><w:t>after</w:t></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> IF </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> MERGEFIELD Name </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>Alice</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> = "Alice" "Hi" "Bye" </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>Hi</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::new().strip_hyperlinks(true)).unwrap();
+        assert_eq!(extracted, "Overview\nBefore after\nHi");
+
+        let options = ExtractOptions {
+            include_field_instructions: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "{TOC \\o \"1-3\" \\h}{HYPERLINK \\l \"_Toc1\"}Overview <#_Toc1>\n\
+             Before {XE \"Index entry\"}after\n\
+             {IF Alice = \"Alice\" \"Hi\" \"Bye\"}Hi"
+        );
+    }
+
+    #[test]
+    fn test_hyperlink_elements_resolve_through_relationships() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">See </w:t></w:r>
+      <w:hyperlink r:id="rId5"><w:r><w:t>the site</w:t></w:r></w:hyperlink>
+      <w:r><w:t>.</w:t></w:r>
+    </w:p>
+    <w:p>
+      <w:hyperlink w:anchor="Summary"><w:r><w:t>Summary</w:t></w:r></w:hyperlink>
+    </w:p>
+    <w:p>
+      <w:hyperlink r:id="rId99"><w:r><w:t>Dangling</w:t></w:r></w:hyperlink>
+    </w:p>"#);
+        let rels = rels_xml(r#"<Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/" TargetMode="External"/>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+        ])
+        .unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted,
+            "See the site <https://example.com/>.\nSummary <#Summary>\nDangling"
+        );
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::new().strip_hyperlinks(true)).unwrap();
+        assert_eq!(extracted, "See the site.\nSummary\nDangling");
+
+        let options = ExtractOptions {
+            link_style: LinkStyle::Template("{text} ({target})".to_string()),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert!(extracted.starts_with("See the site (https://example.com/)."));
+    }
+
+    #[test]
+    fn test_link_styles_apply_to_elements_and_fields() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:hyperlink r:id="rId5"><w:r><w:t>Element link</w:t></w:r></w:hyperlink>
+    </w:p>
+    <w:p>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> HYPERLINK "https://example.org/docs" \o "Docs" </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>Field link</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>"#);
+        let rels = rels_xml(r#"<Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/" TargetMode="External"/>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+        ])
+        .unwrap();
+
+        let expectations = [
+            (LinkStyle::Strip, "Element link\nField link"),
+            (
+                LinkStyle::Url,
+                "Element link <https://example.com/>\nField link <https://example.org/docs>",
+            ),
+            (
+                LinkStyle::Markdown,
+                "[Element link](https://example.com/)\n[Field link](https://example.org/docs)",
+            ),
+        ];
+        for (link_style, expected) in expectations {
+            let options = ExtractOptions {
+                link_style,
+                ..ExtractOptions::default()
+            };
+            let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+            assert_eq!(extracted, expected);
+        }
+    }
+
+
+    #[test]
+    fn test_tracked_changes_are_accepted_by_default() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">The fee is </w:t></w:r>
+      <w:del w:id="1" w:author="Alice" w:date="2024-01-01T00:00:00Z">
+        <w:r><w:delText>ten</w:delText></w:r>
+      </w:del>
+      <w:ins w:id="2" w:author="Alice" w:date="2024-01-01T00:00:00Z">
+        <w:r><w:t>twenty</w:t></w:r>
+      </w:ins>
+      <w:r><w:t xml:space="preserve"> pounds.</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "The fee is twenty pounds.");
+
+        let options = ExtractOptions {
+            revisions: RevisionMode::All,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "The fee is tentwenty pounds.");
+    }
+
+    #[test]
+    fn test_revision_modes_with_insertion_deletion_and_move() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:moveFrom w:id="1" w:author="Bob" w:date="2024-01-01T00:00:00Z">
+        <w:r><w:t xml:space="preserve">Moved. </w:t></w:r>
+      </w:moveFrom>
+      <w:r><w:t xml:space="preserve">Pay </w:t></w:r>
+      <w:del w:id="2" w:author="Bob" w:date="2024-01-01T00:00:00Z">
+        <w:r><w:delText xml:space="preserve">ten </w:delText></w:r>
+      </w:del>
+      <w:ins w:id="3" w:author="Bob" w:date="2024-01-01T00:00:00Z">
+        <w:r><w:t xml:space="preserve">twenty </w:t></w:r>
+      </w:ins>
+      <w:r><w:t>pounds.</w:t></w:r>
+      <w:moveTo w:id="4" w:author="Bob" w:date="2024-01-01T00:00:00Z">
+        <w:r><w:t xml:space="preserve"> Moved.</w:t></w:r>
+      </w:moveTo>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let expectations = [
+            (RevisionMode::Accept, "Pay twenty pounds. Moved."),
+            (RevisionMode::Reject, "Moved. Pay ten pounds."),
+            (RevisionMode::All, "Moved. Pay ten twenty pounds. Moved."),
+        ];
+        for (revisions, expected) in expectations {
+            let options = ExtractOptions {
+                revisions,
+                ..ExtractOptions::default()
+            };
+            let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+            assert_eq!(extracted, expected);
+        }
+    }
+
+    #[test]
+    fn test_hidden_text_is_skipped() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:pPr><w:rPr><w:vanish/></w:rPr></w:pPr>
+      <w:r><w:t xml:space="preserve">Visible </w:t></w:r>
+      <w:r><w:rPr><w:vanish/></w:rPr><w:t xml:space="preserve">hidden </w:t></w:r>
+      <w:r>
+        <w:rPr>
+          <w:vanish w:val="false"/>
+          <w:rPrChange w:id="1" w:author="Bob"><w:rPr><w:vanish/></w:rPr></w:rPrChange>
+        </w:rPr>
+        <w:t xml:space="preserve">shown </w:t>
+      </w:r>
+      <w:r><w:rPr><w:specVanish/></w:rPr><w:t>special</w:t></w:r>
+      <w:r><w:t>end</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Visible shown end");
+
+        let options = ExtractOptions {
+            include_hidden: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Visible hidden shown specialend");
+    }
+
+    #[test]
+    fn test_comments_inline_and_at_end() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:commentRangeStart w:id="0"/>
+      <w:r><w:t>Payment terms</w:t></w:r>
+      <w:commentRangeEnd w:id="0"/>
+      <w:r><w:rPr><w:rStyle w:val="CommentReference"/></w:rPr><w:commentReference w:id="0"/></w:r>
+      <w:r><w:rPr><w:rStyle w:val="CommentReference"/></w:rPr><w:commentReference w:id="1"/></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:t>Delivery</w:t></w:r>
+      <w:r><w:commentReference w:id="2"/></w:r>
+    </w:p>"#);
+        let comments = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:comments xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+            xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordml">
+  <w:comment w:id="0" w:author="Alice" w:date="2024-03-01T10:00:00Z" w:initials="A">
+    <w:p w14:paraId="1A2B3C4D"><w:r><w:t>Check the</w:t></w:r></w:p>
+    <w:p w14:paraId="1A2B3C4E"><w:r><w:t>amounts.</w:t></w:r></w:p>
+  </w:comment>
+  <w:comment w:id="1" w:author="Bob" w:date="2024-03-02T09:30:00Z" w:initials="B">
+    <w:p w14:paraId="2B3C4D5E"><w:r><w:t>Done.</w:t></w:r></w:p>
+  </w:comment>
+  <w:comment w:id="2" w:author="Carol" w:initials="C">
+    <w:p w14:paraId="3C4D5E6F"><w:r><w:t>Which address?</w:t></w:r></w:p>
+  </w:comment>
+</w:comments>"#;
+        let comments_extended = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w15:commentsEx xmlns:w15="http://schemas.microsoft.com/office/word/2012/wordml">
+  <w15:commentEx w15:paraId="1A2B3C4E" w15:done="0"/>
+  <w15:commentEx w15:paraId="2B3C4D5E" w15:paraIdParent="1A2B3C4E" w15:done="0"/>
+  <w15:commentEx w15:paraId="3C4D5E6F" w15:done="0"/>
+</w15:commentsEx>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/comments.xml", comments),
+            ("word/commentsExtended.xml", comments_extended),
+        ])
+        .unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Payment terms\nDelivery");
+
+        let options = ExtractOptions {
+            comments: CommentMode::Inline,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "Payment terms[comment by Alice: Check the amounts.][reply by Bob: Done.]\n\
+             Delivery[comment by Carol: Which address?]"
+        );
+
+        let options = ExtractOptions {
+            comments: CommentMode::End,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "Payment terms\nDelivery\n\nComments:\n\
+             [comment by Alice: Check the amounts.] (2024-03-01T10:00:00Z)\n\
+             [reply by Bob: Done.] (2024-03-02T09:30:00Z)\n\
+             [comment by Carol: Which address?]"
+        );
+    }
+
+    #[test]
+    fn test_comments_without_comments_part() {
+        let (_temp_dir, docx_path) = create_test_docx("No comments here.").unwrap();
+        for comments in [CommentMode::Inline, CommentMode::End] {
+            let options = ExtractOptions {
+                comments,
+                ..ExtractOptions::default()
+            };
+            let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+            assert_eq!(extracted, "No comments here.");
+        }
+    }
+
+    #[test]
+    fn test_footnotes_placement() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t>Claim</w:t></w:r>
+      <w:r><w:rPr><w:rStyle w:val="FootnoteReference"/></w:rPr><w:footnoteReference w:id="3"/></w:r>
+      <w:r><w:t xml:space="preserve"> and counterclaim</w:t></w:r>
+      <w:r><w:rPr><w:rStyle w:val="FootnoteReference"/></w:rPr><w:footnoteReference w:id="2"/></w:r>
+      <w:r><w:t>.</w:t></w:r>
+    </w:p>"#);
+        let footnotes = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:footnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+             xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <w:footnote w:type="separator" w:id="0"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+  <w:footnote w:type="continuationSeparator" w:id="1"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>
+  <w:footnote w:id="2">
+    <w:p><w:r><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> See </w:t></w:r>
+      <w:hyperlink r:id="rId1"><w:r><w:t>the ruling</w:t></w:r></w:hyperlink></w:p>
+  </w:footnote>
+  <w:footnote w:id="3">
+    <w:p><w:r><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> First note,</w:t></w:r></w:p>
+    <w:p><w:r><w:t>second paragraph.</w:t></w:r></w:p>
+  </w:footnote>
+</w:footnotes>"#;
+        let footnote_rels = rels_xml(r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/ruling" TargetMode="External"/>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/footnotes.xml", footnotes),
+            ("word/_rels/footnotes.xml.rels", &footnote_rels),
+        ])
+        .unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Claim and counterclaim.");
+
+        let options = ExtractOptions {
+            footnotes: NoteMode::End,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "Claim[^1] and counterclaim[^2].\n\n\
+             [^1]: First note, second paragraph.\n\
+             [^2]: See the ruling <https://example.com/ruling>"
+        );
+
+        let options = ExtractOptions {
+            footnotes: NoteMode::Inline,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "Claim[footnote 1: First note, second paragraph.] and counterclaim\
+             [footnote 2: See the ruling <https://example.com/ruling>]."
+        );
+    }
+
+    #[test]
+    fn test_footnotes_and_endnotes_are_numbered_independently() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t>One</w:t></w:r>
+      <w:r><w:footnoteReference w:id="1"/></w:r>
+      <w:r><w:t xml:space="preserve"> two</w:t></w:r>
+      <w:r><w:endnoteReference w:id="1"/></w:r>
+      <w:r><w:t xml:space="preserve"> three</w:t></w:r>
+      <w:r><w:endnoteReference w:id="2"/></w:r>
+    </w:p>"#);
+        let footnotes = r#"<w:footnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:footnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+  <w:footnote w:type="continuationSeparator" w:id="0"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>
+  <w:footnote w:id="1"><w:p><w:r><w:t>A footnote.</w:t></w:r></w:p></w:footnote>
+</w:footnotes>"#;
+        let endnotes = r#"<w:endnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:endnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:endnote>
+  <w:endnote w:type="continuationSeparator" w:id="0"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:endnote>
+  <w:endnote w:id="1"><w:p><w:r><w:t>First endnote.</w:t></w:r></w:p></w:endnote>
+  <w:endnote w:id="2"><w:p><w:r><w:t>Second endnote.</w:t></w:r></w:p></w:endnote>
+</w:endnotes>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/footnotes.xml", footnotes),
+            ("word/endnotes.xml", endnotes),
+        ])
+        .unwrap();
+
+        let options = ExtractOptions {
+            footnotes: NoteMode::End,
+            endnotes: NoteMode::End,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "One[^1] two[^i] three[^ii]\n\n\
+             [^1]: A footnote.\n\n\
+             [^i]: First endnote.\n\
+             [^ii]: Second endnote."
+        );
+
+        let options = ExtractOptions {
+            endnotes: NoteMode::Inline,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "One two[endnote i: First endnote.] three[endnote ii: Second endnote.]"
+        );
+
+        let notes = extract_notes_from_docx(&docx_path, &ExtractOptions::default()).unwrap();
+        let listed: Vec<(NoteKind, &str, &str)> = notes
+            .iter()
+            .map(|note| (note.kind, note.id.as_str(), note.text.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (NoteKind::Footnote, "1", "A footnote."),
+                (NoteKind::Endnote, "1", "First endnote."),
+                (NoteKind::Endnote, "2", "Second endnote."),
+            ]
+        );
+    }
+
+
+    #[test]
+    fn test_headers_and_footers() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t>Section one.</w:t></w:r>
+      <w:pPr>
+        <w:sectPr>
+          <w:headerReference w:type="default" r:id="rId1"/>
+          <w:headerReference w:type="first" r:id="rId2"/>
+          <w:footerReference w:type="default" r:id="rId3"/>
+          <w:titlePg/>
+        </w:sectPr>
+      </w:pPr>
+    </w:p>
+    <w:p><w:r><w:t>Section two.</w:t></w:r></w:p>
+    <w:sectPr>
+      <w:headerReference w:type="default" r:id="rId4"/>
+      <w:footerReference w:type="default" r:id="rId3"/>
+    </w:sectPr>"#);
+        let rels = rels_xml(r#"
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header1.xml"/>
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header2.xml"/>
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer" Target="footer1.xml"/>
+  <Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header3.xml"/>"#);
+        let header = |text: &str| {
+            format!(
+                r#"<w:hdr xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:p><w:r><w:t>{}</w:t></w:r></w:p></w:hdr>"#,
+                text
+            )
+        };
+        let header1 = header("DOC-123 CONFIDENTIAL");
+        let header2 = header("Cover page");
+        let header3 = header("DOC-123 CONFIDENTIAL");
+        let footer1 = r#"<w:ftr xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:p><w:r><w:t>Internal use only</w:t></w:r></w:p></w:ftr>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+            ("word/header1.xml", &header1),
+            ("word/header2.xml", &header2),
+            ("word/header3.xml", &header3),
+            ("word/footer1.xml", footer1),
+        ])
+        .unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Section one.\nSection two.");
+
+        let options = ExtractOptions {
+            include_headers_footers: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "[header] DOC-123 CONFIDENTIAL\n\
+             [first page header] Cover page\n\n\
+             Section one.\nSection two.\n\n\
+             [footer] Internal use only"
+        );
+    }
+
+    #[test]
+    fn test_text_box_text_appears_once() {
+        let xml_content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" xmlns:wps="http://schemas.microsoft.com/office/word/2010/wordprocessingShape" xmlns:v="urn:schemas-microsoft-com:vml" mc:Ignorable="w14 wp14">
+  <w:body>
+    <w:p>
+      <w:r><w:t>Before the box.</w:t></w:r>
+      <w:r>
+        <mc:AlternateContent>
+          <mc:Choice Requires="wps">
+            <w:drawing>
+              <wps:wsp>
+                <wps:txbx>
+                  <w:txbxContent>
+                    <w:p><w:r><w:t>Boxed text</w:t></w:r></w:p>
+                  </w:txbxContent>
+                </wps:txbx>
+              </wps:wsp>
+            </w:drawing>
+          </mc:Choice>
+          <mc:Fallback>
+            <w:pict>
+              <v:shape>
+                <v:textbox>
+                  <w:txbxContent>
+                    <w:p><w:r><w:t>Boxed text</w:t></w:r></w:p>
+                  </w:txbxContent>
+                </v:textbox>
+              </v:shape>
+            </w:pict>
+          </mc:Fallback>
+        </mc:AlternateContent>
+      </w:r>
+      <w:r><w:t xml:space="preserve"> After the box.</w:t></w:r>
+    </w:p>
+  </w:body>
+</w:document>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted.matches("Boxed text").count(), 1);
+        assert_eq!(extracted, "Before the box.\nBoxed text\n After the box.");
+
+        let options = ExtractOptions {
+            include_text_boxes: false,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Before the box. After the box.");
+
+        // A Choice requiring markup we do not understand falls back to VML.
+        let xml_content = xml_content.replace(r#"Requires="wps""#, r#"Requires="wps cx1""#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted.matches("Boxed text").count(), 1);
+    }
+
+    #[test]
+    fn test_image_alt_text() {
+        let picture = |doc_pr: &str| {
+            format!(
+                r#"<wp:docPr id="1" name="Picture 1"{}/>
+          <a:graphic><a:graphicData><pic:pic><pic:blipFill><a:blip r:embed="rId1"/></pic:blipFill></pic:pic></a:graphicData></a:graphic>"#,
+                doc_pr
+            )
+        };
+        let xml_content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture">
+  <w:body>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Chart: </w:t></w:r>
+      <w:r><w:drawing><wp:inline>
+          {}
+      </wp:inline></w:drawing></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:drawing><wp:anchor>
+          {}
+      </wp:anchor></w:drawing></w:r>
+    </w:p>
+  </w:body>
+</w:document>"#,
+            picture(r#" descr="Sales &amp; costs,&#10;2023""#),
+            picture("")
+        );
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Chart:");
+
+        let options = ExtractOptions {
+            image_alt_text: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Chart: [image: Sales & costs, 2023]\n[image]");
+    }
+
+    #[test]
+    fn test_equations() {
+        let xml_content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math">
+  <w:body>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Area: </w:t></w:r>
+      <m:oMath>
+        <m:r><m:t>A=π</m:t></m:r>
+        <m:sSup>
+          <m:sSupPr><m:ctrlPr><w:rPr><w:i/></w:rPr></m:ctrlPr></m:sSupPr>
+          <m:e><m:r><m:t>r</m:t></m:r></m:e>
+          <m:sup><m:r><m:t>2</m:t></m:r></m:sup>
+        </m:sSup>
+      </m:oMath>
+    </w:p>
+    <w:p>
+      <m:oMathPara>
+        <m:oMath>
+          <m:r><m:t>x=</m:t></m:r>
+          <m:f>
+            <m:fPr><m:type m:val="bar"/></m:fPr>
+            <m:num><m:r><m:t>a+b</m:t></m:r></m:num>
+            <m:den><m:r><m:t>2</m:t></m:r></m:den>
+          </m:f>
+        </m:oMath>
+      </m:oMathPara>
+    </w:p>
+  </w:body>
+</w:document>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Area: A=πr^2\nx=(a+b)/2");
+
+        let options = ExtractOptions {
+            equations: EquationMode::Skip,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Area: [equation]\n[equation]");
+    }
+
+    #[test]
+    fn test_table_rows_and_cells() {
+        let cell = |paragraphs: &str| format!("<w:tc><w:tcPr><w:tcW w:w=\"2000\"/></w:tcPr>{}</w:tc>", paragraphs);
+        let paragraph = |text: &str| format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", text);
+        let xml_content = document_xml(&format!(
+            "{}<w:tbl><w:tblPr/><w:tblGrid/><w:tr>{}{}{}</w:tr><w:tr>{}{}{}</w:tr></w:tbl>{}",
+            paragraph("Before"),
+            cell(&paragraph("Name")),
+            cell(&paragraph("Role")),
+            cell(&paragraph("Notes")),
+            cell(&paragraph("Ada")),
+            cell(&paragraph("Engineer")),
+            cell(&format!("{}{}", paragraph("First line."), paragraph("Second line."))),
+            paragraph("After")
+        ));
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted,
+            "Before\nName\tRole\tNotes\nAda\tEngineer\tFirst line. Second line.\nAfter"
+        );
+
+        let options = ExtractOptions {
+            table_delimiter: " | ".to_string(),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert!(extracted.contains("Ada | Engineer | First line. Second line.\n"));
+
+        // A nested table is flattened into its cell.
+        let nested = format!(
+            "<w:tbl><w:tr>{}{}</w:tr></w:tbl>",
+            cell(&paragraph("x")),
+            cell(&paragraph("y"))
+        );
+        let xml_content = document_xml(&format!(
+            "<w:tbl><w:tr>{}{}</w:tr></w:tbl>",
+            cell(&nested),
+            cell(&paragraph("z"))
+        ));
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "x y\tz");
+    }
+
+    #[test]
+    fn test_cell_paragraphs_with_custom_separator() {
+        let xml_content = document_xml(
+            "<w:tbl><w:tr><w:tc>\
+               <w:p><w:r><w:t>Read | write</w:t></w:r></w:p>\
+               <w:p><w:r><w:t>abc</w:t></w:r></w:p>\
+             </w:tc><w:tc><w:p><w:r><w:t>Beech</w:t></w:r></w:p></w:tc></w:tr></w:tbl>\
+             <w:p><w:r><w:t>After</w:t></w:r></w:p>",
+        );
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        // Only the separators written between paragraphs become spaces, not
+        // text that happens to match them.
+        for (separator, expected) in [
+            (" | ", "Read | write abc\tBeech | After |"),
+            ("", "Read | write abc\tBeechAfter"),
+            ("e", "Read | write abc\tBeecheAftere"),
+        ] {
+            let options = ExtractOptions::new().paragraph_separator(separator);
+            let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+            assert_eq!(extracted, expected, "separator {:?}", separator);
+        }
+    }
+
+    #[test]
+    fn test_markdown_pipe_tables() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>Exit codes:</w:t></w:r></w:p>
+    <w:tbl>
+      <w:tr>
+        <w:tc><w:p><w:r><w:t>Code</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>Meaning</w:t></w:r></w:p></w:tc>
+      </w:tr>
+      <w:tr>
+        <w:tc><w:p><w:r><w:t>0</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>Success</w:t></w:r></w:p></w:tc>
+      </w:tr>
+      <w:tr>
+        <w:tc><w:p><w:r><w:t>1</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>Read | write error</w:t></w:r></w:p></w:tc>
+      </w:tr>
+    </w:tbl>
+    <w:p><w:r><w:t>Done.</w:t></w:r></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let options = ExtractOptions {
+            format: OutputFormat::Markdown,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        let expected = "\
+Exit codes:
+
+| Code | Meaning |
+| --- | --- |
+| 0 | Success |
+| 1 | Read \\| write error |
+
+Done.";
+        assert_eq!(extracted, expected);
+
+        // Plain text output is unaffected.
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Exit codes:\nCode\tMeaning\n0\tSuccess\n1\tRead | write error\nDone.");
+
+        // Vertically merged cells fall back to delimited rows.
+        let merged = xml_content.replacen("<w:tc>", "<w:tc><w:tcPr><w:vMerge w:val=\"restart\"/></w:tcPr>", 1);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&merged).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert!(extracted.contains("Code\tMeaning\n0\tSuccess\n"));
+    }
+
+    #[test]
+    fn test_merged_cells_keep_columns_aligned() {
+        let xml_content = document_xml(r#"
+    <w:tbl>
+      <w:tr>
+        <w:tc><w:tcPr><w:gridSpan w:val="3"/></w:tcPr><w:p><w:r><w:t>Quarterly results</w:t></w:r></w:p></w:tc>
+      </w:tr>
+      <w:tr>
+        <w:tc><w:tcPr><w:vMerge w:val="restart"/></w:tcPr><w:p><w:r><w:t>North</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>Q1</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>10</w:t></w:r></w:p></w:tc>
+      </w:tr>
+      <w:tr>
+        <w:tc><w:tcPr><w:vMerge/></w:tcPr><w:p/></w:tc>
+        <w:tc><w:p><w:r><w:t>Q2</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>12</w:t></w:r></w:p></w:tc>
+      </w:tr>
+    </w:tbl>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Quarterly results\t\t\nNorth\tQ1\t10\n\tQ2\t12");
+        assert!(extracted.lines().all(|line| line.split('\t').count() == 3));
+
+        let options = ExtractOptions {
+            merged_cells: MergedCells::Repeat,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "Quarterly results\tQuarterly results\tQuarterly results\nNorth\tQ1\t10\nNorth\tQ2\t12"
+        );
+    }
+
+    #[test]
+    fn test_list_numbering() {
+        let numbering = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">
+    <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl>
+    <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/><w:lvlText w:val="%2)"/></w:lvl>
+    <w:lvl w:ilvl="2"><w:start w:val="1"/><w:numFmt w:val="lowerRoman"/><w:lvlText w:val="%1.%3."/></w:lvl>
+  </w:abstractNum>
+  <w:abstractNum w:abstractNumId="1">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/><w:lvlText w:val="&#61623;"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+  <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+  <w:num w:numId="3">
+    <w:abstractNumId w:val="0"/>
+    <w:lvlOverride w:ilvl="0"><w:startOverride w:val="1"/></w:lvlOverride>
+  </w:num>
+</w:numbering>"#;
+        let item = |num_id: &str, level: usize, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="ListParagraph"/><w:numPr><w:ilvl w:val="{}"/><w:numId w:val="{}"/></w:numPr></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                level, num_id, text
+            )
+        };
+        let xml_content = document_xml(&[
+            item("1", 0, "Unpack the parts."),
+            item("1", 1, "Check the screws."),
+            item("1", 1, "Check the panels."),
+            item("1", 2, "Count the hinges."),
+            item("1", 0, "Assemble the frame."),
+            item("1", 1, "Start with the base."),
+            item("2", 0, "Keep the manual."),
+            item("3", 0, "Open the box."),
+            item("3", 0, "Recycle it."),
+        ]
+        .concat());
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/numbering.xml", numbering),
+        ])
+        .unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted,
+            "1. Unpack the parts.\n\
+             \x20 a) Check the screws.\n\
+             \x20 b) Check the panels.\n\
+             \x20   1.i. Count the hinges.\n\
+             2. Assemble the frame.\n\
+             \x20 a) Start with the base.\n\
+             • Keep the manual.\n\
+             1. Open the box.\n\
+             2. Recycle it."
+        );
+
+        // Markdown nests an item under the text of the one before it.
+        let options = ExtractOptions::new().format(OutputFormat::Markdown);
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "1. Unpack the parts.\n\
+             \x20  1. Check the screws.\n\
+             \x20  2. Check the panels.\n\
+             \x20     1. Count the hinges.\n\
+             2. Assemble the frame.\n\
+             \x20  1. Start with the base.\n\
+             - Keep the manual.\n\
+             1. Open the box.\n\
+             2. Recycle it."
+        );
+    }
+
+    #[test]
+    fn test_heading_styles() {
+        let styles = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:pPr><w:outlineLvl w:val="0"/></w:pPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr></w:style>
+  <w:style w:type="paragraph" w:styleId="AppendixHeading"><w:name w:val="Appendix Heading"/><w:basedOn w:val="Heading2"/></w:style>
+  <w:style w:type="paragraph" w:styleId="AppendixSubheading"><w:name w:val="Appendix Subheading"/><w:basedOn w:val="AppendixHeading"/></w:style>
+</w:styles>"#;
+        let paragraph = |style: &str, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                style, text
+            )
+        };
+        let xml_content = document_xml(&[
+            paragraph("Heading1", "Installation"),
+            paragraph("Normal", "Read this first."),
+            paragraph("Heading2", "Requirements"),
+            paragraph("AppendixSubheading", "Appendix A"),
+        ]
+        .concat());
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Installation\nRead this first.\nRequirements\nAppendix A");
+
+        let options = ExtractOptions {
+            mark_headings: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "[H1] Installation\nRead this first.\n[H2] Requirements\n[H2] Appendix A"
+        );
+
+        let options = ExtractOptions {
+            format: OutputFormat::Markdown,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "# Installation\nRead this first.\n## Requirements\n## Appendix A"
+        );
+
+        let headings = extract_headings_from_docx(&docx_path, &options).unwrap();
+        let outline: Vec<(usize, &str)> = headings
+            .iter()
+            .map(|heading| (heading.level, heading.text.as_str()))
+            .collect();
+        assert_eq!(outline, [(1, "Installation"), (2, "Requirements"), (2, "Appendix A")]);
+    }
+
+    #[test]
+    fn test_style_filters() {
+        let styles = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+  <w:style w:type="paragraph" w:styleId="berschrift1"><w:name w:val="heading 1"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Beschriftung"><w:name w:val="caption"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Verzeichnis1"><w:name w:val="toc 1"/></w:style>
+  <w:style w:type="paragraph" w:styleId="CodeBlock"><w:name w:val="Code Block"/></w:style>
+</w:styles>"#;
+        let paragraph = |style: &str, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                style, text
+            )
+        };
+        let xml_content = document_xml(&[
+            paragraph("Verzeichnis1", "Setup 1"),
+            paragraph("berschrift1", "Setup"),
+            "<w:p><w:r><w:t>Run the installer:</w:t></w:r></w:p>".to_string(),
+            paragraph("CodeBlock", "./install.sh"),
+            paragraph("Beschriftung", "Figure 1: The installer"),
+        ]
+        .concat());
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+        let extract = |include: &[&str], exclude: &[&str]| {
+            let options = ExtractOptions {
+                include_styles: include.iter().map(|style| style.to_string()).collect(),
+                exclude_styles: exclude.iter().map(|style| style.to_string()).collect(),
+                ..ExtractOptions::default()
+            };
+            extract_text_from_docx_with(&docx_path, &options).unwrap()
+        };
+
+        assert_eq!(extract(&["CodeBlock"], &[]), "./install.sh");
+        assert_eq!(
+            extract(&[], &["TOC*", "Caption"]),
+            "Setup\nRun the installer:\n./install.sh"
+        );
+        // Paragraphs without a style have the default style, Normal.
+        assert_eq!(
+            extract(&["Heading*", "Normal", "Code*"], &["code block"]),
+            "Setup\nRun the installer:"
+        );
+    }
+
+    #[test]
+    fn test_content_controls() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">Customer: </w:t></w:r>
+      <w:sdt>
+        <w:sdtPr>
+          <w:alias w:val="Customer name"/>
+          <w:tag w:val="customer"/>
+          <w:placeholder><w:docPart w:val="DefaultPlaceholder_-1854013440"/></w:placeholder>
+          <w:showingPlcHdr/>
+          <w:text/>
+        </w:sdtPr>
+        <w:sdtContent>
+          <w:r><w:rPr><w:rStyle w:val="PlaceholderText"/></w:rPr><w:t>Click or tap here to enter text.</w:t></w:r>
+        </w:sdtContent>
+      </w:sdt>
+    </w:p>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Priority: </w:t></w:r>
+      <w:sdt>
+        <w:sdtPr>
+          <w:tag w:val="priority"/>
+          <w:dropDownList>
+            <w:listItem w:displayText="Low" w:value="1"/>
+            <w:listItem w:displayText="High" w:value="3"/>
+          </w:dropDownList>
+        </w:sdtPr>
+        <w:sdtEndPr/>
+        <w:sdtContent><w:r><w:t>High</w:t></w:r></w:sdtContent>
+      </w:sdt>
+    </w:p>
+    <w:sdt>
+      <w:sdtPr><w:alias w:val="Notes"/></w:sdtPr>
+      <w:sdtContent>
+        <w:p><w:r><w:t>Deliver by Friday.</w:t></w:r></w:p>
+        <w:p><w:r><w:t>Call first.</w:t></w:r></w:p>
+      </w:sdtContent>
+    </w:sdt>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Customer: \nPriority: High\nDeliver by Friday.\nCall first.");
+
+        let options = ExtractOptions {
+            annotate_content_controls: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "Customer: [Customer name: ]\nPriority: [priority: High]\n[Notes: Deliver by Friday. Call first.]"
+        );
+    }
+
+    #[test]
+    fn test_main_part_found_through_package_relationships() {
+        let package_rels = rels_xml(r#"
+  <Relationship Id="R7c1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="/word/document2.xml"/>"#);
+        let document_rels = rels_xml(r#"
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com" TargetMode="External"/>"#);
+        let xml_content = document_xml(r#"
+    <w:p><w:hyperlink r:id="rId1"><w:r><w:t>Renamed part</w:t></w:r></w:hyperlink></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("_rels/.rels", &package_rels),
+            ("word/document2.xml", &xml_content),
+            ("word/_rels/document2.xml.rels", &document_rels),
+        ])
+        .unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Renamed part <https://example.com>");
+
+        // Part names are compared without regard to case.
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("_rels/.rels", &package_rels),
+            ("Word/Document2.xml", &xml_content),
+        ])
+        .unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::new().strip_hyperlinks(true)).unwrap();
+        assert_eq!(extracted, "Renamed part");
+    }
+
+
+    #[test]
+    fn test_encrypted_document_is_reported() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("protected.docx");
+        std::fs::write(&path, compound_file(&["EncryptionInfo", "EncryptedPackage"])).unwrap();
+        let path = path.to_str().unwrap();
+
+        let error = extract_text_from_docx_with(path, &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::Encrypted { path: reported } if reported == path
+        ));
+        assert!(error.to_string().contains("remove the password"));
+    }
+
+    /// Builds a compound file header followed by directory entry names.
+    fn compound_file(streams: &[&str]) -> Vec<u8> {
+        let mut content = vec![0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+        content.resize(512, 0);
+        for stream in streams {
+            let mut entry: Vec<u8> = stream.encode_utf16().flat_map(u16::to_le_bytes).collect();
+            entry.resize(128, 0);
+            content.extend(entry);
+        }
+        content
+    }
+
+    #[test]
+    fn test_legacy_and_corrupt_files_are_told_apart() {
+        let temp_dir = tempdir().unwrap();
+        let classify = |name: &str, content: Vec<u8>| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            let error = extract_text_from_docx_with(path.to_str().unwrap(), &ExtractOptions::default()).unwrap_err();
+            match &error {
+                MergeError::Encrypted { .. } => "encrypted",
+                MergeError::LegacyDoc { .. } => "legacy",
+                MergeError::Zip { .. } => "corrupt",
+                _ => "other",
+            }
+        };
+
+        assert_eq!(classify("report.docx", compound_file(&["WordDocument", "1Table"])), "legacy");
+        assert_eq!(classify("report.doc", compound_file(&[])), "legacy");
+        assert_eq!(classify("secret.docx", compound_file(&["EncryptedPackage"])), "encrypted");
+        assert_eq!(classify("budget.docx", compound_file(&["Workbook"])), "corrupt");
+        assert_eq!(classify("truncated.docx", b"PK\x03\x04 truncated".to_vec()), "corrupt");
+    }
+
+    #[test]
+    fn test_inflated_size_limits() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("bomb.docx");
+        let mut zip = zip::ZipWriter::new(File::create(&file_path).unwrap());
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let padding = " ".repeat(100_000);
+        zip.start_file("word/styles.xml", options).unwrap();
+        zip.write_all(format!("<w:styles>{}</w:styles>", padding).as_bytes()).unwrap();
+        zip.start_file("word/document.xml", options).unwrap();
+        zip.write_all(document_xml(&format!("<w:p><w:r><w:t>Hi</w:t></w:r></w:p>{}", padding)).as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+        let docx_path = file_path.to_str().unwrap();
+        assert!(std::fs::metadata(docx_path).unwrap().len() < 5_000);
+
+        assert_eq!(extract_text_from_docx_with(docx_path, &ExtractOptions::default()).unwrap(), "Hi");
+
+        let options = ExtractOptions {
+            max_part_size: 50_000,
+            ..ExtractOptions::default()
+        };
+        let error = extract_text_from_docx_with(docx_path, &options).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::PartTooLarge { part, limit: 50_000, .. } if part == "word/styles.xml"
+        ));
+
+        let options = ExtractOptions {
+            max_total_uncompressed: 150_000,
+            ..ExtractOptions::default()
+        };
+        let error = extract_text_from_docx_with(docx_path, &options).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::PackageTooLarge { part, .. } if part == "word/document.xml"
+        ));
+    }
+
+    #[test]
+    fn test_other_office_packages_are_reported() {
+        let package = |main_part: &str, content_type: &str| {
+            let content_types = format!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/{}" ContentType="{}"/>
+</Types>"#,
+                main_part, content_type
+            );
+            let package_rels = rels_xml(&format!(
+                r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="{}"/>"#,
+                main_part
+            ));
+            create_test_docx_with_parts(&[
+                ("[Content_Types].xml", &content_types),
+                ("_rels/.rels", &package_rels),
+                (main_part, "<root/>"),
+            ])
+            .unwrap()
+        };
+
+        let (_temp_dir, xlsx_path) = package(
+            "xl/workbook.xml",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml",
+        );
+        let error = extract_text_from_docx_with(&xlsx_path, &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(&error, MergeError::NotWordDocument { .. }));
+        assert!(error.to_string().ends_with("is a spreadsheet (.xlsx), not a Word document"));
+
+        let (_temp_dir, pptx_path) = package(
+            "ppt/presentation.xml",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml",
+        );
+        let error = extract_text_from_docx_with(&pptx_path, &ExtractOptions::default()).unwrap_err();
+        assert!(error.to_string().ends_with("is a presentation (.pptx), not a Word document"));
+
+        let (_temp_dir, dotm_path) = package(
+            "word/document.xml",
+            "application/vnd.ms-word.template.macroEnabledTemplate.main+xml",
+        );
+        assert_eq!(extract_text_from_docx_with(&dotm_path, &ExtractOptions::default()).unwrap(), "");
+    }
+
+    #[test]
+    fn test_templates_and_macro_enabled_documents() {
+        let content_types = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="bin" ContentType="application/vnd.ms-office.vbaProject"/>
+  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.template.main+xml"/>
+</Types>"#;
+        let xml_content = document_xml("<w:p><w:r><w:t>Dear [name],</w:t></w:r></w:p>");
+        let (temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("[Content_Types].xml", content_types),
+            ("word/document.xml", &xml_content),
+            ("word/vbaProject.bin", "\u{1}binary macros"),
+        ])
+        .unwrap();
+        let dotx_path = temp_dir.path().join("letter.dotx");
+        std::fs::rename(&docx_path, &dotx_path).unwrap();
+        let dotx_path = dotx_path.to_str().unwrap().to_string();
+        assert_eq!(extract_text_from_docx_with(&dotx_path, &ExtractOptions::default()).unwrap(), "Dear [name],");
+
+        // A directory stands for the Word and OpenDocument documents in it.
+        for name in ["b.DOCM", "c.dotm", "d.odt", "notes.txt", "~lock.docx.tmp"] {
+            std::fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+        let directory = temp_dir.path().to_str().unwrap().to_string();
+        let mut names: Vec<String> = expand_patterns(&[&directory])
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["b.DOCM", "c.dotm", "d.odt", "letter.dotx"]);
+    }
+
+    #[test]
+    fn test_odt_documents_merge_with_docx() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+                         xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+  <office:body>
+    <office:text>
+      <text:p>From LibreOffice<text:tab/>with a tab</text:p>
+      <text:p>and a<text:line-break/>line break.</text:p>
+    </office:text>
+  </office:body>
+</office:document-content>"#;
+        // The format is told by the `mimetype` entry, not the file name.
+        let (_odt_dir, odt_path) = create_test_docx_with_parts(&[
+            ("mimetype", "application/vnd.oasis.opendocument.text"),
+            ("content.xml", content),
+        ])
+        .unwrap();
+        let (_docx_dir, docx_path) = create_test_docx("From Word").unwrap();
+        let options = ExtractOptions::default();
+        assert_eq!(
+            extract_text_from_odt(&odt_path, &options).unwrap(),
+            "From LibreOffice\twith a tab\nand a\nline break."
+        );
+        let merged = merge_docx_files_with(&[&docx_path, &odt_path], &options).unwrap();
+        assert_eq!(merged, "From Word\n\nFrom LibreOffice\twith a tab\nand a\nline break.");
+
+        let (_ods_dir, ods_path) = create_test_docx_with_parts(&[
+            ("mimetype", "application/vnd.oasis.opendocument.spreadsheet"),
+            ("content.xml", content),
+        ])
+        .unwrap();
+        let error = extract_text_from_file(&ods_path, &options).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::NotWordDocument { .. }
+        ));
+    }
+
+    #[test]
+    fn test_flat_opc_documents() {
+        let flat = r#"<?xml version="1.0" standalone="yes"?>
+<?mso-application progid="Word.Document"?>
+<pkg:package xmlns:pkg="http://schemas.microsoft.com/office/2006/xmlPackage">
+  <pkg:part pkg:name="/_rels/.rels" pkg:contentType="application/vnd.openxmlformats-package.relationships+xml">
+    <pkg:xmlData>
+      <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+        <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+      </Relationships>
+    </pkg:xmlData>
+  </pkg:part>
+  <pkg:part pkg:name="/word/document.xml" pkg:contentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml">
+    <pkg:xmlData>
+      <w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+        <w:body>
+          <w:p><w:r><w:t>Saved as</w:t></w:r><w:r><w:t xml:space="preserve"> Word XML &amp; more</w:t></w:r></w:p>
+        </w:body>
+      </w:document>
+    </pkg:xmlData>
+  </pkg:part>
+  <pkg:part pkg:name="/word/media/image1.png" pkg:contentType="image/png" pkg:compression="store">
+    <pkg:binaryData>iVBORw0KGgo=</pkg:binaryData>
+  </pkg:part>
+</pkg:package>"#;
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("flat.xml");
+        std::fs::write(&path, flat).unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(extract_text_from_docx_with(path, &ExtractOptions::default()).unwrap(), "Saved as Word XML & more");
+
+        let other = temp_dir.path().join("other.xml");
+        std::fs::write(&other, "<?xml version=\"1.0\"?><catalog/>").unwrap();
+        let error = extract_text_from_docx_with(other.to_str().unwrap(), &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::Zip { .. }
+        ));
+    }
+
+    #[test]
+    fn test_plain_text_files_are_merged_verbatim() {
+        let (temp_dir, docx_path) = create_test_docx("From Word").unwrap();
+        let notes_path = temp_dir.path().join("notes.md");
+        std::fs::write(&notes_path, "\u{FEFF}# Notes\n\n- *kept* as is\n").unwrap();
+        let notes_path = notes_path.to_str().unwrap();
+        let merged = merge_docx_files_with(&[&docx_path, notes_path], &ExtractOptions::default()).unwrap();
+        assert_eq!(merged, "From Word\n\n# Notes\n\n- *kept* as is");
+
+        let latin1_path = temp_dir.path().join("latin1.txt");
+        std::fs::write(&latin1_path, b"caf\xE9").unwrap();
+        let error = merge_docx_files_with(&[latin1_path.to_str().unwrap()], &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::NotUtf8 { .. }
+        ));
+    }
+
+    #[test]
+    fn test_glossary_entries() {
+        let document_rels = rels_xml(
+            r#"<Relationship Id="rId9" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/glossaryDocument" Target="glossary/document.xml"/>"#,
+        );
+        let glossary_rels = rels_xml(
+            r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/terms" TargetMode="External"/>"#,
+        );
+        let glossary = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:glossaryDocument xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+                    xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <w:docParts>
+    <w:docPart>
+      <w:docPartPr>
+        <w:name w:val="Liability clause"/>
+        <w:category><w:name w:val="General"/><w:gallery w:val="autoTxt"/></w:category>
+      </w:docPartPr>
+      <w:docPartBody>
+        <w:p><w:r><w:t xml:space="preserve">Liability is limited, see </w:t></w:r><w:hyperlink r:id="rId1"><w:r><w:t>the terms</w:t></w:r></w:hyperlink><w:r><w:t>.</w:t></w:r></w:p>
+      </w:docPartBody>
+    </w:docPart>
+    <w:docPart>
+      <w:docPartPr><w:name w:val="Signature"/></w:docPartPr>
+      <w:docPartBody>
+        <w:p><w:r><w:t>Yours sincerely,</w:t></w:r></w:p>
+      </w:docPartBody>
+    </w:docPart>
+  </w:docParts>
+</w:glossaryDocument>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &document_xml("<w:p><w:r><w:t>Contract</w:t></w:r></w:p>")),
+            ("word/_rels/document.xml.rels", &document_rels),
+            ("word/glossary/document.xml", glossary),
+            ("word/glossary/_rels/document.xml.rels", &glossary_rels),
+        ])
+        .unwrap();
+        assert_eq!(extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap(), "Contract");
+
+        let options = ExtractOptions {
+            include_glossary: true,
+            ..ExtractOptions::default()
+        };
+        assert_eq!(
+            extract_text_from_docx_with(&docx_path, &options).unwrap(),
+            "Contract\n\nGlossary:\n[Liability clause]\n\
+             Liability is limited, see the terms <https://example.com/terms>.\n\
+             [Signature]\nYours sincerely,"
+        );
+
+        // Documents without a glossary are unaffected.
+        let (_temp_dir, docx_path) = create_test_docx("No glossary").unwrap();
+        assert_eq!(extract_text_from_docx_with(&docx_path, &options).unwrap(), "No glossary");
+    }
+
+    #[test]
+    fn test_table_of_contents_modes() {
+        let styles = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style>
+  <w:style w:type="paragraph" w:styleId="TOC1"><w:name w:val="toc 1"/></w:style>
+  <w:style w:type="paragraph" w:styleId="TOC2"><w:name w:val="toc 2"/></w:style>
+</w:styles>"#;
+        let entry = |style: &str, text: &str, page: &str| {
+            format!(
+                r#"<w:r><w:t>{}</w:t></w:r><w:r><w:tab/></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> PAGEREF _Toc{} \h </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>{}</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>"#,
+                text, style, page
+            )
+        };
+        let body = format!(
+            r#"
+    <w:p><w:r><w:t>Contents</w:t></w:r></w:p>
+    <w:p>
+      <w:pPr><w:pStyle w:val="TOC1"/></w:pPr>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> TOC \o "1-3" \h \z \u </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      {}
+    </w:p>
+    <w:p><w:pPr><w:pStyle w:val="TOC2"/></w:pPr>{}</w:p>
+    <w:p><w:pPr><w:pStyle w:val="TOC1"/></w:pPr>{}</w:p>
+    <w:p><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>
+    <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Introduction</w:t></w:r></w:p>
+    <w:p><w:r><w:t>Body text.</w:t></w:r></w:p>
+    <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Scope</w:t></w:r></w:p>"#,
+            entry("1", "Introduction", "1"),
+            entry("2", "Background", "1"),
+            entry("3", "Scope", "2")
+        );
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &document_xml(&body)),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+        let extract = |toc| {
+            let options = ExtractOptions {
+                toc,
+                ..ExtractOptions::default()
+            };
+            extract_text_from_docx_with(&docx_path, &options).unwrap()
+        };
+        assert_eq!(
+            extract(TocMode::Keep),
+            "Contents\nIntroduction\t1\nBackground\t1\nScope\t2\n\nIntroduction\nBody text.\nScope"
+        );
+        assert_eq!(extract(TocMode::Skip), "Contents\nIntroduction\nBody text.\nScope");
+        assert_eq!(
+            extract(TocMode::Marker),
+            "Contents\n[table of contents]\nIntroduction\nBody text.\nScope"
+        );
+    }
+
+    #[test]
+    fn test_symbols_map_to_unicode() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:sym w:font="Wingdings" w:char="F0FC"/><w:t xml:space="preserve"> Tested </w:t></w:r>
+      <w:r><w:sym w:font="Symbol" w:char="F061"/><w:sym w:font="Wingdings" w:char="F0A0"/></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "✓ Tested α[symbol]");
+
+        let options = ExtractOptions {
+            unmapped_symbols: UnmappedSymbols::Raw,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "✓ Tested α\u{F0A0}");
+    }
+
+    #[test]
+    fn test_no_break_and_soft_hyphens() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t>self</w:t><w:noBreakHyphen/><w:t>test of hyphen</w:t><w:softHyphen/><w:t>ation</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "self\u{2011}test of hyphenation");
+
+        let options = ExtractOptions {
+            ascii_hyphens: true,
+            keep_soft_hyphens: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "self-test of hyphen\u{AD}ation");
+    }
+
+    #[test]
+    fn test_rendered_page_break_markers() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>First page.</w:t></w:r></w:p>
+    <w:p><w:r><w:lastRenderedPageBreak/><w:t>Second page.</w:t></w:r></w:p>
+    <w:p><w:r><w:t xml:space="preserve">Still second, </w:t></w:r><w:r><w:lastRenderedPageBreak/><w:t>third.</w:t></w:r></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "First page.\nSecond page.\nStill second, third.");
+
+        let options = ExtractOptions {
+            page_marker: Some("[page {n}] ".to_string()),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(
+            extracted,
+            "First page.\n[page 2] Second page.\nStill second, [page 3] third."
+        );
+        assert_eq!(extract_stats(&docx_path, &ExtractOptions::default()).unwrap().pages, 3);
+    }
+
+    #[test]
+    fn test_bookmark_ranges() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>Preamble.</w:t></w:r></w:p>
+    <w:tbl><w:tr><w:tc><w:p><w:r><w:t>Parties</w:t></w:r></w:p></w:tc><w:tc><w:p/></w:tc></w:tr></w:tbl>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Clause 4. </w:t></w:r>
+      <w:bookmarkStart w:id="1" w:name="ClauseLiability"/>
+      <w:r><w:t xml:space="preserve">Liability is </w:t></w:r>
+      <w:bookmarkStart w:id="2" w:name="Cap"/><w:r><w:t>capped</w:t></w:r><w:bookmarkEnd w:id="2"/>
+    </w:p>
+    <w:tbl><w:tr><w:tc><w:p><w:r><w:t>Exclusions</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+    <w:p><w:bookmarkStart w:id="3" w:name="_GoBack"/><w:bookmarkEnd w:id="3"/><w:r><w:t>at fees paid.</w:t></w:r></w:p>
+    <w:bookmarkEnd w:id="1"/>
+    <w:p><w:r><w:t>Clause 5.</w:t></w:r></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let mut options = ExtractOptions {
+            bookmark: Some("ClauseLiability".to_string()),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Liability is capped\nExclusions\nat fees paid.");
+
+        options.bookmark = Some("Cap".to_string());
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "capped");
+
+        options.bookmark = Some("Missing".to_string());
+        let error = extract_text_from_docx_with(&docx_path, &options).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::BookmarkNotFound { name, .. } if name == "Missing"
+        ));
+
+        let names = extract_bookmarks_from_docx(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(names, ["ClauseLiability", "Cap"]);
+    }
+
+    #[test]
+    fn test_section_separators() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>Main part.</w:t></w:r></w:p>
+    <w:p>
+      <w:pPr><w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:pPr>
+      <w:r><w:t>End of part one.</w:t></w:r>
+    </w:p>
+    <w:p><w:r><w:t>Appendix A.</w:t></w:r></w:p>
+    <w:sectPr><w:type w:val="continuous"/><w:pgSz w:w="12240" w:h="15840"/></w:sectPr>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Main part.\nEnd of part one.\nAppendix A.");
+
+        let mut options = ExtractOptions {
+            section_separator: "***\n".to_string(),
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Main part.\nEnd of part one.\n***\nAppendix A.");
+
+        options.section_separator = "[{type} section]\n".to_string();
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Main part.\nEnd of part one.\n[continuous section]\nAppendix A.");
+    }
+
+    #[test]
+    fn test_ruby_guides() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r>
+        <w:ruby>
+          <w:rubyPr><w:rubyAlign w:val="distributeSpace"/><w:hps w:val="10"/><w:lid w:val="ja-JP"/></w:rubyPr>
+          <w:rt><w:r><w:t>かんじ</w:t></w:r></w:rt>
+          <w:rubyBase><w:r><w:t>漢字</w:t></w:r></w:rubyBase>
+        </w:ruby>
+      </w:r>
+      <w:r><w:t>を読む</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "漢字を読む");
+
+        let options = ExtractOptions {
+            ruby: RubyMode::Annotate,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "漢字(かんじ)を読む");
+    }
+
+    #[test]
+    fn test_punctuation_normalization_spares_link_targets() {
+        let rels = rels_xml(
+            r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/a–b" TargetMode="External"/>"#,
+        );
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">“Don’t panic” – see </w:t></w:r>
+      <w:hyperlink r:id="rId2"><w:r><w:t>the menu…</w:t></w:r></w:hyperlink>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+        ])
+        .unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "“Don’t panic” – see the menu… <https://example.com/a–b>");
+
+        let options = ExtractOptions {
+            normalize_punctuation: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "\"Don't panic\" - see the menu... <https://example.com/a–b>");
+    }
+
+    #[test]
+    fn test_nfc_composes_decomposed_characters() {
+        let (_temp_dir, docx_path) = create_test_docx("Cafe\u{301} de\u{301}ja\u{300} vu").unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Cafe\u{301} de\u{301}ja\u{300} vu");
+
+        let options = ExtractOptions {
+            nfc: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Caf\u{E9} d\u{E9}j\u{E0} vu");
+    }
+
+    #[test]
+    fn test_strip_invisible_characters() {
+        let (_temp_dir, docx_path) = create_test_docx("zero\u{200B}width\u{FEFF}text").unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "zero\u{200B}width\u{FEFF}text");
+
+        let options = ExtractOptions {
+            strip_invisible: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "zerowidthtext");
+    }
+
+    #[test]
+    fn test_struck_through_text_can_be_skipped() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:r><w:t xml:space="preserve">Keep this, </w:t></w:r>
+      <w:r><w:rPr><w:strike/></w:rPr><w:t xml:space="preserve">remove this, </w:t></w:r>
+      <w:r><w:rPr><w:dstrike w:val="1"/></w:rPr><w:t xml:space="preserve">and this, </w:t></w:r>
+      <w:r><w:rPr><w:strike w:val="0"/></w:rPr><w:t>but not this.</w:t></w:r>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Keep this, remove this, and this, but not this.");
+
+        let options = ExtractOptions {
+            skip_strikethrough: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "Keep this, but not this.");
+    }
+
+    #[test]
+    fn test_blank_line_policies() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>Dear reader,</w:t></w:r></w:p>
+    <w:p/><w:p/><w:p></w:p><w:p><w:r><w:t xml:space="preserve">  </w:t></w:r></w:p><w:p/>
+    <w:p><w:r><w:t>Yours.</w:t></w:r></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let extract = |blank_lines| {
+            let options = ExtractOptions {
+                blank_lines,
+                ..ExtractOptions::default()
+            };
+            extract_text_from_docx_with(&docx_path, &options).unwrap()
+        };
+        assert_eq!(extract(BlankLines::Keep), "Dear reader,\n\n\n\n  \n\nYours.");
+        assert_eq!(extract(BlankLines::Collapse), "Dear reader,\n\nYours.");
+        assert_eq!(extract(BlankLines::Strip), "Dear reader,\nYours.");
+    }
+
+    #[test]
+    fn test_non_standard_namespace_prefixes() {
+        // Some generators bind WordprocessingML to `ns0`, or make it the
+        // default namespace (its attributes still need a prefix).
+        let xml_content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<ns0:document xmlns:ns0="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <ns0:body>
+    <ns0:p><ns0:pPr><ns0:pStyle ns0:val="Title"/></ns0:pPr><ns0:r><ns0:t>Report</ns0:t></ns0:r></ns0:p>
+    <ns0:p>
+      <ns0:r><ns0:t xml:space="preserve">Total: </ns0:t></ns0:r>
+      <ns0:r><ns0:fldChar ns0:fldCharType="begin"/></ns0:r>
+      <ns0:r><ns0:instrText xml:space="preserve"> =SUM(ABOVE) </ns0:instrText></ns0:r>
+      <ns0:r><ns0:fldChar ns0:fldCharType="separate"/></ns0:r>
+      <ns0:r><ns0:t>42</ns0:t></ns0:r>
+      <ns0:r><ns0:fldChar ns0:fldCharType="end"/></ns0:r>
+      <ns0:r><ns0:tab/><ns0:t>units</ns0:t></ns0:r>
+    </ns0:p>
+  </ns0:body>
+</ns0:document>"#;
+        let styles = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styles xmlns="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:wml="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <style wml:type="paragraph" wml:styleId="Title"><name wml:val="Title"/><pPr><outlineLvl wml:val="0"/></pPr></style>
+</styles>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", xml_content),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+
+        let options = ExtractOptions {
+            mark_headings: true,
+            ..ExtractOptions::default()
+        };
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "[H1] Report\nTotal: 42\tunits");
+
+        // A `w` prefix bound to some other namespace is not WordprocessingML.
+        let xml_content = r#"<w:document xmlns:w="urn:not-word"><w:body><w:p><w:r><w:t>Hidden</w:t></w:r></w:p></w:body></w:document>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(xml_content).unwrap();
+        assert_eq!(extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap(), "");
+    }
+
+    #[test]
+    fn test_alternate_content_only_in_fallback() {
+        // Third-party writers may leave the Choice empty, requiring markup we
+        // do not read, and put the text only in a VML fallback.
+        let xml_content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" xmlns:cx1="http://schemas.microsoft.com/office/drawing/2015/9/8/chartex" xmlns:v="urn:schemas-microsoft-com:vml">
+  <w:body>
+    <w:p>
+      <w:r>
+        <mc:AlternateContent>
+          <mc:Choice Requires="cx1"><w:drawing/></mc:Choice>
+          <mc:Fallback>
+            <w:pict><v:shape><v:textbox><w:txbxContent>
+              <w:p><w:r><w:t>Only in the fallback</w:t></w:r></w:p>
+            </w:txbxContent></v:textbox></v:shape></w:pict>
+          </mc:Fallback>
+        </mc:AlternateContent>
+      </w:r>
+    </w:p>
+    <mc:AlternateContent>
+      <mc:Choice Requires="undeclared"><w:p><w:r><w:t>Choice</w:t></w:r></w:p></mc:Choice>
+      <mc:Fallback><w:p><w:r><w:t>Fallback paragraph</w:t></w:r></w:p></mc:Fallback>
+    </mc:AlternateContent>
+    <mc:AlternateContent xmlns:shape="http://schemas.microsoft.com/office/word/2010/wordprocessingShape">
+      <mc:Choice Requires="shape"><w:p><w:r><w:t>Understood choice</w:t></w:r></w:p></mc:Choice>
+      <mc:Fallback><w:p><w:r><w:t>Unused fallback</w:t></w:r></w:p></mc:Fallback>
+    </mc:AlternateContent>
+  </w:body>
+</w:document>"#;
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(xml_content).unwrap();
+
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted.matches("Only in the fallback").count(), 1);
+        // The paragraph holding the text box is left empty.
+        assert_eq!(
+            extracted,
+            "Only in the fallback\n\nFallback paragraph\nUnderstood choice"
+        );
+    }
+
+    #[test]
+    fn test_utf16_document_part() {
+        let xml_content = document_xml(r#"<w:p><w:r><w:t>Übersicht für 2024</w:t></w:r></w:p>"#)
+            .replacen(r#"encoding="UTF-8""#, r#"encoding="UTF-16""#, 1);
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(xml_content.encode_utf16().flat_map(u16::to_le_bytes));
+
+        let temp_dir = tempdir().unwrap();
+        let docx_path = temp_dir.path().join("utf16.docx");
+        let mut zip = zip::ZipWriter::new(File::create(&docx_path).unwrap());
+        zip.start_file("word/document.xml", FileOptions::default()).unwrap();
+        zip.write_all(&bytes).unwrap();
+        zip.finish().unwrap();
+
+        let extracted = extract_text_from_docx_with(docx_path.to_str().unwrap(), &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "Übersicht für 2024");
+    }
+
+    #[test]
+    fn test_extract_options_builder() {
+        assert_eq!(
+            format!("{:?}", ExtractOptions::new()),
+            format!("{:?}", ExtractOptions::default())
+        );
+        let options = ExtractOptions::new()
+            .strip_hyperlinks(true)
+            .paragraph_separator("\n\n")
+            .exclude_style("TOC*")
+            .exclude_style("Caption")
+            .bookmark("Summary")
+            .footnotes(NoteMode::End);
+        assert_eq!(options.link_style, LinkStyle::Strip);
+        assert_eq!(options.paragraph_separator, "\n\n");
+        assert_eq!(options.exclude_styles, ["TOC*", "Caption"]);
+        assert_eq!(options.bookmark.as_deref(), Some("Summary"));
+        assert_eq!(options.footnotes, NoteMode::End);
+        assert_eq!(options.tab_string, "\t");
+        assert_eq!(ExtractOptions::new().strip_hyperlinks(false).link_style, LinkStyle::Url);
+
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>First</w:t></w:r><w:r><w:tab/><w:t>tabbed</w:t></w:r></w:p>
+    <w:p><w:r><w:t>Second</w:t></w:r></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let options = ExtractOptions::new().paragraph_separator("\n\n").tab_string(" -> ");
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, "First -> tabbed\n\nSecond");
+
+        #[allow(deprecated)]
+        let extracted = extract_text_from_docx(&docx_path, true).unwrap();
+        assert_eq!(extracted, "First\ttabbed\nSecond");
+    }
+
+    #[test]
+    fn test_errors_name_the_file_and_cause() {
+        let temp_dir = tempdir().unwrap();
+        let missing = temp_dir.path().join("missing.docx");
+        let missing = missing.to_str().unwrap();
+        let error = extract_text_from_docx_with(missing, &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::Io { path, source } if path == missing && source.kind() == std::io::ErrorKind::NotFound
+        ));
+
+        let not_zip = temp_dir.path().join("notes.docx");
+        std::fs::write(&not_zip, "plain text, not a package").unwrap();
+        let error = extract_text_from_docx_with(not_zip.to_str().unwrap(), &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(&error, MergeError::Zip { path, .. } if path.ends_with("notes.docx")));
+
+        let xml_content = document_xml("<w:p><w:r><w:t>Unclosed</w:r></w:p>");
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let error = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap_err();
+        let MergeError::Xml { path, part, position, .. } = &error else {
+            panic!("expected an XML error, got {:?}", error);
+        };
+        assert_eq!(path, &docx_path);
+        assert_eq!(part.as_deref(), Some("word/document.xml"));
+        assert!(*position > xml_content.find("Unclosed").unwrap());
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{} (word/document.xml @ byte {}): Expecting </w:t> found </w:r>",
+                docx_path, position
+            )
+        );
+
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[("word/other.xml", "<x/>")]).unwrap();
+        let error = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(&error, MergeError::MissingPart { part, .. } if part == "word/document.xml"));
+    }
+
+    #[test]
+    fn test_extract_text_from_reader() {
+        let (_temp_dir, docx_path) = create_test_docx("From memory").unwrap();
+        let bytes = std::fs::read(&docx_path).unwrap();
+        let extracted = extract_text_from_reader(Cursor::new(bytes), &ExtractOptions::default()).unwrap();
+        assert_eq!(extracted, "From memory");
+
+        let bytes = b"not a package".to_vec();
+        let error = extract_text_from_reader_named(Cursor::new(bytes), "upload-17", &ExtractOptions::default())
+            .unwrap_err();
+        assert!(matches!(&error, MergeError::Zip { path, .. } if path == "upload-17"));
+        let error = extract_text_from_reader(Cursor::new(Vec::new()), &ExtractOptions::default()).unwrap_err();
+        assert!(error.to_string().starts_with("<reader> is damaged"));
+    }
+
+
+    /// Records how the text arrives at a writer.
+    #[derive(Default)]
+    struct RecordingWriter {
+        written: Vec<u8>,
+        writes: usize,
+        largest_write: usize,
+    }
+
+    impl Write for RecordingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            self.writes += 1;
+            self.largest_write = self.largest_write.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_extract_text_to_writer_streams_paragraphs() {
+        let paragraphs: String = (0..20_000)
+            .map(|n| format!("<w:p><w:r><w:t>Paragraph number {}</w:t></w:r></w:p><w:p/>", n))
+            .collect();
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&document_xml(&paragraphs)).unwrap();
+        let path = docx_path.as_str();
+        let options = ExtractOptions::new().blank_lines(BlankLines::Collapse);
+
+        let mut writer = RecordingWriter::default();
+        extract_text_to_writer(path, &mut writer, &options).unwrap();
+        let expected = extract_text_from_docx_with(path, &options).unwrap();
+        assert_eq!(String::from_utf8(writer.written).unwrap(), expected);
+        assert!(expected.len() > 400_000);
+        // The text arrives a paragraph at a time, never all at once.
+        assert!(writer.writes > 20_000);
+        assert!(writer.largest_write < 100);
+
+        let mut merged = Vec::new();
+        merge_docx_files_to_writer(&[path, path], &mut merged, &options.clone().into()).unwrap();
+        assert_eq!(
+            String::from_utf8(merged).unwrap(),
+            merge_docx_files_with(&[path, path], &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_paragraphs_stop_early() {
+        let body: String = (0..1000)
+            .map(|n| format!("<w:p><w:r><w:t>Paragraph {}</w:t></w:r></w:p>", n))
+            .collect();
+        // The body never ends properly, which only reading to the end finds.
+        let xml_content = document_xml(&format!(
+            r#"<w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Report</w:t></w:r></w:p><w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="4"/></w:numPr></w:pPr><w:r><w:t>Item</w:t></w:r></w:p>{}<w:p></w:body>"#,
+            body
+        ));
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let first: Vec<Paragraph> = paragraphs(&docx_path, &ExtractOptions::default())
+            .take(3)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            first,
+            [
+                Paragraph {
+                    text: "Report".to_string(),
+                    style: Some("Title".to_string()),
+                    list_level: None,
+                },
+                Paragraph {
+                    text: "Item".to_string(),
+                    style: None,
+                    list_level: Some(1),
+                },
+                Paragraph {
+                    text: "Paragraph 0".to_string(),
+                    style: None,
+                    list_level: None,
+                },
+            ]
+        );
+
+        let all: Vec<_> = paragraphs(&docx_path, &ExtractOptions::default()).collect();
+        assert_eq!(all.len(), 1003);
+        assert!(matches!(all.last(), Some(Err(MergeError::Xml { .. }))));
+    }
+
+    /// Counts the words of each paragraph style, and records the links,
+    /// the spans of cells and how sections start.
+    #[derive(Default)]
+    struct WordsPerStyle {
+        style: Option<String>,
+        words: HashMap<String, usize>,
+        links: Vec<String>,
+        cell_spans: Vec<usize>,
+        sections: Vec<Option<String>>,
+    }
+
+    impl DocxVisitor for WordsPerStyle {
+        fn paragraph_start(&mut self, paragraph: &ParagraphInfo) {
+            self.style = paragraph.style.clone();
+        }
+
+        fn text(&mut self, text: &str, _run: &RunInfo) {
+            let style = self.style.clone().unwrap_or_else(|| "Normal".to_string());
+            *self.words.entry(style).or_default() += text.split_whitespace().count();
+        }
+
+        fn hyperlink_start(&mut self, link: &LinkInfo) {
+            self.links.extend(link.target.clone());
+        }
+
+        fn cell_start(&mut self, cell: &CellInfo) {
+            self.cell_spans.push(cell.grid_span);
+        }
+
+        fn section_end(&mut self, section: &SectionInfo) {
+            self.sections.push(section.start.clone());
+        }
+    }
+
+    #[test]
+    fn test_extract_with_visitor() {
+        let xml_content = document_xml(
+            r#"<w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Annual report</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">See </w:t></w:r><w:hyperlink w:anchor="results"><w:r><w:t>the results</w:t></w:r></w:hyperlink></w:p>
+            <w:p><w:pPr><w:pStyle w:val="TOC1"/></w:pPr><w:r><w:t>Results 3</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:t>One cell</w:t></w:r></w:p></w:tc><w:tc><w:tcPr><w:gridSpan w:val="2"/></w:tcPr><w:p><w:r><w:del><w:t>gone</w:t></w:del></w:r></w:p></w:tc></w:tr></w:tbl>
+            <w:sectPr><w:type w:val="continuous"/></w:sectPr>"#,
+        );
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+
+        let mut visitor = WordsPerStyle::default();
+        let options = ExtractOptions::new().toc(TocMode::Skip);
+        extract_with_visitor(&docx_path, &mut visitor, &options).unwrap();
+        assert_eq!(
+            visitor.words,
+            HashMap::from([("Title".to_string(), 2), ("Normal".to_string(), 5)])
+        );
+        assert_eq!(visitor.links, ["#results"]);
+        assert_eq!(visitor.cell_spans, [1, 2]);
+        assert_eq!(visitor.sections, [Some("continuous".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_docx() {
+        let core = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Field notes</dc:title></cp:coreProperties>"#;
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style></w:styles>"#;
+        let item = |level: usize, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="{}"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                level, text
+            )
+        };
+        let xml_content = document_xml(&format!(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Birds</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Seen at </w:t></w:r><w:hyperlink w:anchor="lake"><w:r><w:t>the lake</w:t></w:r></w:hyperlink></w:p>
+            {}{}{}
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:t>Heron</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>2</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+            item(0, "Heron"),
+            item(0, "Grebe"),
+            item(1, "Little grebe")
+        ));
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("docProps/core.xml", core),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+
+        let document = parse_docx(&docx_path, &ExtractOptions::default()).unwrap();
+        let run = |text: &str, link: Option<&str>| Run {
+            text: text.to_string(),
+            link: link.map(str::to_string),
+            ..Run::default()
+        };
+        let paragraph = |runs: Vec<Run>| Block::Paragraph {
+            style: None,
+            heading_level: None,
+            runs,
+        };
+        assert_eq!(document.metadata.title.as_deref(), Some("Field notes"));
+        assert_eq!(
+            document.blocks,
+            [
+                Block::Paragraph {
+                    style: Some("Heading1".to_string()),
+                    heading_level: Some(1),
+                    runs: vec![run("Birds", None)],
+                },
+                paragraph(vec![run("Seen at ", None), run("the lake", Some("#lake"))]),
+                Block::List {
+                    level: 0,
+                    numbered: false,
+                    items: vec![vec![run("Heron", None)], vec![run("Grebe", None)]],
+                },
+                Block::List {
+                    level: 1,
+                    numbered: false,
+                    items: vec![vec![run("Little grebe", None)]],
+                },
+                Block::Table {
+                    rows: vec![vec![
+                        vec![paragraph(vec![run("Heron", None)])],
+                        vec![paragraph(vec![run("2", None)])],
+                    ]],
+                },
+            ]
+        );
+        assert_eq!(
+            document.to_plain_text(),
+            "Birds\nSeen at the lake\nHeron\nGrebe\n  Little grebe\nHeron\t2"
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&document).unwrap();
+            let read_back: Document = serde_json::from_str(&json).unwrap();
+            assert_eq!(read_back, document);
+        }
+    }
+
+    #[test]
+    fn test_paths_need_not_be_strings() {
+        let xml = document_xml("<w:p><w:r><w:t>Grüße</w:t></w:r></w:p>");
+        let (temp_dir, docx_path) = create_test_docx_with_xml(&xml).unwrap();
+        let directory = temp_dir.path().join("Grüße").join("数据");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("brief.docx");
+        std::fs::rename(&docx_path, &path).unwrap();
+
+        let options = ExtractOptions::default();
+        assert_eq!(extract_text_from_docx_with(&path, &options).unwrap(), "Grüße");
+        assert_eq!(merge_docx_files_with(&[&path, &path], &options).unwrap(), "Grüße\n\nGrüße");
+
+        let missing = directory.join("fehlt.docx");
+        let error = extract_text_from_file(&missing, &options).unwrap_err();
+        assert!(matches!(&error, MergeError::Io { path, .. } if *path == missing));
+        assert!(error.to_string().starts_with(&missing.display().to_string()));
+    }
+
+    #[test]
+    fn test_merge_options_separator_and_headers() {
+        let (_first_dir, first) = create_test_docx_with_xml(&document_xml("<w:p><w:r><w:t>One</w:t></w:r></w:p>")).unwrap();
+        let (_second_dir, second) = create_test_docx_with_xml(&document_xml("<w:p><w:r><w:t>Two</w:t></w:r></w:p>")).unwrap();
+        let options = MergeOptions::new().separator("\n---\n").file_header("==> {path} <==");
+
+        let mut merged = Vec::new();
+        merge_docx_files_to_writer(&[&first, &second], &mut merged, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(merged).unwrap(),
+            format!("==> {} <==\nOne\n---\n==> {} <==\nTwo", first, second)
+        );
+    }
+
+    #[test]
+    fn test_file_headers_and_footers_fill_in_placeholders() {
+        let core = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Field notes</dc:title></cp:coreProperties>"#;
+        let (_first_dir, first) = create_test_docx_with_parts(&[
+            ("word/document.xml", &document_xml("<w:p><w:r><w:t>One</w:t></w:r></w:p>")),
+            ("docProps/core.xml", core),
+        ])
+        .unwrap();
+        let (_second_dir, second) = create_test_docx_with_xml(&document_xml("<w:p><w:r><w:t>Two</w:t></w:r></w:p>")).unwrap();
+        let options = MergeOptions::new()
+            .file_header("===== {index}/{total}: {name} ({title}) =====")
+            .file_footer("----- end of {stem} -----");
+
+        for threads in [1, 2] {
+            let options = MergeOptions { threads, ..options.clone() };
+            let mut merged = Vec::new();
+            merge_docx_files_to_writer(&[&first, &second], &mut merged, &options).unwrap();
+            assert_eq!(
+                String::from_utf8(merged).unwrap(),
+                "===== 1/2: test.docx (Field notes) =====\nOne\n----- end of test -----\n\n\
+                 ===== 2/2: test.docx (test) =====\nTwo\n----- end of test -----"
+            );
+        }
+    }
+
+    #[test]
+    fn test_chapter_headings_nest_the_headings_of_each_file() {
+        let core = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Birds</dc:title></cp:coreProperties>"#;
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style></w:styles>"#;
+        let chapter = |heading: &str, text: &str| {
+            document_xml(&format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p><w:p><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                heading, text
+            ))
+        };
+        let first_xml = chapter("Herons", "Grey and tall.");
+        let (_first_dir, first) = create_test_docx_with_parts(&[
+            ("word/document.xml", &first_xml),
+            ("word/styles.xml", styles),
+            ("docProps/core.xml", core),
+        ])
+        .unwrap();
+        let second_dir = tempdir().unwrap();
+        let second = second_dir.path().join("field_notes-2024.docx");
+        let (_dir, untitled) = create_test_docx_with_parts(&[
+            ("word/document.xml", &chapter("Lakes", "Still water.")),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+        std::fs::copy(&untitled, &second).unwrap();
+
+        let options = MergeOptions::new()
+            .extract(ExtractOptions::new().format(OutputFormat::Markdown))
+            .chapter_headings(1);
+        let mut merged = Vec::new();
+        merge_docx_files_to_writer(&[PathBuf::from(&first), second], &mut merged, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(merged).unwrap(),
+            "# Birds\n\n## Herons\n\nGrey and tall.\n\n# Field Notes 2024\n\n## Lakes\n\nStill water."
+        );
+    }
+
+    #[test]
+    fn test_parallel_merge_keeps_input_order() {
+        let mut temp_dirs = Vec::new();
+        let mut paths = Vec::new();
+        for n in 0..12 {
+            // Files of different lengths finish extracting in a different
+            // order from the one they are given in.
+            let body: String = (0..(12 - n) * 200)
+                .map(|i| format!("<w:p><w:r><w:t>File {} paragraph {}</w:t></w:r></w:p>", n, i))
+                .collect();
+            let (temp_dir, path) = create_test_docx_with_xml(&document_xml(&body)).unwrap();
+            temp_dirs.push(temp_dir);
+            paths.push(path);
+        }
+
+        let serial = merge_docx_files_with(&paths, &ExtractOptions::default()).unwrap();
+        assert!(serial.starts_with("File 0 paragraph 0\n"));
+        assert!(serial.ends_with("File 11 paragraph 199"));
+        for threads in [0, 2, 3, 8] {
+            let mut merged = Vec::new();
+            let options = MergeOptions::new().threads(threads);
+            merge_docx_files_to_writer(&paths, &mut merged, &options).unwrap();
+            assert_eq!(String::from_utf8(merged).unwrap(), serial, "{} threads", threads);
+        }
+
+        // The first file to fail in input order is reported, whichever
+        // fails first.
+        let broken_dir = tempdir().unwrap();
+        let broken = broken_dir.path().join("broken.docx");
+        std::fs::write(&broken, "not a package").unwrap();
+        let missing = broken_dir.path().join("missing.docx");
+        let mut with_errors: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        with_errors.insert(9, missing.clone());
+        with_errors.insert(3, broken.clone());
+        for threads in [1, 4] {
+            let mut merged = Vec::new();
+            let options = MergeOptions::new().threads(threads);
+            let error = merge_docx_files_to_writer(&with_errors, &mut merged, &options).unwrap_err();
+            assert!(matches!(&error, MergeError::Zip { path, .. } if *path == broken), "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_skip_and_report_failed_files() {
+        let (temp_dir, good) = create_test_docx_with_xml(&document_xml("<w:p><w:r><w:t>Good</w:t></w:r></w:p>")).unwrap();
+        let bad = temp_dir.path().join("bad.docx");
+        std::fs::write(&bad, "not a package").unwrap();
+        let paths = [PathBuf::from(&good), bad.clone(), PathBuf::from(&good)];
+
+        let error = merge_files_with_options(&paths, &MergeOptions::new()).unwrap_err();
+        assert!(matches!(&error, MergeError::Zip { path, .. } if *path == bad));
+
+        for threads in [1, 2] {
+            let options = MergeOptions::new().on_error(ErrorPolicy::SkipAndReport).threads(threads);
+            let merged = merge_files_with_options(&paths, &options).unwrap();
+            assert_eq!(merged.text, "Good\n\nGood");
+            assert_eq!(merged.skipped.len(), 1);
+            assert_eq!(merged.skipped[0].path, bad);
+            assert!(matches!(merged.skipped[0].error, MergeError::Zip { .. }));
+
+            let merged = merge_files_with_options(&paths, &options.error_markers(true)).unwrap();
+            assert_eq!(
+                merged.text,
+                format!("Good\n\n[failed to extract: {}]\n\nGood", merged.skipped[0].error)
+            );
+            assert!(merged.text.contains(&format!("{} is damaged", bad.display())));
+        }
+    }
+
+    #[test]
+    fn test_extract_metadata() {
+        let core = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/"><dc:title>Quarterly figures</dc:title><dc:subject>Finance</dc:subject><dc:creator>A. Writer</dc:creator><cp:lastModifiedBy>An Editor</cp:lastModifiedBy><cp:revision>12</cp:revision><dcterms:created>2024-01-02T08:00:00Z</dcterms:created><dcterms:modified>2024-03-04T17:30:00Z</dcterms:modified></cp:coreProperties>"#;
+        let app = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"><Template>Normal.dotm</Template><Pages>3</Pages><Words>742</Words><Characters>4231</Characters><Application>Microsoft Office Word</Application></Properties>"#;
+        let document = document_xml("<w:p><w:r><w:t>Figures</w:t></w:r></w:p>");
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", document.as_str()),
+            ("docProps/core.xml", core),
+            ("docProps/app.xml", app),
+        ])
+        .unwrap();
+        let metadata = extract_metadata(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            metadata,
+            Metadata {
+                title: Some("Quarterly figures".to_string()),
+                subject: Some("Finance".to_string()),
+                author: Some("A. Writer".to_string()),
+                last_modified_by: Some("An Editor".to_string()),
+                created: Some("2024-01-02T08:00:00Z".to_string()),
+                modified: Some("2024-03-04T17:30:00Z".to_string()),
+                revision: Some(12),
+                application: Some("Microsoft Office Word".to_string()),
+                pages: Some(3),
+                words: Some(742),
+                characters: Some(4231),
+                custom_properties: Vec::new(),
+            }
+        );
+        assert_eq!(metadata.fields()[0], ("title", "Quarterly figures".to_string()));
+
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&document).unwrap();
+        let metadata = extract_metadata(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(metadata, Metadata::default());
+        assert!(metadata.fields().is_empty());
+    }
+
+    #[test]
+    fn test_extract_custom_properties() {
+        let custom = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Client"><vt:lpwstr>Acme &amp; Sons</vt:lpwstr></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="3" name="Contract number"><vt:i4>1042</vt:i4></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="4" name="Signed"><vt:bool>false</vt:bool></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="5" name="Rate"><vt:decimal>1.25</vt:decimal></property></Properties>"#;
+        let rels = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties" Target="docProps/stamped.xml"/></Relationships>"#;
+        let document = document_xml("<w:p><w:r><w:t>Contract</w:t></w:r></w:p>");
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("_rels/.rels", rels),
+            ("word/document.xml", document.as_str()),
+            ("docProps/stamped.xml", custom),
+        ])
+        .unwrap();
+        let expected = vec![
+            ("Client".to_string(), PropertyValue::Text("Acme & Sons".to_string())),
+            ("Contract number".to_string(), PropertyValue::Integer(1042)),
+            ("Signed".to_string(), PropertyValue::Bool(false)),
+            ("Rate".to_string(), PropertyValue::Other("1.25".to_string())),
+        ];
+        let options = ExtractOptions::default();
+        assert_eq!(extract_custom_properties(&docx_path, &options).unwrap(), expected);
+        assert_eq!(extract_metadata(&docx_path, &options).unwrap().custom_properties, expected);
+
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&document).unwrap();
+        assert!(extract_custom_properties(&docx_path, &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_hyperlinks() {
+        let xml_content = document_xml(r#"
+    <w:p>
+      <w:hyperlink r:id="rId5"><w:r><w:t>the site</w:t></w:r></w:hyperlink>
+      <w:r><w:t xml:space="preserve"> and </w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="begin"/></w:r>
+      <w:r><w:instrText xml:space="preserve"> HYPERLINK "mailto:team@example.com" </w:instrText></w:r>
+      <w:r><w:fldChar w:fldCharType="separate"/></w:r>
+      <w:r><w:t>write to us</w:t></w:r>
+      <w:r><w:fldChar w:fldCharType="end"/></w:r>
+    </w:p>
+    <w:p>
+      <w:hyperlink w:anchor="Summary"><w:r><w:t>Summary</w:t></w:r></w:hyperlink>
+      <w:hyperlink r:id="rId5"><w:r><w:t>the site</w:t></w:r></w:hyperlink>
+      <w:hyperlink r:id="rId5"><w:r><w:t>our home page</w:t></w:r></w:hyperlink>
+      <w:hyperlink r:id="rId99"><w:r><w:t>Dangling</w:t></w:r></w:hyperlink>
+    </w:p>"#);
+        let rels = rels_xml(r#"<Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/" TargetMode="External"/>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+        ])
+        .unwrap();
+
+        let link = |text: &str, target: &str, kind| Hyperlink {
+            text: text.to_string(),
+            target: target.to_string(),
+            kind,
+        };
+        assert_eq!(
+            extract_hyperlinks(&docx_path, &ExtractOptions::default()).unwrap(),
+            [
+                link("the site", "https://example.com/", LinkKind::External),
+                link("write to us", "mailto:team@example.com", LinkKind::Mailto),
+                link("Summary", "#Summary", LinkKind::Internal),
+                link("our home page", "https://example.com/", LinkKind::External),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_media() {
+        let document = document_xml(r#"<w:p><w:r><w:drawing><wp:inline><wp:docPr id="1" name="Picture 1" descr="Company logo"/><a:graphic><a:graphicData><pic:pic><pic:blipFill><a:blip r:embed="rId7"/></pic:blipFill></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#);
+        let rels = rels_xml(r#"<Relationship Id="rId7" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.png"/>"#);
+        let png: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0\x1f\x15\xc4\x89";
+        let temp_dir = tempdir().unwrap();
+        let docx_path = temp_dir.path().join("report.docx");
+        let mut zip = zip::ZipWriter::new(File::create(&docx_path).unwrap());
+        let entries: [(&str, &[u8]); 4] = [
+            ("word/document.xml", document.as_bytes()),
+            ("word/_rels/document.xml.rels", rels.as_bytes()),
+            ("word/media/image1.png", png),
+            ("word/media/../../escaped.png", png),
+        ];
+        for (name, content) in entries {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let media_dir = temp_dir.path().join("out").join("media");
+        let options = ExtractOptions::new().format(OutputFormat::Markdown).media_dir(&media_dir);
+        let written = extract_media(&docx_path, &media_dir, &options).unwrap();
+        let image = media_dir.join("report-image1.png");
+        assert_eq!(written, std::slice::from_ref(&image));
+        assert_eq!(std::fs::read(&image).unwrap(), png);
+        assert!(!temp_dir.path().join("escaped.png").exists());
+        assert!(!temp_dir.path().join("out").join("escaped.png").exists());
+
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, format!("![Company logo]({})", image.display()));
+
+        // The limits on inflating apply to media too.
+        let error = extract_media(&docx_path, &media_dir, &options.max_part_size(8)).unwrap_err();
+        assert!(matches!(error, MergeError::PartTooLarge { .. }), "{:?}", error);
+    }
+
+    #[test]
+    fn test_embedded_objects() {
+        let document = document_xml(r##"<w:p><w:r><w:t xml:space="preserve">Budget: </w:t></w:r><w:r><w:object w:dxaOrig="1520" w:dyaOrig="960"><v:shape id="_x0000_i1025" type="#_x0000_t75"><v:imagedata r:id="rId4" o:title=""/></v:shape><o:OLEObject Type="Embed" ProgID="Excel.Sheet.12" ShapeID="_x0000_i1025" DrawAspect="Icon" ObjectID="_1700000000" r:id="rId5"/></w:object></w:r></w:p>"##);
+        let rels = rels_xml(r#"<Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.emf"/><Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/package" Target="embeddings/budget.xlsx"/>"#);
+        let content_types = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="xml" ContentType="application/xml"/><Default Extension="xlsx" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+        let blob = [0x50, 0x4b, 0x03, 0x04, 0, 1, 2, 3, 4, 5, 6, 7];
+        let blob = String::from_utf8_lossy(&blob).into_owned();
+        let (temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("[Content_Types].xml", content_types),
+            ("word/document.xml", &document),
+            ("word/_rels/document.xml.rels", &rels),
+            ("word/embeddings/budget.xlsx", &blob),
+        ])
+        .unwrap();
+
+        let options = ExtractOptions::default();
+        assert_eq!(
+            extract_text_from_docx_with(&docx_path, &options).unwrap(),
+            "Budget: [embedded object: budget.xlsx]"
+        );
+        assert_eq!(
+            extract_text_from_docx_with(&docx_path, &options.clone().object_placeholders(false)).unwrap(),
+            "Budget:"
+        );
+        assert_eq!(
+            list_embedded_objects(&docx_path, &options).unwrap(),
+            [EmbeddedObject {
+                name: "budget.xlsx".to_string(),
+                content_type: Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string()),
+                size: blob.len() as u64,
+            }]
+        );
+        let parts = list_parts(&docx_path, &options).unwrap();
+        let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["[Content_Types].xml", "word/document.xml", "word/_rels/document.xml.rels", "word/embeddings/budget.xlsx"]
+        );
+        assert_eq!(
+            parts[1].content_type.as_deref(),
+            Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml")
+        );
+        assert_eq!(parts[1].size, document.len() as u64);
+
+        let out_dir = temp_dir.path().join("objects");
+        let written = extract_embedded_objects(&docx_path, &out_dir, &options).unwrap();
+        assert_eq!(written, [out_dir.join("test-budget.xlsx")]);
+        assert_eq!(std::fs::read_to_string(&written[0]).unwrap(), blob);
+    }
+
+    #[test]
+    fn test_extract_stats() {
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style></w:styles>"#;
+        let xml_content = document_xml(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Field notes</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Two herons, one egret. </w:t></w:r><w:r><w:rPr><w:vanish/></w:rPr><w:t>Hidden words</w:t></w:r></w:p>
+            <w:p/>
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:t>鳥の観察</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+        );
+        let (_temp_dir, path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/styles.xml", styles),
+        ])
+        .unwrap();
+        let expected = TextStats {
+            words: 2 + 4 + 4,
+            characters: 11 + 23 + 4,
+            characters_no_spaces: 10 + 19 + 4,
+            paragraphs: 3,
+            headings: 1,
+            pages: 1,
+        };
+        assert_eq!(extract_stats(&path, &ExtractOptions::default()).unwrap(), expected);
+
+        // The counts are of the text the options keep, and the same however
+        // the files are merged.
+        let options = ExtractOptions::new().include_hidden(true);
+        assert_eq!(extract_stats(&path, &options).unwrap().words, 12);
+        for threads in [1, 2] {
+            for on_error in [ErrorPolicy::FailFast, ErrorPolicy::SkipAndReport] {
+                let options = MergeOptions::new().threads(threads).on_error(on_error).stats(true);
+                let merged = merge_files_with_options(&[&path, &path], &options).unwrap();
+                assert_eq!(merged.stats.len(), 2);
+                assert_eq!(merged.stats[1].path, PathBuf::from(&path));
+                assert_eq!(merged.stats[1].stats, expected);
+            }
+        }
+        let merged = merge_files_with_options(&[&path], &MergeOptions::new()).unwrap();
+        assert!(merged.stats.is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_extraction_stops() {
+        let body = "<w:p><w:r><w:t>Paragraph of a long document</w:t></w:r></w:p>".repeat(200_000);
+        let (_temp_dir, path) = create_test_docx_with_xml(&document_xml(&body)).unwrap();
+        let token = CancellationToken::new();
+        let options = ExtractOptions::new().cancellation(token.clone());
+        let extraction = {
+            let path = path.clone();
+            std::thread::spawn(move || extract_text_from_docx_with(path, &options))
+        };
+        token.cancel();
+        let start = std::time::Instant::now();
+        let result = extraction.join().unwrap();
+        assert!(matches!(result, Err(MergeError::Cancelled)), "{:?}", result.map(|text| text.len()));
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+
+        // A cancelled merge stops even when it skips files that fail.
+        for threads in [1, 2] {
+            let options = MergeOptions::new()
+                .threads(threads)
+                .on_error(ErrorPolicy::SkipAndReport)
+                .cancellation(token.clone());
+            let error = merge_files_with_options(&[&path, &path], &options).unwrap_err();
+            assert!(matches!(error, MergeError::Cancelled));
+        }
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let (_temp_dir, good) = create_test_docx_with_xml(&document_xml(
+            "<w:p><w:r><w:t>Good</w:t></w:r></w:p>",
+        ))
+        .unwrap();
+        let bad_dir = tempdir().unwrap();
+        let bad = bad_dir.path().join("bad.docx");
+        std::fs::write(&bad, "not a package").unwrap();
+        let paths = [PathBuf::from(&good), bad, PathBuf::from(&good)];
+
+        let merge = |options: MergeOptions| {
+            let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let recorded = events.clone();
+            let options = options.on_progress(move |event| {
+                recorded.lock().unwrap().push(match event {
+                    ProgressEvent::FileStarted { index, total, .. } => {
+                        format!("started {}/{}", index, total)
+                    }
+                    ProgressEvent::FileFinished {
+                        index,
+                        bytes_extracted,
+                    } => format!("finished {} ({} bytes)", index, bytes_extracted),
+                    ProgressEvent::FileFailed { index, .. } => format!("failed {}", index),
+                });
+            });
+            let result = merge_files_with_options(&paths, &options);
+            let events = events.lock().unwrap().clone();
+            (result, events)
+        };
+
+        let skipping = MergeOptions::new().on_error(ErrorPolicy::SkipAndReport);
+        let (result, events) = merge(skipping.clone());
+        assert_eq!(result.unwrap().text, "Good\n\nGood");
+        assert_eq!(
+            events,
+            [
+                "started 0/3",
+                "finished 0 (4 bytes)",
+                "started 1/3",
+                "failed 1",
+                "started 2/3",
+                "finished 2 (4 bytes)",
+            ]
+        );
+
+        let (result, events) = merge(MergeOptions::new());
+        assert!(result.is_err());
+        assert_eq!(events, ["started 0/3", "finished 0 (4 bytes)", "started 1/3", "failed 1"]);
+
+        // Extracted at once, the files start together and end in order.
+        let (_, events) = merge(skipping.threads(2));
+        assert_eq!(
+            events,
+            [
+                "started 0/3",
+                "started 1/3",
+                "started 2/3",
+                "finished 0 (4 bytes)",
+                "failed 1",
+                "finished 2 (4 bytes)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extractor_shares_parsed_styles() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Extractor>();
+
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style></w:styles>"#;
+        let document = |title: &str| {
+            document_xml(&format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p><w:p><w:r><w:t>Body</w:t></w:r></w:p>"#,
+                title
+            ))
+        };
+        let first = document("First");
+        let second = document("Second");
+        let (_first_dir, first) =
+            create_test_docx_with_parts(&[("word/document.xml", &first), ("word/styles.xml", styles)])
+                .unwrap();
+        let (_second_dir, second) =
+            create_test_docx_with_parts(&[("word/document.xml", &second), ("word/styles.xml", styles)])
+                .unwrap();
+
+        let options = ExtractOptions::new().format(OutputFormat::Markdown);
+        let extractor = Extractor::new(options.clone());
+        for path in [&first, &second, &first] {
+            assert_eq!(
+                extractor.extract(path).unwrap(),
+                extract_text_from_file(path, &options).unwrap()
+            );
+        }
+        assert_eq!(extractor.extract(&second).unwrap(), "# Second\n\nBody");
+        assert_eq!(extractor.cached_styles(), 1);
+    }
+
+    #[test]
+    fn test_text_cache_skips_unchanged_files() {
+        let (_temp_dir, path) = create_test_docx_with_xml(&document_xml(
+            "<w:p><w:r><w:t>Original</w:t></w:r></w:p>",
+        ))
+        .unwrap();
+        let cache_dir = tempdir().unwrap();
+        let options = MergeOptions::new().cache(DirCache::new(cache_dir.path()));
+        let paths = [PathBuf::from(&path)];
+        assert_eq!(merge_files_with_options(&paths, &options).unwrap().text, "Original");
+
+        // Text changed in the cache is what a second merge writes, so the
+        // file was not extracted again.
+        let entries: Vec<PathBuf> = std::fs::read_dir(cache_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        std::fs::write(&entries[0], "Cached").unwrap();
+        assert_eq!(merge_files_with_options(&paths, &options).unwrap().text, "Cached");
+
+        // Other options, or other content, need another entry.
+        let mut other = options.clone();
+        other.extract.mark_headings = true;
+        assert_eq!(merge_files_with_options(&paths, &other).unwrap().text, "Original");
+        let (_other_dir, changed) = create_test_docx_with_xml(&document_xml(
+            "<w:p><w:r><w:t>Changed</w:t></w:r></w:p>",
+        ))
+        .unwrap();
+        std::fs::copy(&changed, &path).unwrap();
+        assert_eq!(merge_files_with_options(&paths, &options).unwrap().text, "Changed");
+
+        DirCache::new(cache_dir.path()).clear().unwrap();
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_warnings_for_equations_and_unmapped_symbols() {
+        let xml_content = document_xml(r#"
+    <w:p><w:r><w:t>Results</w:t></w:r></w:p>
+    <w:p>
+      <w:r><w:sym w:font="Wingdings" w:char="F0FC"/><w:t xml:space="preserve"> Passed </w:t></w:r>
+      <w:r><w:sym w:font="Wingdings" w:char="F0A0"/></w:r>
+    </w:p>
+    <w:p>
+      <w:r><w:t xml:space="preserve">Area: </w:t></w:r>
+      <m:oMath><m:r><m:t>A=πr</m:t></m:r></m:oMath>
+    </w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(&xml_content).unwrap();
+        let options = ExtractOptions::default().equations(EquationMode::Skip);
+        let (text, warnings) = extract_text_with_warnings(&docx_path, &options).unwrap();
+        assert_eq!(text, "Results\n✓ Passed [symbol]\nArea: [equation]");
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::UnmappedSymbol {
+                    path: PathBuf::from(&docx_path),
+                    paragraph: 1,
+                    font: "Wingdings".to_string(),
+                    code: 0xF0A0,
+                },
+                Warning::EquationSkipped {
+                    path: PathBuf::from(&docx_path),
+                    paragraph: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            warnings[1].to_string(),
+            format!("{}: paragraph 3: equation left out", docx_path)
+        );
+
+        // Equations written out are no cause for a warning.
+        let (_, warnings) = extract_text_with_warnings(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_truncated_part_error_names_file_part_and_byte() {
+        let xml_content = document_xml("<w:p><w:r><w:t>First</w:t></w:r></w:p><w:p><w:r><w:t>Second</w:t></w:r></w:p>");
+        let truncated = &xml_content[..xml_content.find("<w:p><w:r><w:t>Second").unwrap()];
+        let (_temp_dir, docx_path) = create_test_docx_with_xml(truncated).unwrap();
+        let error = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap_err();
+        assert!(matches!(
+            &error,
+            MergeError::Xml { part: Some(part), position, .. }
+                if part == "word/document.xml" && *position == truncated.len()
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{} (word/document.xml @ byte {}): Unexpected EOF during reading w:body",
+                docx_path,
+                truncated.len()
+            )
+        );
+    }
+
+    #[test]
+    fn test_overlapping_patterns_match_each_file_once() {
+        let temp_dir = tempdir().unwrap();
+        let docs = temp_dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        for name in ["report-b.docx", "notes.docx", "report-a.docx", "agenda.docx"] {
+            std::fs::write(docs.join(name), "").unwrap();
+        }
+        let docs = docs.to_str().unwrap();
+        let all = format!("{}/*.docx", docs);
+        let reports = format!("{}/report*.docx", docs);
+        // The same file again, by another path.
+        let notes = format!("{}/../docs/notes.docx", docs);
+        let names = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let expanded = expand_patterns(&[reports.as_str(), &all, &notes, docs]).unwrap();
+        assert_eq!(names(expanded), ["report-a.docx", "report-b.docx", "agenda.docx", "notes.docx"]);
+
+        let expanded = expand_patterns_with(
+            &[&reports, &notes, &all],
+            &ExpandOptions::default().sort(SortOrder::Name).keep_duplicates(true),
+        )
+        .unwrap();
+        assert_eq!(
+            names(expanded),
+            [
+                "report-a.docx",
+                "report-b.docx",
+                "notes.docx",
+                "agenda.docx",
+                "notes.docx",
+                "report-a.docx",
+                "report-b.docx",
+            ]
+        );
+    }
+
+    // Sorts a link to nothing, which has no time to read.
+    #[cfg(unix)]
+    #[test]
+    fn test_files_sorted_by_modification_time() {
+        let temp_dir = tempdir().unwrap();
+        let epoch = std::time::UNIX_EPOCH;
+        let days = |n: u64| epoch + std::time::Duration::from_secs(n * 86_400);
+        for (name, modified) in [("a.docx", days(3)), ("b.docx", days(1)), ("c.docx", days(2))] {
+            let file = File::create(temp_dir.path().join(name)).unwrap();
+            file.set_modified(modified).unwrap();
+        }
+        std::os::unix::fs::symlink("nowhere", temp_dir.path().join("missing.docx")).unwrap();
+        let all = format!("{}/*.docx", temp_dir.path().display());
+        let names = |options: ExpandOptions| -> Vec<String> {
+            expand_patterns_with(&[&all], &options)
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let by_time = ExpandOptions::default().sort(SortOrder::Modified);
+        // A file whose time cannot be read comes last, either way.
+        assert_eq!(names(by_time.clone()), ["b.docx", "c.docx", "a.docx", "missing.docx"]);
+        assert_eq!(
+            names(by_time.reverse(true)),
+            ["a.docx", "c.docx", "b.docx", "missing.docx"]
+        );
+        assert_eq!(
+            names(ExpandOptions::default().sort(SortOrder::None).reverse(true)),
+            ["missing.docx", "c.docx", "b.docx", "a.docx"]
+        );
+    }
+
+    #[test]
+    fn test_recursive_directory_expansion() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("book");
+        for directory in ["part1/drafts", "part2", ".git"] {
+            std::fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        for file in [
+            "preface.docx",
+            "part1/chapter1.docx",
+            "part1/drafts/chapter1-old.dotx",
+            "part2/chapter2.docm",
+            "part2/notes.pdf",
+            ".git/stray.docx",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        // A link back up must not be followed round.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("part2/loop")).unwrap();
+        let directory = root.to_str().unwrap().to_string();
+        let relative = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| {
+                    let path = path.strip_prefix(&root).unwrap();
+                    path.to_string_lossy().replace('\\', "/")
+                })
+                .collect()
+        };
+
+        let expanded = expand_patterns(&[&directory]).unwrap();
+        assert_eq!(relative(expanded), ["preface.docx"]);
+
+        let options = ExpandOptions::default().recursive(true);
+        let expanded = expand_patterns_with(&[&directory], &options).unwrap();
+        assert_eq!(
+            relative(expanded),
+            [
+                "part1/chapter1.docx",
+                "part1/drafts/chapter1-old.dotx",
+                "part2/chapter2.docm",
+                "preface.docx",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_excluded_files_and_lockfiles_are_left_out() {
+        let temp_dir = tempdir().unwrap();
+        let reports = temp_dir.path().join("reports");
+        std::fs::create_dir_all(reports.join("2024/drafts")).unwrap();
+        for file in [
+            "2024/q1.docx",
+            "2024/~$q1.docx",
+            "2024/q2~old.docx",
+            "2024/drafts/q3.docx",
+        ] {
+            std::fs::write(reports.join(file), "").unwrap();
+        }
+        let all = format!("{}/**/*.docx", reports.display());
+        let names = |options: &ExpandOptions| -> Vec<String> {
+            expand_patterns_with(&[&all], options)
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        // drafts/q3.docx sorts before q1.docx, by its directory.
+        assert_eq!(names(&ExpandOptions::default()), ["q3.docx", "q1.docx", "q2~old.docx"]);
+        assert_eq!(
+            names(&ExpandOptions::default().include_lockfiles(true)),
+            ["q3.docx", "q1.docx", "q2~old.docx", "~$q1.docx"]
+        );
+        let excluding = ExpandOptions::default()
+            .exclude("**/drafts/**")
+            .exclude("*~*.docx")
+            .exclude("**/archive/**");
+        assert_eq!(names(&excluding), ["q1.docx"]);
+
+        let error = expand_patterns_with(&[&all], &ExpandOptions::default().exclude("[")).unwrap_err();
+        assert!(matches!(error, MergeError::Pattern { .. }));
+    }
+
+    #[test]
+    fn test_file_list_keeps_its_order() {
+        let temp_dir = tempdir().unwrap();
+        let book = temp_dir.path().join("book");
+        std::fs::create_dir(&book).unwrap();
+        for name in ["chapter1.docx", "chapter2.docx", "chapter10.docx"] {
+            std::fs::write(book.join(name), "").unwrap();
+        }
+        let list = book.join("chapters.txt");
+        std::fs::write(
+            &list,
+            "# The book, in order\nchapter10.docx\n\n  chapter2.docx\r\n# chapter3.docx\nchapter1.docx\n",
+        )
+        .unwrap();
+
+        let listed = read_file_list(&list, false).unwrap();
+        assert_eq!(
+            listed,
+            [
+                book.join("chapter10.docx"),
+                book.join("chapter2.docx"),
+                book.join("chapter1.docx"),
+            ]
+        );
+
+        // Listed files come first, unsorted, and patterns add only the rest.
+        std::fs::write(book.join("appendix.docx"), "").unwrap();
+        let all = format!("{}/*.docx", book.display());
+        let options = ExpandOptions {
+            listed: listed.clone(),
+            ..ExpandOptions::default()
+        };
+        let expanded = expand_patterns_with(&[&all], &options).unwrap();
+        assert_eq!(expanded[..3], listed[..]);
+        assert_eq!(expanded[3..], [book.join("appendix.docx")]);
+
+        std::fs::write(&list, "chapter1.docx\n# gone\nchapter4.docx\n").unwrap();
+        let error = read_file_list(&list, false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}, line 3: {} does not exist",
+                list.display(),
+                book.join("chapter4.docx").display()
+            )
+        );
+        assert_eq!(read_file_list(&list, true).unwrap(), [book.join("chapter1.docx")]);
+    }
+
+    #[test]
+    fn test_table_of_contents_lines_point_at_their_entries() {
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style><w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/></w:style></w:styles>"#;
+        let heading = |style: &str, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                style, text
+            )
+        };
+        let paragraph = |text: &str| format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", text);
+        let first_xml = document_xml(&format!(
+            "{}{}<w:p/>{}{}",
+            heading("Heading1", "Birds"),
+            paragraph("Herons."),
+            heading("Heading2", "Notes"),
+            paragraph("Grey and tall.")
+        ));
+        let second_xml = document_xml(&format!(
+            "{}{}{}",
+            paragraph("Preface."),
+            heading("Heading1", "Notes"),
+            paragraph("Still water.")
+        ));
+        let (_first_dir, first) =
+            create_test_docx_with_parts(&[("word/document.xml", &first_xml), ("word/styles.xml", styles)])
+                .unwrap();
+        let (_second_dir, second) =
+            create_test_docx_with_parts(&[("word/document.xml", &second_xml), ("word/styles.xml", styles)])
+                .unwrap();
+        let paths = [first, second];
+
+        for (extract, headings) in [
+            (ExtractOptions::new().format(OutputFormat::Markdown), 3),
+            (ExtractOptions::new().mark_headings(true), 3),
+            (ExtractOptions::new(), 0),
+        ] {
+            let options = MergeOptions::new()
+                .extract(extract.clone())
+                .file_header("== {index} ==");
+            let merged = merge_files_with_options(&paths, &options).unwrap();
+            assert_eq!(merged.file_starts.len(), 2);
+            let text = with_table_of_contents(&merged.text, &merged.file_starts, &extract);
+            assert!(text.ends_with(&merged.text));
+            let lines: Vec<&str> = text.lines().collect();
+            let entries: Vec<(&str, usize)> = lines
+                .iter()
+                .filter_map(|line| {
+                    let (entry, number) = line.rsplit_once(", line ")?;
+                    Some((entry.trim_start(), number.parse().ok()?))
+                })
+                .collect();
+            assert_eq!(entries.len(), 2 + headings, "{}", text);
+            let mut files = 0;
+            for (entry, number) in entries {
+                let target = lines[number - 1];
+                if entry.contains("test") {
+                    files += 1;
+                    assert_eq!(target, format!("== {} ==", files), "{}", text);
+                } else {
+                    let title = entry.trim_start_matches("- [").split(']').next().unwrap();
+                    assert!(target.ends_with(&format!(" {}", title)), "{} at {}", entry, target);
+                }
+            }
+        }
+
+        let markdown = ExtractOptions::new().format(OutputFormat::Markdown);
+        let merged = merge_files_with_options(&paths, &MergeOptions::new().extract(markdown.clone())).unwrap();
+        let text = with_table_of_contents(&merged.text, &merged.file_starts, &markdown);
+        let path = |path: &str| template::escape_markdown(path);
+        assert_eq!(
+            text.lines().take(9).collect::<Vec<_>>(),
+            [
+                "**Contents**".to_string(),
+                String::new(),
+                format!("- {}, line 9", path(&paths[0])),
+                "  - [Birds](#birds), line 9".to_string(),
+                "    - [Notes](#notes), line 15".to_string(),
+                format!("- {}, line 19", path(&paths[1])),
+                "  - [Notes](#notes-1), line 21".to_string(),
+                String::new(),
+                "# Birds".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_output_round_trips() {
+        let core = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Field notes</dc:title><dc:creator>Ada</dc:creator></cp:coreProperties>"#;
+        let body = document_xml(
+            r#"<w:p><w:r><w:t>Herons wade.</w:t></w:r></w:p><w:p><w:r><w:sym w:font="Wingdings" w:char="F0FF"/></w:r></w:p>"#,
+        );
+        let (_dir, docx) =
+            create_test_docx_with_parts(&[("word/document.xml", &body), ("docProps/core.xml", core)]).unwrap();
+        let text_dir = tempdir().unwrap();
+        let text_file = text_dir.path().join("plain_notes.txt");
+        std::fs::write(&text_file, "Two words.").unwrap();
+
+        let options = MergeOptions::new().file_header("== {name} ==");
+        let output = merge_files_to_json(&[PathBuf::from(&docx), text_file.clone()], &options).unwrap();
+        let mut json = Vec::new();
+        output.write_to(&mut json, true).unwrap();
+        let read_back: JsonOutput = serde_json::from_slice(&json).unwrap();
+        assert_eq!(read_back, output);
+
+        assert_eq!(read_back.tool, "mddocmrg");
+        assert_eq!(read_back.version, env!("CARGO_PKG_VERSION"));
+        assert!(read_back.generated.ends_with('Z'));
+        assert!(read_back.skipped.is_empty());
+        let [first, second] = &read_back.files[..] else {
+            panic!("{:?}", read_back.files);
+        };
+        assert_eq!(first.path, Path::new(&docx));
+        assert_eq!(first.title, "Field notes");
+        assert_eq!(first.metadata.as_ref().unwrap().author.as_deref(), Some("Ada"));
+        assert_eq!(first.text, "== test.docx ==\nHerons wade.\n[symbol]");
+        // The counts are of the text extracted, placeholder included.
+        assert_eq!(first.stats.words, 3);
+        assert!(matches!(first.warnings[..], [Warning::UnmappedSymbol { .. }]));
+        assert_eq!(second.path, text_file);
+        assert_eq!(second.title, "plain_notes");
+        assert_eq!(second.metadata, None);
+        assert_eq!(second.text, "== plain_notes.txt ==\nTwo words.");
+        assert_eq!(second.stats.paragraphs, 1);
+    }
+
+    #[test]
+    fn test_json_output_lists_skipped_files() {
+        let dir = tempdir().unwrap();
+        let damaged = dir.path().join("damaged.docx");
+        std::fs::write(&damaged, "not a zip").unwrap();
+        let options = MergeOptions::new().on_error(ErrorPolicy::SkipAndReport);
+        let mut json = Vec::new();
+        let report = merge_files_to_json_writer(&[&damaged], &mut json, &options, false).unwrap();
+        assert_eq!(report.skipped.len(), 1);
+        let output: JsonOutput = serde_json::from_slice(&json).unwrap();
+        assert!(output.files.is_empty());
+        assert_eq!(output.skipped[0].path, damaged);
+        assert_eq!(output.skipped[0].error, report.skipped[0].error.to_string());
+        assert!(!String::from_utf8(json).unwrap().contains('\n'));
+    }
+
+    #[test]
+    fn test_json_lines_are_written_whole_in_input_order() {
+        /// Keeps what is written, and checks that each flush ends a line.
+        struct Lines(Vec<u8>, usize);
+
+        impl Write for Lines {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                assert_eq!(self.0.last(), Some(&b'\n'));
+                self.1 += 1;
+                Ok(())
+            }
+        }
+
+        let dir = tempdir().unwrap();
+        let mut paths = Vec::new();
+        for n in 0..10 {
+            // Longer files first, so that later ones finish first.
+            let path = dir.path().join(format!("file{}.txt", n));
+            std::fs::write(&path, format!("File {}.\n", n).repeat((10 - n) * 500)).unwrap();
+            paths.push(path);
+        }
+        for threads in [1, 3] {
+            let mut lines = Lines(Vec::new(), 0);
+            let options = MergeOptions::new().threads(threads);
+            let report = merge_files_to_json_lines(&paths, &mut lines, &options).unwrap();
+            assert!(report.stats.is_empty());
+            assert_eq!(lines.1, paths.len());
+            let text = String::from_utf8(lines.0).unwrap();
+            let files: Vec<JsonFile> = text
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            assert_eq!(files.len(), paths.len());
+            for (n, file) in files.iter().enumerate() {
+                assert_eq!(file.path, paths[n], "{} threads", threads);
+                assert_eq!(file.title, format!("file{}", n));
+                assert_eq!(file.stats.paragraphs, ((10 - n) * 500) as u64);
+            }
+        }
+
+        // A merge that fails leaves whole lines for the files before.
+        let broken = dir.path().join("broken.docx");
+        std::fs::write(&broken, "not a package").unwrap();
+        let with_broken = [paths[8].clone(), paths[9].clone(), broken];
+        let mut written = Vec::new();
+        let error = merge_files_to_json_lines(&with_broken, &mut written, &MergeOptions::new()).unwrap_err();
+        assert!(matches!(error, MergeError::Zip { .. }), "{:?}", error);
+        let text = String::from_utf8(written).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().all(|line| serde_json::from_str::<JsonFile>(line).is_ok()));
+    }
+
+    #[test]
+    fn test_markdown_output_matches_golden_file() {
+        let styles = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:pPr><w:outlineLvl w:val="0"/></w:pPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr></w:style>
+</w:styles>"#;
+        let numbering = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/><w:lvlText w:val="&#61623;"/></w:lvl>
+    <w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/><w:lvlText w:val="o"/></w:lvl>
+  </w:abstractNum>
+  <w:abstractNum w:abstractNumId="1">
+    <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/><w:lvlText w:val="%1)"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+  <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+</w:numbering>"#;
+        let rels = rels_xml(r#"<Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/guide" TargetMode="External"/>"#);
+        let xml_content = document_xml(r#"
+    <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Release notes</w:t></w:r></w:p>
+    <w:p>
+      <w:r><w:t xml:space="preserve">This release is </w:t></w:r>
+      <w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve">much </w:t></w:r>
+      <w:r><w:rPr><w:b/></w:rPr><w:t>faster</w:t></w:r>
+      <w:r><w:t xml:space="preserve"> and </w:t></w:r>
+      <w:r><w:rPr><w:b/><w:i/></w:rPr><w:t>safer</w:t></w:r>
+      <w:r><w:rPr><w:i/></w:rPr><w:t xml:space="preserve"> than ever</w:t></w:r>
+      <w:r><w:rPr><w:i w:val="0"/></w:rPr><w:t>.</w:t></w:r>
+    </w:p>
+    <w:p><w:r><w:t>#1 on the list: 2*3 and snake_case.</w:t></w:r></w:p>
+    <w:p><w:r><w:t>1. Not a list item</w:t></w:r><w:r><w:br/><w:t>&gt; nor a quote.</w:t></w:r></w:p>
+    <w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Changes</w:t></w:r></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Faster start-up</w:t></w:r></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Half the time on large files</w:t></w:r></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t xml:space="preserve">See the </w:t></w:r><w:hyperlink r:id="rId5"><w:r><w:rPr><w:i/></w:rPr><w:t>guide</w:t></w:r></w:hyperlink></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="2"/></w:numPr></w:pPr><w:r><w:t>Back up</w:t></w:r></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="2"/></w:numPr></w:pPr><w:r><w:t>Upgrade</w:t></w:r></w:p>
+    <w:p><w:r><w:br w:type="page"/></w:r></w:p>
+    <w:tbl>
+      <w:tr><w:tc><w:p><w:r><w:t>Option</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Default</w:t></w:r></w:p></w:tc></w:tr>
+      <w:tr><w:tc><w:p><w:r><w:t>--jobs</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:rPr><w:b/></w:rPr><w:t>all|cores</w:t></w:r></w:p></w:tc></w:tr>
+    </w:tbl>
+    <w:p><w:r><w:t>That is all.</w:t></w:r></w:p>"#);
+        let (_temp_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+            ("word/styles.xml", styles),
+            ("word/numbering.xml", numbering),
+        ])
+        .unwrap();
+
+        let options = ExtractOptions::new().format(OutputFormat::Markdown);
+        let extracted = extract_text_from_docx_with(&docx_path, &options).unwrap();
+        assert_eq!(extracted, include_str!("../tests/golden/rich.md").trim_end());
+
+        // Plain text has none of the markup.
+        let extracted = extract_text_from_docx_with(&docx_path, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted,
+            "Release notes\n\
+             This release is much faster and safer than ever.\n\
+             #1 on the list: 2*3 and snake_case.\n\
+             1. Not a list item\n\
+             > nor a quote.\n\
+             Changes\n\
+             • Faster start-up\n\
+             \x20 o Half the time on large files\n\
+             • See the guide <https://example.com/guide>\n\
+             a) Back up\n\
+             b) Upgrade\n\
+             \x0c\n\
+             Option\tDefault\n\
+             --jobs\tall|cores\n\
+             That is all."
+        );
+    }
+
+    #[test]
+    fn test_html_output_is_well_formed() {
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/></w:style></w:styles>"#;
+        let numbering = r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl>
+    <w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/><w:lvlText w:val="o"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+</w:numbering>"#;
+        let rels = rels_xml(r#"<Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/?a=1&amp;b=2" TargetMode="External"/>"#);
+        let item = |level: usize, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="{}"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+                level, text
+            )
+        };
+        let xml_content = document_xml(&format!(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Q&amp;A &lt;draft&gt;</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Read the </w:t></w:r><w:hyperlink r:id="rId5"><w:r><w:t>"FAQ"</w:t></w:r></w:hyperlink><w:r><w:br w:type="page"/><w:t>Next page</w:t></w:r></w:p>
+            {}{}{}
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:t>Cell</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1 &lt; 2</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+            item(0, "Plan"),
+            item(1, "Draft it"),
+            item(0, "Ship")
+        ));
+        let (_docx_dir, docx_path) = create_test_docx_with_parts(&[
+            ("word/document.xml", &xml_content),
+            ("word/_rels/document.xml.rels", &rels),
+            ("word/styles.xml", styles),
+            ("word/numbering.xml", numbering),
+        ])
+        .unwrap();
+        let temp_dir = tempdir().unwrap();
+        let notes = temp_dir.path().join("Release Notes.txt");
+        std::fs::write(&notes, "Fixed <b> & more\n\nThanks").unwrap();
+        let bad = temp_dir.path().join("bad.docx");
+        std::fs::write(&bad, "not a zip").unwrap();
+
+        let paths = [PathBuf::from(&docx_path), notes, bad];
+        let options = MergeOptions::new().on_error(ErrorPolicy::SkipAndReport);
+        let mut written = Vec::new();
+        let report = merge_files_to_html_writer(&paths, &mut written, &options, Some("p { margin: 0 }"))
+            .unwrap();
+        assert_eq!(report.skipped.len(), 1);
+        let html = String::from_utf8(written).unwrap();
+
+        // Every element closes, in order, and the text reads back as written.
+        let mut reader = quick_xml::Reader::from_str(&html);
+        let mut open = Vec::new();
+        let mut ids = Vec::new();
+        let mut text = String::new();
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(e) => {
+                    if let Some(id) = attribute(&e, b"id") {
+                        ids.push(id);
+                    }
+                    open.push(e.name().as_ref().to_vec());
+                }
+                Event::End(e) => assert_eq!(open.pop().as_deref(), Some(e.name().as_ref())),
+                Event::Text(e) => text.push_str(&e.unescape().unwrap()),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        assert!(open.is_empty());
+        assert_eq!(ids, ["test", "release-notes"]);
+        assert!(text.contains("Q&A <draft>"));
+        assert!(text.contains("1 < 2"));
+        assert!(text.contains("Fixed <b> & more"));
+
+        assert!(html.starts_with("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\"/>\n<title>Merged documents</title>\n<style>\np { margin: 0 }\n</style>\n"));
+        assert!(html.contains("<h1>Q&amp;A &lt;draft&gt;</h1>\n"));
+        assert!(html.contains(
+            "<p>Read the <a href=\"https://example.com/?a=1&amp;b=2\">&quot;FAQ&quot;</a>Next page</p>\n"
+        ));
+        assert!(html.contains("<ol>\n<li>Plan<ul>\n<li>Draft it</li>\n</ul>\n</li>\n<li>Ship</li>\n</ol>\n"));
+        assert!(html.contains("<table>\n<tr>\n<td>\n<p>Cell</p>\n</td>\n<td>\n<p>1 &lt; 2</p>\n</td>\n</tr>\n</table>\n"));
+        assert!(html.contains("<section id=\"release-notes\">\n<p>Fixed &lt;b&gt; &amp; more</p>\n<p>Thanks</p>\n</section>\n"));
+    }
+}
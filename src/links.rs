@@ -0,0 +1,90 @@
+#[cfg(feature = "cli")]
+use crate::visitor::{DocxVisitor, LinkInfo, RunInfo};
+
+/// A hyperlink in a document, from a `w:hyperlink` element or a HYPERLINK
+/// field; see [`extract_hyperlinks`](crate::extract_hyperlinks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hyperlink {
+    /// The visible text of the link.
+    pub text: String,
+    /// Where the link goes: a URL, or `#name` for a bookmark in the
+    /// document.
+    pub target: String,
+    /// What kind of target it is.
+    pub kind: LinkKind,
+}
+
+/// What a hyperlink points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinkKind {
+    /// A web page or another file.
+    External,
+    /// A bookmark in the document itself.
+    Internal,
+    /// An e-mail address (`mailto:`).
+    Mailto,
+}
+
+impl LinkKind {
+    /// The kind of a link to `target`.
+    pub fn of(target: &str) -> Self {
+        if target.starts_with('#') {
+            LinkKind::Internal
+        } else if target
+            .get(..7)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        {
+            LinkKind::Mailto
+        } else {
+            LinkKind::External
+        }
+    }
+
+    /// The name of the kind, as the command line lists it.
+    pub fn name(self) -> &'static str {
+        match self {
+            LinkKind::External => "external",
+            LinkKind::Internal => "internal",
+            LinkKind::Mailto => "mailto",
+        }
+    }
+}
+
+/// Collects the hyperlinks of a document, each target and text once.
+#[cfg(feature = "cli")]
+#[derive(Debug, Default)]
+pub(crate) struct LinkCollector {
+    /// The links found, in the order they end.
+    pub links: Vec<Hyperlink>,
+    /// The targets and text so far of the open links, innermost last.
+    open: Vec<(Option<String>, String)>,
+}
+
+#[cfg(feature = "cli")]
+impl DocxVisitor for LinkCollector {
+    fn text(&mut self, text: &str, _run: &RunInfo) {
+        if let Some((_, link_text)) = self.open.last_mut() {
+            link_text.push_str(text);
+        }
+    }
+
+    fn hyperlink_start(&mut self, link: &LinkInfo) {
+        self.open.push((link.target.clone(), String::new()));
+    }
+
+    fn hyperlink_end(&mut self) {
+        let Some((Some(target), text)) = self.open.pop() else {
+            return;
+        };
+        let link = Hyperlink {
+            text: text.trim().to_string(),
+            kind: LinkKind::of(&target),
+            target,
+        };
+        if !self.links.contains(&link) {
+            self.links.push(link);
+        }
+    }
+}
@@ -1,271 +1,1260 @@
+use std::ffi::OsString;
 use std::fs::File;
-use std::io::Read;
-use std::path::Path;
-use zip::read::ZipArchive;
-use quick_xml::Reader;
-use quick_xml::events::Event;
-use glob::glob;
-
-/// Extracts the text content from the provided DOCX file.
-/// If `strip_hyperlinks` is true, any field instruction text (inside <w:instrText>)
-/// that starts with "HYPERLINK" is skipped. This generally removes the hyperlink's
-/// underlying field code while keeping the visible text.
-pub fn extract_text_from_docx(
-    path: &str,
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::builder::styling::Style;
+use clap::builder::StyledStr;
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+
+use mddocmrg::{
+    expand_patterns_with, extract_bookmarks_from_docx, extract_embedded_objects, extract_hyperlinks,
+    extract_media, extract_metadata, extract_stats, extract_text_with_warnings, list_embedded_objects, list_parts,
+    merge_docx_files_to_writer, merge_files_to_html_writer, merge_files_to_json_lines, merge_files_to_json_writer, read_file_list,
+    read_file_list_from, split_files, with_table_of_contents, BlankLines, CommentMode, DirCache, EquationMode,
+    ErrorPolicy, ExpandOptions, ExtractOptions, FileStats, LinkStyle, MergeError, MergeOptions, MergedCells,
+    NoteMode, OutputFormat, ProgressEvent, RevisionMode, RubyMode, SkippedFile, SortOrder, SplitOptions,
+    TextStats, TocMode, UnmappedSymbols, Warning,
+};
+
+/// Where the merged text goes when no output is given.
+const DEFAULT_OUTPUT: &str = "merged.txt";
+
+/// Where the JSON of `--format json` goes when no output is given.
+const DEFAULT_JSON_OUTPUT: &str = "merged.json";
+
+/// Where the JSON Lines of `--format jsonl` go when no output is given.
+const DEFAULT_JSON_LINES_OUTPUT: &str = "merged.jsonl";
+
+/// Where the HTML of `--format html` goes when no output is given.
+const DEFAULT_HTML_OUTPUT: &str = "merged.html";
+
+/// Merges of more files than this show a progress bar, when standard error
+/// is a terminal.
+const PROGRESS_MIN_FILES: usize = 5;
+
+/// What the exit statuses mean, at the end of the help.
+const EXIT_STATUS: &str = "\
+Exit status: 0 on success, 1 for usage errors, 2 if a file cannot be read or
+written, 3 if a document is damaged, 4 if it is of an unsupported kind, 5 if it
+exceeds the size limits, 6 if a requested bookmark is missing, 7 if files were
+skipped under --skip-errors.";
+
+/// Merges plain text extracted from DOCX files into a single output.
+#[derive(Parser)]
+// An option given twice takes its last value, as shell aliases that set
+// one expect.
+#[command(name = "mddocmrg", version, after_help = EXIT_STATUS, arg_required_else_help = true)]
+#[command(args_override_self = true)]
+struct Cli {
+    /// Print nothing but the text, the counts asked for and errors.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Report each file as it is merged and how long it took, details such
+    /// as invisible characters removed, and each warning, such as for
+    /// equations left out.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+// Parsed once, so the size of the merge options does not matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand)]
+enum Command {
+    /// Merge the text of the files matching the patterns.
+    ///
+    /// Merges plain text extracted from DOCX and ODT files matching the
+    /// given patterns. .txt and .md files (UTF-8) are included as they are.
+    /// A directory stands for the .docx, .docm, .dotx, .dotm, .odt and .ott
+    /// files in it.
+    Merge(MergeArgs),
+    /// Write the text of one file to standard output.
+    Extract(ExtractCommand),
+    /// Print the properties, parts and counts of each file.
+    Info(InfoCommand),
+    /// Print the files the patterns match, without extracting them.
+    List(ListCommand),
+}
+
+#[derive(Args)]
+struct MergeArgs {
+    /// Files, glob patterns or directories.
+    #[arg(required_unless_present = "files_from", value_name = "PATTERN")]
+    patterns: Vec<String>,
+    /// Write the merged text to <PATH>, or to standard output if it is '-'
+    /// (default: merged.txt, or merged.json, merged.jsonl or merged.html
+    /// with --format json, jsonl or html).
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+    /// Write the text of each file to a file of its own in <DIR> instead of
+    /// merging them, making <DIR> if need be.
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "toc_output", "stats_only"])]
+    split_output: Option<PathBuf>,
+    /// With --split-output, the name of each file written, with the
+    /// placeholders of --file-header (default: "{stem}.txt", or .md or
+    /// .json with --format md or json). Names that come out the same get
+    /// -2, -3 and so on.
+    #[arg(long, value_name = "TEMPLATE", requires = "split_output")]
+    output_name: Option<String>,
+    /// Overwrite the output file if it exists.
+    #[arg(long)]
+    force: bool,
+    #[command(flatten)]
+    order: OrderArgs,
+    // Text options take values that start with '-', such as "---", as
+    // separators often do.
+    /// Text written between files (default: a blank line). \n, \t, \r,
+    /// \f and \\ stand for a line break, tab, carriage return, form feed
+    /// and backslash.
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true, value_parser = unescape)]
+    separator: Option<String>,
+    /// A separator written between files: blank (a blank line), hr (a
+    /// Markdown rule, '---', between blank lines), pagebreak (a form feed
+    /// on a line of its own) or none.
+    #[arg(long, value_name = "PRESET", conflicts_with = "separator",
+        value_parser = separator_preset)]
+    separator_preset: Option<String>,
+    /// Leave out files that cannot be extracted, list them at the end and
+    /// exit with status 7.
+    #[arg(long)]
+    skip_errors: bool,
+    /// Like --skip-errors, writing [failed to extract: <error>] in place of
+    /// each file left out.
+    #[arg(long)]
+    error_markers: bool,
+    /// Extract <N> files at once (default: one per CPU; 1 for one at a
+    /// time).
+    #[arg(long, value_name = "N", value_parser = parse_jobs)]
+    jobs: Option<usize>,
+    /// Write a line before each file's text, such as
+    /// "===== {index}/{total}: {name} =====". {path}, {name}, {stem},
+    /// {index}, {total}, {title} and {mtime} stand for the file's.
+    #[arg(long, value_name = "TEMPLATE", allow_hyphen_values = true)]
+    file_header: Option<String>,
+    /// Write a line after each file's text, with the placeholders of
+    /// --file-header.
+    #[arg(long, value_name = "TEMPLATE", allow_hyphen_values = true)]
+    file_footer: Option<String>,
+    /// Write a heading of level <LEVEL> (1 to 6) before each file's text,
+    /// with its title or name, and put the file's own headings under it.
+    #[arg(long, value_name = "LEVEL", value_parser = parse_heading_level)]
+    chapter_headings: Option<usize>,
+    /// Begin the output with a table of contents listing each file, and
+    /// the headings in it, with the line it starts on; in Markdown, the
+    /// headings link to their anchors.
+    #[arg(long)]
+    toc_output: bool,
+    /// After merging, print the words, characters, paragraphs, headings and
+    /// pages of each file and in total to standard error.
+    #[arg(long)]
+    stats: bool,
+    /// Print the counts of --stats without writing the merged text.
+    #[arg(long)]
+    stats_only: bool,
+    /// Keep each file's text in <DIR>, and take it from there on later runs
+    /// while the file and options are unchanged.
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+    /// Extract every file, even with --cache-dir.
+    #[arg(long)]
+    no_cache: bool,
+    /// Empty the --cache-dir before merging.
+    #[arg(long, requires = "cache_dir")]
+    cache_clear: bool,
+    /// Print the bookmark names of each file instead of merging.
+    #[arg(long)]
+    list_bookmarks: bool,
+    /// Print the hyperlinks of each file, one per line, instead of merging.
+    #[arg(long)]
+    list_links: bool,
+    /// With --list-links, print CSV rows of file, kind, target and text.
+    #[arg(long)]
+    csv: bool,
+    /// Print the files embedded in each file instead of merging.
+    #[arg(long)]
+    list_objects: bool,
+    /// Copy the files embedded in each file to <DIR>, named after the file.
+    #[arg(long, value_name = "DIR")]
+    extract_objects: Option<PathBuf>,
+    /// Print the properties of each file instead of merging, as the info
+    /// command does without its parts and counts.
+    #[arg(long, hide = true)]
+    info: bool,
+    #[command(flatten)]
+    extract: ExtractArgs,
+}
+
+#[derive(Args)]
+struct ExtractCommand {
+    /// The Word, OpenDocument or plain text file.
+    file: PathBuf,
+    #[command(flatten)]
+    extract: ExtractArgs,
+}
+
+#[derive(Args)]
+struct InfoCommand {
+    /// Files, glob patterns or directories.
+    #[arg(required = true, value_name = "PATTERN")]
+    patterns: Vec<String>,
+    /// The options the text is counted with.
+    #[command(flatten)]
+    extract: ExtractArgs,
+}
+
+#[derive(Args)]
+struct ListCommand {
+    /// Files, glob patterns or directories.
+    #[arg(required_unless_present = "files_from", value_name = "PATTERN")]
+    patterns: Vec<String>,
+    #[command(flatten)]
+    order: OrderArgs,
+}
+
+/// The options of which files the patterns stand for, and in what order.
+#[derive(Args)]
+struct OrderArgs {
+    /// The order of the files: natural (default), by name with chapter2
+    /// before chapter10; name; mtime or ctime, oldest first; size,
+    /// smallest first; or none, as the patterns find them.
+    #[arg(long, value_name = "ORDER",
+        value_parser = named(SortOrder::from_name, "sort order", "name, natural, mtime, ctime, size or none"))]
+    sort: Option<SortOrder>,
+    /// Reverse the order of the files.
+    #[arg(long)]
+    reverse: bool,
+    /// Take a file as often as the patterns match it, rather than once.
+    #[arg(long)]
+    keep_duplicates: bool,
+    /// Take the documents in the directories under a directory named too,
+    /// but for hidden ones.
+    #[arg(short, long)]
+    recursive: bool,
+    /// Leave out the files matching a glob pattern, such as
+    /// '**/drafts/**' (repeatable).
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Keep the lock files Word leaves beside open documents (~$*.docx).
+    #[arg(long)]
+    include_lockfiles: bool,
+    /// Take the files listed in <PATH>, one on each line, or on standard
+    /// input if it is '-', in the order listed and before those the
+    /// patterns match. Relative paths are taken from the list's directory.
+    #[arg(long, value_name = "PATH")]
+    files_from: Option<String>,
+    /// Leave out listed files that do not exist, with a warning, rather
+    /// than failing.
+    #[arg(long, requires = "files_from")]
+    skip_missing: bool,
+}
+
+impl OrderArgs {
+    /// The expand options these arguments give.
+    fn options(&self) -> ExpandOptions {
+        ExpandOptions {
+            sort: self.sort.unwrap_or_default(),
+            reverse: self.reverse,
+            keep_duplicates: self.keep_duplicates,
+            recursive: self.recursive,
+            exclude: self.exclude.clone(),
+            include_lockfiles: self.include_lockfiles,
+            listed: self.listed(),
+        }
+    }
+
+    /// The files `--files-from` lists, exiting with an error if one is
+    /// missing.
+    fn listed(&self) -> Vec<PathBuf> {
+        let listed = match self.files_from.as_deref() {
+            None => return Vec::new(),
+            Some("-") => read_file_list_from(
+                std::io::stdin().lock(),
+                Path::new("<stdin>"),
+                Path::new(""),
+                self.skip_missing,
+            ),
+            Some(list) => read_file_list(list, self.skip_missing),
+        };
+        listed.unwrap_or_else(|error| fail(error))
+    }
+}
+
+/// The options of how text is extracted, shared by the commands that
+/// extract it.
+#[derive(Args)]
+#[command(next_help_heading = "Extraction")]
+struct ExtractArgs {
+    /// Output format: text (default), md, json, a JSON document giving
+    /// the text of each file with its title, properties and counts,
+    /// jsonl, the same for each file on a line of its own, written as it
+    /// is extracted, or html, a web page with a section for each file.
+    #[arg(long, value_name = "FORMAT",
+        value_parser = named(Format::from_name, "output format", "text, md, json, jsonl or html"))]
+    format: Option<Format>,
+    /// With --format json, indent the JSON.
+    #[arg(long)]
+    pretty: bool,
+    /// With --format html, put the stylesheet in <FILE> in the page.
+    #[arg(long, value_name = "FILE")]
+    html_css: Option<PathBuf>,
+    /// Keep only the visible text of hyperlinks (same as --links strip).
+    #[arg(short = 's', long, conflicts_with_all = ["links", "hyperlink_format"])]
     strip_hyperlinks: bool,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let file = File::open(path)?;
-    let mut archive = ZipArchive::new(file)?;
-    let mut document_xml = archive.by_name("word/document.xml")?;
-    let mut xml_content = String::new();
-    document_xml.read_to_string(&mut xml_content)?;
-
-    let mut reader = Reader::from_str(&xml_content);
-    reader.trim_text(true);
-    let mut buf = Vec::new();
-    let mut text = String::new();
-
-    // Track whether we're inside a hyperlink field instruction.
-    let mut in_instr_text = false;
-
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                match e.name().as_ref() {
-                    b"w:instrText" => in_instr_text = true,
-                    _ => {}
-                }
-            }
-            Ok(Event::End(ref e)) => {
-                match e.name().as_ref() {
-                    b"w:instrText" => in_instr_text = false,
-                    _ => {}
-                }
-            }
-            Ok(Event::Text(e)) => {
-                // If stripping hyperlinks and we're in an instruction text element,
-                // skip appending this text.
-                if strip_hyperlinks && in_instr_text {
-                    // Skip this text.
-                } else {
-                    text.push_str(&e.unescape()?.into_owned());
-                    text.push(' ');
-                }
+    /// How hyperlinks are written: strip, url (default) or markdown.
+    #[arg(long, value_name = "STYLE", conflicts_with = "hyperlink_format",
+        value_parser = named(LinkStyle::from_name, "link style", "strip, url or markdown"))]
+    links: Option<LinkStyle>,
+    /// How links are written, using {text} and {target} (default:
+    /// "{text} <{target}>").
+    #[arg(long, value_name = "TEMPLATE", allow_hyphen_values = true)]
+    hyperlink_format: Option<String>,
+    /// Tracked changes: accept (default), reject or all.
+    #[arg(long, value_name = "MODE",
+        value_parser = named(RevisionMode::from_name, "revision mode", "accept, reject or all"))]
+    revisions: Option<RevisionMode>,
+    /// Include text formatted as hidden.
+    #[arg(long)]
+    include_hidden: bool,
+    /// Leave out struck-through text.
+    #[arg(long)]
+    skip_strikethrough: bool,
+    /// Include reviewer comments where they are anchored.
+    #[arg(long, conflicts_with = "comments")]
+    include_comments: bool,
+    /// Reviewer comments: skip (default), inline or end.
+    #[arg(long, value_name = "PLACEMENT",
+        value_parser = named(CommentMode::from_name, "comment placement", "skip, inline or end"))]
+    comments: Option<CommentMode>,
+    /// Footnotes: skip (default), inline or end.
+    #[arg(long, value_name = "PLACEMENT",
+        value_parser = named(NoteMode::from_name, "note placement", "skip, inline or end"))]
+    footnotes: Option<NoteMode>,
+    /// Endnotes: skip (default), inline or end.
+    #[arg(long, value_name = "PLACEMENT",
+        value_parser = named(NoteMode::from_name, "note placement", "skip, inline or end"))]
+    endnotes: Option<NoteMode>,
+    /// Include section headers before and footers after the text.
+    #[arg(long)]
+    include_headers_footers: bool,
+    /// Write the glossary (AutoText, building blocks) after the text.
+    #[arg(long)]
+    include_glossary: bool,
+    /// Leave out the text of text boxes and shapes.
+    #[arg(long)]
+    skip_text_boxes: bool,
+    /// Write [image: <alt text>] for pictures.
+    #[arg(long)]
+    image_alt_text: bool,
+    /// Leave out the [embedded object: <file>] written for embedded
+    /// objects.
+    #[arg(long)]
+    skip_object_placeholders: bool,
+    /// Copy the pictures of each file to <DIR>, named after the file;
+    /// Markdown output links to them as ![alt](<DIR>/<file>).
+    #[arg(long, value_name = "DIR")]
+    extract_media: Option<PathBuf>,
+    /// Equations: linear (default) or skip.
+    #[arg(long, value_name = "MODE",
+        value_parser = named(EquationMode::from_name, "equation mode", "linear or skip"))]
+    equations: Option<EquationMode>,
+    /// Tables of contents: keep (default), skip or marker.
+    #[arg(long, value_name = "MODE",
+        value_parser = named(TocMode::from_name, "TOC mode", "skip, keep or marker"))]
+    toc: Option<TocMode>,
+    /// Phonetic guides: base (default, base text only) or annotate.
+    #[arg(long, value_name = "MODE",
+        value_parser = named(RubyMode::from_name, "ruby mode", "base or annotate"))]
+    ruby: Option<RubyMode>,
+    /// Text written for tab characters (default: a tab).
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true)]
+    tab_string: Option<String>,
+    /// Text written for page breaks (default: a form feed).
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true)]
+    page_break: Option<String>,
+    /// Blank lines: keep (default), collapse runs to one, or strip.
+    #[arg(long, value_name = "POLICY",
+        value_parser = named(BlankLines::from_name, "blank line policy", "keep, collapse or strip"))]
+    blank_lines: Option<BlankLines>,
+    /// Text written between sections, with {type} for the kind of break.
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true)]
+    section_separator: Option<String>,
+    /// Write [page <n>] where pages started when the document was last
+    /// laid out. Page numbers are approximate.
+    #[arg(long, conflicts_with = "page_marker")]
+    page_markers: bool,
+    // Write page markers using a template with {n}, e.g. "== p.{n} ==".
+    #[arg(long, value_name = "TEMPLATE", allow_hyphen_values = true, help = page_marker_help())]
+    page_marker: Option<String>,
+    /// Text written between table cells (default: a tab).
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true)]
+    table_delimiter: Option<String>,
+    /// Cells covered by merged cells: empty (default) or repeat.
+    #[arg(long, value_name = "MODE",
+        value_parser = named(MergedCells::from_name, "merged cell mode", "empty or repeat"))]
+    merged_cells: Option<MergedCells>,
+    /// Text written per level before nested list items (default: two
+    /// spaces). Markdown indents them as far as their parents' labels.
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true)]
+    list_indent: Option<String>,
+    /// Prefix headings with their level, as in [H2].
+    #[arg(long)]
+    mark_headings: bool,
+    /// Keep only paragraphs in matching styles (repeatable).
+    #[arg(long, value_name = "PATTERN")]
+    include_style: Vec<String>,
+    /// Drop paragraphs in matching styles, e.g. 'TOC*' (repeatable).
+    #[arg(long, value_name = "PATTERN")]
+    exclude_style: Vec<String>,
+    /// Write content controls as [<title>: <text>].
+    #[arg(long)]
+    annotate_controls: bool,
+    /// Extract only the text of the named bookmark.
+    #[arg(long, value_name = "NAME")]
+    bookmark: Option<String>,
+    /// Write non-breaking hyphens as '-' rather than U+2011.
+    #[arg(long)]
+    ascii_hyphens: bool,
+    /// Keep optional hyphens as U+00AD rather than dropping them.
+    #[arg(long)]
+    keep_soft_hyphens: bool,
+    /// Write curly quotes, dashes, ellipses and non-breaking spaces as
+    /// ASCII.
+    #[arg(long)]
+    normalize_punctuation: bool,
+    /// Normalize the text to Unicode NFC (composed characters).
+    #[arg(long)]
+    nfc: bool,
+    /// Remove zero-width, control and other invisible characters.
+    #[arg(long)]
+    strip_invisible: bool,
+    /// Symbols without a Unicode equivalent: placeholder (default) or raw.
+    #[arg(long, value_name = "MODE",
+        value_parser = named(UnmappedSymbols::from_name, "symbol mode", "placeholder or raw"))]
+    unmapped_symbols: Option<UnmappedSymbols>,
+}
+
+/// What `--format` asks for: text in a markup, JSON holding plain text,
+/// or HTML.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text(OutputFormat),
+    Json,
+    JsonLines,
+    Html,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Format::Json),
+            "jsonl" | "ndjson" => Some(Format::JsonLines),
+            "html" | "htm" => Some(Format::Html),
+            name => OutputFormat::from_name(name).map(Format::Text),
+        }
+    }
+}
+
+impl ExtractArgs {
+    /// Whether the text is written as a JSON document.
+    fn json(&self) -> bool {
+        self.format == Some(Format::Json)
+    }
+
+    /// Whether the text is written as JSON Lines.
+    fn json_lines(&self) -> bool {
+        self.format == Some(Format::JsonLines)
+    }
+
+    /// Whether the text is written as HTML.
+    fn html(&self) -> bool {
+        self.format == Some(Format::Html)
+    }
+
+    /// The stylesheet of --html-css, read; a usage error without --format
+    /// html.
+    fn html_css(&self) -> Option<String> {
+        let path = self.html_css.as_ref()?;
+        if !self.html() {
+            exit_usage(Cli::command().error(ErrorKind::ArgumentConflict, "--html-css needs --format html"));
+        }
+        let css = std::fs::read_to_string(path)
+            .unwrap_or_else(|source| fail(MergeError::Io { path: path.clone(), source }));
+        Some(css)
+    }
+
+    /// The extract options these arguments give.
+    fn options(&self, verbose: bool) -> ExtractOptions {
+        let mut options = ExtractOptions {
+            include_hidden: self.include_hidden,
+            skip_strikethrough: self.skip_strikethrough,
+            include_headers_footers: self.include_headers_footers,
+            include_glossary: self.include_glossary,
+            include_text_boxes: !self.skip_text_boxes,
+            image_alt_text: self.image_alt_text,
+            object_placeholders: !self.skip_object_placeholders,
+            media_dir: self.extract_media.clone(),
+            mark_headings: self.mark_headings,
+            include_styles: self.include_style.clone(),
+            exclude_styles: self.exclude_style.clone(),
+            annotate_content_controls: self.annotate_controls,
+            bookmark: self.bookmark.clone(),
+            ascii_hyphens: self.ascii_hyphens,
+            keep_soft_hyphens: self.keep_soft_hyphens,
+            normalize_punctuation: self.normalize_punctuation,
+            nfc: self.nfc,
+            strip_invisible: self.strip_invisible,
+            verbose,
+            ..ExtractOptions::default()
+        };
+        if self.strip_hyperlinks {
+            options.link_style = LinkStyle::Strip;
+        }
+        if let Some(style) = &self.links {
+            options.link_style = style.clone();
+        }
+        if let Some(template) = &self.hyperlink_format {
+            options.link_style = LinkStyle::Template(template.clone());
+        }
+        if self.include_comments {
+            options.comments = CommentMode::Inline;
+        }
+        if self.page_markers {
+            options.page_marker = Some("[page {n}]".to_string());
+        }
+        if let Some(template) = &self.page_marker {
+            options.page_marker = Some(template.clone());
+        }
+        if let Some(Format::Text(format)) = self.format {
+            options = options.format(format);
+        }
+        if let Some(mode) = self.revisions {
+            options.revisions = mode;
+        }
+        if let Some(mode) = self.comments {
+            options.comments = mode;
+        }
+        if let Some(mode) = self.footnotes {
+            options.footnotes = mode;
+        }
+        if let Some(mode) = self.endnotes {
+            options.endnotes = mode;
+        }
+        if let Some(mode) = self.equations {
+            options.equations = mode;
+        }
+        if let Some(mode) = self.toc {
+            options.toc = mode;
+        }
+        if let Some(mode) = self.ruby {
+            options.ruby = mode;
+        }
+        if let Some(policy) = self.blank_lines {
+            options.blank_lines = policy;
+        }
+        if let Some(mode) = self.merged_cells {
+            options.merged_cells = mode;
+        }
+        if let Some(mode) = self.unmapped_symbols {
+            options.unmapped_symbols = mode;
+        }
+        if let Some(text) = &self.tab_string {
+            options.tab_string = text.clone();
+        }
+        if let Some(text) = &self.page_break {
+            options.page_break = text.clone();
+        }
+        if let Some(text) = &self.section_separator {
+            options.section_separator = text.clone();
+        }
+        if let Some(text) = &self.table_delimiter {
+            options.table_delimiter = text.clone();
+        }
+        if let Some(text) = &self.list_indent {
+            options.list_indent = text.clone();
+        }
+        options
+    }
+}
+
+/// The help of --page-marker. Clap reads `{n}` in help as a line break, so
+/// the `n` of the placeholder is styled apart, which keeps the braces
+/// around it whether or not the style is shown.
+fn page_marker_help() -> StyledStr {
+    let n = format!("{{{}n{:#}}}", Style::new().bold(), Style::new().bold());
+    format!("Write page markers using a template with {n}, e.g. \"== p.{n} ==\"").into()
+}
+
+/// A parser of the values of an option, by the `from_name` of their type.
+fn named<T: Clone + Send + Sync + 'static>(
+    from_name: fn(&str) -> Option<T>,
+    what: &'static str,
+    expected: &'static str,
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    move |value| from_name(value).ok_or_else(|| format!("Unknown {} '{}'; expected {}.", what, value, expected))
+}
+
+/// Turns the escape sequences of a `--separator` value into the characters
+/// they stand for. A backslash before any other character is kept.
+fn unescape(value: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('f') => unescaped.push('\x0c'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
             }
-            Ok(Event::Eof) => break,
-            Err(e) => return Err(Box::new(e)),
-            _ => {} // Ignore other events.
+            None => unescaped.push('\\'),
         }
-        buf.clear();
     }
-    Ok(text.trim().to_string())
+    Ok(unescaped)
 }
 
-/// Merges the text extracted from multiple DOCX files into one string.
-/// Each file's text is separated by two newline characters.
-pub fn merge_docx_files(
-    paths: &[&str],
-    strip_hyperlinks: bool,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut merged_text = String::new();
-    for path in paths {
-        let text = extract_text_from_docx(path, strip_hyperlinks)?;
-        merged_text.push_str(&text);
-        merged_text.push_str("\n\n");
+/// Parses a `--separator-preset` value into the separator it stands for.
+fn separator_preset(value: &str) -> Result<String, String> {
+    let separator = match value {
+        "blank" => "\n\n",
+        "hr" => "\n\n---\n\n",
+        "pagebreak" => "\n\x0c\n",
+        "none" => "",
+        _ => {
+            return Err(format!(
+                "Unknown separator preset '{}'; expected blank, hr, pagebreak or none.",
+                value
+            ))
+        }
+    };
+    Ok(separator.to_string())
+}
+
+/// Parses a `--chapter-headings` value.
+fn parse_heading_level(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(level) if (1..=6).contains(&level) => Ok(level),
+        _ => Err(format!("Invalid heading level '{}'; expected 1 to 6.", value)),
+    }
+}
+
+/// Parses a `--jobs` value.
+fn parse_jobs(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(format!("Invalid job count '{}'; expected a positive number.", value)),
+    }
+}
+
+/// Writes what the library and the program log to standard error, so that
+/// standard output has nothing but text. Which records are written is up
+/// to the level [`init_logging`] sets.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
     }
-    Ok(merged_text.trim().to_string())
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            log::Level::Error => eprintln!("error: {}", record.args()),
+            log::Level::Warn => eprintln!("warning: {}", record.args()),
+            _ => eprintln!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
 }
 
-/// Prints usage instructions.
-fn print_usage(program: &str) {
-    let prog_name = Path::new(program)
-        .file_name()
-        .map(|s| s.to_string_lossy())
-        .unwrap_or_else(|| "docx_merger".into());
-    println!("Usage: {} [options] <file_pattern1> <file_pattern2> ...", prog_name);
-    println!("Merges plain text extracted from DOCX files matching the given patterns.");
-    println!("Options:");
-    println!("  -h, -?                 Display this help message and exit.");
-    println!("  --strip-hyperlinks, -s  Remove hyperlink field instructions from the output.");
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Logs errors alone when `quiet`, details and warnings too when
+/// `verbose`, and otherwise warnings and a summary of what was done.
+fn init_logging(quiet: bool, verbose: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => log::LevelFilter::Error,
+        (false, true) => log::LevelFilter::Debug,
+        (false, false) => log::LevelFilter::Info,
+    };
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// `count` of a thing, as in "1 file" or "2 files".
+fn plural(count: usize, thing: &str) -> String {
+    match count {
+        1 => format!("1 {}", thing),
+        count => format!("{} {}s", count, thing),
+    }
+}
+
+/// The end of a summary line, after what was done: how long it took if
+/// `verbose`, and how many warnings there were.
+fn summary_end(warnings: &[Warning], elapsed: Duration, verbose: bool) -> String {
+    let mut end = String::new();
+    if verbose {
+        end.push_str(&format!(" in {:.2}s", elapsed.as_secs_f64()));
+    }
+    if !warnings.is_empty() {
+        end.push_str(&format!(", with {}", plural(warnings.len(), "warning")));
+        if !verbose {
+            let them = if warnings.len() == 1 { "it" } else { "them" };
+            end.push_str(&format!("; use --verbose to see {}", them));
+        }
+    }
+    end.push('.');
+    end
+}
+
+/// The exit status for a failure, by what went wrong, so that scripts can
+/// tell unreadable files from damaged and unsupported ones. Usage errors
+/// exit with 1.
+fn exit_code(error: &MergeError) -> i32 {
+    match error {
+        MergeError::Pattern { .. } => 1,
+        MergeError::Io { .. } | MergeError::Write { .. } | MergeError::ListedFileMissing { .. } => 2,
+        MergeError::Zip { .. } | MergeError::Xml { .. } | MergeError::MissingPart { .. } => 3,
+        MergeError::Encrypted { .. }
+        | MergeError::LegacyDoc { .. }
+        | MergeError::NotWordDocument { .. }
+        | MergeError::NotUtf8 { .. } => 4,
+        MergeError::PartTooLarge { .. } | MergeError::PackageTooLarge { .. } => 5,
+        MergeError::BookmarkNotFound { .. } => 6,
+        // Nothing here cancels; 130 is what shells report for an interrupt.
+        MergeError::Cancelled => 130,
+    }
+}
+
+/// The exit status when files were left out under `--skip-errors`.
+const SKIPPED_FILES_EXIT_CODE: i32 = 7;
+
+/// Reports a failure and exits with its status.
+fn fail(error: MergeError) -> ! {
+    eprintln!("Error: {}", error);
+    std::process::exit(exit_code(&error));
+}
+
+/// Quotes a CSV field if it holds a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Prints the counts of each file merged, and their totals, to standard
+/// error.
+fn print_stats(files: &[FileStats]) {
+    let names: Vec<String> = files.iter().map(|file| file.path.display().to_string()).collect();
+    let width = names.iter().map(|name| name.chars().count()).chain([5]).max().unwrap_or(5);
+    let row = |name: &str, stats: &TextStats| {
+        eprintln!(
+            "{:<width$}  {:>8}  {:>10}  {:>9}  {:>10}  {:>8}  {:>5}",
+            name,
+            stats.words,
+            stats.characters,
+            stats.characters_no_spaces,
+            stats.paragraphs,
+            stats.headings,
+            stats.pages,
+            width = width
+        );
+    };
+    eprintln!(
+        "{:<width$}  {:>8}  {:>10}  {:>9}  {:>10}  {:>8}  {:>5}",
+        "file", "words", "characters", "no spaces", "paragraphs", "headings", "pages",
+        width = width
+    );
+    let mut total = TextStats::default();
+    for (name, file) in names.iter().zip(files) {
+        row(name, &file.stats);
+        total += file.stats;
+    }
+    row("total", &total);
+}
+
+/// The files the patterns stand for, exiting with an error if there are
+/// none.
+fn resolve_patterns(patterns: &[String], options: &ExpandOptions) -> Vec<PathBuf> {
+    let file_paths = expand_patterns_with(patterns, options).unwrap_or_else(|error| fail(error));
+    if file_paths.is_empty() {
+        eprintln!("No files found matching the specified patterns.");
+        std::process::exit(1);
+    }
+    file_paths
+}
+
+/// Reports a usage error, or prints the help or version asked for, and
+/// exits: with 1 for an error, as for the other usage errors.
+fn exit_usage(error: clap::Error) -> ! {
+    let _ = error.print();
+    std::process::exit(if error.use_stderr() { 1 } else { 0 });
+}
+
+/// Parses the command line, and says whether it lacked a command. Such a
+/// command line, as all were before there were commands, is read as the
+/// patterns and options of `merge`; a pattern cannot then be the name of a
+/// command.
+fn parse_args(args: Vec<OsString>) -> (Cli, bool) {
+    let error = match Cli::try_parse_from(&args) {
+        Ok(cli) => return (cli, false),
+        Err(error) => error,
+    };
+    let cli = Cli::command();
+    let names_command = args
+        .iter()
+        .skip(1)
+        .any(|arg| arg.to_str().is_some_and(|arg| arg == "help" || cli.find_subcommand(arg).is_some()));
+    if names_command || !matches!(error.kind(), ErrorKind::InvalidSubcommand | ErrorKind::UnknownArgument) {
+        exit_usage(error);
+    }
+    let mut merge_args = args;
+    merge_args.insert(1.min(merge_args.len()), "merge".into());
+    match Cli::try_parse_from(merge_args) {
+        Ok(cli) => (cli, true),
+        Err(error) => exit_usage(error),
+    }
 }
 
 /// Main function.
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    let program = args.get(0).cloned().unwrap_or_else(|| "docx_merger".to_string());
+fn main() {
+    let (cli, legacy) = parse_args(std::env::args_os().collect());
+    init_logging(cli.quiet, cli.verbose);
+    if legacy {
+        log::warn!("running mddocmrg without a command is deprecated; use `mddocmrg merge` with the same arguments.");
+    }
+    match cli.command {
+        Command::Merge(args) => merge(args, cli.verbose, cli.quiet),
+        Command::Extract(args) => extract(args, cli.verbose),
+        Command::Info(args) => info(args, cli.verbose),
+        Command::List(args) => {
+            for path in resolve_patterns(&args.patterns, &args.order.options()) {
+                println!("{}", path.display());
+            }
+        }
+    }
+}
+
+/// Writes the text of one file to standard output.
+fn extract(args: ExtractCommand, verbose: bool) {
+    let started = Instant::now();
+    let options = args.extract.options(verbose);
+    if let Some(media_dir) = &options.media_dir {
+        extract_media(&args.file, media_dir, &options).unwrap_or_else(|error| fail(error));
+    }
+    let stdout_error = |source| MergeError::Io { path: "<stdout>".into(), source };
+    let mut stdout = std::io::stdout().lock();
+    let css = args.extract.html_css();
+    let warnings = if args.extract.json() || args.extract.json_lines() || args.extract.html() {
+        let options = MergeOptions::from(options);
+        let written = if args.extract.json() {
+            merge_files_to_json_writer(&[&args.file], &mut stdout, &options, args.extract.pretty)
+        } else if args.extract.html() {
+            merge_files_to_html_writer(&[&args.file], &mut stdout, &options, css.as_deref())
+        } else {
+            merge_files_to_json_lines(&[&args.file], &mut stdout, &options)
+        };
+        match written {
+            Ok(report) => report.warnings,
+            Err(MergeError::Write { source }) => fail(stdout_error(source)),
+            Err(error) => fail(error),
+        }
+    } else {
+        let (text, warnings) =
+            extract_text_with_warnings(&args.file, &options).unwrap_or_else(|error| fail(error));
+        if let Err(source) = stdout.write_all(text.as_bytes()).and_then(|_| stdout.flush()) {
+            fail(stdout_error(source));
+        }
+        warnings
+    };
+    for warning in &warnings {
+        log::debug!("warning: {}", warning);
+    }
+    log::info!(
+        "Extracted {}{}",
+        args.file.display(),
+        summary_end(&warnings, started.elapsed(), verbose)
+    );
+}
 
-    println!("{} - Merges plain text extracted from DOCX files into a single output.", program);
+/// Prints the properties, parts and counts of each file.
+fn info(args: InfoCommand, verbose: bool) {
+    let options = args.extract.options(verbose);
+    for path in resolve_patterns(&args.patterns, &ExpandOptions::default()) {
+        println!("{}:", path.display());
+        print_metadata(&path, &options);
+        let parts = list_parts(&path, &options).unwrap_or_else(|error| fail(error));
+        if !parts.is_empty() {
+            println!("  parts:");
+            for part in parts {
+                let content_type = part.content_type.as_deref().unwrap_or("unknown type");
+                println!("    {}\t{}\t{} bytes", part.name, content_type, part.size);
+            }
+        }
+        let stats = extract_stats(&path, &options).unwrap_or_else(|error| fail(error));
+        println!("  text:");
+        println!("    words: {}", stats.words);
+        println!("    characters: {}", stats.characters);
+        println!("    characters without spaces: {}", stats.characters_no_spaces);
+        println!("    paragraphs: {}", stats.paragraphs);
+        println!("    headings: {}", stats.headings);
+        println!("    pages: {}", stats.pages);
+    }
+}
 
-    if args.len() < 2 {
-        print_usage(&program);
+/// Prints the properties of a file, indented under its name.
+fn print_metadata(path: &Path, options: &ExtractOptions) {
+    let metadata = extract_metadata(path, options).unwrap_or_else(|error| fail(error));
+    for (name, value) in metadata.fields() {
+        println!("  {}: {}", name, value);
+    }
+    if !metadata.custom_properties.is_empty() {
+        println!("  custom properties:");
+        for (name, value) in &metadata.custom_properties {
+            println!("    {}: {}", name, value);
+        }
+    }
+}
+
+/// Exits with a usage error if the merged text would overwrite one of the
+/// files merged, or a file that exists without `force`. `chosen` says
+/// whether the path was given with -o, which the errors then do not
+/// suggest.
+fn check_output(path: &Path, file_paths: &[PathBuf], force: bool, chosen: bool) {
+    if !path.exists() {
+        return;
+    }
+    let output = std::fs::canonicalize(path).ok();
+    let input = file_paths
+        .iter()
+        .find(|input| output.is_some() && std::fs::canonicalize(input).ok() == output);
+    if let Some(input) = input {
+        if chosen {
+            eprintln!(
+                "Output {} is the input {}; the merged text would overwrite it.",
+                path.display(),
+                input.display()
+            );
+        } else {
+            eprintln!(
+                "{} is also one of the files merged; use -o to write the merged text elsewhere.",
+                path.display()
+            );
+        }
         std::process::exit(1);
     }
+    if !force {
+        if chosen {
+            eprintln!("{} already exists; use --force to overwrite it.", path.display());
+        } else {
+            eprintln!(
+                "{} already exists; use --force to overwrite it, or -o to write elsewhere.",
+                path.display()
+            );
+        }
+        std::process::exit(1);
+    }
+}
 
-    // Process command-line arguments.
-    let mut patterns = Vec::new();
-    let mut strip_hyperlinks = false;
-    for arg in args.iter().skip(1) {
-        match arg.as_str() {
-            "-h" | "-?" => {
-                print_usage(&program);
-                std::process::exit(0);
+/// Merges the text of the files the patterns match, or lists what they
+/// hold when asked to instead.
+fn merge(args: MergeArgs, verbose: bool, quiet: bool) {
+    let started = Instant::now();
+    let options = args.extract.options(verbose);
+    let json = args.extract.json();
+    let json_lines = args.extract.json_lines();
+    let html = args.extract.html();
+    if (json || json_lines || html) && args.toc_output {
+        let error = Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            "--toc-output cannot be used with --format json, jsonl or html",
+        );
+        exit_usage(error);
+    }
+    if json_lines && args.split_output.is_some() {
+        let error = Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            "--split-output cannot be used with --format jsonl; use --format json for a file each",
+        );
+        exit_usage(error);
+    }
+    if html && args.split_output.is_some() {
+        let error = Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            "--split-output cannot be used with --format html",
+        );
+        exit_usage(error);
+    }
+    let css = args.extract.html_css();
+    let file_paths = resolve_patterns(&args.patterns, &args.order.options());
+
+    if args.list_bookmarks {
+        for path in &file_paths {
+            println!("{}:", path.display());
+            let names = extract_bookmarks_from_docx(path, &options).unwrap_or_else(|error| fail(error));
+            for name in names {
+                println!("  {}", name);
             }
-            "--strip-hyperlinks" | "-s" => {
-                strip_hyperlinks = true;
+        }
+        return;
+    }
+
+    if args.list_links {
+        if args.csv {
+            println!("file,kind,target,text");
+        }
+        for path in &file_paths {
+            let links = extract_hyperlinks(path, &options).unwrap_or_else(|error| fail(error));
+            if !args.csv {
+                println!("{}:", path.display());
             }
-            _ => {
-                patterns.push(arg);
+            for link in links {
+                if args.csv {
+                    let file = path.display().to_string();
+                    let fields = [file.as_str(), link.kind.name(), &link.target, &link.text];
+                    let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                    println!("{}", row.join(","));
+                } else {
+                    println!("  {}\t{}", link.target, link.text);
+                }
             }
         }
+        return;
     }
 
-    // Expand wildcards using the glob crate.
-    let mut file_paths = Vec::new();
-    for pattern in patterns {
-        for entry in glob(pattern)? {
-            match entry {
-                Ok(path) => file_paths.push(path.to_string_lossy().into_owned()),
-                Err(e) => eprintln!("Error processing pattern {}: {}", pattern, e),
+    if args.list_objects {
+        for path in &file_paths {
+            println!("{}:", path.display());
+            let objects = list_embedded_objects(path, &options).unwrap_or_else(|error| fail(error));
+            for object in objects {
+                let content_type = object.content_type.as_deref().unwrap_or("unknown type");
+                println!("  {}\t{}\t{} bytes", object.name, content_type, object.size);
             }
         }
+        return;
     }
 
-    if file_paths.is_empty() {
-        eprintln!("No files found matching the specified patterns.");
-        std::process::exit(1);
+    if args.info {
+        for path in &file_paths {
+            println!("{}:", path.display());
+            print_metadata(path, &options);
+        }
+        return;
     }
 
-    let paths_ref: Vec<&str> = file_paths.iter().map(|s| s.as_str()).collect();
-    let merged_text = merge_docx_files(&paths_ref, strip_hyperlinks)?;
-    std::fs::write("merged.txt", merged_text)?;
-    println!("Merged text written to merged.txt");
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::tempdir;
-    use std::fs::File;
-    use std::io::Write;
-    use zip::write::FileOptions;
-    use zip::CompressionMethod;
-
-    /// Creates a temporary DOCX file with minimal content (a single paragraph).
-    fn create_test_docx(text: &str) -> Result<(tempfile::TempDir, String), Box<dyn std::error::Error>> {
-        let temp_dir = tempdir()?;
-        let file_path = temp_dir.path().join("test.docx");
-        let file_path_str = file_path.to_str().unwrap().to_string();
-
-        let file = File::create(&file_path)?;
-        let mut zip = zip::ZipWriter::new(file);
-        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
-
-        // Create a minimal document.xml with a single paragraph.
-        let xml_content = format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
-  <w:body>
-    <w:p>
-      <w:r>
-        <w:t>{}</w:t>
-      </w:r>
-    </w:p>
-  </w:body>
-</w:document>"#, text);
-
-        zip.start_file("word/document.xml", options)?;
-        zip.write_all(xml_content.as_bytes())?;
-        zip.finish()?;
-
-        Ok((temp_dir, file_path_str))
-    }
-
-    /// Creates a temporary DOCX file with custom XML content.
-    fn create_test_docx_with_xml(xml_content: &str) -> Result<(tempfile::TempDir, String), Box<dyn std::error::Error>> {
-        let temp_dir = tempdir()?;
-        let file_path = temp_dir.path().join("test.docx");
-        let file_path_str = file_path.to_str().unwrap().to_string();
-
-        let file = File::create(&file_path)?;
-        let mut zip = zip::ZipWriter::new(file);
-        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
-
-        zip.start_file("word/document.xml", options)?;
-        zip.write_all(xml_content.as_bytes())?;
-        zip.finish()?;
-
-        Ok((temp_dir, file_path_str))
-    }
-
-    #[test]
-    fn test_extract_text_from_docx_without_strip() {
-        let test_text = "Hello, world!";
-        let (_temp_dir, docx_path) = create_test_docx(test_text).unwrap();
-        let extracted = extract_text_from_docx(&docx_path, false).unwrap();
-        assert!(extracted.contains(test_text));
-    }
-
-    #[test]
-    fn test_merge_docx_files_without_strip() {
-        let test_text1 = "First document text.";
-        let test_text2 = "Second document text.";
-        let (_temp_dir1, docx_path1) = create_test_docx(test_text1).unwrap();
-        let (_temp_dir2, docx_path2) = create_test_docx(test_text2).unwrap();
-
-        let merged = merge_docx_files(&[&docx_path1, &docx_path2], false).unwrap();
-        assert!(merged.contains(test_text1));
-        assert!(merged.contains(test_text2));
-        assert!(merged.contains("\n\n"));
-    }
-
-    #[test]
-    fn test_invalid_docx_file() {
-        let temp_dir = tempdir().unwrap();
-        let file_path = temp_dir.path().join("invalid.docx");
-        let file_path_str = file_path.to_str().unwrap().to_string();
-        std::fs::write(&file_path, "Not a valid docx file").unwrap();
-        let result = extract_text_from_docx(&file_path_str, false);
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_strip_hyperlink_instr_text() {
-        // Create a DOCX file that contains a hyperlink field.
-        // Typically, a hyperlink field is stored as an instruction text (w:instrText)
-        // followed by the visible text.
-        let xml_content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
-            xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
-  <w:body>
-    <w:p>
-      <w:r>
-        <w:fldChar w:fldCharType="begin"/>
-      </w:r>
-      <w:r>
-        <w:instrText>HYPERLINK "https://example.com" \t "_blank"</w:instrText>
-      </w:r>
-      <w:r>
-        <w:fldChar w:fldCharType="separate"/>
-      </w:r>
-      <w:r>
-        <w:t>Visible Link Text</w:t>
-      </w:r>
-      <w:r>
-        <w:fldChar w:fldCharType="end"/>
-      </w:r>
-    </w:p>
-  </w:body>
-</w:document>"#;
-        let (_temp_dir, docx_path) = create_test_docx_with_xml(xml_content).unwrap();
-
-        // When strip_hyperlinks is true, the hyperlink field instruction should be omitted.
-        let extracted_with_strip = extract_text_from_docx(&docx_path, true).unwrap();
-        // When strip_hyperlinks is false, both the instruction and the visible text will appear.
-        let extracted_without_strip = extract_text_from_docx(&docx_path, false).unwrap();
-
-        assert!(!extracted_with_strip.contains("HYPERLINK"), "Instruction text should be stripped");
-        assert!(extracted_with_strip.contains("Visible Link Text"), "Visible text should be kept");
-        assert!(extracted_without_strip.contains("HYPERLINK"), "Instruction text is present when not stripping");
-        assert!(extracted_without_strip.contains("Visible Link Text"), "Visible text is present");
+    // Standard output is `None`, and gets nothing but the merged text.
+    // With --stats-only the text goes nowhere, and there is no path either.
+    let output_path = match args.output.as_deref() {
+        _ if args.stats_only || args.split_output.is_some() => None,
+        Some("-") => None,
+        Some(path) => Some(PathBuf::from(path)),
+        None if json => Some(PathBuf::from(DEFAULT_JSON_OUTPUT)),
+        None if json_lines => Some(PathBuf::from(DEFAULT_JSON_LINES_OUTPUT)),
+        None if html => Some(PathBuf::from(DEFAULT_HTML_OUTPUT)),
+        None => Some(PathBuf::from(DEFAULT_OUTPUT)),
+    };
+    if let Some(path) = &output_path {
+        check_output(path, &file_paths, args.force, args.output.is_some());
+    }
+    if let Some(media_dir) = &options.media_dir {
+        for path in &file_paths {
+            extract_media(path, media_dir, &options).unwrap_or_else(|error| fail(error));
+        }
+    }
+    if let Some(objects_dir) = &args.extract_objects {
+        for path in &file_paths {
+            extract_embedded_objects(path, objects_dir, &options).unwrap_or_else(|error| fail(error));
+        }
+    }
+
+    let mut merge_options = MergeOptions::new().threads(args.jobs.unwrap_or(0));
+    if let Some(separator) = args.separator.or(args.separator_preset) {
+        merge_options.separator = separator;
+    }
+    if args.skip_errors || args.error_markers {
+        merge_options.on_error = ErrorPolicy::SkipAndReport;
+    }
+    merge_options.error_markers = args.error_markers;
+    merge_options.file_header = args.file_header;
+    merge_options.file_footer = args.file_footer;
+    merge_options.chapter_headings = args.chapter_headings;
+    if let Some(dir) = &args.cache_dir {
+        let cache = DirCache::new(dir);
+        if args.cache_clear {
+            cache.clear().unwrap_or_else(|source| fail(MergeError::Io { path: dir.clone(), source }));
+        }
+        if !args.no_cache {
+            merge_options = merge_options.cache(cache);
+        }
+    }
+
+    let mut merge_options = MergeOptions {
+        extract: options,
+        stats: args.stats || args.stats_only,
+        ..merge_options
+    };
+    // Verbose output reports each file itself.
+    let show_progress =
+        !quiet && !verbose && std::io::stderr().is_terminal() && file_paths.len() > PROGRESS_MIN_FILES;
+    let progress = show_progress.then(|| {
+        let bar = ProgressBar::new(file_paths.len() as u64);
+        if let Ok(style) = ProgressStyle::with_template("{wide_bar} {pos}/{len} {msg}") {
+            bar.set_style(style);
+        }
+        bar
+    });
+    if let Some(bar) = progress.clone() {
+        merge_options = merge_options.on_progress(move |event| match event {
+            ProgressEvent::FileStarted { path, .. } => bar.set_message(path.display().to_string()),
+            ProgressEvent::FileFinished { .. } | ProgressEvent::FileFailed { .. } => bar.inc(1),
+        });
+    }
+    if let Some(dir) = &args.split_output {
+        let split_options = SplitOptions {
+            merge: merge_options,
+            name: args.output_name,
+            overwrite: args.force,
+            json,
+            pretty: args.extract.pretty,
+        };
+        let split = split_files(&file_paths, dir, &split_options);
+        if let Some(bar) = &progress {
+            bar.finish_and_clear();
+        }
+        let report = split.unwrap_or_else(|error| match error {
+            MergeError::Io { path, source } if source.kind() == std::io::ErrorKind::AlreadyExists => {
+                eprintln!(
+                    "{} already exists; use --force to overwrite it, or --output-name to name the files otherwise.",
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+            error => fail(error),
+        });
+        if split_options.merge.stats {
+            print_stats(&report.stats);
+        }
+        for warning in &report.warnings {
+            log::debug!("warning: {}", warning);
+        }
+        for file in &report.written {
+            log::info!("{} -> {}", file.path.display(), file.output.display());
+        }
+        log::info!(
+            "Wrote {} into {}{}",
+            files_written(file_paths.len(), report.skipped.len()),
+            dir.display(),
+            summary_end(&report.warnings, started.elapsed(), verbose)
+        );
+        exit_if_skipped(&report.skipped, file_paths.len());
+        return;
+    }
+
+    let output_error = |source| MergeError::Io {
+        path: output_path.clone().unwrap_or_else(|| "<stdout>".into()),
+        source,
+    };
+    let mut writer: BufWriter<Box<dyn Write>> = match &output_path {
+        _ if args.stats_only => BufWriter::new(Box::new(std::io::sink())),
+        Some(path) => {
+            let file = File::create(path).unwrap_or_else(|source| fail(output_error(source)));
+            BufWriter::new(Box::new(file))
+        }
+        None => BufWriter::new(Box::new(std::io::stdout().lock())),
+    };
+    // Each file's text is written as it is read, and a failure leaves no
+    // partial output file behind. JSON Lines output keeps the lines already
+    // written, since each is whole. A table of contents needs the whole
+    // text, so the text is kept until it is done.
+    let mut toc_text = Vec::new();
+    let merged = if json {
+        merge_files_to_json_writer(&file_paths, &mut writer, &merge_options, args.extract.pretty)
+    } else if json_lines {
+        merge_files_to_json_lines(&file_paths, &mut writer, &merge_options)
+    } else if html {
+        merge_files_to_html_writer(&file_paths, &mut writer, &merge_options, css.as_deref())
+    } else if args.toc_output {
+        merge_docx_files_to_writer(&file_paths, &mut toc_text, &merge_options)
+    } else {
+        merge_docx_files_to_writer(&file_paths, &mut writer, &merge_options)
+    };
+    if let Some(bar) = &progress {
+        bar.finish_and_clear();
+    }
+    let report = match merged {
+        Ok(report) => report,
+        Err(error) => {
+            drop(writer);
+            if let (Some(path), false) = (&output_path, json_lines) {
+                let _ = std::fs::remove_file(path);
+            }
+            match error {
+                MergeError::Write { source } => fail(output_error(source)),
+                error => fail(error),
+            }
+        }
+    };
+    if args.toc_output {
+        let text = with_table_of_contents(
+            &String::from_utf8_lossy(&toc_text),
+            &report.file_starts,
+            &merge_options.extract,
+        );
+        writer
+            .write_all(text.as_bytes())
+            .and_then(|()| writer.flush())
+            .unwrap_or_else(|source| fail(output_error(source)));
+    }
+    if merge_options.stats {
+        print_stats(&report.stats);
+    }
+    for warning in &report.warnings {
+        log::debug!("warning: {}", warning);
+    }
+    let files = files_written(file_paths.len(), report.skipped.len());
+    let done = match &output_path {
+        _ if args.stats_only => format!("Counted {}", files),
+        Some(path) => format!("Merged {} into {}", files, path.display()),
+        None => format!("Merged {} to standard output", files),
+    };
+    log::info!("{}{}", done, summary_end(&report.warnings, started.elapsed(), verbose));
+    exit_if_skipped(&report.skipped, file_paths.len());
+}
+
+/// How many of `total` files were written, as in "3 files" or "2 of 3
+/// files".
+fn files_written(total: usize, skipped: usize) -> String {
+    match total - skipped {
+        written if written == total => plural(written, "file"),
+        written => format!("{} of {}", written, plural(total, "file")),
+    }
+}
+
+/// Lists the files left out under --skip-errors, if there are any, and
+/// exits with status 7.
+fn exit_if_skipped(skipped: &[SkippedFile], total: usize) {
+    if !skipped.is_empty() {
+        eprintln!("{} of {} files could not be extracted and were left out:", skipped.len(), total);
+        for file in skipped {
+            eprintln!("  {}", file.error);
+        }
+        std::process::exit(SKIPPED_FILES_EXIT_CODE);
     }
 }
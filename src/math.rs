@@ -0,0 +1,212 @@
+use quick_xml::events::BytesStart;
+
+use crate::attribute;
+
+/// An OMML element being linearized: its own text, which includes the
+/// rendering of its children, and the children by name for the structures
+/// that arrange them (`m:f`, `m:sSup`, ...).
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    text: String,
+    parts: Vec<(String, String)>,
+    /// Characters set in the element's properties (`m:begChr`, `m:chr`, ...).
+    chars: Vec<(String, String)>,
+}
+
+impl Element {
+    fn part(&self, name: &str) -> &str {
+        self.parts
+            .iter()
+            .find(|(part, _)| part == name)
+            .map_or("", |(_, text)| text.as_str())
+    }
+
+    fn parts_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.parts
+            .iter()
+            .filter(move |(part, _)| part == name)
+            .map(|(_, text)| text.as_str())
+    }
+
+    fn char_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.chars
+            .iter()
+            .find(|(property, _)| property == name)
+            .map_or(default, |(_, value)| value.as_str())
+    }
+
+    /// The linear form of the element, e.g. `(a+b)/2` for a fraction.
+    fn render(&self) -> String {
+        match self.name.as_str() {
+            "f" => format!("{}/{}", group(self.part("num")), group(self.part("den"))),
+            "sSup" => format!("{}^{}", group(self.part("e")), group(self.part("sup"))),
+            "sSub" => format!("{}_{}", group(self.part("e")), group(self.part("sub"))),
+            "sSubSup" => format!(
+                "{}_{}^{}",
+                group(self.part("e")),
+                group(self.part("sub")),
+                group(self.part("sup"))
+            ),
+            "sPre" => format!(
+                "_{}^{}{}",
+                group(self.part("sub")),
+                group(self.part("sup")),
+                group(self.part("e"))
+            ),
+            "rad" => match self.part("deg") {
+                "" => format!("√{}", group(self.part("e"))),
+                degree => format!("√[{}]{}", degree, group(self.part("e"))),
+            },
+            "d" => {
+                let separator = self.char_or("sepChr", "|");
+                let elements: Vec<&str> = self.parts_named("e").collect();
+                format!(
+                    "{}{}{}",
+                    self.char_or("begChr", "("),
+                    elements.join(separator),
+                    self.char_or("endChr", ")")
+                )
+            }
+            "nary" => {
+                let mut text = self.char_or("chr", "∫").to_string();
+                if !self.part("sub").is_empty() {
+                    text.push_str(&format!("_{}", group(self.part("sub"))));
+                }
+                if !self.part("sup").is_empty() {
+                    text.push_str(&format!("^{}", group(self.part("sup"))));
+                }
+                format!("{} {}", text, self.part("e"))
+            }
+            "func" => {
+                let argument = self.part("e");
+                if argument.starts_with('(') {
+                    format!("{}{}", self.part("fName"), argument)
+                } else {
+                    format!("{}({})", self.part("fName"), argument)
+                }
+            }
+            "limLow" => format!("{}_{}", self.part("e"), group(self.part("lim"))),
+            "limUpp" => format!("{}^{}", self.part("e"), group(self.part("lim"))),
+            "m" => {
+                let rows: Vec<&str> = self.parts_named("mr").collect();
+                format!("[{}]", rows.join("; "))
+            }
+            "mr" => self.parts_named("e").collect::<Vec<_>>().join(", "),
+            "eqArr" => self.parts_named("e").collect::<Vec<_>>().join("; "),
+            _ => self.text.clone(),
+        }
+    }
+}
+
+/// Parenthesizes an operand unless it is a single number or identifier, or
+/// already enclosed.
+fn group(operand: &str) -> String {
+    let simple = operand.chars().all(|c| c.is_alphanumeric() || c == '.')
+        || (operand.starts_with('(') && operand.ends_with(')'));
+    if simple && !operand.is_empty() {
+        operand.to_string()
+    } else {
+        format!("({})", operand)
+    }
+}
+
+/// Builds a best-effort linear form of an equation (`m:oMath`) from the
+/// events inside it.
+#[derive(Debug)]
+pub struct Equation {
+    stack: Vec<Element>,
+    /// Depth of property elements (`m:fPr`, `w:rPr`, ...), which hold no text.
+    properties_depth: usize,
+    in_text: bool,
+}
+
+impl Equation {
+    pub fn new() -> Self {
+        Equation {
+            stack: vec![Element::default()],
+            properties_depth: 0,
+            in_text: false,
+        }
+    }
+
+    pub fn start(&mut self, e: &BytesStart) {
+        let name = local_name(e);
+        if self.properties_depth > 0 || name.ends_with("Pr") {
+            self.properties_depth += 1;
+            return;
+        }
+        if name == "t" {
+            self.in_text = true;
+        }
+        self.stack.push(Element {
+            name,
+            ..Element::default()
+        });
+    }
+
+    pub fn end(&mut self) {
+        if self.properties_depth > 0 {
+            self.properties_depth -= 1;
+            return;
+        }
+        self.in_text = false;
+        if self.stack.len() < 2 {
+            return;
+        }
+        if let Some(element) = self.stack.pop() {
+            let rendered = element.render();
+            if let Some(parent) = self.stack.last_mut() {
+                parent.text.push_str(&rendered);
+                parent.parts.push((element.name, rendered));
+            }
+        }
+    }
+
+    pub fn empty(&mut self, e: &BytesStart) {
+        let name = local_name(e);
+        if self.properties_depth > 0 {
+            // Characters of delimiters, n-ary operators and accents.
+            if let (Some(element), Some(value)) = (self.stack.last_mut(), attribute(e, b"m:val")) {
+                element.chars.push((name, value));
+            }
+        } else if !name.ends_with("Pr") {
+            self.start(e);
+            self.end();
+        }
+    }
+
+    pub fn text(&mut self, content: &str) {
+        if self.in_text {
+            if let Some(element) = self.stack.last_mut() {
+                element.text.push_str(content);
+            }
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.stack
+            .into_iter()
+            .next()
+            .map(|root| root.text)
+            .unwrap_or_default()
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group() {
+        assert_eq!(group("x"), "x");
+        assert_eq!(group("12"), "12");
+        assert_eq!(group("a+b"), "(a+b)");
+        assert_eq!(group("(a+b)"), "(a+b)");
+        assert_eq!(group(""), "()");
+    }
+}
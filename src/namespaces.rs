@@ -0,0 +1,328 @@
+use std::io::BufRead;
+
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::name::{LocalName, Namespace, QName, ResolveResult};
+use quick_xml::NsReader;
+
+use crate::error::XmlError;
+
+/// The namespaces extraction understands, by the prefix the parsers match
+/// them with. Documents may bind them to any prefix, or make one the
+/// default namespace; strict OOXML uses its own URIs for the same markup.
+/// Package-level namespaces have no prefix, as they are usually default.
+const NAMESPACES: &[(&str, &str)] = &[
+    (
+        "w",
+        "http://schemas.openxmlformats.org/wordprocessingml/2006/main",
+    ),
+    ("w", "http://purl.oclc.org/ooxml/wordprocessingml/main"),
+    (
+        "r",
+        "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+    ),
+    (
+        "r",
+        "http://purl.oclc.org/ooxml/officeDocument/relationships",
+    ),
+    (
+        "m",
+        "http://schemas.openxmlformats.org/officeDocument/2006/math",
+    ),
+    ("m", "http://purl.oclc.org/ooxml/officeDocument/math"),
+    (
+        "wp",
+        "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing",
+    ),
+    (
+        "wp",
+        "http://purl.oclc.org/ooxml/drawingml/wordprocessingDrawing",
+    ),
+    ("a", "http://schemas.openxmlformats.org/drawingml/2006/main"),
+    ("a", "http://purl.oclc.org/ooxml/drawingml/main"),
+    (
+        "pic",
+        "http://schemas.openxmlformats.org/drawingml/2006/picture",
+    ),
+    ("pic", "http://purl.oclc.org/ooxml/drawingml/picture"),
+    (
+        "mc",
+        "http://schemas.openxmlformats.org/markup-compatibility/2006",
+    ),
+    (
+        "w14",
+        "http://schemas.microsoft.com/office/word/2010/wordml",
+    ),
+    (
+        "w15",
+        "http://schemas.microsoft.com/office/word/2012/wordml",
+    ),
+    (
+        "wp14",
+        "http://schemas.microsoft.com/office/word/2010/wordprocessingDrawing",
+    ),
+    (
+        "wps",
+        "http://schemas.microsoft.com/office/word/2010/wordprocessingShape",
+    ),
+    (
+        "wpg",
+        "http://schemas.microsoft.com/office/word/2010/wordprocessingGroup",
+    ),
+    (
+        "wpc",
+        "http://schemas.microsoft.com/office/word/2010/wordprocessingCanvas",
+    ),
+    (
+        "a14",
+        "http://schemas.microsoft.com/office/drawing/2010/main",
+    ),
+    ("v", "urn:schemas-microsoft-com:vml"),
+    ("o", "urn:schemas-microsoft-com:office:office"),
+    ("xml", "http://www.w3.org/XML/1998/namespace"),
+    (
+        "cp",
+        "http://schemas.openxmlformats.org/package/2006/metadata/core-properties",
+    ),
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    (
+        "",
+        "http://schemas.openxmlformats.org/officeDocument/2006/extended-properties",
+    ),
+    (
+        "",
+        "http://purl.oclc.org/ooxml/officeDocument/extendedProperties",
+    ),
+    (
+        "",
+        "http://schemas.openxmlformats.org/officeDocument/2006/custom-properties",
+    ),
+    (
+        "",
+        "http://purl.oclc.org/ooxml/officeDocument/customProperties",
+    ),
+    (
+        "vt",
+        "http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes",
+    ),
+    (
+        "vt",
+        "http://purl.oclc.org/ooxml/officeDocument/docPropsVTypes",
+    ),
+    (
+        "",
+        "http://schemas.openxmlformats.org/package/2006/relationships",
+    ),
+    (
+        "",
+        "http://schemas.openxmlformats.org/package/2006/content-types",
+    ),
+];
+
+/// The prefix the parsers use for a namespace, if it is one they know.
+fn canonical_prefix(namespace: Namespace) -> Option<&'static str> {
+    NAMESPACES
+        .iter()
+        .find(|(_, uri)| uri.as_bytes() == namespace.as_ref())
+        .map(|(prefix, _)| *prefix)
+}
+
+/// Reads XML with the names of elements and attributes rewritten to the
+/// prefixes of [`NAMESPACES`], so that parsers can match `w:p` whatever
+/// prefix the document binds the WordprocessingML namespace to. Names in
+/// other namespaces are written `{uri}name`, so they never match, whatever
+/// their prefix; names in no namespace keep the name they have.
+///
+/// The XML is read from a string, or streamed from any buffered reader,
+/// such as the decompressor of a zip entry, without holding all of it.
+/// XML that ends with elements still open, as a truncated part does, is
+/// an error.
+pub struct WordReader<R> {
+    reader: NsReader<R>,
+    /// Holds the event last read.
+    buf: Vec<u8>,
+    /// The canonical names of the elements open, innermost last.
+    open: Vec<String>,
+}
+
+impl<'a> WordReader<&'a [u8]> {
+    pub fn from_str(xml: &'a str) -> Self {
+        WordReader::from_reader(xml.as_bytes())
+    }
+}
+
+impl<R: BufRead> WordReader<R> {
+    pub fn from_reader(reader: R) -> Self {
+        WordReader {
+            reader: NsReader::from_reader(reader),
+            buf: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    /// Reads the next event, with canonical names in start, end and empty
+    /// element events. The event borrows the reader until the next.
+    pub fn read_event(&mut self) -> Result<Event<'_>, XmlError> {
+        self.buf.clear();
+        let reader = &mut self.reader;
+        let event = reader
+            .read_event_into(&mut self.buf)
+            .map_err(|source| error_at(reader, source))?;
+        let reader = &self.reader;
+        Ok(match event {
+            Event::Start(e) => {
+                let start = canonical_start(reader, &e)?;
+                self.open
+                    .push(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+                Event::Start(start)
+            }
+            Event::Empty(e) => Event::Empty(canonical_start(reader, &e)?),
+            Event::End(e) => {
+                self.open.pop();
+                let (namespace, local_name) = reader.resolve_element(e.name());
+                Event::End(BytesEnd::new(canonical_name(
+                    namespace,
+                    local_name,
+                    e.name(),
+                )))
+            }
+            Event::Eof => match self.open.pop() {
+                Some(name) => {
+                    return Err(error_at(reader, quick_xml::Error::UnexpectedEof(name)));
+                }
+                None => Event::Eof,
+            },
+            event => event,
+        })
+    }
+
+    /// An error at the current position, such as text that does not
+    /// unescape.
+    pub fn error(&self, source: impl Into<quick_xml::Error>) -> XmlError {
+        error_at(&self.reader, source)
+    }
+}
+
+/// An error at the position `reader` has read to.
+fn error_at<R>(reader: &NsReader<R>, source: impl Into<quick_xml::Error>) -> XmlError {
+    XmlError {
+        position: reader.buffer_position(),
+        source: source.into(),
+    }
+}
+
+/// A start tag with the canonical names of the element and its attributes.
+fn canonical_start<R>(
+    reader: &NsReader<R>,
+    e: &BytesStart,
+) -> Result<BytesStart<'static>, XmlError> {
+    let (namespace, local_name) = reader.resolve_element(e.name());
+    let name = canonical_name(namespace, local_name, e.name());
+    let mut start = BytesStart::new(name.clone());
+    for attr in e.attributes() {
+        let attr = attr.map_err(|source| error_at(reader, source))?;
+        let key = attr.key.as_ref();
+        // Namespace declarations stay as they are; nothing matches them.
+        if key == b"xmlns" || key.starts_with(b"xmlns:") {
+            start.push_attribute((key, attr.value.as_ref()));
+            continue;
+        }
+        let (namespace, local_name) = reader.resolve_attribute(attr.key);
+        let key = canonical_name(namespace, local_name, attr.key);
+        if name == "mc:Choice" && key == "Requires" {
+            let value = attr
+                .unescape_value()
+                .map_err(|source| error_at(reader, source))?;
+            let requires = canonical_prefixes(reader, &value);
+            start.push_attribute((key.as_str(), requires.as_str()));
+        } else {
+            start.push_attribute((key.as_bytes(), attr.value.as_ref()));
+        }
+    }
+    Ok(start)
+}
+
+/// Rewrites a list of prefixes, such as the `Requires` of an
+/// `mc:Choice`, to the prefixes of [`NAMESPACES`].
+fn canonical_prefixes<R>(reader: &NsReader<R>, prefixes: &str) -> String {
+    prefixes
+        .split_whitespace()
+        .map(|prefix| {
+            let name = format!("{}:_", prefix);
+            match reader.resolve_element(QName(name.as_bytes())) {
+                (ResolveResult::Bound(namespace), _) => {
+                    canonical_prefix(namespace).unwrap_or(prefix)
+                }
+                _ => prefix,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The name the parsers know an element or attribute by: its local name
+/// with the canonical prefix of its namespace. A prefix that was never
+/// declared is kept as it is, `name` being all there is to go by.
+fn canonical_name(namespace: ResolveResult, local_name: LocalName, name: QName) -> String {
+    let local_name = String::from_utf8_lossy(local_name.as_ref());
+    match namespace {
+        ResolveResult::Bound(namespace) => match canonical_prefix(namespace) {
+            Some("") => local_name.into_owned(),
+            Some(prefix) => format!("{}:{}", prefix, local_name),
+            None => format!(
+                "{{{}}}{}",
+                String::from_utf8_lossy(namespace.as_ref()),
+                local_name
+            ),
+        },
+        ResolveResult::Unbound => local_name.into_owned(),
+        ResolveResult::Unknown(_) => String::from_utf8_lossy(name.as_ref()).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribute;
+
+    #[test]
+    fn test_names_use_canonical_prefixes() {
+        let xml = r#"<ns0:document xmlns:ns0="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:x="urn:other" xmlns:shape="http://schemas.microsoft.com/office/word/2010/wordprocessingShape" xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006"><ns0:p ns0:rsidR="1" x:a="b"/><mc:Choice Requires="shape x"/><x:p/></ns0:document>"#;
+        let mut reader = WordReader::from_str(xml);
+        let mut names = Vec::new();
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(e) | Event::Empty(e) => {
+                    names.push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                    if let Some(value) = attribute(&e, b"w:rsidR") {
+                        names.push(value);
+                    }
+                    if let Some(value) = attribute(&e, b"{urn:other}a") {
+                        names.push(value);
+                    }
+                    if let Some(value) = attribute(&e, b"Requires") {
+                        names.push(value);
+                    }
+                }
+                Event::End(e) => {
+                    names.push(format!("/{}", String::from_utf8_lossy(e.name().as_ref())))
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        assert_eq!(
+            names,
+            [
+                "w:document",
+                "w:p",
+                "1",
+                "b",
+                "mc:Choice",
+                "wps x",
+                "{urn:other}p",
+                "/w:document"
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+
+use crate::error::XmlError;
+use crate::namespaces::WordReader;
+use crate::{attribute, roman_numeral};
+
+/// One level of a list definition (`w:lvl`).
+#[derive(Debug, Clone)]
+struct Level {
+    start: usize,
+    /// `w:numFmt`: `decimal`, `lowerLetter`, `bullet`, ...
+    format: String,
+    /// `w:lvlText`: the label with `%1`..`%9` standing for the counters of
+    /// the levels, or the bullet character.
+    text: String,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            start: 1,
+            format: "decimal".to_string(),
+            text: String::new(),
+        }
+    }
+}
+
+/// A list instance (`w:num`): an abstract definition plus overrides.
+#[derive(Debug, Clone, Default)]
+struct Instance {
+    abstract_id: String,
+    /// `w:startOverride` values by level, which restart the list.
+    start_overrides: HashMap<usize, usize>,
+}
+
+/// The list definitions of `word/numbering.xml`.
+#[derive(Debug, Clone, Default)]
+pub struct Numbering {
+    /// Levels of each abstract definition (`w:abstractNum`) by level index.
+    abstract_levels: HashMap<String, HashMap<usize, Level>>,
+    instances: HashMap<String, Instance>,
+}
+
+impl Numbering {
+    /// Parses the XML of a numbering part.
+    pub fn parse(xml: &str) -> Result<Self, XmlError> {
+        let mut reader = WordReader::from_str(xml);
+        let mut numbering = Numbering::default();
+        let mut abstract_id: Option<String> = None;
+        let mut level: Option<(usize, Level)> = None;
+        let mut instance: Option<(String, Instance)> = None;
+        let mut override_level: Option<usize> = None;
+        loop {
+            match reader.read_event()? {
+                Event::Start(ref e) => match e.name().as_ref() {
+                    b"w:abstractNum" => abstract_id = attribute(e, b"w:abstractNumId"),
+                    b"w:lvl" if override_level.is_none() => {
+                        level = Some((level_index(e), Level::default()));
+                    }
+                    b"w:num" => {
+                        instance = attribute(e, b"w:numId").map(|id| (id, Instance::default()));
+                    }
+                    b"w:lvlOverride" => override_level = Some(level_index(e)),
+                    _ => {}
+                },
+                Event::Empty(ref e) => {
+                    let value = attribute(e, b"w:val");
+                    match (e.name().as_ref(), value) {
+                        (b"w:start", Some(value)) => {
+                            if let (Some((_, level)), Ok(start)) = (level.as_mut(), value.parse()) {
+                                level.start = start;
+                            }
+                        }
+                        (b"w:numFmt", Some(value)) => {
+                            if let Some((_, level)) = level.as_mut() {
+                                level.format = value;
+                            }
+                        }
+                        (b"w:lvlText", Some(value)) => {
+                            if let Some((_, level)) = level.as_mut() {
+                                level.text = value;
+                            }
+                        }
+                        (b"w:abstractNumId", Some(value)) => {
+                            if let Some((_, instance)) = instance.as_mut() {
+                                instance.abstract_id = value;
+                            }
+                        }
+                        (b"w:startOverride", Some(value)) => {
+                            if let (Some((_, instance)), Some(index), Ok(start)) =
+                                (instance.as_mut(), override_level, value.parse())
+                            {
+                                instance.start_overrides.insert(index, start);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::End(ref e) => match e.name().as_ref() {
+                    b"w:lvl" => {
+                        if let (Some(id), Some((index, level))) = (&abstract_id, level.take()) {
+                            numbering
+                                .abstract_levels
+                                .entry(id.clone())
+                                .or_default()
+                                .insert(index, level);
+                        }
+                    }
+                    b"w:abstractNum" => abstract_id = None,
+                    b"w:lvlOverride" => override_level = None,
+                    b"w:num" => {
+                        if let Some((id, instance)) = instance.take() {
+                            numbering.instances.insert(id, instance);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(numbering)
+    }
+
+    fn level(&self, num_id: &str, index: usize) -> Option<&Level> {
+        let instance = self.instances.get(num_id)?;
+        self.abstract_levels.get(&instance.abstract_id)?.get(&index)
+    }
+
+    /// Whether the paragraphs of a list level are numbered, rather than
+    /// bulleted or unlabelled.
+    pub fn numbered(&self, num_id: &str, index: usize) -> bool {
+        self.level(num_id, index)
+            .is_some_and(|level| !matches!(level.format.as_str(), "bullet" | "none"))
+    }
+}
+
+/// The counters of the lists read so far, per list instance and level.
+#[derive(Debug, Default)]
+pub struct ListCounters {
+    counters: HashMap<String, Vec<usize>>,
+}
+
+impl ListCounters {
+    /// Advances the counter of a list paragraph at the given level and
+    /// returns its label, e.g. `2.`, `b)` or `•`. Counters of deeper levels
+    /// start over, as they do in Word.
+    pub fn next_label(&mut self, numbering: &Numbering, num_id: &str, index: usize) -> Option<String> {
+        let level = numbering.level(num_id, index)?;
+        let instance = numbering.instances.get(num_id)?;
+        let start = |index: usize| {
+            instance
+                .start_overrides
+                .get(&index)
+                .copied()
+                .or_else(|| numbering.level(num_id, index).map(|level| level.start))
+                .unwrap_or(1)
+        };
+        let counters = self.counters.entry(num_id.to_string()).or_default();
+        if counters.len() <= index {
+            // Skipped levels count as having reached their start value.
+            while counters.len() < index {
+                counters.push(start(counters.len()));
+            }
+            counters.push(start(index));
+        } else {
+            counters.truncate(index + 1);
+            counters[index] += 1;
+        }
+        if level.format == "bullet" {
+            return Some(bullet(&level.text));
+        }
+        let mut label = level.text.clone();
+        for (number, counter) in counters.iter().enumerate() {
+            let format = numbering
+                .level(num_id, number)
+                .map_or("decimal", |level| level.format.as_str());
+            label = label.replace(&format!("%{}", number + 1), &format_number(*counter, format));
+        }
+        Some(label)
+    }
+
+    /// The counter of a list level as [`next_label`](Self::next_label)
+    /// last advanced it, e.g. 2 for the label `b)`.
+    pub fn counter(&self, num_id: &str, index: usize) -> Option<usize> {
+        self.counters.get(num_id)?.get(index).copied()
+    }
+}
+
+fn level_index(e: &BytesStart) -> usize {
+    attribute(e, b"w:ilvl")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Formats a list counter in a `w:numFmt` style. Formats without a plain
+/// text equivalent fall back to decimal.
+fn format_number(number: usize, format: &str) -> String {
+    match format {
+        "none" => String::new(),
+        "decimalZero" => format!("{:02}", number),
+        "lowerLetter" | "upperLetter" => {
+            // a..z, then aa..zz, and so on.
+            let letter = (b'a' + ((number.max(1) - 1) % 26) as u8) as char;
+            let label = letter.to_string().repeat((number.max(1) - 1) / 26 + 1);
+            if format == "upperLetter" {
+                label.to_uppercase()
+            } else {
+                label
+            }
+        }
+        "lowerRoman" => roman_numeral(number).to_lowercase(),
+        "upperRoman" => roman_numeral(number),
+        _ => number.to_string(),
+    }
+}
+
+/// Maps bullet characters from symbol fonts, which live in the private use
+/// area, to their Unicode look-alikes.
+fn bullet(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{F0A7}' => '▪',
+            '\u{F0D8}' => '➢',
+            '\u{F0FC}' => '✓',
+            '\u{F076}' => '❖',
+            '\u{F000}'..='\u{F0FF}' => '•',
+            _ => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(3, "decimal"), "3");
+        assert_eq!(format_number(2, "lowerLetter"), "b");
+        assert_eq!(format_number(28, "upperLetter"), "BB");
+        assert_eq!(format_number(4, "lowerRoman"), "iv");
+        assert_eq!(format_number(7, "decimalZero"), "07");
+        assert_eq!(bullet("\u{F0B7}"), "•");
+    }
+}
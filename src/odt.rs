@@ -0,0 +1,293 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::XmlError;
+use crate::{attribute, invisible, punctuation, ExtractOptions, OutputFormat};
+
+/// Media types (the `mimetype` entry) of OpenDocument text documents and
+/// templates.
+pub const ODT_MEDIA_TYPES: [&str; 2] = [
+    "application/vnd.oasis.opendocument.text",
+    "application/vnd.oasis.opendocument.text-template",
+];
+
+/// Accumulates the text of an OpenDocument `content.xml`. Unlike
+/// WordprocessingML, text sits directly in paragraphs (`text:p`, `text:h`)
+/// and spans, so this only needs to track paragraphs, links and tables.
+struct OdtCollector<'a> {
+    options: &'a ExtractOptions,
+    text: String,
+    paragraphs: Vec<String>,
+    /// Open links: their target and where their text starts in the open
+    /// paragraph.
+    links: Vec<(Option<String>, usize)>,
+    /// Rows of the open tables, innermost table last, and where the open
+    /// cell's text starts.
+    tables: Vec<(Vec<Vec<String>>, usize)>,
+    /// Depth of elements whose content is not part of the text: notes,
+    /// annotations and the record of tracked changes.
+    skipped_depth: usize,
+}
+
+impl<'a> OdtCollector<'a> {
+    fn start(&mut self, e: &BytesStart) {
+        if self.skipped_depth > 0 {
+            self.skipped_depth += 1;
+            return;
+        }
+        match e.name().as_ref() {
+            b"text:p" | b"text:h" => {
+                self.paragraphs.push(String::new());
+                self.heading_marker(e);
+            }
+            b"text:a" => {
+                let start = self.paragraphs.last().map_or(0, String::len);
+                self.links.push((attribute(e, b"xlink:href"), start));
+            }
+            b"table:table" => self.tables.push((Vec::new(), 0)),
+            b"table:table-row" => {
+                if let Some((rows, _)) = self.tables.last_mut() {
+                    rows.push(Vec::new());
+                }
+            }
+            b"table:table-cell" => {
+                let start = self.text.len();
+                if let Some((_, cell_start)) = self.tables.last_mut() {
+                    *cell_start = start;
+                }
+            }
+            b"text:note" | b"office:annotation" | b"text:tracked-changes" => {
+                self.skipped_depth = 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        if self.skipped_depth > 0 {
+            self.skipped_depth -= 1;
+            return;
+        }
+        match name {
+            b"text:p" | b"text:h" => {
+                if let Some(paragraph) = self.paragraphs.pop() {
+                    if self.options.nfc {
+                        self.text.extend(paragraph.trim().nfc());
+                    } else {
+                        self.text.push_str(paragraph.trim());
+                    }
+                    self.text.push_str(&self.options.paragraph_separator);
+                }
+            }
+            b"text:a" => self.end_link(),
+            b"table:table-cell" => self.end_cell(),
+            b"table:table" => self.end_table(),
+            _ => {}
+        }
+    }
+
+    fn empty(&mut self, e: &BytesStart) {
+        if self.skipped_depth > 0 {
+            return;
+        }
+        match e.name().as_ref() {
+            b"text:p" | b"text:h" => self.text.push_str(&self.options.paragraph_separator),
+            b"text:tab" => {
+                let options = self.options;
+                self.push(&options.tab_string);
+            }
+            b"text:line-break" => self.push("\n"),
+            // `text:s` stands for `text:c` spaces, which would otherwise
+            // collapse into one.
+            b"text:s" => {
+                let count = attribute(e, b"text:c")
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or(1);
+                self.push(&" ".repeat(count));
+            }
+            b"table:table-cell" | b"table:covered-table-cell" => {
+                if let Some((rows, _)) = self.tables.last_mut() {
+                    if let Some(row) = rows.last_mut() {
+                        row.push(String::new());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Appends character data to the open paragraph. Outside paragraphs
+    /// character data is only formatting whitespace. Inside them, runs of
+    /// whitespace collapse into a single space, as ODF specifies.
+    fn text(&mut self, content: &str) {
+        if self.skipped_depth > 0 || self.paragraphs.is_empty() {
+            return;
+        }
+        let mut collapsed = String::with_capacity(content.len());
+        for c in content.chars() {
+            // Only XML whitespace collapses; no-break spaces are text.
+            if matches!(c, ' ' | '\t' | '\n' | '\r') {
+                if !collapsed.ends_with(' ') {
+                    collapsed.push(' ');
+                }
+            } else {
+                collapsed.push(c);
+            }
+        }
+        let paragraph = self.paragraphs.last().map_or("", String::as_str);
+        if paragraph.is_empty() || paragraph.ends_with(' ') {
+            collapsed = collapsed.trim_start_matches(' ').to_string();
+        }
+        if self.options.normalize_punctuation {
+            collapsed = punctuation::to_ascii(&collapsed).into_owned();
+        }
+        if self.options.strip_invisible {
+            collapsed = invisible::strip_invisible(&collapsed).0.into_owned();
+        }
+        self.push(&collapsed);
+    }
+
+    fn push(&mut self, content: &str) {
+        if let Some(paragraph) = self.paragraphs.last_mut() {
+            paragraph.push_str(content);
+        }
+    }
+
+    /// Marks headings (`text:h`) by their outline level, as the DOCX
+    /// extraction does for heading styles.
+    fn heading_marker(&mut self, e: &BytesStart) {
+        if e.name().as_ref() != b"text:h" {
+            return;
+        }
+        let level: usize = attribute(e, b"text:outline-level")
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(1);
+        match self.options.format {
+            OutputFormat::Markdown => self.push(&format!("{} ", "#".repeat(level))),
+            OutputFormat::Text if self.options.mark_headings => {
+                self.push(&format!("[H{}] ", level))
+            }
+            OutputFormat::Text => {}
+        }
+    }
+
+    fn end_link(&mut self) {
+        let Some((target, start)) = self.links.pop() else {
+            return;
+        };
+        let (Some(target), Some(paragraph)) = (target, self.paragraphs.last_mut()) else {
+            return;
+        };
+        if start > paragraph.len() {
+            return;
+        }
+        let text = paragraph.split_off(start);
+        match self.options.link_style.render(&text, &target) {
+            Some(rendered) => paragraph.push_str(&rendered),
+            None => paragraph.push_str(&text),
+        }
+    }
+
+    /// Moves the text of the cell into its row, on a single line.
+    fn end_cell(&mut self) {
+        let Some((rows, start)) = self.tables.last_mut() else {
+            return;
+        };
+        let start = (*start).min(self.text.len());
+        let text = self
+            .text
+            .split_off(start)
+            .replace(self.options.paragraph_separator.as_str(), "\n");
+        let cell = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(row) = rows.last_mut() {
+            row.push(cell);
+        }
+    }
+
+    fn end_table(&mut self) {
+        let Some((rows, _)) = self.tables.pop() else {
+            return;
+        };
+        let delimiter = if self.tables.is_empty() {
+            self.options.table_delimiter.as_str()
+        } else {
+            " "
+        };
+        for row in &rows {
+            self.text.push_str(&row.join(delimiter));
+            self.text.push_str(&self.options.paragraph_separator);
+        }
+    }
+}
+
+/// Extracts the text of an OpenDocument text document from its
+/// `content.xml`. Paragraph, tab, link, table and heading options apply as
+/// they do to Word documents; notes, comments and tracked deletions are
+/// left out.
+pub fn extract_odt_text(xml: &str, options: &ExtractOptions) -> Result<String, XmlError> {
+    let mut collector = OdtCollector {
+        options,
+        text: String::new(),
+        paragraphs: Vec::new(),
+        links: Vec::new(),
+        tables: Vec::new(),
+        skipped_depth: 0,
+    };
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let error = |reader: &Reader<&[u8]>, source| XmlError {
+        position: reader.buffer_position(),
+        source,
+    };
+    // Cancelling stops reading; the caller tells it from the end.
+    while !options.is_cancelled() {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => collector.start(e),
+            Ok(Event::End(ref e)) => collector.end(e.name().as_ref()),
+            Ok(Event::Empty(ref e)) => collector.empty(e),
+            Ok(Event::Text(e)) => match e.unescape() {
+                Ok(text) => collector.text(&text),
+                Err(source) => return Err(error(&reader, source)),
+            },
+            Ok(Event::Eof) => break,
+            Err(source) => return Err(error(&reader, source)),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(collector.text.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odt_paragraphs_spaces_and_links() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <office:body>
+    <office:text>
+      <text:h text:outline-level="2">Overview</text:h>
+      <text:p>Two<text:s text:c="2"/>spaces,   collapsed
+        whitespace<text:tab/>and a <text:span>span</text:span>.</text:p>
+      <text:p>See <text:a xlink:href="https://example.com">the site</text:a><text:note><text:note-body><text:p>A note.</text:p></text:note-body></text:note>.</text:p>
+      <table:table>
+        <table:table-row><table:table-cell><text:p>a</text:p></table:table-cell><table:table-cell><text:p>b</text:p></table:table-cell></table:table-row>
+      </table:table>
+    </office:text>
+  </office:body>
+</office:document-content>"#;
+        let text = extract_odt_text(xml, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            text,
+            "Overview\nTwo  spaces, collapsed whitespace\tand a span.\nSee the site <https://example.com>.\na\tb"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+#[cfg(feature = "cli")]
+use quick_xml::events::Event;
+
+#[cfg(feature = "cli")]
+use crate::attribute;
+#[cfg(feature = "cli")]
+use crate::error::XmlError;
+#[cfg(feature = "cli")]
+use crate::namespaces::WordReader;
+
+/// The value of a custom document property (`docProps/custom.xml`), such as
+/// a contract number a template stamps into the documents made from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyValue {
+    /// Text (`vt:lpwstr`, `vt:lpstr` or `vt:bstr`).
+    Text(String),
+    /// A whole number (`vt:i4` and the other integer types).
+    Integer(i64),
+    /// Yes or no (`vt:bool`).
+    Bool(bool),
+    /// A date and time (`vt:filetime`), as written, e.g.
+    /// `2024-05-01T00:00:00Z`.
+    DateTime(String),
+    /// A value of another type, or one that is not valid for its type, as
+    /// written.
+    Other(String),
+}
+
+#[cfg(feature = "cli")]
+impl PropertyValue {
+    /// Reads the text of a value of type `vt_type`, such as `vt:i4`.
+    fn parse(vt_type: &str, text: String) -> Self {
+        let parsed = match vt_type {
+            "vt:lpwstr" | "vt:lpstr" | "vt:bstr" => return PropertyValue::Text(text),
+            "vt:filetime" | "vt:date" => return PropertyValue::DateTime(text.trim().to_string()),
+            "vt:i1" | "vt:i2" | "vt:i4" | "vt:i8" | "vt:int" | "vt:ui1" | "vt:ui2" | "vt:ui4"
+            | "vt:ui8" | "vt:uint" => text.trim().parse().ok().map(PropertyValue::Integer),
+            "vt:bool" => match text.trim() {
+                "true" | "1" => Some(PropertyValue::Bool(true)),
+                "false" | "0" => Some(PropertyValue::Bool(false)),
+                _ => None,
+            },
+            _ => None,
+        };
+        parsed.unwrap_or(PropertyValue::Other(text))
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PropertyValue::Text(text)
+            | PropertyValue::DateTime(text)
+            | PropertyValue::Other(text) => f.write_str(text),
+            PropertyValue::Integer(value) => write!(f, "{}", value),
+            PropertyValue::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Parses the XML of a custom properties part into the properties by name,
+/// in the order they are written.
+#[cfg(feature = "cli")]
+pub fn read_custom_properties(xml: &str) -> Result<Vec<(String, PropertyValue)>, XmlError> {
+    let mut reader = WordReader::from_str(xml);
+    let mut properties = Vec::new();
+    // The name of the property being read, and the type and text of its
+    // value once it starts.
+    let mut name: Option<String> = None;
+    let mut value: Option<(String, String)> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) if e.name().as_ref() == b"property" => {
+                name = attribute(e, b"name");
+                value = None;
+            }
+            Event::Start(ref e) if name.is_some() && value.is_none() => {
+                let vt_type = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                value = Some((vt_type, String::new()));
+            }
+            Event::Empty(ref e) if name.is_some() && value.is_none() => {
+                let vt_type = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                value = Some((vt_type, String::new()));
+            }
+            Event::Text(e) => {
+                if let Some((_, text)) = value.as_mut() {
+                    match e.unescape() {
+                        Ok(unescaped) => text.push_str(&unescaped),
+                        Err(error) => return Err(reader.error(error)),
+                    }
+                }
+            }
+            Event::End(ref e) if e.name().as_ref() == b"property" => {
+                if let (Some(name), Some((vt_type, text))) = (name.take(), value.take()) {
+                    properties.push((name, PropertyValue::parse(&vt_type, text)));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(properties)
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_custom_properties() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Contract number"><vt:lpwstr>C-1042</vt:lpwstr></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="3" name="Copies"><vt:i4>12</vt:i4></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="4" name="Approved"><vt:bool>true</vt:bool></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="5" name="Due"><vt:filetime>2024-05-01T00:00:00Z</vt:filetime></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="6" name="Rate"><vt:r8>1.5</vt:r8></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="7" name="Notes"><vt:lpwstr/></property></Properties>"#;
+        assert_eq!(
+            read_custom_properties(xml).unwrap(),
+            [
+                (
+                    "Contract number".to_string(),
+                    PropertyValue::Text("C-1042".to_string())
+                ),
+                ("Copies".to_string(), PropertyValue::Integer(12)),
+                ("Approved".to_string(), PropertyValue::Bool(true)),
+                (
+                    "Due".to_string(),
+                    PropertyValue::DateTime("2024-05-01T00:00:00Z".to_string())
+                ),
+                ("Rate".to_string(), PropertyValue::Other("1.5".to_string())),
+                ("Notes".to_string(), PropertyValue::Text(String::new())),
+            ]
+        );
+    }
+}
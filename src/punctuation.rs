@@ -0,0 +1,69 @@
+use std::borrow::Cow;
+
+/// Typographic characters and the ASCII written for them.
+const ASCII_EQUIVALENTS: &[(char, &str)] = &[
+    ('\u{2018}', "'"),   // left single quotation mark
+    ('\u{2019}', "'"),   // right single quotation mark, also the apostrophe
+    ('\u{201A}', "'"),   // single low-9 quotation mark
+    ('\u{201B}', "'"),   // single high-reversed-9 quotation mark
+    ('\u{201C}', "\""),  // left double quotation mark
+    ('\u{201D}', "\""),  // right double quotation mark
+    ('\u{201E}', "\""),  // double low-9 quotation mark
+    ('\u{201F}', "\""),  // double high-reversed-9 quotation mark
+    ('\u{2010}', "-"),   // hyphen
+    ('\u{2011}', "-"),   // non-breaking hyphen
+    ('\u{2012}', "-"),   // figure dash
+    ('\u{2013}', "-"),   // en dash
+    ('\u{2014}', "--"),  // em dash
+    ('\u{2015}', "--"),  // horizontal bar
+    ('\u{2026}', "..."), // horizontal ellipsis
+    ('\u{00A0}', " "),   // no-break space
+    ('\u{2007}', " "),   // figure space
+    ('\u{202F}', " "),   // narrow no-break space
+];
+
+/// The ASCII equivalent of a typographic character, if it has one.
+fn ascii_equivalent(c: char) -> Option<&'static str> {
+    ASCII_EQUIVALENTS
+        .iter()
+        .find(|(typographic, _)| *typographic == c)
+        .map(|(_, ascii)| *ascii)
+}
+
+/// Replaces curly quotes, dashes, ellipses and non-breaking spaces with
+/// their ASCII equivalents. Text without any is returned as it is.
+pub fn to_ascii(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| ascii_equivalent(c).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars() {
+        match ascii_equivalent(c) {
+            Some(ascii) => normalized.push_str(ascii),
+            None => normalized.push(c),
+        }
+    }
+    Cow::Owned(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_equivalent_is_ascii() {
+        for (typographic, ascii) in ASCII_EQUIVALENTS {
+            assert!(!typographic.is_ascii());
+            assert!(!ascii.is_empty() && ascii.is_ascii(), "{:?}", typographic);
+            assert_eq!(to_ascii(&typographic.to_string()), *ascii);
+        }
+        assert_eq!(
+            to_ascii("\u{201C}It\u{2019}s\u{00A0}1990\u{2013}99\u{2014}roughly\u{2026}\u{201D}"),
+            "\"It's 1990-99--roughly...\""
+        );
+        assert!(matches!(
+            to_ascii("plain «text»"),
+            Cow::Borrowed("plain «text»")
+        ));
+    }
+}
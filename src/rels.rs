@@ -0,0 +1,143 @@
+use quick_xml::events::Event;
+use std::collections::HashMap;
+
+use crate::attribute;
+use crate::error::XmlError;
+use crate::namespaces::WordReader;
+
+/// A single entry of a relationships part (`*.rels`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    /// The relationship type URI, e.g. `.../relationships/hyperlink`.
+    pub rel_type: String,
+    /// The target, either a part name relative to the source part or, for
+    /// external relationships, a URI.
+    pub target: String,
+    /// `TargetMode="External"`: the target lies outside the package.
+    pub external: bool,
+}
+
+/// The relationships of one part, keyed by relationship id (`rId5`).
+#[derive(Debug, Clone, Default)]
+pub struct Relationships {
+    by_id: HashMap<String, Relationship>,
+}
+
+impl Relationships {
+    /// Parses the XML of a relationships part.
+    pub fn parse(xml: &str) -> Result<Self, XmlError> {
+        let mut reader = WordReader::from_str(xml);
+        let mut by_id = HashMap::new();
+        loop {
+            match reader.read_event()? {
+                Event::Start(ref e) | Event::Empty(ref e)
+                    if e.local_name().as_ref() == b"Relationship" =>
+                {
+                    let (Some(id), Some(target)) = (attribute(e, b"Id"), attribute(e, b"Target"))
+                    else {
+                        continue;
+                    };
+                    let relationship = Relationship {
+                        rel_type: attribute(e, b"Type").unwrap_or_default(),
+                        target,
+                        external: attribute(e, b"TargetMode").as_deref() == Some("External"),
+                    };
+                    by_id.insert(id, relationship);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(Relationships { by_id })
+    }
+
+    /// Looks up a relationship by id.
+    pub fn get(&self, id: &str) -> Option<&Relationship> {
+        self.by_id.get(id)
+    }
+
+    /// Finds the internal relationship whose type URI ends in the given
+    /// name (`officeDocument`), which is the same in the transitional and
+    /// strict schemas. If there are several, the one with the lowest id wins.
+    pub fn find_by_type(&self, type_name: &str) -> Option<&Relationship> {
+        self.by_id
+            .iter()
+            .filter(|(_, relationship)| {
+                !relationship.external
+                    && relationship.rel_type.rsplit('/').next() == Some(type_name)
+            })
+            .min_by_key(|(id, _)| id.as_str())
+            .map(|(_, relationship)| relationship)
+    }
+}
+
+/// Resolves a relationship target against the part that holds the
+/// relationship, giving the name of the target part within the package
+/// (`header1.xml` from `word/document.xml` is `word/header1.xml`). The
+/// package's own relationships (`_rels/.rels`) have the empty source part.
+pub fn resolve_part_name(source_part: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return normalize_segments(Vec::new(), absolute);
+    }
+    let mut segments: Vec<&str> = source_part.split('/').collect();
+    // Drop the file name of the source part, keeping its directory.
+    segments.pop();
+    normalize_segments(segments, target)
+}
+
+fn normalize_segments<'a>(mut segments: Vec<&'a str>, target: &'a str) -> String {
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_relationships() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+  <Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/?a=1&amp;b=2" TargetMode="External"/>
+</Relationships>"#;
+        let rels = Relationships::parse(xml).unwrap();
+        let styles = rels.get("rId1").unwrap();
+        assert_eq!(styles.target, "styles.xml");
+        assert!(!styles.external);
+        let link = rels.get("rId5").unwrap();
+        assert_eq!(link.target, "https://example.com/?a=1&b=2");
+        assert!(link.external);
+        assert!(rels.get("rId9").is_none());
+        assert_eq!(rels.find_by_type("styles"), Some(styles));
+        assert!(rels.find_by_type("hyperlink").is_none());
+    }
+
+    #[test]
+    fn test_resolve_part_name() {
+        assert_eq!(
+            resolve_part_name("word/document.xml", "header1.xml"),
+            "word/header1.xml"
+        );
+        assert_eq!(
+            resolve_part_name("word/document.xml", "../customXml/item1.xml"),
+            "customXml/item1.xml"
+        );
+        assert_eq!(
+            resolve_part_name("word/document.xml", "/word/footer2.xml"),
+            "word/footer2.xml"
+        );
+        assert_eq!(
+            resolve_part_name("", "word/document.xml"),
+            "word/document.xml"
+        );
+    }
+}
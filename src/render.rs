@@ -0,0 +1,663 @@
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::sink::TextSink;
+use crate::stats::TextStats;
+use crate::visitor::{CellInfo, ControlInfo, DocxVisitor, LinkInfo, ParagraphInfo, RunInfo, SectionInfo};
+use crate::{single_line, ExtractOptions, Heading, LinkStyle, MergedCells, OutputFormat, Paragraph};
+
+/// Bold and italic, as Markdown marks them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Emphasis {
+    bold: bool,
+    italic: bool,
+}
+
+impl Emphasis {
+    fn of(run: &RunInfo) -> Self {
+        Emphasis {
+            bold: run.bold,
+            italic: run.italic,
+        }
+    }
+
+    /// The marker that opens and closes text with this emphasis.
+    fn marker(self) -> &'static str {
+        match (self.bold, self.italic) {
+            (true, true) => "***",
+            (true, false) => "**",
+            (false, true) => "*",
+            (false, false) => "",
+        }
+    }
+}
+
+/// A hyperlink that is currently open.
+#[derive(Debug)]
+struct OpenHyperlink {
+    /// The resolved target, if the link has one.
+    target: Option<String>,
+    /// Length of the open paragraph when the link started; the link text is
+    /// everything after it.
+    start: usize,
+}
+
+/// A table that is currently open. Cell text is written in the output like
+/// any other text and moved into the table when the cell ends.
+#[derive(Debug, Default)]
+struct Table {
+    rows: Vec<Vec<String>>,
+    /// Some cells are merged vertically, which pipe tables cannot show.
+    vertically_merged: bool,
+    /// Length of the text when the open cell started.
+    cell_start: usize,
+    /// Grid columns the open cell spans.
+    cell_span: usize,
+    /// The open cell continues a vertically merged cell from the row above.
+    cell_continues: bool,
+    /// Where paragraph separators were written in the open cell, as the
+    /// start and end of each in the text.
+    cell_breaks: Vec<(usize, usize)>,
+}
+
+/// A content control that is currently open.
+#[derive(Debug)]
+struct OpenControl {
+    control: ControlInfo,
+    /// Whether the content is inside the open paragraph, and the length of
+    /// that paragraph or of the text when it started.
+    inline: bool,
+    start: usize,
+}
+
+/// Writes the plain text or Markdown of a document, as `options.format`
+/// says, from what extraction visits.
+pub(crate) struct TextRenderer<'a> {
+    options: &'a ExtractOptions,
+    /// The text written so far, but for the paragraphs still open.
+    pub text: String,
+    /// Text of the paragraphs currently open, which nest when a text box
+    /// is anchored in a paragraph.
+    paragraphs: Vec<String>,
+    /// What the open paragraphs were started with.
+    paragraph_infos: Vec<ParagraphInfo>,
+    /// Emphasis of the Markdown written last, whose marker is still open.
+    emphasis: Emphasis,
+    /// Where the run text of the open paragraph starts, after its heading
+    /// marker or list label.
+    text_start: usize,
+    hyperlinks: Vec<OpenHyperlink>,
+    /// Tables currently open, innermost last.
+    tables: Vec<Table>,
+    /// Content controls currently open, innermost last.
+    content_controls: Vec<OpenControl>,
+    /// The paragraph being written has a Markdown list label.
+    labelled: bool,
+    /// Widths of the Markdown list labels last written, with the space
+    /// after them, by level.
+    label_widths: Vec<usize>,
+    /// The last block written was a Markdown list item. Items follow each
+    /// other without a blank line, which would make the list a loose one.
+    list_item_written: bool,
+    /// The open heading: its paragraph depth, level, and where its text
+    /// starts after the marker.
+    heading: Option<(usize, usize, usize)>,
+    pub headings: Vec<Heading>,
+    /// Where the separator goes after the last section that ended. It is
+    /// written once the next section ends and tells its type.
+    section_break: Option<usize>,
+    /// Counts of the paragraphs written so far, if wanted.
+    pub stats: Option<TextStats>,
+    /// Paragraphs written since last taken, kept only for
+    /// [`paragraphs`](crate::paragraphs).
+    pub written_paragraphs: Option<Vec<Paragraph>>,
+}
+
+impl<'a> TextRenderer<'a> {
+    pub fn new(options: &'a ExtractOptions) -> Self {
+        TextRenderer {
+            options,
+            text: String::new(),
+            paragraphs: Vec::new(),
+            paragraph_infos: Vec::new(),
+            emphasis: Emphasis::default(),
+            text_start: 0,
+            hyperlinks: Vec::new(),
+            tables: Vec::new(),
+            content_controls: Vec::new(),
+            labelled: false,
+            label_widths: Vec::new(),
+            list_item_written: false,
+            heading: None,
+            headings: Vec::new(),
+            section_break: None,
+            stats: None,
+            written_paragraphs: None,
+        }
+    }
+
+    /// Appends output to the open paragraph, or directly to the text when
+    /// outside of any paragraph.
+    fn push(&mut self, content: &str) {
+        match self.paragraphs.last_mut() {
+            Some(paragraph) => paragraph.push_str(content),
+            None => self.text.push_str(content),
+        }
+    }
+
+    /// Ends a block of the text with the paragraph separator, noting where
+    /// it is if a cell is open.
+    fn push_separator(&mut self) {
+        let start = self.text.len();
+        self.text.push_str(&self.options.paragraph_separator);
+        self.list_item_written = false;
+        if let Some(table) = self.tables.last_mut() {
+            table.cell_breaks.push((start, self.text.len()));
+        }
+    }
+
+    /// Whether text written now starts a line of Markdown, past the heading
+    /// marker or list label of its paragraph.
+    fn at_line_start(&self) -> bool {
+        let Some(paragraph) = self.paragraphs.last() else {
+            return self.text.is_empty() || self.text.ends_with('\n');
+        };
+        paragraph
+            .get(self.text_start..)
+            .is_none_or(|text| text.trim().is_empty())
+            || paragraph.trim_end_matches([' ', '\t']).ends_with('\n')
+    }
+
+    /// Writes run content in Markdown, closing and opening bold and italic
+    /// markers where the formatting changes, so that adjacent runs
+    /// formatted alike share one pair. Markers go inside the whitespace
+    /// around the text, where Markdown recognises them.
+    fn emphasize(&mut self, content: &str, emphasis: Emphasis) {
+        let text = content.trim_start();
+        if text.is_empty() || emphasis == self.emphasis {
+            self.push(content);
+            return;
+        }
+        self.close_emphasis();
+        self.push(&content[..content.len() - text.len()]);
+        self.push(emphasis.marker());
+        self.emphasis = emphasis;
+        self.push(text);
+    }
+
+    /// Closes the open bold or italic marker before any trailing whitespace.
+    fn close_emphasis(&mut self) {
+        let marker = std::mem::take(&mut self.emphasis).marker();
+        if marker.is_empty() {
+            return;
+        }
+        let text = self.paragraphs.last_mut().unwrap_or(&mut self.text);
+        let end = text.trim_end().len();
+        text.insert_str(end, marker);
+    }
+
+    /// Marks a heading paragraph by its level and remembers where its text
+    /// starts.
+    fn write_heading_marker(&mut self, level: usize) {
+        let marked = level + self.options.heading_offset;
+        match self.options.format {
+            OutputFormat::Markdown => self.push(&format!("{} ", "#".repeat(marked))),
+            OutputFormat::Text if self.options.mark_headings => self.push(&format!("[H{}] ", marked)),
+            OutputFormat::Text => {}
+        }
+        let start = self.paragraphs.last().map_or(0, String::len);
+        self.heading = Some((self.paragraphs.len(), level, start));
+        self.text_start = start;
+    }
+
+    /// Writes the bullet or number of a list paragraph, indented by its
+    /// level. Markdown numbers lists with decimals only.
+    fn write_list_label(&mut self, paragraph: &ParagraphInfo) {
+        let (Some(level), Some(label)) = (paragraph.list_level, paragraph.list_label.as_deref()) else {
+            return;
+        };
+        if label.is_empty() {
+            return;
+        }
+        let (indent, label) = match self.options.format {
+            // An item nests in the one before only if indented to where
+            // its text starts.
+            OutputFormat::Markdown => {
+                let label = match paragraph.list_number {
+                    Some(number) => format!("{}.", number),
+                    None => "-".to_string(),
+                };
+                self.labelled = true;
+                self.label_widths.truncate(level);
+                let indent = " ".repeat(self.label_widths.iter().sum());
+                self.label_widths.push(label.chars().count() + 1);
+                (indent, label)
+            }
+            OutputFormat::Text => (self.options.list_indent.repeat(level), label.to_string()),
+        };
+        self.push(&format!("{}{} ", indent, label));
+        self.text_start = self.paragraphs.last().map_or(0, String::len);
+    }
+
+    /// Moves the text of the cell into its row, on a single line: paragraph
+    /// and line breaks inside a cell become spaces so they do not break the
+    /// row.
+    fn end_cell(&mut self) {
+        let Some(table) = self.tables.last_mut() else {
+            return;
+        };
+        let start = table.cell_start.min(self.text.len());
+        let text = self.text.split_off(start);
+        // The cell's paragraphs are told apart by where their separators
+        // were written, as the separator may well appear in the text.
+        let mut paragraphs = Vec::new();
+        let mut from = 0;
+        for &(break_start, break_end) in &table.cell_breaks {
+            let (Some(break_start), Some(break_end)) =
+                (break_start.checked_sub(start), break_end.checked_sub(start))
+            else {
+                continue;
+            };
+            if let Some(paragraph) = text.get(from..break_start) {
+                if break_end <= text.len() {
+                    paragraphs.push(paragraph);
+                    from = break_end;
+                }
+            }
+        }
+        paragraphs.push(text.get(from..).unwrap_or_default());
+        let cell = paragraphs
+            .iter()
+            .flat_map(|paragraph| paragraph.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Cells are laid out on the table grid: a merged cell fills every
+        // grid column it spans, and a continuation of a vertical merge keeps
+        // its column rather than disappearing.
+        let Some((row, previous_rows)) = table.rows.split_last_mut() else {
+            return;
+        };
+        let repeat = self.options.merged_cells == MergedCells::Repeat;
+        let cell = if table.cell_continues && repeat {
+            previous_rows
+                .last()
+                .and_then(|previous| previous.get(row.len()))
+                .cloned()
+                .unwrap_or_default()
+        } else if table.cell_continues {
+            String::new()
+        } else {
+            cell
+        };
+        for column in 0..table.cell_span {
+            if column == 0 || repeat {
+                row.push(cell.clone());
+            } else {
+                row.push(String::new());
+            }
+        }
+    }
+
+    /// Writes each row of the table on its own line. A table nested in a
+    /// cell is flattened into that cell, so its cells are only separated
+    /// by spaces.
+    fn end_table(&mut self) {
+        let Some(mut table) = self.tables.pop() else {
+            return;
+        };
+        if self.options.bookmark.is_some() {
+            // Rows outside the bookmark have only empty cells.
+            table.rows.retain(|row| row.iter().any(|cell| !cell.is_empty()));
+        }
+        let options = self.options;
+        if options.format == OutputFormat::Markdown
+            && self.tables.is_empty()
+            && !table.vertically_merged
+        {
+            self.write_pipe_table(&table);
+            return;
+        }
+        let delimiter = if self.tables.is_empty() {
+            options.table_delimiter.as_str()
+        } else {
+            " "
+        };
+        for row in &table.rows {
+            self.text.push_str(&row.join(delimiter));
+            self.push_separator();
+        }
+    }
+
+    /// Writes a table as a Markdown pipe table whose first row is the
+    /// header, set off from the surrounding text by blank lines.
+    fn write_pipe_table(&mut self, table: &Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        while !self.text.is_empty() && !self.text.ends_with("\n\n") {
+            self.text.push('\n');
+        }
+        self.list_item_written = false;
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        for (index, row) in table.rows.iter().enumerate() {
+            let cells = (0..columns)
+                .map(|column| {
+                    row.get(column)
+                        .map_or(String::new(), |cell| cell.replace('|', "\\|"))
+                })
+                .collect();
+            self.text.push_str(&line(cells));
+            if index == 0 {
+                self.text.push_str(&line(vec!["---".to_string(); columns]));
+            }
+        }
+        self.text.push('\n');
+    }
+
+    /// Writes the text rendered so far to `sink`, if nothing rendered later
+    /// can change it: no paragraph, table or content control is open and no
+    /// section break is waiting for its type. Headers go before the text
+    /// but are only known at the end, so with headers nothing is written
+    /// before then. The last two characters stay, for tables that check
+    /// whether the text ends with a blank line.
+    pub fn flush(&mut self, sink: &mut dyn TextSink) {
+        if self.options.include_headers_footers
+            || !self.paragraphs.is_empty()
+            || !self.tables.is_empty()
+            || !self.content_controls.is_empty()
+            || self.section_break.is_some()
+        {
+            return;
+        }
+        if let Some((end, _)) = self.text.char_indices().rev().nth(1) {
+            sink.write_text(&self.text[..end]);
+            self.text.drain(..end);
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.text.trim_end().to_string()
+    }
+}
+
+impl DocxVisitor for TextRenderer<'_> {
+    fn paragraph_start(&mut self, paragraph: &ParagraphInfo) {
+        self.close_emphasis();
+        self.text_start = 0;
+        // A paragraph opening inside another one is a block boundary: whatever
+        // the outer paragraph has so far goes on its own line.
+        if let Some(outer) = self.paragraphs.last_mut() {
+            if !outer.is_empty() {
+                let outer = std::mem::take(outer);
+                self.text.push_str(&outer);
+                self.push_separator();
+            }
+        }
+        self.paragraphs.push(String::new());
+        self.paragraph_infos.push(paragraph.clone());
+        if let Some(level) = paragraph.heading_level {
+            self.write_heading_marker(level);
+        }
+        self.write_list_label(paragraph);
+    }
+
+    fn paragraph_end(&mut self) {
+        self.close_emphasis();
+        self.text_start = 0;
+        let info = self.paragraph_infos.pop().unwrap_or_default();
+        let list_item = std::mem::take(&mut self.labelled);
+        let depth = self.paragraphs.len();
+        let Some(mut paragraph) = self.paragraphs.pop() else {
+            return;
+        };
+        if self.options.nfc && !is_nfc(&paragraph) {
+            paragraph = paragraph.nfc().collect();
+        }
+        if let Some((heading_depth, level, start)) = self.heading {
+            if heading_depth == depth {
+                self.heading = None;
+                let text = paragraph.get(start..).unwrap_or("").trim().to_string();
+                if !text.is_empty() {
+                    self.headings.push(Heading { level, text });
+                }
+            }
+        }
+        if let Some(stats) = self.stats.as_mut() {
+            stats.add_paragraph(&paragraph, info.heading_level.is_some());
+        }
+        if list_item && self.list_item_written && self.text.ends_with("\n\n") {
+            self.text.pop();
+        }
+        self.text.push_str(&paragraph);
+        self.push_separator();
+        self.list_item_written = list_item;
+        if let Some(written) = self.written_paragraphs.as_mut() {
+            written.push(Paragraph {
+                text: paragraph,
+                style: info.style,
+                list_level: info.list_level,
+            });
+        }
+    }
+
+    fn text(&mut self, text: &str, run: &RunInfo) {
+        if self.options.format == OutputFormat::Markdown {
+            let escaped = escape_markdown_text(text, self.at_line_start());
+            self.emphasize(&escaped, Emphasis::of(run));
+        } else {
+            self.push(text);
+        }
+    }
+
+    fn marker(&mut self, text: &str) {
+        if self.paragraphs.is_empty() {
+            self.text.push_str(text);
+            self.push_separator();
+        } else {
+            self.push(text);
+        }
+    }
+
+    /// Writes the configured page break, which in Markdown is a rule by
+    /// default.
+    fn page_break(&mut self) {
+        let options = self.options;
+        if options.format == OutputFormat::Markdown && options.page_break == "\x0c" {
+            self.close_emphasis();
+            // A rule needs a blank line before it, or it underlines the
+            // text before as a heading.
+            let empty = self.paragraphs.last().is_none_or(String::is_empty);
+            let rule = if !empty {
+                "\n\n---\n\n"
+            } else if self.text.is_empty() || self.text.ends_with("\n\n") {
+                "---"
+            } else {
+                "\n---"
+            };
+            self.push(rule);
+        } else {
+            self.push(&options.page_break);
+        }
+    }
+
+    /// Writes the page marker, if requested, for the page starting here.
+    fn rendered_page_break(&mut self, page: usize) {
+        if let Some(template) = &self.options.page_marker {
+            let marker = template.replace("{n}", &page.to_string());
+            self.push(&marker);
+        }
+    }
+
+    fn hyperlink_start(&mut self, link: &LinkInfo) {
+        self.close_emphasis();
+        let start = self.paragraphs.last().map_or(0, String::len);
+        self.hyperlinks.push(OpenHyperlink {
+            target: link.target.clone(),
+            start,
+        });
+    }
+
+    /// Rewrites the link text written since the link started using the
+    /// configured link style.
+    fn hyperlink_end(&mut self) {
+        let Some(hyperlink) = self.hyperlinks.pop() else {
+            return;
+        };
+        self.close_emphasis();
+        let (Some(target), Some(paragraph)) = (hyperlink.target, self.paragraphs.last_mut()) else {
+            return;
+        };
+        if hyperlink.start > paragraph.len() {
+            return;
+        }
+        let text = paragraph.split_off(hyperlink.start);
+        let link_style = match (self.options.format, &self.options.link_style) {
+            (OutputFormat::Markdown, LinkStyle::Url) => &LinkStyle::Markdown,
+            (_, link_style) => link_style,
+        };
+        match link_style.render(&text, &target) {
+            Some(rendered) => paragraph.push_str(&rendered),
+            None => paragraph.push_str(&text),
+        }
+    }
+
+    fn content_control_start(&mut self, control: &ControlInfo) {
+        let (inline, start) = match self.paragraphs.last() {
+            Some(paragraph) => (true, paragraph.len()),
+            None => (false, self.text.len()),
+        };
+        self.content_controls.push(OpenControl {
+            control: control.clone(),
+            inline,
+            start,
+        });
+    }
+
+    /// Annotates the content of the control with its title if requested.
+    fn content_control_end(&mut self) {
+        let Some(open) = self.content_controls.pop() else {
+            return;
+        };
+        let Some(label) = open.control.label() else {
+            return;
+        };
+        if !self.options.annotate_content_controls {
+            return;
+        }
+        if open.inline {
+            if let Some(paragraph) = self.paragraphs.last_mut().filter(|paragraph| open.start <= paragraph.len()) {
+                let content = paragraph.split_off(open.start);
+                paragraph.push_str(&format!("[{}: {}]", label, content));
+            }
+        } else if open.start <= self.text.len() {
+            // A control holding whole paragraphs becomes a line of its own.
+            let content = self.text.split_off(open.start);
+            self.text.push_str(&format!("[{}: {}]", label, single_line(&content)));
+            self.push_separator();
+        }
+    }
+
+    /// Completes the separator after the section before, now that the type
+    /// of the section after it is known, and notes where the next one goes.
+    fn section_end(&mut self, section: &SectionInfo) {
+        if self.options.section_separator.is_empty() {
+            return;
+        }
+        if let Some(position) = self.section_break.take() {
+            let section_type = section.start.as_deref().unwrap_or("nextPage");
+            let separator = self.options.section_separator.replace("{type}", section_type);
+            if self.text.is_char_boundary(position) {
+                self.text.insert_str(position, &separator);
+            }
+        }
+        self.section_break = Some(self.text.len());
+    }
+
+    fn table_start(&mut self) {
+        self.tables.push(Table::default());
+    }
+
+    fn table_end(&mut self) {
+        self.end_table();
+    }
+
+    fn row_start(&mut self) {
+        if let Some(table) = self.tables.last_mut() {
+            table.rows.push(Vec::new());
+        }
+    }
+
+    fn cell_start(&mut self, cell: &CellInfo) {
+        let start = self.text.len();
+        if let Some(table) = self.tables.last_mut() {
+            table.cell_start = start;
+            table.cell_span = std::cmp::max(cell.grid_span, 1);
+            table.cell_continues = cell.continues_merge;
+            table.vertically_merged |= cell.vertically_merged;
+            table.cell_breaks.clear();
+        }
+    }
+
+    fn cell_end(&mut self) {
+        self.end_cell();
+    }
+}
+
+/// Escapes what Markdown would read as markup in run text: backslashes,
+/// the `*` and `_` of emphasis, and at the start of a line what would make
+/// it a heading (`#`), a blockquote (`>`) or a list item (`- `, `+ `,
+/// `1. `, `1) `).
+pub(crate) fn escape_markdown_text(text: &str, line_start: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut line_start = line_start;
+    // Digits starting the line, which a `.` or `)` would make the number
+    // of a list item.
+    let mut number = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let marker_ends = chars.peek().is_none_or(|next| matches!(next, ' ' | '\t'));
+        let markup = match c {
+            '\\' | '*' | '_' => true,
+            '#' | '>' => line_start,
+            '-' | '+' => line_start && marker_ends,
+            '.' | ')' => number && marker_ends,
+            _ => false,
+        };
+        if markup {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        number = c.is_ascii_digit() && (line_start || number);
+        line_start = match c {
+            '\n' => true,
+            ' ' | '\t' => line_start,
+            _ => false,
+        };
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_escapes_block_markers_at_line_start() {
+        for (text, expected) in [
+            ("1. Not a list", "1\\. Not a list"),
+            ("12) Nor this", "12\\) Nor this"),
+            ("- Nor this", "\\- Nor this"),
+            ("+ Nor this", "\\+ Nor this"),
+            ("> Not a quote", "\\> Not a quote"),
+            ("#1 and >2", "\\#1 and >2"),
+            ("A - b + c 1. d", "A - b + c 1. d"),
+            ("3.5 and -2", "3.5 and -2"),
+            ("Two\n  - lines", "Two\n  \\- lines"),
+        ] {
+            assert_eq!(escape_markdown_text(text, true), expected);
+        }
+        assert_eq!(escape_markdown_text("1. Mid-line", false), "1. Mid-line");
+    }
+}
@@ -0,0 +1,255 @@
+use std::io;
+#[cfg(feature = "cli")]
+use std::io::Write;
+
+use crate::BlankLines;
+
+/// Where extracted text goes as it is written: a `String`, a writer, or
+/// another [`TextWriter`].
+pub(crate) trait TextSink {
+    fn write_text(&mut self, text: &str);
+
+    /// The error writing failed with, if it failed.
+    fn take_error(&mut self) -> Option<io::Error> {
+        None
+    }
+}
+
+impl TextSink for String {
+    fn write_text(&mut self, text: &str) {
+        self.push_str(text);
+    }
+}
+
+/// Passes text on to another sink, counting its bytes.
+#[cfg(feature = "cli")]
+pub(crate) struct CountingSink<'s> {
+    sink: &'s mut dyn TextSink,
+    /// The bytes of text written so far.
+    pub bytes: u64,
+}
+
+#[cfg(feature = "cli")]
+impl<'s> CountingSink<'s> {
+    pub fn new(sink: &'s mut dyn TextSink) -> Self {
+        CountingSink { sink, bytes: 0 }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl TextSink for CountingSink<'_> {
+    fn write_text(&mut self, text: &str) {
+        self.bytes += text.len() as u64;
+        self.sink.write_text(text);
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.sink.take_error()
+    }
+}
+
+/// Writes text to an `io::Write`. The first error is kept, and the text
+/// after it dropped, so that the parsers writing need not handle it.
+#[cfg(feature = "cli")]
+pub(crate) struct IoSink<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "cli")]
+impl<W: Write> IoSink<W> {
+    pub fn new(writer: W) -> Self {
+        IoSink {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flushes the writer, or returns the error writing failed with.
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl<W: Write> TextSink for IoSink<W> {
+    fn write_text(&mut self, text: &str) {
+        if self.error.is_none() {
+            if let Err(error) = self.writer.write_all(text.as_bytes()) {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+/// Writes a document's text to a sink as it is extracted, doing what would
+/// otherwise take the whole text: whitespace is held back until text
+/// follows it, so the end is trimmed, and the blank line policy is applied
+/// a line at a time.
+pub(crate) struct TextWriter<'s> {
+    sink: &'s mut dyn TextSink,
+    blank_lines: BlankLines,
+    /// Leading whitespace is dropped as well.
+    trim_start: bool,
+    /// Whether anything has been written.
+    started: bool,
+    /// Whitespace at the end of the text so far.
+    whitespace: String,
+    /// The line being written when blank lines are collapsed or stripped;
+    /// whether it is blank is only known once it ends.
+    line: String,
+    /// Whether the last line written was blank; `None` before the first.
+    last_line_blank: Option<bool>,
+    /// How many line breaks have been written.
+    line_breaks: usize,
+}
+
+impl<'s> TextWriter<'s> {
+    pub fn new(sink: &'s mut dyn TextSink, blank_lines: BlankLines) -> Self {
+        TextWriter {
+            sink,
+            blank_lines,
+            trim_start: false,
+            started: false,
+            whitespace: String::new(),
+            line: String::new(),
+            last_line_blank: None,
+            line_breaks: 0,
+        }
+    }
+
+    /// Drops leading whitespace too, as when files are merged.
+    #[cfg(feature = "cli")]
+    pub fn trim_start(mut self) -> Self {
+        self.trim_start = true;
+        self
+    }
+
+    /// The line, counted from 1, that the text written next starts on,
+    /// after the whitespace held back before it. Blank lines must be kept.
+    #[cfg(feature = "cli")]
+    pub fn next_line(&self) -> usize {
+        debug_assert_eq!(self.blank_lines, BlankLines::Keep);
+        let held = if self.started {
+            self.whitespace.matches('\n').count()
+        } else {
+            0
+        };
+        self.line_breaks + held + 1
+    }
+
+    /// Drops the whitespace written since the last text.
+    pub fn trim_end(&mut self) {
+        self.whitespace.clear();
+    }
+
+    /// Writes the last line, leaving out the whitespace after it, and
+    /// returns the error writing failed with, if it failed.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.blank_lines != BlankLines::Keep {
+            self.end_line();
+        }
+        match self.sink.take_error() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn write_lines(&mut self, text: &str) {
+        if self.blank_lines == BlankLines::Keep {
+            self.line_breaks += text.matches('\n').count();
+            self.sink.write_text(text);
+            return;
+        }
+        let mut rest = text;
+        while let Some(index) = rest.find('\n') {
+            self.line.push_str(&rest[..index]);
+            self.end_line();
+            rest = &rest[index + 1..];
+        }
+        self.line.push_str(rest);
+    }
+
+    /// Writes the line just ended, unless the policy drops it.
+    fn end_line(&mut self) {
+        let blank = self.line.trim().is_empty();
+        let after_blank = self.last_line_blank == Some(true);
+        if !(blank && (self.blank_lines == BlankLines::Strip || after_blank)) {
+            if self.last_line_blank.is_some() {
+                self.line_breaks += 1;
+                self.sink.write_text("\n");
+            }
+            self.sink.write_text(&self.line);
+            self.last_line_blank = Some(blank);
+        }
+        self.line.clear();
+    }
+}
+
+impl TextSink for TextWriter<'_> {
+    fn write_text(&mut self, text: &str) {
+        let text = if self.trim_start && !self.started {
+            text.trim_start()
+        } else {
+            text
+        };
+        let Some((index, last)) = text.char_indices().rev().find(|(_, c)| !c.is_whitespace())
+        else {
+            self.whitespace.push_str(text);
+            return;
+        };
+        let end = index + last.len_utf8();
+        self.started = true;
+        let whitespace = std::mem::take(&mut self.whitespace);
+        self.write_lines(&whitespace);
+        self.write_lines(&text[..end]);
+        self.whitespace.push_str(&text[end..]);
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.sink.take_error()
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_writer_matches_whole_text() {
+        let text = "\n  first\n\n\n  \nsecond  \n\n\tthird\n\n \n";
+        for blank_lines in [BlankLines::Keep, BlankLines::Collapse, BlankLines::Strip] {
+            let expected = blank_lines.apply(text.trim_end().to_string());
+            for chunk_size in [1, 2, 5, text.len()] {
+                let mut output = String::new();
+                let mut writer = TextWriter::new(&mut output, blank_lines);
+                for chunk in text.as_bytes().chunks(chunk_size) {
+                    writer.write_text(std::str::from_utf8(chunk).unwrap());
+                }
+                writer.finish().unwrap();
+                assert_eq!(
+                    output, expected,
+                    "{:?} in chunks of {}",
+                    blank_lines, chunk_size
+                );
+            }
+        }
+
+        let mut output = String::new();
+        let mut writer = TextWriter::new(&mut output, BlankLines::Keep).trim_start();
+        for chunk in ["\n\n", " a", "  \n", "\n\n", "b\n", "\n\n"] {
+            writer.write_text(chunk);
+        }
+        writer.trim_end();
+        writer.write_text("\n");
+        writer.finish().unwrap();
+        assert_eq!(output, "a  \n\n\nb");
+    }
+}
@@ -0,0 +1,246 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+use std::time::SystemTime;
+
+/// The order files are merged in.
+///
+/// The orders by name sort the files each pattern matches, and leave the
+/// patterns in the order given. The orders by time or size sort all the
+/// files together, those equal staying in the order of their patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// By name, with numbers compared by value and letters regardless of
+    /// case, so that `chapter2.docx` comes before `chapter10.docx`.
+    #[default]
+    Natural,
+    /// By name, compared character by character, so that
+    /// `chapter10.docx` comes before `chapter2.docx`.
+    Name,
+    /// Oldest modified first.
+    Modified,
+    /// Oldest created first, where the file system records when files are
+    /// created.
+    Created,
+    /// Smallest first.
+    Size,
+    /// As the patterns give them, each in the order it finds its files.
+    None,
+}
+
+impl SortOrder {
+    /// Parses an order name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "natural" => Some(SortOrder::Natural),
+            "name" | "lexical" => Some(SortOrder::Name),
+            "mtime" => Some(SortOrder::Modified),
+            "ctime" => Some(SortOrder::Created),
+            "size" => Some(SortOrder::Size),
+            "none" => Some(SortOrder::None),
+            _ => None,
+        }
+    }
+
+    /// Sorts the files one pattern matches, if this is an order by name.
+    pub(crate) fn sort_matches(self, paths: &mut [PathBuf], reverse: bool) {
+        if matches!(self, SortOrder::Natural | SortOrder::Name) {
+            paths.sort_by(|a, b| reversed(self.compare(a, b), reverse));
+        }
+    }
+
+    /// Sorts the files of all the patterns, if this is an order by time or
+    /// size. A file whose time or size cannot be read comes last, with a
+    /// warning.
+    pub(crate) fn sort_files(self, paths: &mut Vec<PathBuf>, reverse: bool) {
+        let what = match self {
+            SortOrder::Modified => "modification time",
+            SortOrder::Created => "creation time",
+            SortOrder::Size => "size",
+            SortOrder::None if reverse => {
+                paths.reverse();
+                return;
+            }
+            _ => return,
+        };
+        let mut keyed: Vec<(Option<Key>, PathBuf)> = paths
+            .drain(..)
+            .map(|path| match self.key(&path) {
+                Ok(key) => (Some(key), path),
+                Err(e) => {
+                    log::warn!(
+                        "{}: cannot read its {}, so it comes last: {}",
+                        path.display(),
+                        what,
+                        e
+                    );
+                    (None, path)
+                }
+            })
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => reversed(a.cmp(b), reverse),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        });
+        paths.extend(keyed.into_iter().map(|(_, path)| path));
+    }
+
+    /// What a file is sorted by in an order by time or size.
+    fn key(self, path: &Path) -> io::Result<Key> {
+        let metadata = fs::metadata(path)?;
+        Ok(match self {
+            SortOrder::Created => Key::Time(metadata.created()?),
+            SortOrder::Size => Key::Size(metadata.len()),
+            _ => Key::Time(metadata.modified()?),
+        })
+    }
+
+    /// Compares two paths in an order by name, a directory at a time.
+    fn compare(self, a: &Path, b: &Path) -> Ordering {
+        match self {
+            SortOrder::Natural => {
+                let mut a = a.components();
+                let mut b = b.components();
+                loop {
+                    match (a.next(), b.next()) {
+                        (None, None) => return Ordering::Equal,
+                        (None, Some(_)) => return Ordering::Less,
+                        (Some(_), None) => return Ordering::Greater,
+                        (Some(a), Some(b)) => {
+                            let ordering = natural_cmp(
+                                &a.as_os_str().to_string_lossy(),
+                                &b.as_os_str().to_string_lossy(),
+                            );
+                            if ordering != Ordering::Equal {
+                                return ordering;
+                            }
+                        }
+                    }
+                }
+            }
+            _ => a.cmp(b),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Time(SystemTime),
+    Size(u64),
+}
+
+fn reversed(ordering: Ordering, reverse: bool) -> Ordering {
+    if reverse {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+/// Compares names with the runs of digits in them taken as numbers, and
+/// letters regardless of case. Names equal so, such as `a2` and `A02`, are
+/// compared character by character, so that only equal names are equal.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    loose_cmp(a, b).then_with(|| a.cmp(b))
+}
+
+fn loose_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        let ordering = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            // Only ASCII digits make numbers; other digits sort as letters.
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                compare_numbers(&digits(&mut a), &digits(&mut b))
+            }
+            (Some(&x), Some(&y)) => {
+                a.next();
+                b.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Takes the run of digits `chars` starts with.
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// Compares runs of digits by value, however long they are.
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_order() {
+        let mut names = vec![
+            "chapter10.docx",
+            "Chapter2.docx",
+            "chapter2.docx",
+            "chapter02.docx",
+            "chapter1.docx",
+            "chapter.docx",
+            "appendix.docx",
+            "chapter99999999999999999999999.docx",
+            "chapter2a.docx",
+            "Éclairs.docx",
+            "chapter١.docx",
+        ];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            [
+                "appendix.docx",
+                "chapter.docx",
+                "chapter1.docx",
+                "Chapter2.docx",
+                "chapter02.docx",
+                "chapter2.docx",
+                "chapter2a.docx",
+                "chapter10.docx",
+                "chapter99999999999999999999999.docx",
+                "chapter١.docx",
+                "Éclairs.docx",
+            ]
+        );
+        assert_eq!(natural_cmp("b.docx", "B.docx"), Ordering::Greater);
+        assert_eq!(natural_cmp("a.docx", "a.docx"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_paths_are_compared_a_directory_at_a_time() {
+        let order = SortOrder::Natural;
+        assert_eq!(
+            order.compare(Path::new("part2/z.docx"), Path::new("part10/a.docx")),
+            Ordering::Less
+        );
+        assert_eq!(
+            order.compare(Path::new("a/b.docx"), Path::new("a-b/a.docx")),
+            Ordering::Less
+        );
+        assert_eq!(
+            SortOrder::Name.compare(Path::new("part2/z.docx"), Path::new("part10/a.docx")),
+            Ordering::Greater
+        );
+    }
+}
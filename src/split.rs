@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::template::render_file_template;
+use crate::{
+    chaptered, extract_each, FileStats, JsonOutput, MergeError, MergeOptions, Merger, OutputFormat,
+    SkippedFile, Warning,
+};
+
+/// How [`split_files`] writes the text of each file to a file of its own.
+#[derive(Debug, Clone, Default)]
+pub struct SplitOptions {
+    /// How the text of each file is extracted and written, as in a merge
+    /// of that file alone: its header, footer and chapter heading are
+    /// written, and files that cannot be extracted fail or are skipped as
+    /// [`on_error`](MergeOptions::on_error) says. The separator is not
+    /// used.
+    pub merge: MergeOptions,
+    /// Template of the name of each file written, with the placeholders
+    /// of [`MergeOptions::file_header`], such as `{index}-{stem}.txt`. It
+    /// may name directories, which are made as needed; parts that would
+    /// lead out of the directory written to are left out. `None`, the
+    /// default, names each file after its stem, with the extension of the
+    /// [`format`](crate::ExtractOptions::format), or `.json`.
+    pub name: Option<String>,
+    /// Replace files already in the directory, rather than failing.
+    pub overwrite: bool,
+    /// Write each file as the [`JsonOutput`] of that file alone, rather
+    /// than as text.
+    pub json: bool,
+    /// Indent the JSON.
+    pub pretty: bool,
+}
+
+impl SplitOptions {
+    /// The default options, to be changed through the methods below.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets [`merge`](Self::merge).
+    pub fn merge(mut self, merge: MergeOptions) -> Self {
+        self.merge = merge;
+        self
+    }
+
+    /// Sets [`name`](Self::name).
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets [`overwrite`](Self::overwrite).
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Sets [`json`](Self::json), and [`pretty`](Self::pretty) with it.
+    pub fn json(mut self, json: bool, pretty: bool) -> Self {
+        self.json = json;
+        self.pretty = pretty;
+        self
+    }
+
+    /// The extension of the files written, without the dot.
+    fn extension(&self) -> &'static str {
+        if self.json {
+            "json"
+        } else {
+            self.merge.extract.format.extension()
+        }
+    }
+}
+
+/// A file [`split_files`] wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitFile {
+    /// The file extracted, as given.
+    pub path: PathBuf,
+    /// The file its text was written to.
+    pub output: PathBuf,
+}
+
+/// What [`split_files`] did.
+#[derive(Debug, Default)]
+pub struct SplitReport {
+    /// The files written, in the order given.
+    pub written: Vec<SplitFile>,
+    /// The files skipped under
+    /// [`ErrorPolicy::SkipAndReport`](crate::ErrorPolicy::SkipAndReport),
+    /// for which nothing was written unless
+    /// [`error_markers`](MergeOptions::error_markers) is on.
+    pub skipped: Vec<SkippedFile>,
+    /// The counts of each file written, if the options ask for them.
+    pub stats: Vec<FileStats>,
+    /// What extracting the files warned of.
+    pub warnings: Vec<Warning>,
+}
+
+/// Writes the text of each file to a file of its own in `dir`, making the
+/// directory if it does not exist, instead of merging them. Files are
+/// extracted as many at a time as [`MergeOptions::threads`] says.
+///
+/// Two files whose names come out the same, such as `a/notes.docx` and
+/// `b/notes.docx`, are told apart by a number: `notes.txt` and
+/// `notes-2.txt`.
+pub fn split_files(
+    paths: &[impl AsRef<Path> + Sync],
+    dir: impl AsRef<Path>,
+    options: &SplitOptions,
+) -> Result<SplitReport, MergeError> {
+    let dir = dir.as_ref();
+    let mut merge = chaptered(&options.merge);
+    if options.json {
+        // Every file is counted, for its JSON.
+        merge.to_mut().stats = true;
+    }
+    let merge = &*merge;
+    fs::create_dir_all(dir).map_err(|source| MergeError::Io {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    let names = output_names(paths, options);
+    let mut report = SplitReport::default();
+    extract_each(paths, merge, |index, path, text| {
+        let (written, mut merged) = Merger::alone(index, path, text, merge, paths.len())?;
+        let written = match written {
+            Some(text) if options.json => {
+                let mut json = JsonOutput::new();
+                json.add(path, Some(text), &merged, &merge.extract);
+                let mut written = Vec::new();
+                json.write_to(&mut written, options.pretty)
+                    .map_err(|source| MergeError::Write { source })?;
+                Some(String::from_utf8_lossy(&written).into_owned())
+            }
+            written => written,
+        };
+        report.skipped.append(&mut merged.skipped);
+        if options.merge.stats {
+            report.stats.append(&mut merged.stats);
+        }
+        report.warnings.append(&mut merged.warnings);
+        if let Some(written) = written {
+            let output = dir.join(&names[index]);
+            write_file(&output, &written, options.overwrite)?;
+            report.written.push(SplitFile {
+                path: path.to_path_buf(),
+                output,
+            });
+        }
+        Ok(())
+    })?;
+    Ok(report)
+}
+
+/// The name of the file each file's text is written to, in the directory
+/// written to, each different from the others.
+fn output_names(paths: &[impl AsRef<Path>], options: &SplitOptions) -> Vec<PathBuf> {
+    let extract = &options.merge.extract;
+    let template = options
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{{stem}}.{}", options.extension()));
+    // Names are not escaped as Markdown text is.
+    let plain = extract.clone().format(OutputFormat::Text);
+    let mut taken = HashSet::new();
+    paths
+        .iter()
+        .enumerate()
+        .map(|(index, path)| {
+            let name = render_file_template(&template, path.as_ref(), index, paths.len(), &plain);
+            let mut name: PathBuf = Path::new(&name)
+                .components()
+                .filter(|component| matches!(component, Component::Normal(_)))
+                .collect();
+            if name.as_os_str().is_empty() {
+                name = PathBuf::from(format!("{}.{}", index + 1, options.extension()));
+            }
+            unique_name(name, &mut taken)
+        })
+        .collect()
+}
+
+/// `name`, or if it is taken, the first of `name-2`, `name-3` and so on
+/// that is not, before its extension. Names differing only in case are
+/// the same, as they are on some file systems.
+fn unique_name(name: PathBuf, taken: &mut HashSet<String>) -> PathBuf {
+    let mut candidate = name.clone();
+    let mut number = 1;
+    while !taken.insert(candidate.to_string_lossy().to_lowercase()) {
+        number += 1;
+        let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+        let numbered = match name.extension() {
+            Some(extension) => format!("{}-{}.{}", stem, number, extension.to_string_lossy()),
+            None => format!("{}-{}", stem, number),
+        };
+        candidate = name.with_file_name(numbered);
+    }
+    candidate
+}
+
+/// Writes a file, making its directory if need be.
+fn write_file(path: &Path, text: &str, overwrite: bool) -> Result<(), MergeError> {
+    let io_error = |source| MergeError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(overwrite)
+        .truncate(overwrite)
+        .create_new(!overwrite)
+        .open(path)
+        .map_err(io_error)?;
+    file.write_all(text.as_bytes()).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_names() {
+        let mut taken = HashSet::new();
+        let mut name = |name: &str| unique_name(PathBuf::from(name), &mut taken);
+        assert_eq!(name("notes.txt"), Path::new("notes.txt"));
+        assert_eq!(name("Notes.txt"), Path::new("Notes-2.txt"));
+        assert_eq!(name("notes.txt"), Path::new("notes-3.txt"));
+        assert_eq!(name("notes-2.txt"), Path::new("notes-2-2.txt"));
+        assert_eq!(name("dir/README"), Path::new("dir/README"));
+        assert_eq!(name("dir/README"), Path::new("dir/README-2"));
+    }
+
+    #[test]
+    fn test_split_files_writes_each_as_merged_alone() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let first = temp_dir.path().join("first.txt");
+        let second = temp_dir.path().join("second.txt");
+        fs::write(&first, "One\n").unwrap();
+        fs::write(&second, "Two").unwrap();
+        let out = temp_dir.path().join("out");
+        let options = SplitOptions::new()
+            .merge(MergeOptions::new().file_header("== {index}/{total} =="))
+            .name("../{path}");
+
+        let report = split_files(&[&first, &second], &out, &options).unwrap();
+        let outputs: Vec<PathBuf> = report.written.iter().map(|file| file.output.clone()).collect();
+        // The name leads out of the directory, and by an absolute path, so
+        // only its normal parts are kept.
+        let inside = |path: &Path| out.join(path.strip_prefix("/").unwrap_or(path));
+        assert_eq!(outputs, [inside(&first), inside(&second)]);
+        assert_eq!(fs::read_to_string(&outputs[0]).unwrap(), "== 1/2 ==\nOne");
+        assert_eq!(fs::read_to_string(&outputs[1]).unwrap(), "== 2/2 ==\nTwo");
+
+        let error = split_files(&[&first], &out, &options).unwrap_err();
+        assert!(matches!(error, MergeError::Io { ref source, .. } if source.kind() == std::io::ErrorKind::AlreadyExists));
+        split_files(&[&first], &out, &options.overwrite(true)).unwrap();
+    }
+}
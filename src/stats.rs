@@ -0,0 +1,95 @@
+use std::ops::AddAssign;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Counts of the text extracted from a document; see
+/// [`extract_stats`](crate::extract_stats). They are counted as the text is
+/// extracted, so they describe what the options keep, and may differ from
+/// the counts Word records in the document's properties.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextStats {
+    /// Words, as Unicode word boundaries (UAX #29) tell them: punctuation
+    /// is not a word, and each ideograph of Chinese or Japanese text is one,
+    /// while a run of katakana is one word.
+    pub words: u64,
+    /// Characters, spaces included, line breaks between paragraphs not.
+    pub characters: u64,
+    /// Characters other than whitespace.
+    pub characters_no_spaces: u64,
+    /// Paragraphs with text; empty paragraphs are left out.
+    pub paragraphs: u64,
+    /// Paragraphs in a heading style, counted among the paragraphs too.
+    pub headings: u64,
+    /// Pages, as Word last laid the document out: one more than the page
+    /// breaks it rendered (`w:lastRenderedPageBreak`), so only as up to
+    /// date as the last save by Word. Files without them, such as plain
+    /// text, are one page.
+    pub pages: u64,
+}
+
+impl TextStats {
+    /// Counts the text of a paragraph.
+    pub(crate) fn add_paragraph(&mut self, text: &str, heading: bool) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.words += text.unicode_words().count() as u64;
+        for c in text.chars() {
+            self.characters += 1;
+            if !c.is_whitespace() {
+                self.characters_no_spaces += 1;
+            }
+        }
+        self.paragraphs += 1;
+        if heading {
+            self.headings += 1;
+        }
+    }
+
+    /// Counts text that has no paragraph structure other than its lines,
+    /// such as a plain text file, taking each line for a paragraph and the
+    /// whole for a page.
+    #[cfg(feature = "cli")]
+    pub(crate) fn add_lines(&mut self, text: &str) {
+        for line in text.lines() {
+            self.add_paragraph(line, false);
+        }
+        self.pages += 1;
+    }
+}
+
+impl AddAssign for TextStats {
+    fn add_assign(&mut self, other: TextStats) {
+        self.words += other.words;
+        self.characters += other.characters;
+        self.characters_no_spaces += other.characters_no_spaces;
+        self.paragraphs += other.paragraphs;
+        self.headings += other.headings;
+        self.pages += other.pages;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_follow_unicode_boundaries() {
+        let mut stats = TextStats::default();
+        stats.add_paragraph("Don't stop - it's 3.5 km.", false);
+        stats.add_paragraph("   ", false);
+        stats.add_paragraph("日本語のテキスト", true);
+        assert_eq!(
+            stats,
+            TextStats {
+                words: 5 + 5,
+                characters: 25 + 8,
+                characters_no_spaces: 20 + 8,
+                paragraphs: 2,
+                headings: 1,
+                pages: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use quick_xml::events::Event;
+use std::collections::HashMap;
+
+use crate::attribute;
+use crate::error::XmlError;
+use crate::namespaces::WordReader;
+
+/// A style definition (`w:style`), as far as extraction cares about it.
+#[derive(Debug, Clone, Default)]
+struct Style {
+    /// Display name (`w:name`), e.g. `heading 2`.
+    name: Option<String>,
+    based_on: Option<String>,
+    /// `w:outlineLvl`: 0 for top-level headings, 9 for body text.
+    outline_level: Option<usize>,
+}
+
+/// The styles of `word/styles.xml`, keyed by style id.
+#[derive(Debug, Clone, Default)]
+pub struct Styles {
+    by_id: HashMap<String, Style>,
+    /// The style of paragraphs that do not name one (`w:default="1"`).
+    default_paragraph_style: Option<String>,
+}
+
+impl Styles {
+    /// Parses the XML of a styles part.
+    pub fn parse(xml: &str) -> Result<Self, XmlError> {
+        let mut reader = WordReader::from_str(xml);
+        let mut by_id = HashMap::new();
+        let mut default_paragraph_style = None;
+        let mut current: Option<(String, Style)> = None;
+        loop {
+            match reader.read_event()? {
+                Event::Start(ref e) if e.name().as_ref() == b"w:style" => {
+                    current = attribute(e, b"w:styleId").map(|id| (id, Style::default()));
+                    let paragraph = attribute(e, b"w:type").as_deref() == Some("paragraph");
+                    let default = attribute(e, b"w:default")
+                        .is_some_and(|value| value == "1" || value == "true");
+                    if let (true, true, Some((id, _))) = (paragraph, default, &current) {
+                        default_paragraph_style = Some(id.clone());
+                    }
+                }
+                Event::Empty(ref e) => {
+                    let (Some((_, style)), Some(value)) =
+                        (current.as_mut(), attribute(e, b"w:val"))
+                    else {
+                        continue;
+                    };
+                    match e.name().as_ref() {
+                        b"w:name" => style.name = Some(value),
+                        b"w:basedOn" => style.based_on = Some(value),
+                        b"w:outlineLvl" => style.outline_level = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+                Event::End(ref e) if e.name().as_ref() == b"w:style" => {
+                    if let Some((id, style)) = current.take() {
+                        by_id.insert(id, style);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(Styles {
+            by_id,
+            default_paragraph_style,
+        })
+    }
+
+    /// The heading level (1 to 9) of paragraphs in the given style. A style
+    /// is a heading if it sets an outline level or is one of the built-in
+    /// heading styles, either itself or through the styles it is based on.
+    pub fn heading_level(&self, style_id: &str) -> Option<usize> {
+        let mut id = style_id;
+        // basedOn chains are short; the limit only guards against cycles.
+        for _ in 0..self.by_id.len() + 1 {
+            let style = self.by_id.get(id)?;
+            if let Some(level) = style.outline_level {
+                return (level < 9).then_some(level + 1);
+            }
+            if let Some(level) = style.name.as_deref().and_then(built_in_heading_level) {
+                return Some(level);
+            }
+            id = style.based_on.as_deref()?;
+        }
+        None
+    }
+
+    /// The display name of a style, if it has one.
+    pub fn name(&self, style_id: &str) -> Option<&str> {
+        self.by_id.get(style_id)?.name.as_deref()
+    }
+
+    /// The id of the default paragraph style, usually `Normal`.
+    pub fn default_paragraph_style(&self) -> Option<&str> {
+        self.default_paragraph_style.as_deref()
+    }
+}
+
+/// The level of a built-in heading style from its name, `heading 1` to
+/// `heading 9`. Word writes these names in English whatever the UI language.
+fn built_in_heading_level(name: &str) -> Option<usize> {
+    let level = name
+        .to_ascii_lowercase()
+        .strip_prefix("heading ")?
+        .parse()
+        .ok()?;
+    (1..=9).contains(&level).then_some(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heading_levels_follow_based_on_chains() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/></w:style>
+  <w:style w:type="paragraph" w:styleId="ChapterTitle">
+    <w:name w:val="Chapter Title"/><w:basedOn w:val="Normal"/>
+    <w:pPr><w:outlineLvl w:val="0"/></w:pPr>
+  </w:style>
+  <w:style w:type="paragraph" w:styleId="Loop"><w:name w:val="Loop"/><w:basedOn w:val="Loop"/></w:style>
+</w:styles>"#;
+        let styles = Styles::parse(xml).unwrap();
+        assert_eq!(styles.heading_level("Heading2"), Some(2));
+        assert_eq!(styles.heading_level("ChapterTitle"), Some(1));
+        assert_eq!(styles.heading_level("Normal"), None);
+        assert_eq!(styles.heading_level("Loop"), None);
+        assert_eq!(styles.heading_level("Missing"), None);
+        assert_eq!(styles.name("Heading2"), Some("heading 2"));
+        assert_eq!(styles.default_paragraph_style(), Some("Normal"));
+    }
+}
@@ -0,0 +1,239 @@
+/// Unicode look-alikes of Wingdings glyphs, by character code.
+const WINGDINGS: &[(u8, char)] = &[
+    (0x21, '✏'),
+    (0x22, '✂'),
+    (0x28, '☎'),
+    (0x2A, '✉'),
+    (0x36, '⌛'),
+    (0x37, '⌨'),
+    (0x3F, '✍'),
+    (0x41, '✌'),
+    (0x43, '👍'),
+    (0x44, '👎'),
+    (0x45, '☜'),
+    (0x46, '☞'),
+    (0x4A, '☺'),
+    (0x4C, '☹'),
+    (0x4E, '☠'),
+    (0x52, '☼'),
+    (0x54, '❄'),
+    (0x58, '✠'),
+    (0x6C, '●'),
+    (0x6D, '❍'),
+    (0x6E, '■'),
+    (0x6F, '□'),
+    (0x70, '◻'),
+    (0x71, '❑'),
+    (0x72, '❒'),
+    (0x74, '⧫'),
+    (0x75, '◆'),
+    (0x76, '❖'),
+    (0x78, '⌧'),
+    (0x7A, '⌘'),
+    (0x9F, '•'),
+    (0xA7, '▪'),
+    (0xA8, '◻'),
+    (0xAB, '★'),
+    (0xD8, '➢'),
+    (0xDF, '←'),
+    (0xE0, '→'),
+    (0xE1, '↑'),
+    (0xE2, '↓'),
+    (0xE3, '↖'),
+    (0xE4, '↗'),
+    (0xE5, '↙'),
+    (0xE6, '↘'),
+    (0xE8, '➔'),
+    (0xEF, '⇦'),
+    (0xF0, '⇨'),
+    (0xF1, '⇧'),
+    (0xF2, '⇩'),
+    (0xF3, '⬄'),
+    (0xF4, '⇳'),
+    (0xFB, '✗'),
+    (0xFC, '✓'),
+    (0xFD, '☒'),
+    (0xFE, '☑'),
+];
+
+/// Unicode equivalents of the Symbol font: Greek letters where Latin ones
+/// would be, and mathematical signs. Digits and most punctuation are the
+/// same as in ASCII.
+const SYMBOL: &[(u8, char)] = &[
+    (0x22, '∀'),
+    (0x24, '∃'),
+    (0x27, '∋'),
+    (0x2A, '∗'),
+    (0x2D, '−'),
+    (0x40, '≅'),
+    (0x41, 'Α'),
+    (0x42, 'Β'),
+    (0x43, 'Χ'),
+    (0x44, 'Δ'),
+    (0x45, 'Ε'),
+    (0x46, 'Φ'),
+    (0x47, 'Γ'),
+    (0x48, 'Η'),
+    (0x49, 'Ι'),
+    (0x4A, 'ϑ'),
+    (0x4B, 'Κ'),
+    (0x4C, 'Λ'),
+    (0x4D, 'Μ'),
+    (0x4E, 'Ν'),
+    (0x4F, 'Ο'),
+    (0x50, 'Π'),
+    (0x51, 'Θ'),
+    (0x52, 'Ρ'),
+    (0x53, 'Σ'),
+    (0x54, 'Τ'),
+    (0x55, 'Υ'),
+    (0x56, 'ς'),
+    (0x57, 'Ω'),
+    (0x58, 'Ξ'),
+    (0x59, 'Ψ'),
+    (0x5A, 'Ζ'),
+    (0x5C, '∴'),
+    (0x5E, '⊥'),
+    (0x61, 'α'),
+    (0x62, 'β'),
+    (0x63, 'χ'),
+    (0x64, 'δ'),
+    (0x65, 'ε'),
+    (0x66, 'φ'),
+    (0x67, 'γ'),
+    (0x68, 'η'),
+    (0x69, 'ι'),
+    (0x6A, 'ϕ'),
+    (0x6B, 'κ'),
+    (0x6C, 'λ'),
+    (0x6D, 'μ'),
+    (0x6E, 'ν'),
+    (0x6F, 'ο'),
+    (0x70, 'π'),
+    (0x71, 'θ'),
+    (0x72, 'ρ'),
+    (0x73, 'σ'),
+    (0x74, 'τ'),
+    (0x75, 'υ'),
+    (0x76, 'ϖ'),
+    (0x77, 'ω'),
+    (0x78, 'ξ'),
+    (0x79, 'ψ'),
+    (0x7A, 'ζ'),
+    (0x7E, '∼'),
+    (0xA2, '′'),
+    (0xA3, '≤'),
+    (0xA5, '∞'),
+    (0xA7, '♣'),
+    (0xA8, '♦'),
+    (0xA9, '♥'),
+    (0xAA, '♠'),
+    (0xAB, '↔'),
+    (0xAC, '←'),
+    (0xAD, '↑'),
+    (0xAE, '→'),
+    (0xAF, '↓'),
+    (0xB0, '°'),
+    (0xB1, '±'),
+    (0xB2, '″'),
+    (0xB3, '≥'),
+    (0xB4, '×'),
+    (0xB5, '∝'),
+    (0xB6, '∂'),
+    (0xB7, '•'),
+    (0xB8, '÷'),
+    (0xB9, '≠'),
+    (0xBA, '≡'),
+    (0xBB, '≈'),
+    (0xBC, '…'),
+    (0xC5, '⊕'),
+    (0xC6, '∅'),
+    (0xC7, '∩'),
+    (0xC8, '∪'),
+    (0xC9, '⊃'),
+    (0xCA, '⊇'),
+    (0xCC, '⊂'),
+    (0xCD, '⊆'),
+    (0xCE, '∈'),
+    (0xCF, '∉'),
+    (0xD0, '∠'),
+    (0xD1, '∇'),
+    (0xD2, '®'),
+    (0xD3, '©'),
+    (0xD4, '™'),
+    (0xD5, '∏'),
+    (0xD6, '√'),
+    (0xD7, '⋅'),
+    (0xD8, '¬'),
+    (0xD9, '∧'),
+    (0xDA, '∨'),
+    (0xDB, '⇔'),
+    (0xDC, '⇐'),
+    (0xDD, '⇑'),
+    (0xDE, '⇒'),
+    (0xDF, '⇓'),
+    (0xE0, '◊'),
+    (0xE1, '〈'),
+    (0xE5, '∑'),
+    (0xF1, '〉'),
+    (0xF2, '∫'),
+];
+
+/// Unicode look-alikes of Webdings glyphs, by character code.
+const WEBDINGS: &[(u8, char)] = &[
+    (0x33, '◀'),
+    (0x34, '▶'),
+    (0x35, '▲'),
+    (0x36, '▼'),
+    (0x3C, '■'),
+    (0x61, '✔'),
+    (0x63, '☐'),
+    (0x6E, '■'),
+    (0x72, '✖'),
+];
+
+/// The Unicode character for a symbol (`w:sym`) in the given font. Symbol
+/// fonts put their glyphs at `F020`..`F0FF` in the private use area, which
+/// Word also writes without the `F000` offset; those of Symbol, Wingdings
+/// and Webdings map to their closest Unicode equivalents. Symbols in other
+/// fonts are taken as the Unicode characters they are, unless they are in
+/// the private use area. Returns `None` for glyphs without an equivalent.
+pub fn symbol_char(font: &str, code: u32) -> Option<char> {
+    let glyph = match code {
+        0xF000..=0xF0FF => code - 0xF000,
+        _ => code,
+    };
+    // Symbol keeps the ASCII digits and punctuation it does not replace.
+    let (table, keeps_ascii) = match font.trim().to_ascii_lowercase().as_str() {
+        "symbol" => (SYMBOL, true),
+        "wingdings" => (WINGDINGS, false),
+        "webdings" => (WEBDINGS, false),
+        _ => {
+            let c = char::from_u32(code)?;
+            let private_use = ('\u{E000}'..='\u{F8FF}').contains(&c);
+            return (!private_use && !c.is_control()).then_some(c);
+        }
+    };
+    let glyph = u8::try_from(glyph).ok()?;
+    if let Some(&(_, c)) = table.iter().find(|(code, _)| *code == glyph) {
+        return Some(c);
+    }
+    let ascii = glyph as char;
+    (keeps_ascii && ascii.is_ascii_graphic() && !ascii.is_ascii_alphabetic()).then_some(ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_chars() {
+        assert_eq!(symbol_char("Wingdings", 0xF0FC), Some('✓'));
+        assert_eq!(symbol_char("Wingdings", 0xE0), Some('→'));
+        assert_eq!(symbol_char("Symbol", 0xF061), Some('α'));
+        assert_eq!(symbol_char("Symbol", 0xF031), Some('1'));
+        assert_eq!(symbol_char("Wingdings", 0xF0A0), None);
+        assert_eq!(symbol_char("Arial", 0x2192), Some('→'));
+        assert_eq!(symbol_char("Arial", 0xF0E0), None);
+    }
+}
@@ -0,0 +1,205 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{extract_metadata, ExtractOptions, OutputFormat};
+
+/// Fills in the placeholders of a file header or footer for the file at
+/// `path`, the `index`th (from 0) of `total`. Braces around anything else
+/// are written as they are. In Markdown, the values are escaped, so that a
+/// name such as `my_notes.docx` is not taken for emphasis.
+pub(crate) fn render_file_template(
+    template: &str,
+    path: &Path,
+    index: usize,
+    total: usize,
+    options: &ExtractOptions,
+) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest
+            .find('}')
+            .and_then(|end| Some((end, placeholder(&rest[1..end], path, index, total, options)?)));
+        match value {
+            Some((end, value)) => {
+                match options.format {
+                    OutputFormat::Markdown => rendered.push_str(&escape_markdown(&value)),
+                    OutputFormat::Text => rendered.push_str(&value),
+                }
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// What a placeholder stands for, or `None` if it is none.
+fn placeholder(
+    name: &str,
+    path: &Path,
+    index: usize,
+    total: usize,
+    options: &ExtractOptions,
+) -> Option<String> {
+    let stem = || {
+        path.file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())
+    };
+    Some(match name {
+        "path" => path.display().to_string(),
+        "name" => path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+        "stem" => stem(),
+        // Counted from 1, for people.
+        "index" => (index + 1).to_string(),
+        "total" => total.to_string(),
+        // Documents without a title, and files without properties, are
+        // known by their names.
+        "title" => extract_metadata(path, options)
+            .ok()
+            .and_then(|metadata| metadata.title)
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(stem),
+        "mtime" => std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_or_else(|_| String::new(), format_time),
+        _ => return None,
+    })
+}
+
+/// The heading of a file merged as a chapter: its title, or else its name
+/// without extension, with `_` and `-` as spaces and each word capitalized,
+/// so that `field_notes-2024.docx` becomes `Field Notes 2024`.
+pub(crate) fn chapter_title(path: &Path, options: &ExtractOptions) -> String {
+    let title = extract_metadata(path, options)
+        .ok()
+        .and_then(|metadata| metadata.title)
+        .filter(|title| !title.trim().is_empty());
+    if let Some(title) = title {
+        return title.trim().to_string();
+    }
+    let stem = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+    stem.split(|c: char| c == '_' || c == '-' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The heading line of a file merged as a chapter, at `level`, marked as
+/// the options mark headings.
+pub(crate) fn chapter_heading(path: &Path, level: usize, options: &ExtractOptions) -> String {
+    let title = chapter_title(path, options);
+    match options.format {
+        OutputFormat::Markdown => format!("{} {}", "#".repeat(level), escape_markdown(&title)),
+        OutputFormat::Text if options.mark_headings => format!("[H{}] {}", level, title),
+        OutputFormat::Text => title,
+    }
+}
+
+/// Writes a time as UTC in RFC 3339 form, e.g. `2024-03-01T09:30:00Z`.
+pub(crate) fn format_time(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+    // Howard Hinnant's civil_from_days, for days since 1970-01-01.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Escapes the characters Markdown gives a meaning to.
+pub(crate) fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '{' | '}' | '[' | ']' | '<' | '>' | '(' | ')' | '#' | '+'
+                | '-' | '.' | '!' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_placeholders() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("my_notes.txt");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(1_709_285_400))
+            .unwrap();
+        let options = ExtractOptions::default();
+
+        assert_eq!(
+            render_file_template("== {index}/{total}: {name} ({title}) {mtime} {other} {", &path, 1, 3, &options),
+            "== 2/3: my_notes.txt (my_notes) 2024-03-01T09:30:00Z {other} {"
+        );
+        assert_eq!(
+            render_file_template("{path}", &path, 0, 1, &options),
+            path.display().to_string()
+        );
+        let markdown = ExtractOptions::new().format(OutputFormat::Markdown);
+        assert_eq!(
+            render_file_template("## {name}", &path, 0, 1, &markdown),
+            "## my\\_notes\\.txt"
+        );
+    }
+
+    #[test]
+    fn test_chapter_title_from_file_name() {
+        let options = ExtractOptions::default();
+        let title = |name: &str| chapter_title(Path::new(name), &options);
+        assert_eq!(title("field_notes-2024.docx"), "Field Notes 2024");
+        assert_eq!(title("dir/ünter__the_NATO-sea.docx"), "Ünter The NATO Sea");
+        assert_eq!(
+            chapter_heading(Path::new("a_b.docx"), 2, &options.clone().format(OutputFormat::Markdown)),
+            "## A B"
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        let time = |seconds| format_time(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(time(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(time(4_102_444_799), "2099-12-31T23:59:59Z");
+    }
+}
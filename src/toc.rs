@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use crate::template::escape_markdown;
+use crate::{ExtractOptions, FileStart, OutputFormat};
+
+/// One line of a table of contents: a file, or a heading in it.
+struct Entry {
+    /// 0 for a file, the heading's level for a heading.
+    depth: usize,
+    /// The file's path or the heading's text, as written in the table.
+    title: String,
+    line: usize,
+}
+
+/// Puts a table of contents before merged text, listing each file, and the
+/// headings in it, with the line it starts on. Headings are found as they
+/// were marked: in Markdown, as `#` lines, which the table links to by
+/// the anchors GitHub gives them; in plain text, as `[H1]` lines when
+/// [`mark_headings`](ExtractOptions::mark_headings) is on, and not at all
+/// otherwise.
+///
+/// `text` and `file_starts` are those of one merge with `options`, as
+/// [`MergedText`](crate::MergedText) has them. The line numbers count the
+/// table's own lines, so they are those of the text returned.
+pub fn with_table_of_contents(
+    text: &str,
+    file_starts: &[FileStart],
+    options: &ExtractOptions,
+) -> String {
+    let markdown = options.format == OutputFormat::Markdown;
+    let lines: Vec<&str> = text.lines().collect();
+    let mut slugs = Slugs::default();
+    let mut entries = Vec::new();
+    for (index, start) in file_starts.iter().enumerate() {
+        let path = start.path.display().to_string();
+        entries.push(Entry {
+            depth: 0,
+            title: if markdown { escape_markdown(&path) } else { path },
+            line: start.line,
+        });
+        let end = file_starts
+            .get(index + 1)
+            .map_or(lines.len(), |next| next.line - 1);
+        for line in start.line..=end {
+            let Some((level, title)) = lines.get(line - 1).and_then(|text| heading(text, options))
+            else {
+                continue;
+            };
+            entries.push(Entry {
+                depth: level,
+                title: if markdown {
+                    format!("[{}](#{})", title, slugs.next(title))
+                } else {
+                    title.to_string()
+                },
+                line,
+            });
+        }
+    }
+
+    // The title, a blank line, the entries and a blank line come before
+    // the text.
+    let offset = entries.len() + 3;
+    let bullet = if markdown { "- " } else { "" };
+    let mut contents = vec![
+        if markdown { "**Contents**" } else { "Contents" }.to_string(),
+        String::new(),
+    ];
+    for entry in &entries {
+        contents.push(format!(
+            "{}{}{}, line {}",
+            "  ".repeat(entry.depth),
+            bullet,
+            entry.title,
+            entry.line + offset
+        ));
+    }
+    contents.push(String::new());
+    let mut with_contents = contents.join("\n");
+    with_contents.push('\n');
+    with_contents.push_str(text);
+    with_contents
+}
+
+/// The level and text of a heading line, as the options mark headings.
+fn heading<'t>(line: &'t str, options: &ExtractOptions) -> Option<(usize, &'t str)> {
+    match options.format {
+        OutputFormat::Markdown => {
+            let title = line.trim_start_matches('#');
+            let level = line.len() - title.len();
+            let title = title.strip_prefix(' ')?;
+            (level > 0).then_some((level, title.trim()))
+        }
+        OutputFormat::Text if options.mark_headings => {
+            let rest = line.strip_prefix("[H")?;
+            let (level, title) = rest.split_once("] ")?;
+            Some((level.parse().ok()?, title.trim()))
+        }
+        OutputFormat::Text => None,
+    }
+}
+
+/// The anchors GitHub gives headings: their text in lower case, without
+/// punctuation and with dashes for spaces, numbered from the second time a
+/// text comes.
+#[derive(Default)]
+pub(crate) struct Slugs {
+    seen: HashMap<String, usize>,
+}
+
+impl Slugs {
+    pub(crate) fn next(&mut self, title: &str) -> String {
+        let slug: String = title
+            .to_lowercase()
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                '-' | '_' => Some(c),
+                c if c.is_alphanumeric() => Some(c),
+                _ => None,
+            })
+            .collect();
+        let count = self.seen.entry(slug.clone()).or_insert(0);
+        *count += 1;
+        match *count {
+            1 => slug,
+            n => format!("{}-{}", slug, n - 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugs_as_github_makes_them() {
+        let mut slugs = Slugs::default();
+        assert_eq!(slugs.next("What's New in 2.0?"), "whats-new-in-20");
+        assert_eq!(slugs.next("Über_alles - Teil 1"), "über_alles---teil-1");
+        assert_eq!(slugs.next("Notes"), "notes");
+        assert_eq!(slugs.next("notes"), "notes-1");
+        assert_eq!(slugs.next("Notes"), "notes-2");
+    }
+}
@@ -0,0 +1,206 @@
+/// Receives the content of a document as it is extracted, for renderers of
+/// other formats; see [`extract_with_visitor`](crate::extract_with_visitor).
+/// Every method does nothing unless implemented. The plain text and
+/// Markdown that extraction writes are rendered by a visitor too.
+///
+/// A visitor sees the text extraction sees, with revisions, fields, hidden
+/// text and the filters of the options applied. A paragraph's content
+/// arrives between its `paragraph_start` and `paragraph_end`, once it is
+/// known to be kept. Paragraphs nest: those of a text box arrive inside the
+/// paragraph the box is anchored in, where it is anchored. Tables arrive as
+/// they are read, their cells holding paragraphs.
+#[allow(unused_variables)]
+pub trait DocxVisitor {
+    /// A paragraph starts.
+    fn paragraph_start(&mut self, paragraph: &ParagraphInfo) {}
+    /// The paragraph last started ends.
+    fn paragraph_end(&mut self) {}
+    /// Text of a run, or the tab, line break or symbol in it.
+    fn text(&mut self, text: &str, run: &RunInfo) {}
+    /// Text extraction writes in place of content that is not run text:
+    /// a placeholder for a picture, object or equation, or the linear
+    /// text of an equation, a note or comment written inline, a field
+    /// instruction, the guide of ruby text after its base text. Outside a
+    /// paragraph, such as the name of a glossary entry, it is a line of its
+    /// own.
+    fn marker(&mut self, text: &str) {}
+    /// A page break (`w:br w:type="page"`).
+    fn page_break(&mut self) {}
+    /// Word started page `page` here when it last laid the document out
+    /// (`w:lastRenderedPageBreak`).
+    fn rendered_page_break(&mut self, page: usize) {}
+    /// A hyperlink starts; its text follows.
+    fn hyperlink_start(&mut self, link: &LinkInfo) {}
+    /// The hyperlink last started ends.
+    fn hyperlink_end(&mut self) {}
+    /// The content of a content control (`w:sdt`) starts, inside a
+    /// paragraph or around paragraphs of its own.
+    fn content_control_start(&mut self, control: &ControlInfo) {}
+    /// The content control last started ends.
+    fn content_control_end(&mut self) {}
+    /// A section ends, after its last paragraph or, for the last section,
+    /// at the end of the document.
+    fn section_end(&mut self, section: &SectionInfo) {}
+    /// A table starts.
+    fn table_start(&mut self) {}
+    /// The table last started ends.
+    fn table_end(&mut self) {}
+    /// A table row starts.
+    fn row_start(&mut self) {}
+    /// The row last started ends.
+    fn row_end(&mut self) {}
+    /// A table cell starts.
+    fn cell_start(&mut self, cell: &CellInfo) {}
+    /// The cell last started ends.
+    fn cell_end(&mut self) {}
+}
+
+/// What a visitor is told about a paragraph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParagraphInfo {
+    /// The id of the paragraph's style (`w:pStyle`), if it names one.
+    pub style: Option<String>,
+    /// The level of a list paragraph, 0 for the outermost; `None` outside
+    /// lists.
+    pub list_level: Option<usize>,
+    /// The list paragraph is numbered rather than bulleted.
+    pub numbered: bool,
+    /// The label of a list paragraph as Word shows it, e.g. `2.`, `b)` or
+    /// `•`; `None` outside lists or if its level has no definition. List
+    /// levels may have an empty label.
+    pub list_label: Option<String>,
+    /// The counter of a numbered list paragraph at its level, e.g. 2 for
+    /// `b)`.
+    pub list_number: Option<usize>,
+    /// The outline level of a heading, 1 for top-level headings; `None`
+    /// for other paragraphs.
+    pub heading_level: Option<usize>,
+}
+
+/// What a visitor is told about the run text is in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunInfo {
+    /// The text is hidden (`w:vanish`), and only extracted because the
+    /// options include hidden text.
+    pub hidden: bool,
+    /// The text is struck through.
+    pub struck: bool,
+    /// The run is bold (`w:b`). Bold given by the run's style is not
+    /// counted.
+    pub bold: bool,
+    /// The run is italic (`w:i`), likewise.
+    pub italic: bool,
+}
+
+/// What a visitor is told about a hyperlink.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkInfo {
+    /// The resolved target, or `#name` for a bookmark in the document;
+    /// `None` if the link has no target.
+    pub target: Option<String>,
+}
+
+/// What a visitor is told about a content control.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ControlInfo {
+    /// The title users see (`w:alias`), if it has one.
+    pub alias: Option<String>,
+    /// The tag (`w:tag`), if it has one.
+    pub tag: Option<String>,
+}
+
+impl ControlInfo {
+    /// The title of the control, or failing that its tag.
+    pub fn label(&self) -> Option<&str> {
+        self.alias.as_deref().or(self.tag.as_deref())
+    }
+}
+
+/// What a visitor is told about a section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionInfo {
+    /// How the section starts (`w:type`): `nextPage`, `continuous`,
+    /// `evenPage`, `oddPage` or `nextColumn`; `None` if not given, which
+    /// Word reads as `nextPage`.
+    pub start: Option<String>,
+}
+
+/// What a visitor is told about a table cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellInfo {
+    /// The columns of the table grid the cell spans (`w:gridSpan`), at
+    /// least 1.
+    pub grid_span: usize,
+    /// The cell is part of cells merged vertically (`w:vMerge`).
+    pub vertically_merged: bool,
+    /// The cell continues a vertical merge from the row above, rather than
+    /// starting one; it holds nothing of its own.
+    pub continues_merge: bool,
+}
+
+impl Default for CellInfo {
+    fn default() -> Self {
+        CellInfo {
+            grid_span: 1,
+            vertically_merged: false,
+            continues_merge: false,
+        }
+    }
+}
+
+/// A call to a visitor, held until the paragraph it is in is known to be
+/// kept.
+#[derive(Debug)]
+pub(crate) enum Visit {
+    ParagraphStart(ParagraphInfo),
+    ParagraphEnd,
+    Text(String, RunInfo),
+    Marker(String),
+    PageBreak,
+    RenderedPageBreak(usize),
+    HyperlinkStart(LinkInfo),
+    HyperlinkEnd,
+    ContentControlStart(ControlInfo),
+    ContentControlEnd,
+    SectionEnd(SectionInfo),
+    TableStart,
+    TableEnd,
+    RowStart,
+    RowEnd,
+    CellStart(CellInfo),
+    CellEnd,
+}
+
+impl Visit {
+    pub fn send(&self, visitor: &mut dyn DocxVisitor) {
+        match self {
+            Visit::ParagraphStart(paragraph) => visitor.paragraph_start(paragraph),
+            Visit::ParagraphEnd => visitor.paragraph_end(),
+            Visit::Text(text, run) => visitor.text(text, run),
+            Visit::Marker(text) => visitor.marker(text),
+            Visit::PageBreak => visitor.page_break(),
+            Visit::RenderedPageBreak(page) => visitor.rendered_page_break(*page),
+            Visit::HyperlinkStart(link) => visitor.hyperlink_start(link),
+            Visit::HyperlinkEnd => visitor.hyperlink_end(),
+            Visit::ContentControlStart(control) => visitor.content_control_start(control),
+            Visit::ContentControlEnd => visitor.content_control_end(),
+            Visit::SectionEnd(section) => visitor.section_end(section),
+            Visit::TableStart => visitor.table_start(),
+            Visit::TableEnd => visitor.table_end(),
+            Visit::RowStart => visitor.row_start(),
+            Visit::RowEnd => visitor.row_end(),
+            Visit::CellStart(cell) => visitor.cell_start(cell),
+            Visit::CellEnd => visitor.cell_end(),
+        }
+    }
+
+    /// Whether the call starts a block that stays when the paragraph it is
+    /// in is left out: a paragraph or table of a text box.
+    pub fn starts_block(&self) -> bool {
+        matches!(self, Visit::ParagraphStart(_) | Visit::TableStart)
+    }
+
+    pub fn ends_block(&self) -> bool {
+        matches!(self, Visit::ParagraphEnd | Visit::TableEnd)
+    }
+}
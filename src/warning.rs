@@ -0,0 +1,100 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Something in a document that extraction got past without failing, but
+/// whose text may not be what the document shows; see
+/// [`extract_text_with_warnings`](crate::extract_text_with_warnings).
+///
+/// Where it is known, a warning says which paragraph it is in, by its
+/// index from 0 among all the paragraphs of the main document, those of
+/// tables and text boxes included. Word numbers no paragraphs, so this is
+/// only an approximate location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Warning {
+    /// A symbol from a symbol font (`w:sym`) with no Unicode equivalent,
+    /// written as [`unmapped_symbols`](crate::ExtractOptions::unmapped_symbols)
+    /// says.
+    UnmappedSymbol {
+        /// The document.
+        path: PathBuf,
+        /// The paragraph the symbol is in.
+        paragraph: usize,
+        /// The font, such as `Wingdings`.
+        font: String,
+        /// The character code in the font.
+        code: u32,
+    },
+    /// An equation written as `[equation]`, under
+    /// [`EquationMode::Skip`](crate::EquationMode::Skip).
+    EquationSkipped {
+        /// The document.
+        path: PathBuf,
+        /// The paragraph the equation is in.
+        paragraph: usize,
+    },
+    /// Content imported from another file, such as HTML or RTF pasted into
+    /// the document (`w:altChunk`), which is not extracted.
+    ImportedContent {
+        /// The document.
+        path: PathBuf,
+        /// The paragraph the content comes before.
+        paragraph: usize,
+        /// The part holding the content, as the document refers to it.
+        part: String,
+    },
+    /// A part the document refers to, such as a header, that the package
+    /// lacks. Its text is left out.
+    MissingPart {
+        /// The document.
+        path: PathBuf,
+        /// The name of the part in the package.
+        part: String,
+    },
+}
+
+impl Warning {
+    /// The document the warning is about.
+    pub fn path(&self) -> &Path {
+        match self {
+            Warning::UnmappedSymbol { path, .. }
+            | Warning::EquationSkipped { path, .. }
+            | Warning::ImportedContent { path, .. }
+            | Warning::MissingPart { path, .. } => path,
+        }
+    }
+
+    /// The index of the paragraph the warning is about, if it is about one.
+    pub fn paragraph(&self) -> Option<usize> {
+        match self {
+            Warning::UnmappedSymbol { paragraph, .. }
+            | Warning::EquationSkipped { paragraph, .. }
+            | Warning::ImportedContent { paragraph, .. } => Some(*paragraph),
+            Warning::MissingPart { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.path().display())?;
+        // Paragraphs are counted from 1 for people.
+        if let Some(paragraph) = self.paragraph() {
+            write!(f, "paragraph {}: ", paragraph + 1)?;
+        }
+        match self {
+            Warning::UnmappedSymbol { font, code, .. } => write!(
+                f,
+                "symbol {:04X} of font {} has no Unicode equivalent",
+                code, font
+            ),
+            Warning::EquationSkipped { .. } => write!(f, "equation left out"),
+            Warning::ImportedContent { part, .. } => {
+                write!(f, "content imported from {} is not extracted", part)
+            }
+            Warning::MissingPart { part, .. } => {
+                write!(f, "{} is missing, though the document refers to it", part)
+            }
+        }
+    }
+}
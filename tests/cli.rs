@@ -0,0 +1,1227 @@
+use assert_cmd::Command;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::FileOptions;
+
+/// Writes a DOCX file with one paragraph per line of `text`.
+fn write_docx(path: &Path, text: &str) {
+    let paragraphs: String = text
+        .lines()
+        .map(|line| format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", line))
+        .collect();
+    write_docx_body(path, &paragraphs);
+}
+
+/// Writes a DOCX file with `body` as the body of its main document.
+fn write_docx_body(path: &Path, body: &str) {
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+        body
+    );
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    zip.start_file("word/document.xml", FileOptions::default())
+        .unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_merges_files_into_merged_txt() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second\nThird");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["a.docx", "b.docx"])
+        .assert()
+        .success();
+
+    let merged = std::fs::read_to_string(temp_dir.path().join("merged.txt")).unwrap();
+    assert_eq!(merged, "First\n\nSecond\nThird");
+}
+
+#[test]
+fn test_failures_exit_with_their_status() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .arg("*.docx")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No files found"));
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .args(["merge", "--links", "fancy", "a.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown link style 'fancy'"));
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .args(["merge", "--jobs", "0", "a.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid job count '0'"));
+    assert!(!temp_dir.path().join("merged.txt").exists());
+
+    std::fs::write(temp_dir.path().join("broken.docx"), "not a package").unwrap();
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .arg("broken.docx")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("broken.docx is damaged"));
+}
+
+#[test]
+fn test_output_option_writes_where_asked() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["-o", "out/all.txt", "--separator", "\n--\n", "a.docx", "b.docx"])
+        .assert()
+        .failure()
+        .code(2);
+    std::fs::create_dir(temp_dir.path().join("out")).unwrap();
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["-o", "out/all.txt", "--separator", "\n--\n", "a.docx", "b.docx"])
+        .assert()
+        .success();
+    let merged = std::fs::read_to_string(temp_dir.path().join("out/all.txt")).unwrap();
+    assert_eq!(merged, "First\n--\nSecond");
+    assert!(!temp_dir.path().join("merged.txt").exists());
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--output", "-", "--file-header", "# {path}", "a.docx", "b.docx"])
+        .assert()
+        .success()
+        .stdout("# a.docx\nFirst\n\n# b.docx\nSecond");
+    assert!(!temp_dir.path().join("merged.txt").exists());
+}
+
+#[test]
+fn test_existing_merged_txt_is_kept_without_force() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "New");
+    let merged_path = temp_dir.path().join("merged.txt");
+    std::fs::write(&merged_path, "Old").unwrap();
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .arg("a.docx")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+    assert_eq!(std::fs::read_to_string(&merged_path).unwrap(), "Old");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--force", "a.docx"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&merged_path).unwrap(), "New");
+}
+
+#[test]
+fn test_skip_errors_writes_partial_output() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    std::fs::write(temp_dir.path().join("b.docx"), "not a package").unwrap();
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["a.docx", "b.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(!temp_dir.path().join("merged.txt").exists());
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--skip-errors", "a.docx", "b.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(7));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 of 2 files could not be extracted"));
+    assert!(stderr.contains("b.docx is damaged"));
+    let merged = std::fs::read_to_string(temp_dir.path().join("merged.txt")).unwrap();
+    assert_eq!(merged, "First");
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--error-markers", "-o", "-", "b.docx", "a.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(7));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("[failed to extract: b.docx is damaged"));
+    assert!(stdout.ends_with("]\n\nFirst"));
+}
+
+#[test]
+fn test_list_links_as_csv() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let field = |target: &str, text: &str| {
+        format!(
+            r#"<w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText>HYPERLINK "{}"</w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>{}</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r>"#,
+            target, text
+        )
+    };
+    let body = format!(
+        "<w:p>{}</w:p><w:p>{}</w:p>",
+        field("https://example.com/a,b", "Say \"hi\""),
+        field("mailto:me@example.com", "Mail")
+    );
+    write_docx_body(&temp_dir.path().join("links.docx"), &body);
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--list-links", "--csv", "links.docx"])
+        .assert()
+        .success()
+        .stdout(
+            "file,kind,target,text\n\
+             links.docx,external,\"https://example.com/a,b\",\"Say \"\"hi\"\"\"\n\
+             links.docx,mailto,mailto:me@example.com,Mail\n",
+        );
+    assert!(!temp_dir.path().join("merged.txt").exists());
+}
+
+#[test]
+fn test_stats_only_writes_no_output() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "One two three");
+    write_docx(&temp_dir.path().join("b.docx"), "Four\n\nFive six");
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--stats-only", "a.docx", "b.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!temp_dir.path().join("merged.txt").exists());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let rows: Vec<Vec<&str>> = stderr.lines().map(|line| line.split_whitespace().collect()).collect();
+    assert_eq!(
+        rows,
+        [
+            vec!["file", "words", "characters", "no", "spaces", "paragraphs", "headings", "pages"],
+            vec!["a.docx", "3", "13", "11", "1", "0", "1"],
+            vec!["b.docx", "3", "12", "11", "2", "0", "1"],
+            vec!["total", "6", "25", "22", "3", "0", "2"],
+            vec!["Counted", "2", "files."],
+        ]
+    );
+}
+
+#[test]
+fn test_cache_dir_keeps_and_clears_texts() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "Cached text");
+    let run = |args: &[&str]| {
+        Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .arg("merge")
+            .args(["-o", "-", "--cache-dir", "cache"])
+            .args(args)
+            .arg("a.docx")
+            .output()
+            .unwrap()
+    };
+    let entries = || std::fs::read_dir(temp_dir.path().join("cache")).map_or(0, |dir| dir.count());
+
+    let output = run(&["--no-cache"]);
+    assert_eq!(output.stdout, b"Cached text");
+    assert_eq!(entries(), 0);
+    let output = run(&[]);
+    assert_eq!(output.stdout, b"Cached text");
+    assert_eq!(entries(), 1);
+    let output = run(&["--cache-clear", "--no-cache"]);
+    assert_eq!(output.stdout, b"Cached text");
+    assert_eq!(entries(), 0);
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--cache-clear", "a.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_warnings_are_counted_and_shown_when_verbose() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx_body(
+        &temp_dir.path().join("a.docx"),
+        r#"<w:p><w:r><w:t xml:space="preserve">Done </w:t><w:sym w:font="Wingdings" w:char="F0A0"/></w:r></w:p>"#,
+    );
+    let run = |args: &[&str]| {
+        Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .arg("merge")
+            .args(["-o", "-"])
+            .args(args)
+            .arg("a.docx")
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Done [symbol]");
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Merged 1 file to standard output, with 1 warning; use --verbose to see it.\n"
+    );
+
+    let output = run(&["--verbose"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", stderr);
+    assert!(lines[0].starts_with("a.docx: 13 bytes extracted in "), "{}", stderr);
+    assert_eq!(
+        lines[1],
+        "warning: a.docx: paragraph 1: symbol F0A0 of font Wingdings has no Unicode equivalent"
+    );
+    assert!(lines[2].starts_with("Merged 1 file to standard output in "), "{}", stderr);
+    assert!(lines[2].ends_with("s, with 1 warning."), "{}", stderr);
+}
+
+#[test]
+fn test_skipped_summary_locates_truncated_xml() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Cut</w:t></w:r>"#;
+    let mut zip = zip::ZipWriter::new(File::create(temp_dir.path().join("b.docx")).unwrap());
+    zip.start_file("word/document.xml", FileOptions::default())
+        .unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("merge")
+        .args(["--skip-errors", "-o", "-", "a.docx", "b.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(7));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let located = format!(
+        "  b.docx (word/document.xml @ byte {}): Unexpected EOF during reading w:p\n",
+        xml.len()
+    );
+    assert!(stderr.ends_with(&located), "{}", stderr);
+}
+
+#[test]
+fn test_patterns_without_a_command_merge_with_a_note() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["-o", "-", "--separator", " | ", "a.docx", "b.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"First | Second");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("deprecated; use `mddocmrg merge`"));
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["--quiet", "-o", "-", "a.docx"])
+        .assert()
+        .success()
+        .stdout("First")
+        .stderr("");
+}
+
+#[test]
+fn test_extract_writes_one_file_to_stdout() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx_body(
+        &temp_dir.path().join("a.docx"),
+        r#"<w:p><w:r><w:t>Title</w:t></w:r></w:p><w:p><w:r><w:t xml:space="preserve">Done </w:t><w:sym w:font="Wingdings" w:char="F0A0"/></w:r></w:p>"#,
+    );
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["extract", "a.docx", "b.docx"])
+        .assert()
+        .code(1);
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["extract", "--unmapped-symbols", "raw", "a.docx"])
+        .assert()
+        .success()
+        .stdout("Title\nDone \u{F0A0}")
+        .stderr("Extracted a.docx, with 1 warning; use --verbose to see it.\n");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["--quiet", "extract", "a.docx"])
+        .assert()
+        .success()
+        .stdout("Title\nDone [symbol]")
+        .stderr("");
+    assert!(!temp_dir.path().join("merged.txt").exists());
+}
+
+#[test]
+fn test_info_prints_properties_parts_and_counts() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "One two three\nFour");
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["info", "a.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "a.docx:");
+    assert_eq!(lines[1], "  parts:");
+    assert!(lines[2].starts_with("    word/document.xml\tunknown type\t"), "{}", stdout);
+    assert_eq!(
+        lines[3..],
+        [
+            "  text:",
+            "    words: 4",
+            "    characters: 17",
+            "    characters without spaces: 15",
+            "    paragraphs: 2",
+            "    headings: 0",
+            "    pages: 1",
+        ]
+    );
+}
+
+#[test]
+fn test_list_prints_matched_files_without_extracting() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    std::fs::write(temp_dir.path().join("b.docx"), "not a package").unwrap();
+    std::fs::write(temp_dir.path().join("notes.pdf"), "not a document").unwrap();
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "*.docx"])
+        .assert()
+        .success()
+        .stdout("a.docx\nb.docx\n");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "*.odt"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_overlapping_patterns_merge_each_file_once() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "b.docx", "*.docx", "./a.docx", "."])
+        .assert()
+        .success()
+        .stdout("b.docx\na.docx\n");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "--keep-duplicates", "b.docx", "*.docx"])
+        .assert()
+        .success()
+        .stdout("b.docx\na.docx\nb.docx\n");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "*.docx", "a.docx"])
+        .assert()
+        .success()
+        .stdout("First\n\nSecond");
+}
+
+#[test]
+fn test_numbered_files_merge_in_natural_order() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("chapter10.docx"), "Ten");
+    write_docx(&temp_dir.path().join("chapter2.docx"), "Two");
+    write_docx(&temp_dir.path().join("Chapter1.docx"), "One");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "*.docx"])
+        .assert()
+        .success()
+        .stdout("One\n\nTwo\n\nTen");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "--sort", "name", "*.docx"])
+        .assert()
+        .success()
+        .stdout("Chapter1.docx\nchapter10.docx\nchapter2.docx\n");
+}
+
+#[test]
+fn test_files_merge_in_order_of_modification() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let notes = [("monday.docx", 1), ("tuesday.docx", 2), ("wednesday.docx", 3)];
+    for (name, day) in notes {
+        let path = temp_dir.path().join(name);
+        write_docx(&path, name.trim_end_matches(".docx"));
+        let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(day * 86_400);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--sort", "mtime", "--separator", " ", "*.docx"])
+        .assert()
+        .success()
+        .stdout("monday tuesday wednesday");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "--sort", "mtime", "--reverse", "*.docx"])
+        .assert()
+        .success()
+        .stdout("wednesday.docx\ntuesday.docx\nmonday.docx\n");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "--sort", "newest", "*.docx"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_recursive_merges_nested_directories() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let book = temp_dir.path().join("book");
+    std::fs::create_dir_all(book.join("part1/drafts")).unwrap();
+    std::fs::create_dir_all(book.join(".cache")).unwrap();
+    write_docx(&book.join("preface.docx"), "Preface");
+    write_docx(&book.join("part1/chapter1.docx"), "One");
+    write_docx(&book.join("part1/drafts/chapter1.docx"), "Draft");
+    write_docx(&book.join(".cache/copy.docx"), "Copy");
+
+    let count = |args: &[&str]| {
+        let output = Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().lines().count()
+    };
+    assert_eq!(count(&["list", "book"]), 1);
+    assert_eq!(count(&["list", "-r", "book"]), 3);
+    assert_eq!(count(&["list", "--recursive", "book", "book/part1"]), 3);
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "-r", "--separator", " ", "book"])
+        .assert()
+        .success()
+        .stdout("One Draft Preface");
+}
+
+#[test]
+fn test_exclude_drops_drafts_and_lockfiles() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let reports = temp_dir.path().join("reports");
+    std::fs::create_dir_all(reports.join("drafts")).unwrap();
+    write_docx(&reports.join("final.docx"), "Final");
+    write_docx(&reports.join("drafts/early.docx"), "Early");
+    std::fs::write(reports.join("~$final.docx"), "owner file").unwrap();
+
+    let list = |args: &[&str], listed: &str| {
+        Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(args)
+            .assert()
+            .success()
+            .stdout(listed.to_string());
+    };
+    list(&["list", "reports/**/*.docx"], "reports/drafts/early.docx\nreports/final.docx\n");
+    list(
+        &["list", "--exclude", "**/drafts/**", "reports/**/*.docx"],
+        "reports/final.docx\n",
+    );
+    list(
+        &["list", "--exclude", "reports/drafts/*", "--exclude", "*.bak", "-r", "reports"],
+        "reports/final.docx\n",
+    );
+    list(
+        &["list", "--include-lockfiles", "reports/*.docx"],
+        "reports/final.docx\nreports/~$final.docx\n",
+    );
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--exclude", "**/drafts/**", "reports/**/*.docx"])
+        .assert()
+        .success()
+        .stdout("Final");
+}
+
+#[test]
+fn test_lockfiles_are_skipped_and_reported() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("x.docx"), "Text");
+    // The user's name, padded, as Word writes it.
+    let mut owner = vec![5u8];
+    owner.extend(b"alice");
+    owner.resize(162, 0);
+    std::fs::write(temp_dir.path().join("~$x.docx"), owner).unwrap();
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "*.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Text");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Skipping ~$x.docx, the lock file"), "{}", stderr);
+    assert!(stderr.contains("Merged 1 file to standard output."), "{}", stderr);
+    assert!(!stderr.contains("error"), "{}", stderr);
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--include-lockfiles", "*.docx"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_files_from_merges_in_listed_order() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let book = temp_dir.path().join("book");
+    std::fs::create_dir(&book).unwrap();
+    write_docx(&book.join("intro.docx"), "Intro");
+    write_docx(&book.join("body.docx"), "Body");
+    write_docx(&book.join("end.docx"), "End");
+    std::fs::write(book.join("chapters.txt"), "# Chapters\nintro.docx\n\nbody.docx\n").unwrap();
+
+    let merge = |args: &[&str]| {
+        Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["merge", "-o", "-", "--separator", " "])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = merge(&["--files-from", "book/chapters.txt"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Intro Body");
+    // Listed files first, then the other files the patterns match.
+    let output = merge(&["--files-from", "book/chapters.txt", "book/*.docx"]);
+    assert_eq!(output.stdout, b"Intro Body End");
+
+    std::fs::write(book.join("chapters.txt"), "intro.docx\nmissing.docx\nend.docx\n").unwrap();
+    let output = merge(&["--files-from", "book/chapters.txt"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("chapters.txt, line 2: book/missing.docx does not exist"), "{}", stderr);
+    let output = merge(&["--files-from", "book/chapters.txt", "--skip-missing"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Intro End");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["list", "--files-from", "-"])
+        .write_stdin("book/end.docx\nbook/intro.docx\n")
+        .assert()
+        .success()
+        .stdout("book/end.docx\nbook/intro.docx\n");
+}
+
+#[test]
+fn test_file_header_and_footer_templates() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--separator", "\n"])
+        .args(["--file-header", "== {index}/{total} {path} =="])
+        .args(["--file-footer", "-- {stem} --"])
+        .args(["a.docx", "b.docx"])
+        .assert()
+        .success()
+        .stdout("== 1/2 a.docx ==\nFirst\n-- a --\n== 2/2 b.docx ==\nSecond\n-- b --");
+}
+
+#[test]
+fn test_separator_escapes_and_presets() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+
+    let runs: [(&[&str], &[u8]); 5] = [
+        (&["--separator", r"\n\t*\\\q\n"], b"First\n\t*\\\\q\nSecond"),
+        (&["--separator-preset", "hr"], b"First\n\n---\n\nSecond"),
+        (&["--separator-preset", "pagebreak"], b"First\n\x0c\nSecond"),
+        (&["--separator-preset", "none"], b"FirstSecond"),
+        (&[], b"First\n\nSecond"),
+    ];
+    for (args, merged) in runs {
+        let output = Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["merge", "-o", "-"])
+            .args(args)
+            .args(["a.docx", "b.docx"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", args);
+        assert_eq!(output.stdout, merged, "{:?}", args);
+    }
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--separator-preset", "fancy", "a.docx"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_toc_output_gives_the_line_each_file_starts_on() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--toc-output", "a.docx", "b.docx"])
+        .assert()
+        .success()
+        .stdout("Contents\n\na.docx, line 6\nb.docx, line 8\n\nFirst\n\nSecond");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--toc-output", "--format", "md", "--chapter-headings", "1"])
+        .args(["a.docx", "b.docx"])
+        .assert()
+        .success()
+        .stdout(
+            "**Contents**\n\n- a\\.docx, line 8\n  - [A](#a), line 8\n- b\\.docx, line 12\n  \
+             - [B](#b), line 12\n\n# A\n\nFirst\n\n# B\n\nSecond",
+        );
+}
+
+#[test]
+fn test_split_output_writes_a_file_per_input() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(temp_dir.path().join("more")).unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second\nThird");
+    write_docx(&temp_dir.path().join("more/a.docx"), "Fourth");
+    std::fs::write(temp_dir.path().join("bad.docx"), "not a zip").unwrap();
+    let read = |name: &str| std::fs::read_to_string(temp_dir.path().join(name)).unwrap();
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--split-output", "out", "--jobs", "2", "a.docx", "b.docx", "more/a.docx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(read("out/a.txt"), "First");
+    assert_eq!(read("out/b.txt"), "Second\nThird");
+    assert_eq!(read("out/a-2.txt"), "Fourth");
+    assert!(!temp_dir.path().join("merged.txt").exists());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("more/a.docx -> {}", Path::new("out").join("a-2.txt").display())));
+    assert!(stderr.contains("Wrote 3 files into out."), "{}", stderr);
+
+    // Files already there are kept without --force.
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--split-output", "out", "a.docx"])
+        .assert()
+        .code(1);
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--split-output", "out", "--format", "md", "--output-name", "{index}/{stem}.md"])
+        .args(["a.docx", "b.docx", "bad.docx", "--skip-errors"])
+        .assert()
+        .code(7);
+    assert_eq!(read("out/1/a.md"), "First");
+    assert_eq!(read("out/2/b.md"), "Second\n\nThird");
+    assert!(!temp_dir.path().join("out/3").exists());
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--split-output", "md", "--format", "md", "b.docx"])
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("md/b.md").exists());
+}
+
+#[test]
+fn test_format_json_gives_each_file_apart() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First file");
+    write_docx(&temp_dir.path().join("b.docx"), "Second\nThird");
+    write_docx_body(
+        &temp_dir.path().join("tab.docx"),
+        "<w:p><w:r><w:t>Second</w:t><w:tab/><w:t>Third</w:t></w:r></w:p>",
+    );
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--format", "json", "a.docx", "b.docx"])
+        .assert()
+        .success();
+    let json = std::fs::read(temp_dir.path().join("merged.json")).unwrap();
+    let output: mddocmrg::JsonOutput = serde_json::from_slice(&json).unwrap();
+    assert_eq!(output.tool, "mddocmrg");
+    assert_eq!(output.files.len(), 2);
+    assert_eq!(output.files[0].path, Path::new("a.docx"));
+    assert_eq!(output.files[0].title, "a");
+    assert_eq!(output.files[0].text, "First file");
+    assert_eq!(output.files[0].stats.words, 2);
+    assert_eq!(output.files[1].text, "Second\nThird");
+    assert_eq!(output.files[1].stats.paragraphs, 2);
+    assert!(!temp_dir.path().join("merged.txt").exists());
+
+    // The text follows the extraction options, and --pretty indents.
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--format", "json", "--pretty", "--tab-string", " / "])
+        .arg("tab.docx")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"{\n  \"tool\": \"mddocmrg\""));
+    let read: mddocmrg::JsonOutput = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(read.files[0].text, "Second / Third");
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .args(["extract", "--format", "json"])
+        .arg(temp_dir.path().join("a.docx"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let read: mddocmrg::JsonOutput = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(read.files[0].text, "First file");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--split-output", "out", "--format", "json", "a.docx"])
+        .assert()
+        .success();
+    let json = std::fs::read(temp_dir.path().join("out/a.json")).unwrap();
+    let read: mddocmrg::JsonOutput = serde_json::from_slice(&json).unwrap();
+    assert_eq!(read.files[0].stats.words, 2);
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--format", "json", "--toc-output", "a.docx"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_format_jsonl_writes_a_line_per_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let names: Vec<String> = (1..=6).map(|n| format!("part{}.docx", n)).collect();
+    for (n, name) in names.iter().enumerate() {
+        let text = format!("Part {} line\n", n + 1).repeat((6 - n) * 100);
+        write_docx(&temp_dir.path().join(name), &text);
+    }
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--format", "jsonl", "--jobs", "4", "part*.docx"])
+        .assert()
+        .success();
+    let text = std::fs::read_to_string(temp_dir.path().join("merged.jsonl")).unwrap();
+    assert!(text.ends_with('\n'));
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), names.len());
+    for (n, line) in lines.iter().enumerate() {
+        let file: mddocmrg::JsonFile = serde_json::from_str(line).unwrap();
+        assert_eq!(file.path, Path::new(&names[n]));
+        assert_eq!(file.stats.paragraphs, ((6 - n) * 100) as u64);
+        assert!(file.text.starts_with(&format!("Part {} line\n", n + 1)));
+    }
+
+    // A failure keeps the lines written before it.
+    std::fs::write(temp_dir.path().join("part9.docx"), "not a zip").unwrap();
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "out.jsonl", "--format", "jsonl", "--jobs", "1", "part1.docx", "part9.docx"])
+        .assert()
+        .code(3);
+    let text = std::fs::read_to_string(temp_dir.path().join("out.jsonl")).unwrap();
+    assert_eq!(text.lines().count(), 1);
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--format", "jsonl", "--split-output", "out", "part1.docx"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_format_md_marks_up_emphasis_and_escapes_text() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx_body(
+        &temp_dir.path().join("a.docx"),
+        r#"<w:p><w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve">Note: </w:t></w:r><w:r><w:t>use *.docx</w:t></w:r></w:p><w:p><w:r><w:br w:type="page"/></w:r></w:p><w:p><w:r><w:t>#done</w:t></w:r></w:p>"#,
+    );
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--format", "md", "a.docx"])
+        .assert()
+        .success()
+        .stdout("**Note:** use \\*.docx\n\n---\n\n\\#done");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "a.docx"])
+        .assert()
+        .success()
+        .stdout("Note: use *.docx\n\x0c\n#done");
+}
+
+#[test]
+fn test_format_html_writes_a_section_per_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("Chapter One.docx"), "Fish &amp; chips");
+    write_docx(&temp_dir.path().join("two.docx"), "&lt;Second&gt;");
+    std::fs::write(temp_dir.path().join("style.css"), "body { font-family: serif }\n").unwrap();
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--format", "html", "--html-css", "style.css"])
+        .args(["Chapter One.docx", "two.docx"])
+        .assert()
+        .success()
+        .stdout(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\"/>\n<title>Merged documents</title>\n\
+             <style>\nbody { font-family: serif }\n</style>\n</head>\n<body>\n\
+             <section id=\"chapter-one\">\n<p>Fish &amp; chips</p>\n</section>\n\
+             <section id=\"two\">\n<p>&lt;Second&gt;</p>\n</section>\n</body>\n</html>\n",
+        );
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "--format", "html", "two.docx"])
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("merged.html").exists());
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--html-css", "style.css", "two.docx"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_option_values_with_equals_or_space() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+
+    let runs = [
+        (["--output=merged.md", "--separator=---"], "First---Second"),
+        (["--output", "merged.md"], "First\n\nSecond"),
+        (["--separator", "\n--\n"], "First\n--\nSecond"),
+    ];
+    for (args, merged) in runs {
+        Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["merge", "--output=other.md", "--force"])
+            .args(args)
+            .args(["a.docx", "b.docx"])
+            .assert()
+            .success();
+        let output = if args[0] == "--separator" { "other.md" } else { "merged.md" };
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join(output)).unwrap(), merged);
+    }
+}
+
+#[test]
+fn test_double_dash_ends_options() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("-draft.docx"), "Draft");
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-o", "-", "--", "-draft.docx"])
+        .assert()
+        .success()
+        .stdout("Draft");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["extract", "--", "-draft.docx"])
+        .assert()
+        .success()
+        .stdout("Draft");
+}
+
+#[test]
+fn test_short_flags_combine() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let body = r#"<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText>HYPERLINK "https://example.com"</w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>Site</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#;
+    write_docx_body(&temp_dir.path().join("a.docx"), body);
+
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-qso", "-", "a.docx"])
+        .assert()
+        .success()
+        .stdout("Site")
+        .stderr("");
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["merge", "-qo-", "a.docx"])
+        .assert()
+        .success()
+        .stdout("Site <https://example.com>");
+}
+
+#[test]
+fn test_help_and_version() {
+    for args in [&["--help"][..], &["merge", "--help"], &["help", "extract"]] {
+        let output = Command::cargo_bin("mddocmrg").unwrap().args(args).output().unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Usage: mddocmrg"));
+    }
+    Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("mddocmrg {}\n", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn test_help_shows_template_placeholders() {
+    let output = Command::cargo_bin("mddocmrg").unwrap().args(["merge", "--help"]).output().unwrap();
+    let help = String::from_utf8_lossy(&output.stdout);
+    assert!(help.contains(r#"a template with {n}, e.g. "== p.{n} =="#), "{}", help);
+    assert!(help.contains("{index}/{total}: {name}"));
+}
+
+#[test]
+fn test_unknown_options_are_errors() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+
+    for args in [&["merge", "--strip-links", "a.docx"][..], &["--strip-links", "a.docx"]] {
+        let output = Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("unexpected argument '--strip-links'"));
+        assert!(!temp_dir.path().join("merged.txt").exists());
+    }
+}
+
+#[test]
+fn test_output_is_never_overwritten_unasked() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "New");
+    std::fs::write(temp_dir.path().join("merged.txt"), "Old merged").unwrap();
+    std::fs::write(temp_dir.path().join("notes.txt"), "Old notes").unwrap();
+    let run = |args: &[&str]| {
+        Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .arg("merge")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let read = |name: &str| std::fs::read_to_string(temp_dir.path().join(name)).unwrap();
+
+    let output = run(&["-o", "-", "a.docx"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"New");
+    assert_eq!(read("merged.txt"), "Old merged");
+
+    let output = run(&["-o", "notes.txt", "a.docx"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "notes.txt already exists; use --force to overwrite it.\n"
+    );
+    assert_eq!(read("notes.txt"), "Old notes");
+
+    // An input is never overwritten, even with --force.
+    let output = run(&["--force", "-o", "./notes.txt", "notes.txt", "a.docx"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Output ./notes.txt is the input notes.txt; the merged text would overwrite it.\n"
+    );
+    let output = run(&["--force", "merged.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("merged.txt is also one of the files merged; use -o to write the merged text elsewhere."));
+    assert_eq!(read("notes.txt"), "Old notes");
+    assert_eq!(read("merged.txt"), "Old merged");
+
+    let output = run(&["--force", "-o", "notes.txt", "a.docx"]);
+    assert!(output.status.success());
+    assert_eq!(read("notes.txt"), "New");
+}
+
+#[test]
+fn test_stdout_has_only_the_text_at_every_level() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_docx(&temp_dir.path().join("a.docx"), "First");
+    write_docx(&temp_dir.path().join("b.docx"), "Second");
+    std::fs::write(temp_dir.path().join("c.docx"), "not a package").unwrap();
+
+    for level in [&["--quiet"][..], &[], &["--verbose"]] {
+        let output = Command::cargo_bin("mddocmrg")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .arg("merge")
+            .args(level)
+            .args(["--skip-errors", "--strip-invisible", "--stats", "-o", "-", "a.docx", "b.docx", "c.docx"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(7));
+        assert_eq!(output.stdout, b"First\n\nSecond", "{:?}", level);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        // Errors and the counts asked for are written at every level.
+        assert!(stderr.contains("c.docx is damaged"), "{}", stderr);
+        assert!(stderr.contains("total"), "{}", stderr);
+        assert_eq!(
+            stderr.contains("Merged 2 of 3 files to standard output"),
+            level != ["--quiet"],
+            "{}",
+            stderr
+        );
+        assert_eq!(
+            stderr.contains("a.docx: removed 0 invisible characters"),
+            level == ["--verbose"],
+            "{}",
+            stderr
+        );
+    }
+
+    let output = Command::cargo_bin("mddocmrg")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["-q", "-v", "extract", "a.docx"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,41 @@
+//! Runs the C program in tests/ffi, compiled by build.rs, against the C
+//! bindings.
+
+use std::ffi::c_int;
+use std::io::{Cursor, Write};
+
+use mddocmrg::{extract_text_from_bytes, ExtractOptions};
+use zip::write::FileOptions;
+
+#[link(name = "mdm_ffi_test", kind = "static")]
+extern "C" {
+    fn mdm_ffi_test(docx: *const u8, len: usize) -> c_int;
+}
+
+#[test]
+fn test_c_program_extracts_text() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Hello</w:t></w:r></w:p><w:p><w:r><w:t>from C</w:t></w:r></w:p></w:body></w:document>"#;
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("word/document.xml", FileOptions::default())
+        .unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    let docx = zip.finish().unwrap().into_inner();
+    assert_eq!(
+        extract_text_from_bytes(&docx, &ExtractOptions::default()).unwrap(),
+        "Hello\nfrom C"
+    );
+
+    assert_eq!(unsafe { mdm_ffi_test(docx.as_ptr(), docx.len()) }, 0);
+}
+
+#[test]
+fn test_header_is_up_to_date() {
+    let generated = std::fs::read_to_string(env!("MDM_GENERATED_HEADER")).unwrap();
+    let checked_in = std::fs::read_to_string("include/mddocmrg.h").unwrap_or_default();
+    assert!(
+        generated == checked_in,
+        "include/mddocmrg.h is out of date; copy {} over it",
+        env!("MDM_GENERATED_HEADER")
+    );
+}
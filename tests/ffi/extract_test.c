@@ -0,0 +1,51 @@
+/*
+ * Calls the C bindings as an application embedding the library would.
+ * tests/ffi.rs runs mdm_ffi_test with a document of two paragraphs,
+ * "Hello" and "from C".
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "mddocmrg.h"
+
+#define CHECK(condition)                                                      \
+    do {                                                                      \
+        if (!(condition)) {                                                   \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #condition);   \
+            return 1;                                                         \
+        }                                                                     \
+    } while (0)
+
+int mdm_ffi_test(const uint8_t *docx, size_t len) {
+    char *text = NULL;
+    char *error = NULL;
+
+    CHECK(mdm_extract_text(docx, len, NULL, &text, &error) == MDM_STATUS_OK);
+    CHECK(error == NULL);
+    CHECK(strcmp(text, "Hello\nfrom C") == 0);
+    mdm_free_string(text);
+
+    MdmOptions options = mdm_default_options();
+    options.paragraph_separator = " / ";
+    CHECK(mdm_extract_text(docx, len, &options, &text, NULL) == MDM_STATUS_OK);
+    CHECK(strcmp(text, "Hello / from C /") == 0);
+    mdm_free_string(text);
+
+    const uint8_t not_a_document[] = "not a document";
+    CHECK(mdm_extract_text(not_a_document, sizeof not_a_document, NULL, &text, &error)
+          == MDM_STATUS_FAILED);
+    CHECK(text == NULL);
+    CHECK(error != NULL && strstr(error, "not a Word document") != NULL);
+    mdm_free_string(error);
+
+    options.links = "sideways";
+    CHECK(mdm_extract_text(docx, len, &options, &text, &error) == MDM_STATUS_INVALID_ARGUMENT);
+    CHECK(strcmp(error, "unknown links: sideways") == 0);
+    mdm_free_string(error);
+
+    CHECK(mdm_extract_text(docx, len, NULL, NULL, &error) == MDM_STATUS_INVALID_ARGUMENT);
+    mdm_free_string(error);
+    mdm_free_string(NULL);
+    return 0;
+}
@@ -0,0 +1,84 @@
+//! Checks that extracting to a writer streams the main document part,
+//! rather than holding all of its XML, by counting the bytes allocated.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs::File;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use mddocmrg::{extract_text_to_writer, ExtractOptions};
+use zip::write::FileOptions;
+use zip::CompressionMethod;
+
+/// The system allocator, keeping track of the most memory in use at once.
+struct PeakAllocator;
+
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let in_use = IN_USE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(in_use, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        IN_USE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+#[test]
+fn test_large_document_is_streamed() {
+    let paragraph = "<w:p><w:r><w:t>A paragraph of a long synthetic document.</w:t></w:r></w:p>";
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+        paragraph.repeat(100_000)
+    );
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("large.docx");
+    let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("word/document.xml", options).unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    zip.finish().unwrap();
+    let xml_size = xml.len();
+    drop(xml);
+
+    let before = IN_USE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let mut written = CountingWriter(0);
+    extract_text_to_writer(&path, &mut written, &ExtractOptions::default()).unwrap();
+    let peak = PEAK.load(Ordering::Relaxed) - before;
+
+    // The text alone is about half the size of the XML; neither is held.
+    assert!(written.0 > xml_size / 2, "{} bytes written", written.0);
+    assert!(
+        peak < xml_size / 20,
+        "{} bytes in use at most, for {} bytes of XML",
+        peak,
+        xml_size
+    );
+}
+
+/// Counts the bytes written to it, and keeps none.
+struct CountingWriter(usize);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}